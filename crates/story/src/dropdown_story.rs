@@ -5,7 +5,10 @@ use gpui::{
 
 use gpui_component::{
     checkbox::Checkbox,
-    dropdown::{Dropdown, DropdownEvent, DropdownItem, DropdownState, SearchableVec},
+    dropdown::{
+        Dropdown, DropdownEvent, DropdownItem, DropdownItemGroup, DropdownMultipleEvent,
+        DropdownState, SearchableGroups, SearchableVec,
+    },
     h_flex, v_flex, ActiveTheme, FocusableCycle, IconName, Sizable,
 };
 
@@ -55,6 +58,8 @@ pub struct DropdownStory {
     disabled: bool,
     country_dropdown: Entity<DropdownState<Vec<Country>>>,
     fruit_dropdown: Entity<DropdownState<SearchableVec<SharedString>>>,
+    multiple_dropdown: Entity<DropdownState<SearchableVec<SharedString>>>,
+    grouped_dropdown: Entity<DropdownState<SearchableGroups<SharedString>>>,
     simple_dropdown1: Entity<DropdownState<Vec<SharedString>>>,
     simple_dropdown2: Entity<DropdownState<SearchableVec<SharedString>>>,
     simple_dropdown3: Entity<DropdownState<Vec<SharedString>>>,
@@ -110,14 +115,51 @@ impl DropdownStory {
         ]);
         let fruit_dropdown = cx.new(|cx| DropdownState::new(fruits, None, window, cx));
 
+        let languages = SearchableVec::new(vec![
+            "Rust".into(),
+            "Go".into(),
+            "C++".into(),
+            "JavaScript".into(),
+            "TypeScript".into(),
+            "Python".into(),
+            "Ruby".into(),
+            "Swift".into(),
+        ]);
+        let files = SearchableGroups::new(vec![
+            DropdownItemGroup::new("Recent", vec!["main.rs".into(), "lib.rs".into()]),
+            DropdownItemGroup::new("Favorites", vec!["Cargo.toml".into(), "README.md".into()]),
+            DropdownItemGroup::new(
+                "All",
+                vec![
+                    "build.rs".into(),
+                    "Cargo.lock".into(),
+                    "Cargo.toml".into(),
+                    "lib.rs".into(),
+                    "main.rs".into(),
+                    "README.md".into(),
+                ],
+            ),
+        ]);
+        let grouped_dropdown = cx.new(|cx| DropdownState::new(files, None, window, cx));
+
+        let multiple_dropdown = cx.new(|cx| {
+            let mut dropdown = DropdownState::new(languages, None, window, cx).multiple();
+            dropdown.set_selected_values(&["Rust".into(), "Go".into()], window, cx);
+            dropdown
+        });
+
         cx.new(|cx| {
             cx.subscribe_in(&country_dropdown, window, Self::on_dropdown_event)
                 .detach();
+            cx.subscribe_in(&multiple_dropdown, window, Self::on_multiple_dropdown_event)
+                .detach();
 
             Self {
                 disabled: false,
                 country_dropdown,
                 fruit_dropdown,
+                multiple_dropdown,
+                grouped_dropdown,
                 simple_dropdown1: cx.new(|cx| {
                     DropdownState::new(
                         vec![
@@ -177,6 +219,18 @@ impl DropdownStory {
         }
     }
 
+    fn on_multiple_dropdown_event(
+        &mut self,
+        _: &Entity<DropdownState<SearchableVec<SharedString>>>,
+        event: &DropdownMultipleEvent<SearchableVec<SharedString>>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) {
+        match event {
+            DropdownMultipleEvent::Change(values) => println!("Selected languages: {:?}", values),
+        }
+    }
+
     fn on_key_tab(&mut self, _: &Tab, window: &mut Window, cx: &mut Context<Self>) {
         self.cycle_focus(true, window, cx);
         cx.notify();
@@ -201,6 +255,8 @@ impl FocusableCycle for DropdownStory {
         vec![
             self.country_dropdown.focus_handle(cx),
             self.fruit_dropdown.focus_handle(cx),
+            self.multiple_dropdown.focus_handle(cx),
+            self.grouped_dropdown.focus_handle(cx),
             self.simple_dropdown1.focus_handle(cx),
             self.simple_dropdown2.focus_handle(cx),
             self.simple_dropdown3.focus_handle(cx),
@@ -240,6 +296,22 @@ impl Render for DropdownStory {
                         .menu_width(px(400.)),
                 ),
             )
+            .child(
+                section("Multiple Select").max_w_128().child(
+                    Dropdown::new(&self.multiple_dropdown)
+                        .disabled(self.disabled)
+                        .cleanable()
+                        .max_visible_tags(2)
+                        .placeholder("Languages"),
+                ),
+            )
+            .child(
+                section("Grouped").max_w_128().child(
+                    Dropdown::new(&self.grouped_dropdown)
+                        .disabled(self.disabled)
+                        .placeholder("Open file"),
+                ),
+            )
             .child(
                 section("Disabled")
                     .max_w_128()
@@ -289,6 +361,10 @@ impl Render for DropdownStory {
                             "fruit: {:?}",
                             self.fruit_dropdown.read(cx).selected_value()
                         ))
+                        .child(format!(
+                            "Languages: {:?}",
+                            self.multiple_dropdown.read(cx).selected_values()
+                        ))
                         .child(format!(
                             "UI: {:?}",
                             self.simple_dropdown1.read(cx).selected_value()
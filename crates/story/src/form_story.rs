@@ -1,31 +1,64 @@
 use gpui::{
     actions, div, prelude::FluentBuilder as _, App, AppContext, Axis, Context, Entity, Focusable,
-    InteractiveElement, IntoElement, ParentElement as _, Render, Styled, Window,
+    InteractiveElement, IntoElement, ParentElement as _, Render, Styled, Subscription, Window,
 };
 use gpui_component::{
-    button::{Button, ButtonGroup},
+    button::{Button, ButtonGroup, ButtonVariants as _},
     checkbox::Checkbox,
     color_picker::{ColorPicker, ColorPickerState},
     date_picker::{DatePicker, DatePickerState},
     divider::Divider,
-    form::{form_field, v_form},
+    form::{form_field, v_form, FormEvent, FormState, Rule, SchemaForm},
     h_flex,
     input::{InputState, TextInput},
     switch::Switch,
-    v_flex, AxisExt, FocusableCycle, Selectable, Sizable, Size,
+    v_flex, AxisExt, Disableable as _, FocusableCycle, Selectable, Sizable, Size,
 };
+use regex::Regex;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
 
 actions!(input_story, [Tab, TabPrev]);
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+enum LogLevel {
+    Debug,
+    Info,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct ServerConfig {
+    /// Host
+    ///
+    /// The host name or IP address to listen on.
+    host: String,
+    #[validate(range(min = 1, max = 65535))]
+    port: u16,
+}
+
+/// The config of a plugin, to show the form generated from the JSON Schema.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+struct PluginConfig {
+    #[validate(length(min = 1, max = 20))]
+    name: String,
+    enabled: bool,
+    log_level: LogLevel,
+    server: ServerConfig,
+    allowed_origins: Vec<String>,
+}
+
 pub struct FormStory {
     name_input: Entity<InputState>,
     email_input: Entity<InputState>,
     bio_input: Entity<InputState>,
     color_state: Entity<ColorPickerState>,
-    subscribe_email: bool,
     date: Entity<DatePickerState>,
+    form: Entity<FormState>,
+    schema_form: Entity<SchemaForm>,
     layout: Axis,
     size: Size,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl super::Story for FormStory {
@@ -65,15 +98,64 @@ impl FormStory {
         });
         let date = cx.new(|cx| DatePickerState::new(window, cx));
 
+        let form = cx.new(|cx| {
+            FormState::new(window, cx)
+                .field(
+                    "name",
+                    &name_input,
+                    [Rule::required(), Rule::max_length(20)],
+                    window,
+                    cx,
+                )
+                .field(
+                    "email",
+                    &email_input,
+                    [
+                        Rule::required(),
+                        Rule::pattern(Regex::new(r"^\S+@\S+\.\S+$").unwrap())
+                            .message("Please enter a valid email address."),
+                    ],
+                    window,
+                    cx,
+                )
+                .field("bio", &bio_input, [Rule::max_length(100)], window, cx)
+                .field("birthday", &date, [Rule::required()], window, cx)
+                .value_field("subscribe_email", false, [], cx)
+        });
+
+        let schema_form = cx.new(|cx| {
+            let mut form = SchemaForm::new(&schema_for!(PluginConfig), window, cx);
+            let config = PluginConfig {
+                name: "Markdown Preview".into(),
+                enabled: true,
+                log_level: LogLevel::Info,
+                server: ServerConfig {
+                    host: "127.0.0.1".into(),
+                    port: 8080,
+                },
+                allowed_origins: vec!["localhost".into()],
+            };
+            _ = form.load(&config, window, cx);
+            form
+        });
+
+        // Update the Save and Revert buttons when the values changed.
+        let _subscriptions = vec![
+            cx.subscribe(&form, |_, _, _: &FormEvent, cx| cx.notify()),
+            cx.subscribe(&schema_form, |_, _, _: &FormEvent, cx| cx.notify()),
+        ];
+
         Self {
             name_input,
             email_input,
             bio_input,
             date,
+            form,
+            schema_form,
             color_state,
-            subscribe_email: false,
             layout: Axis::Vertical,
             size: Size::default(),
+            _subscriptions,
         }
     }
 }
@@ -99,6 +181,9 @@ impl Focusable for FormStory {
 
 impl Render for FormStory {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_dirty = self.form.read(cx).is_dirty(cx);
+        let is_config_dirty = self.schema_form.read(cx).is_dirty(cx);
+
         v_flex()
             .id("form-story")
             .size_full()
@@ -161,18 +246,20 @@ impl Render for FormStory {
                     .child(
                         form_field()
                             .label_fn(|_, _| "Name")
+                            .bind(&self.form, "name")
                             .child(TextInput::new(&self.name_input)),
                     )
                     .child(
                         form_field()
                             .label("Email")
-                            .child(TextInput::new(&self.email_input))
-                            .required(true),
+                            .bind(&self.form, "email")
+                            .child(TextInput::new(&self.email_input)),
                     )
                     .child(
                         form_field()
                             .label("Bio")
                             .when(self.layout.is_vertical(), |this| this.items_start())
+                            .bind(&self.form, "bio")
                             .child(TextInput::new(&self.bio_input))
                             .description_fn(|_, _| {
                                 div().child("Use at most 100 words to describe yourself.")
@@ -186,6 +273,7 @@ impl Render for FormStory {
                     .child(
                         form_field()
                             .label("Birthday")
+                            .bind(&self.form, "birthday")
                             .child(DatePicker::new(&self.date))
                             .description("Select your birthday, we will send you a gift."),
                     )
@@ -193,11 +281,7 @@ impl Render for FormStory {
                         form_field().child(
                             Switch::new("subscribe-newsletter")
                                 .label("Subscribe our newsletter")
-                                .checked(self.subscribe_email)
-                                .on_click(cx.listener(|this, checked: &bool, _, cx| {
-                                    this.subscribe_email = *checked;
-                                    cx.notify();
-                                })),
+                                .bind(&self.form, "subscribe_email"),
                         ),
                     )
                     .child(
@@ -221,6 +305,72 @@ impl Render for FormStory {
                                     cx.notify();
                                 })),
                         ),
+                    )
+                    .child(
+                        form_field().no_label_indent().child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Button::new("save")
+                                        .primary()
+                                        .label("Save")
+                                        .disabled(!is_dirty)
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.form.update(cx, |form, cx| {
+                                                if form.validate(window, cx).is_empty() {
+                                                    println!("Saved: {}", form.values(cx));
+                                                    form.mark_clean(cx);
+                                                }
+                                            });
+                                        })),
+                                )
+                                .child(
+                                    Button::new("revert")
+                                        .label("Revert")
+                                        .disabled(!is_dirty)
+                                        .on_click(cx.listener(|this, _, window, cx| {
+                                            this.form.update(cx, |form, cx| {
+                                                form.reset(window, cx);
+                                            });
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+            .child(Divider::horizontal())
+            .child(self.schema_form.clone())
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("save-config")
+                            .primary()
+                            .label("Save Config")
+                            .disabled(!is_config_dirty)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.schema_form.update(cx, |form, cx| {
+                                    if !form.validate(window, cx).is_empty() {
+                                        return;
+                                    }
+                                    match form.values_as::<PluginConfig>(cx) {
+                                        Ok(config) => {
+                                            println!("Saved: {:?}", config);
+                                            form.mark_clean(cx);
+                                        }
+                                        Err(err) => println!("Invalid config: {}", err),
+                                    }
+                                });
+                            })),
+                    )
+                    .child(
+                        Button::new("revert-config")
+                            .label("Revert")
+                            .disabled(!is_config_dirty)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.schema_form.update(cx, |form, cx| {
+                                    form.reset(window, cx);
+                                });
+                            })),
                     ),
             )
     }
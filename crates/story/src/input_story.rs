@@ -1,14 +1,16 @@
 use gpui::{
     actions, div, App, AppContext as _, Context, Entity, FocusHandle, Focusable,
-    InteractiveElement, IntoElement, KeyBinding, ParentElement as _, Render, Styled, Subscription,
-    Window,
+    InteractiveElement, IntoElement, KeyBinding, ParentElement as _, Render, SharedString, Styled,
+    Subscription, Window,
 };
 
 use crate::section;
 use gpui_component::{
     button::{Button, ButtonVariant, ButtonVariants as _},
     h_flex,
-    input::{InputEvent, InputState, MaskPattern, TextInput},
+    input::{
+        InputEvent, InputState, MaskPattern, TagInput, TagInputEvent, TagInputState, TextInput,
+    },
     v_flex, ContextModal, FocusableCycle, Icon, IconName, Sizable,
 };
 
@@ -37,6 +39,8 @@ pub struct InputStory {
     phone_input: Entity<InputState>,
     mask_input2: Entity<InputState>,
     currency_input: Entity<InputState>,
+    tag_input: Entity<TagInputState>,
+    tag_input2: Entity<TagInputState>,
 
     _subscriptions: Vec<Subscription>,
 }
@@ -90,8 +94,12 @@ impl InputStory {
             InputState::new(window, cx).placeholder("This input have prefix and suffix.")
         });
 
-        let phone_input = cx.new(|cx| InputState::new(window, cx).mask_pattern("(999)-999-9999"));
-        let mask_input2 = cx.new(|cx| InputState::new(window, cx).mask_pattern("AAA-###-AAA"));
+        let phone_input = cx.new(|cx| {
+            InputState::new(window, cx).mask_pattern(MaskPattern::extended("(999)-999-9999? x9999"))
+        });
+        let mask_input2 = cx.new(|cx| {
+            InputState::new(window, cx).mask_pattern(MaskPattern::extended(">AAA-###-AAA"))
+        });
         let currency_input = cx.new(|cx| {
             InputState::new(window, cx).mask_pattern(MaskPattern::Number {
                 separator: Some(','),
@@ -99,10 +107,28 @@ impl InputStory {
             })
         });
 
+        let tag_input = cx.new(|cx| {
+            let mut state = TagInputState::new(window, cx).default_tags(["gpui", "rust"]);
+            state.set_placeholder("Type and press enter or comma to add tags", window, cx);
+            state
+        });
+        let tag_input2 = cx.new(|cx| {
+            let mut state = TagInputState::new(window, cx).max_tags(5).suggestions(
+                [
+                    "Apple", "Banana", "Cherry", "Grape", "Lemon", "Mango", "Orange", "Peach",
+                ]
+                .map(SharedString::from)
+                .to_vec(),
+            );
+            state.set_placeholder("Pick up to 5 fruits", window, cx);
+            state
+        });
+
         let _subscriptions = vec![
             cx.subscribe_in(&input1, window, Self::on_input_event),
             cx.subscribe_in(&input2, window, Self::on_input_event),
             cx.subscribe_in(&phone_input, window, Self::on_input_event),
+            cx.subscribe_in(&tag_input, window, Self::on_tag_input_event),
         ];
 
         Self {
@@ -124,6 +150,8 @@ impl InputStory {
             phone_input,
             mask_input2,
             currency_input,
+            tag_input,
+            tag_input2,
             _subscriptions,
         }
     }
@@ -150,6 +178,18 @@ impl InputStory {
             InputEvent::Blur => println!("Blur"),
         };
     }
+
+    fn on_tag_input_event(
+        &mut self,
+        _: &Entity<TagInputState>,
+        event: &TagInputEvent,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) {
+        match event {
+            TagInputEvent::Change(tags) => println!("Tags: {:?}", tags),
+        };
+    }
 }
 
 impl FocusableCycle for InputStory {
@@ -261,6 +301,12 @@ impl Render for InputStory {
                             )),
                     ),
             )
+            .child(
+                section("Tag Input")
+                    .max_w_md()
+                    .child(TagInput::new(&self.tag_input))
+                    .child(TagInput::new(&self.tag_input2).small()),
+            )
             .child(
                 section("Input Size")
                     .max_w_md()
@@ -10,6 +10,7 @@ use gpui::{
 use gpui_component::{
     button::Button,
     checkbox::Checkbox,
+    fuzzy::{fuzzy_search, match_ranges},
     h_flex, hsl,
     label::Label,
     list::{List, ListDelegate, ListEvent, ListItem},
@@ -51,13 +52,22 @@ struct CompanyListItem {
     base: ListItem,
     ix: usize,
     company: Company,
+    /// The matched positions of the name to highlight.
+    matched_positions: Vec<usize>,
     selected: bool,
 }
 
 impl CompanyListItem {
-    pub fn new(id: impl Into<ElementId>, company: Company, ix: usize, selected: bool) -> Self {
+    pub fn new(
+        id: impl Into<ElementId>,
+        company: Company,
+        matched_positions: Vec<usize>,
+        ix: usize,
+        selected: bool,
+    ) -> Self {
         CompanyListItem {
             company,
+            matched_positions,
             ix,
             base: ListItem::new(id),
             selected,
@@ -104,7 +114,14 @@ impl RenderOnce for CompanyListItem {
                             .max_w(px(500.))
                             .overflow_x_hidden()
                             .flex_nowrap()
-                            .child(Label::new(self.company.name.clone()).whitespace_nowrap())
+                            .child(
+                                Label::new(self.company.name.clone())
+                                    .whitespace_nowrap()
+                                    .highlights(match_ranges(
+                                        &self.company.name,
+                                        &self.matched_positions,
+                                    )),
+                            )
                             .child(
                                 div().text_sm().overflow_x_hidden().child(
                                     Label::new(self.company.industry.clone())
@@ -143,6 +160,7 @@ impl RenderOnce for CompanyListItem {
 struct CompanyListDelegate {
     companies: Vec<Company>,
     matched_companies: Vec<Company>,
+    matched_positions: Vec<Vec<usize>>,
     selected_index: Option<usize>,
     confirmed_index: Option<usize>,
     query: String,
@@ -164,12 +182,11 @@ impl ListDelegate for CompanyListDelegate {
         _: &mut Context<List<Self>>,
     ) -> Task<()> {
         self.query = query.to_string();
-        self.matched_companies = self
-            .companies
-            .iter()
-            .filter(|company| company.name.to_lowercase().contains(&query.to_lowercase()))
-            .cloned()
-            .collect();
+        (self.matched_companies, self.matched_positions) =
+            fuzzy_search(query, self.companies.iter(), |company| company.name.clone())
+                .into_iter()
+                .map(|(company, matched)| (company.clone(), matched.positions))
+                .unzip();
         Task::ready(())
     }
 
@@ -196,7 +213,14 @@ impl ListDelegate for CompanyListDelegate {
     ) -> Option<Self::Item> {
         let selected = Some(ix) == self.selected_index || Some(ix) == self.confirmed_index;
         if let Some(company) = self.matched_companies.get(ix) {
-            return Some(CompanyListItem::new(ix, company.clone(), ix, selected));
+            let matched_positions = self.matched_positions.get(ix).cloned().unwrap_or_default();
+            return Some(CompanyListItem::new(
+                ix,
+                company.clone(),
+                matched_positions,
+                ix,
+                selected,
+            ));
         }
 
         None
@@ -275,6 +299,7 @@ impl ListStory {
 
         let delegate = CompanyListDelegate {
             matched_companies: companies.clone(),
+            matched_positions: vec![],
             companies,
             selected_index: Some(0),
             confirmed_index: None,
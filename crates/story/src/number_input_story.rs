@@ -6,7 +6,10 @@ use regex::Regex;
 
 use crate::section;
 use gpui_component::{
-    input::{InputEvent, InputState, MaskPattern, NumberInput, NumberInputEvent, StepAction},
+    input::{
+        InputEvent, InputState, MaskPattern, NumberFormat, NumberInput, NumberInputEvent,
+        StepAction,
+    },
     v_flex, FocusableCycle, Sizable,
 };
 
@@ -28,6 +31,7 @@ pub struct NumberInputStory {
     number_input2_value: u64,
     number_input3: Entity<InputState>,
     number_input3_value: f64,
+    number_input4: Entity<InputState>,
 
     _subscriptions: Vec<Subscription>,
 }
@@ -78,6 +82,20 @@ impl NumberInputStory {
                 })
         });
 
+        let number_input4 = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Price")
+                .mask_pattern(
+                    NumberFormat::locale(&gpui_component::locale())
+                        .prefix("$")
+                        .fraction(2),
+                )
+                .min_value(0.)
+                .max_value(99999.)
+                .step(0.5)
+                .precision(2)
+        });
+
         let _subscriptions = vec![
             cx.subscribe_in(&number_input1, window, Self::on_input_event),
             cx.subscribe_in(&number_input1, window, Self::on_number_input_event),
@@ -85,6 +103,7 @@ impl NumberInputStory {
             cx.subscribe_in(&number_input2, window, Self::on_number_input_event),
             cx.subscribe_in(&number_input3, window, Self::on_input_event),
             cx.subscribe_in(&number_input3, window, Self::on_number_input_event),
+            cx.subscribe_in(&number_input4, window, Self::on_input_event),
         ];
 
         Self {
@@ -94,6 +113,7 @@ impl NumberInputStory {
             number_input2_value: 0,
             number_input3,
             number_input3_value: 0.0,
+            number_input4,
             _subscriptions,
         }
     }
@@ -111,7 +131,7 @@ impl NumberInputStory {
         this: &Entity<InputState>,
         event: &InputEvent,
         _: &mut Window,
-        _: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
         match event {
             InputEvent::Change(text) => {
@@ -127,6 +147,8 @@ impl NumberInputStory {
                     if let Ok(value) = text.parse::<f64>() {
                         self.number_input3_value = value;
                     }
+                } else if this == &self.number_input4 {
+                    println!("Number value: {:?}", this.read(cx).number_value());
                 }
                 println!("Change: {}", text);
             }
@@ -224,5 +246,10 @@ impl Render for NumberInputStory {
                     .max_w_md()
                     .child(NumberInput::new(&self.number_input3)),
             )
+            .child(
+                section("With min, max, step and precision")
+                    .max_w_md()
+                    .child(NumberInput::new(&self.number_input4)),
+            )
     }
 }
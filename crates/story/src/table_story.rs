@@ -6,8 +6,8 @@ use std::{
 use fake::{Fake, Faker};
 use gpui::{
     div, impl_internal_actions, prelude::FluentBuilder as _, px, AnyElement, App, AppContext,
-    ClickEvent, Context, Edges, Entity, Focusable, InteractiveElement, IntoElement, ParentElement,
-    Pixels, Render, SharedString, StatefulInteractiveElement, Styled, Timer, Window,
+    ClickEvent, ClipboardItem, Context, Edges, Entity, Focusable, InteractiveElement, IntoElement,
+    ParentElement, Pixels, Render, SharedString, StatefulInteractiveElement, Styled, Timer, Window,
 };
 use gpui_component::{
     button::Button,
@@ -18,10 +18,10 @@ use gpui_component::{
     label::Label,
     popup_menu::{PopupMenu, PopupMenuExt},
     red,
-    table::{self, ColFixed, ColSort, Table, TableDelegate, TableEvent},
+    table::{self, CellEditor, ColFixed, ColSort, ExportFormat, Table, TableDelegate, TableEvent},
     v_flex, ActiveTheme as _, Selectable, Sizable as _, Size, StyleSized as _,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, PartialEq, Eq, Deserialize)]
 struct ChangeSize(Size);
@@ -29,9 +29,12 @@ struct ChangeSize(Size);
 #[derive(Clone, PartialEq, Eq, Deserialize)]
 struct OpenDetail(usize);
 
-impl_internal_actions!(table_story, [ChangeSize, OpenDetail]);
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+struct ExportTable(ExportFormat);
+
+impl_internal_actions!(table_story, [ChangeSize, OpenDetail, ExportTable]);
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize)]
 struct Stock {
     id: usize,
     symbol: SharedString,
@@ -180,9 +183,13 @@ struct StockTableDelegate {
     col_order: bool,
     col_sort: bool,
     col_selection: bool,
+    row_selection: bool,
+    cell_selection: bool,
+    cell_editing: bool,
     loading: bool,
     full_loading: bool,
     fixed_cols: bool,
+    frozen_rows: bool,
     eof: bool,
     visible_rows: Range<usize>,
     visible_cols: Range<usize>,
@@ -244,7 +251,11 @@ impl StockTableDelegate {
             col_order: true,
             col_sort: true,
             col_selection: true,
+            row_selection: true,
+            cell_selection: false,
+            cell_editing: true,
             fixed_cols: false,
+            frozen_rows: false,
             loading: false,
             full_loading: false,
             eof: false,
@@ -330,11 +341,21 @@ impl TableDelegate for StockTableDelegate {
 
         if col_ix < 4 {
             Some(ColFixed::Left)
+        } else if col_ix == self.columns.len() - 1 {
+            Some(ColFixed::Right)
         } else {
             None
         }
     }
 
+    fn frozen_rows(&self, _: &App) -> usize {
+        if self.frozen_rows {
+            1
+        } else {
+            0
+        }
+    }
+
     fn can_resize_col(&self, col_ix: usize, _: &App) -> bool {
         return self.col_resize && col_ix > 1;
     }
@@ -343,6 +364,82 @@ impl TableDelegate for StockTableDelegate {
         return self.col_selection;
     }
 
+    fn can_select_rows(&self, _: &App) -> bool {
+        self.row_selection
+    }
+
+    fn can_select_cells(&self, _: &App) -> bool {
+        self.cell_selection
+    }
+
+    fn cell_text(&self, row_ix: usize, col_ix: usize, _: &App) -> SharedString {
+        let (Some(stock), Some(col)) = (self.stocks.get(row_ix), self.columns.get(col_ix)) else {
+            return SharedString::default();
+        };
+
+        // The fields of the stock are named by the column ids.
+        match serde_json::to_value(stock)
+            .ok()
+            .and_then(|value| value.get(col.id.as_ref()).cloned())
+        {
+            Some(serde_json::Value::String(text)) => text.into(),
+            Some(value) => value.to_string().into(),
+            None => SharedString::default(),
+        }
+    }
+
+    fn can_edit_cell(&self, _: usize, col_ix: usize, _: &App) -> bool {
+        self.cell_editing
+            && self.columns.get(col_ix).map_or(false, |col| {
+                matches!(col.id.as_ref(), "symbol" | "name" | "open")
+            })
+    }
+
+    fn cell_editor(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Option<CellEditor> {
+        let stock = self.stocks.get(row_ix)?;
+        let value: SharedString = match self.columns.get(col_ix)?.id.as_ref() {
+            "symbol" => stock.symbol.clone(),
+            "name" => stock.name.clone(),
+            "open" => stock.open.to_string().into(),
+            _ => return None,
+        };
+
+        let input = cx.new(|cx| InputState::new(window, cx).default_value(value));
+        Some(CellEditor::input(&input, cx))
+    }
+
+    fn commit_cell(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        value: serde_json::Value,
+        _: &mut Window,
+        _: &mut Context<Table<Self>>,
+    ) -> Result<(), SharedString> {
+        let text = value.as_str().unwrap_or_default().trim().to_string();
+        let Some(stock) = self.stocks.get_mut(row_ix) else {
+            return Ok(());
+        };
+
+        match self.columns.get(col_ix).map(|col| col.id.as_ref()) {
+            Some("symbol") if text.is_empty() => return Err("Symbol is required.".into()),
+            Some("symbol") => stock.symbol = text.into(),
+            Some("name") => stock.name = text.into(),
+            Some("open") => {
+                stock.open = text.parse().map_err(|_| "Open must be a number.")?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
     fn render_th(
         &self,
         col_ix: usize,
@@ -369,6 +466,7 @@ impl TableDelegate for StockTableDelegate {
             format!("Selected Row: {}", row_ix),
             Box::new(OpenDetail(row_ix)),
         )
+        .menu("Copy", Box::new(table::Copy))
         .separator()
         .menu("Size Large", Box::new(ChangeSize(Size::Large)))
         .menu("Size Medium", Box::new(ChangeSize(Size::Medium)))
@@ -728,6 +826,28 @@ impl TableStory {
         });
     }
 
+    fn toggle_cell_editing(&mut self, checked: &bool, window: &mut Window, cx: &mut Context<Self>) {
+        self.table.update(cx, |table, cx| {
+            table.cancel_edit(window, cx);
+            table.delegate_mut().cell_editing = *checked;
+            cx.notify();
+        });
+    }
+
+    fn toggle_row_selection(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.table.update(cx, |table, cx| {
+            table.delegate_mut().row_selection = *checked;
+            cx.notify();
+        });
+    }
+
+    fn toggle_cell_selection(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.table.update(cx, |table, cx| {
+            table.delegate_mut().cell_selection = *checked;
+            cx.notify();
+        });
+    }
+
     fn toggle_stripe(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
         self.stripe = *checked;
         let stripe = self.stripe;
@@ -745,6 +865,13 @@ impl TableStory {
         });
     }
 
+    fn toggle_frozen_rows(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.table.update(cx, |table, cx| {
+            table.delegate_mut().frozen_rows = *checked;
+            cx.notify();
+        });
+    }
+
     fn on_change_size(&mut self, a: &ChangeSize, _: &mut Window, cx: &mut Context<Self>) {
         self.size = a.0;
         self.table.update(cx, |table, cx| {
@@ -753,6 +880,16 @@ impl TableStory {
         });
     }
 
+    fn on_export_table(&mut self, a: &ExportTable, _: &mut Window, cx: &mut Context<Self>) {
+        let text = self.table.read(cx).export(a.0, cx);
+        println!(
+            "Exported {} bytes as {:?} to the clipboard",
+            text.len(),
+            a.0
+        );
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
     fn toggle_refresh_data(&mut self, checked: &bool, _: &mut Window, cx: &mut Context<Self>) {
         self.refresh_data = *checked;
         cx.notify();
@@ -775,6 +912,9 @@ impl TableStory {
             TableEvent::MoveCol(origin_idx, target_idx) => {
                 println!("Move col index: {} -> {}", origin_idx, target_idx);
             }
+            TableEvent::SelectionChanged(selection) => {
+                println!("Selection changed: {:?}", selection)
+            }
         }
     }
 }
@@ -787,6 +927,7 @@ impl Render for TableStory {
 
         v_flex()
             .on_action(cx.listener(Self::on_change_size))
+            .on_action(cx.listener(Self::on_export_table))
             .size_full()
             .text_sm()
             .gap_4()
@@ -825,6 +966,24 @@ impl Render for TableStory {
                             .selected(delegate.col_selection)
                             .on_click(cx.listener(Self::toggle_col_selection)),
                     )
+                    .child(
+                        Checkbox::new("row-selection")
+                            .label("Multiple Rows Selection")
+                            .selected(delegate.row_selection)
+                            .on_click(cx.listener(Self::toggle_row_selection)),
+                    )
+                    .child(
+                        Checkbox::new("cell-selection")
+                            .label("Cells Selection")
+                            .selected(delegate.cell_selection)
+                            .on_click(cx.listener(Self::toggle_cell_selection)),
+                    )
+                    .child(
+                        Checkbox::new("cell-editing")
+                            .label("Cell Editing")
+                            .selected(delegate.cell_editing)
+                            .on_click(cx.listener(Self::toggle_cell_editing)),
+                    )
                     .child(
                         Checkbox::new("stripe")
                             .label("Stripe")
@@ -837,6 +996,12 @@ impl Render for TableStory {
                             .selected(delegate.fixed_cols)
                             .on_click(cx.listener(Self::toggle_fixed_cols)),
                     )
+                    .child(
+                        Checkbox::new("frozen-rows")
+                            .label("Frozen Rows")
+                            .selected(delegate.frozen_rows)
+                            .on_click(cx.listener(Self::toggle_frozen_rows)),
+                    )
                     .child(
                         Checkbox::new("loading")
                             .label("Loading")
@@ -885,6 +1050,15 @@ impl Render for TableStory {
                                 )
                             }),
                     )
+                    .child(Button::new("export").small().label("Export…").popup_menu(
+                        |menu, _, _| {
+                            menu.menu("CSV", Box::new(ExportTable(ExportFormat::Csv)))
+                                .menu("TSV", Box::new(ExportTable(ExportFormat::Tsv)))
+                                .menu("JSON", Box::new(ExportTable(ExportFormat::Json)))
+                                .menu("Markdown", Box::new(ExportTable(ExportFormat::Markdown)))
+                                .menu("HTML", Box::new(ExportTable(ExportFormat::Html)))
+                        },
+                    ))
                     .child(
                         Button::new("scroll-top")
                             .child("Scroll to Top")
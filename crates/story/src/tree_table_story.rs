@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use fake::Fake;
+use gpui::{
+    div, prelude::FluentBuilder as _, App, AppContext, Context, Entity, Focusable, IntoElement,
+    ParentElement, Render, SharedString, Styled, Task, Timer, Window,
+};
+use gpui_component::{
+    h_flex,
+    table::{Table, TableDelegate},
+    v_flex, ActiveTheme as _,
+};
+
+#[derive(Clone)]
+struct FileRow {
+    path: SharedString,
+    name: SharedString,
+    is_dir: bool,
+    size: u64,
+    depth: usize,
+    expanded: bool,
+}
+
+impl FileRow {
+    fn new(parent: &str, name: String, is_dir: bool, depth: usize) -> Self {
+        Self {
+            path: format!("{}/{}", parent, name).into(),
+            name: name.into(),
+            is_dir,
+            size: if is_dir { 0 } else { (100..99999).fake() },
+            depth,
+            expanded: false,
+        }
+    }
+}
+
+/// Simulate to read the children of the directory, the deepest directories only have files.
+fn read_dir(path: &str, depth: usize) -> Vec<FileRow> {
+    let dirs = if depth < 3 { 3 } else { 0 };
+
+    (0..dirs)
+        .map(|ix| FileRow::new(path, format!("folder-{}", ix + 1), true, depth))
+        .chain((0..4).map(|ix| FileRow::new(path, format!("file-{}.rs", ix + 1), false, depth)))
+        .collect()
+}
+
+struct FileTableDelegate {
+    rows: Vec<FileRow>,
+}
+
+impl FileTableDelegate {
+    fn new() -> Self {
+        Self {
+            rows: read_dir("", 0),
+        }
+    }
+
+    /// Insert the children after the directory if it is still expanded, returns the number of the inserted rows.
+    fn insert_children(&mut self, path: &SharedString, children: Vec<FileRow>) -> usize {
+        let Some(row_ix) = self.rows.iter().position(|row| &row.path == path) else {
+            return 0;
+        };
+        if !self.rows[row_ix].expanded {
+            return 0;
+        }
+
+        let count = children.len();
+        self.rows.splice(row_ix + 1..row_ix + 1, children);
+        count
+    }
+
+    /// Remove the descendants of the directory.
+    fn remove_children(&mut self, row_ix: usize) {
+        let depth = self.rows[row_ix].depth;
+        let end = self.rows[row_ix + 1..]
+            .iter()
+            .position(|row| row.depth <= depth)
+            .map_or(self.rows.len(), |ix| row_ix + 1 + ix);
+        self.rows.drain(row_ix + 1..end);
+    }
+}
+
+impl TableDelegate for FileTableDelegate {
+    fn cols_count(&self, _: &App) -> usize {
+        3
+    }
+
+    fn rows_count(&self, _: &App) -> usize {
+        self.rows.len()
+    }
+
+    fn col_name(&self, col_ix: usize, _: &App) -> SharedString {
+        match col_ix {
+            0 => "Name",
+            1 => "Kind",
+            _ => "Size",
+        }
+        .into()
+    }
+
+    fn col_width(&self, col_ix: usize, _: &App) -> gpui::Pixels {
+        match col_ix {
+            0 => gpui::px(300.),
+            _ => gpui::px(120.),
+        }
+    }
+
+    fn render_td(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> impl IntoElement {
+        let Some(row) = self.rows.get(row_ix) else {
+            return div();
+        };
+
+        match col_ix {
+            0 => div().child(row.name.clone()),
+            1 => div()
+                .text_color(cx.theme().muted_foreground)
+                .child(if row.is_dir { "Folder" } else { "File" }),
+            _ => div().when(!row.is_dir, |this| {
+                this.child(format!("{:.1} KB", row.size as f64 / 1024.))
+            }),
+        }
+    }
+
+    fn is_tree(&self, _: &App) -> bool {
+        true
+    }
+
+    fn row_depth(&self, row_ix: usize, _: &App) -> usize {
+        self.rows.get(row_ix).map_or(0, |row| row.depth)
+    }
+
+    fn row_has_children(&self, row_ix: usize, _: &App) -> bool {
+        self.rows.get(row_ix).map_or(false, |row| row.is_dir)
+    }
+
+    fn is_row_expanded(&self, row_ix: usize, _: &App) -> bool {
+        self.rows.get(row_ix).map_or(false, |row| row.expanded)
+    }
+
+    fn set_row_expanded(
+        &mut self,
+        row_ix: usize,
+        expanded: bool,
+        _: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Option<Task<usize>> {
+        let row = self.rows.get_mut(row_ix)?;
+        row.expanded = expanded;
+        if !expanded {
+            self.remove_children(row_ix);
+            return None;
+        }
+
+        let path = row.path.clone();
+        let children = read_dir(&row.path, row.depth + 1);
+        Some(cx.spawn(async move |view, cx| {
+            // Simulate to read the directory, delay 500ms to load the children.
+            Timer::after(Duration::from_millis(500)).await;
+
+            view.update(cx, |view, cx| {
+                cx.notify();
+                view.delegate_mut().insert_children(&path, children)
+            })
+            .unwrap_or_default()
+        }))
+    }
+}
+
+pub struct TreeTableStory {
+    table: Entity<Table<FileTableDelegate>>,
+}
+
+impl super::Story for TreeTableStory {
+    fn title() -> &'static str {
+        "TreeTable"
+    }
+
+    fn description() -> &'static str {
+        "A table with the hierarchical rows, the children are loaded when expanding."
+    }
+
+    fn new_view(window: &mut Window, cx: &mut App) -> Entity<impl Render + Focusable> {
+        Self::view(window, cx)
+    }
+}
+
+impl Focusable for TreeTableStory {
+    fn focus_handle(&self, cx: &gpui::App) -> gpui::FocusHandle {
+        self.table.focus_handle(cx)
+    }
+}
+
+impl TreeTableStory {
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let table = cx.new(|cx| Table::new(FileTableDelegate::new(), window, cx));
+        Self { table }
+    }
+}
+
+impl Render for TreeTableStory {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let rows_count = self.table.read(cx).delegate().rows_count(cx);
+
+        v_flex()
+            .size_full()
+            .text_sm()
+            .gap_4()
+            .child(
+                h_flex()
+                    .gap_3()
+                    .text_color(cx.theme().muted_foreground)
+                    .child("Press `right` to expand the folder, `left` to collapse.")
+                    .child(format!("Total Rows: {}", rows_count)),
+            )
+            .child(self.table.clone())
+    }
+}
@@ -1,11 +1,13 @@
+use std::rc::Rc;
+
 use crate::{
-    text::Text, v_flex, ActiveTheme, Disableable, IconName, Selectable, Sizable, Size,
-    StyledExt as _,
+    form::FormState, text::Text, v_flex, ActiveTheme, Disableable, IconName, Selectable, Sizable,
+    Size, StyledExt as _,
 };
 use gpui::{
     div, prelude::FluentBuilder as _, px, relative, rems, svg, AnyElement, App, Div, ElementId,
-    InteractiveElement, IntoElement, ParentElement, RenderOnce, StatefulInteractiveElement, Styled,
-    Window,
+    Entity, InteractiveElement, IntoElement, ParentElement, RenderOnce, SharedString,
+    StatefulInteractiveElement, Styled, Window,
 };
 
 /// A Checkbox element.
@@ -18,7 +20,8 @@ pub struct Checkbox {
     checked: bool,
     disabled: bool,
     size: Size,
-    on_click: Option<Box<dyn Fn(&bool, &mut Window, &mut App) + 'static>>,
+    on_click: Option<Rc<dyn Fn(&bool, &mut Window, &mut App) + 'static>>,
+    bind: Option<(Entity<FormState>, SharedString)>,
 }
 
 impl Checkbox {
@@ -32,6 +35,7 @@ impl Checkbox {
             disabled: false,
             size: Size::default(),
             on_click: None,
+            bind: None,
         }
     }
 
@@ -46,7 +50,13 @@ impl Checkbox {
     }
 
     pub fn on_click(mut self, handler: impl Fn(&bool, &mut Window, &mut App) + 'static) -> Self {
-        self.on_click = Some(Box::new(handler));
+        self.on_click = Some(Rc::new(handler));
+        self
+    }
+
+    /// Bind the checked state to the value field of the [`FormState`] by name.
+    pub fn bind(mut self, form: &Entity<FormState>, name: impl Into<SharedString>) -> Self {
+        self.bind = Some((form.clone(), name.into()));
         self
     }
 }
@@ -95,7 +105,13 @@ impl Sizable for Checkbox {
 }
 
 impl RenderOnce for Checkbox {
-    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+    fn render(mut self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+        if let Some((form, name)) = self.bind.take() {
+            let (checked, on_click) = FormState::bind_checked(&form, name, self.on_click, cx);
+            self.checked = checked;
+            self.on_click = Some(on_click);
+        }
+
         let (color, icon_color) = if self.disabled {
             (
                 cx.theme().primary.opacity(0.5),
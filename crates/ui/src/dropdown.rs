@@ -1,20 +1,26 @@
 use gpui::{
-    anchored, canvas, deferred, div, prelude::FluentBuilder, px, rems, AnyElement, App, AppContext,
-    Bounds, ClickEvent, Context, DismissEvent, ElementId, Empty, Entity, EventEmitter, FocusHandle,
-    Focusable, InteractiveElement, IntoElement, KeyBinding, Length, ParentElement, Pixels, Render,
-    RenderOnce, SharedString, StatefulInteractiveElement, Styled, Subscription, Task, WeakEntity,
-    Window,
+    actions, anchored, canvas, deferred, div, prelude::FluentBuilder, px, rems, AnyElement, App,
+    AppContext, Bounds, ClickEvent, Context, DismissEvent, ElementId, Empty, Entity, EventEmitter,
+    FocusHandle, Focusable, InteractiveElement, IntoElement, KeyBinding, Length, ParentElement,
+    Pixels, Render, RenderOnce, SharedString, StatefulInteractiveElement, Styled, Subscription,
+    Task, WeakEntity, Window,
 };
 use rust_i18n::t;
 
 use crate::{
     actions::{Cancel, Confirm, SelectNext, SelectPrev},
+    button::{Button, ButtonVariants as _},
+    form::{FormControl, FormState},
+    fuzzy::fuzzy_search,
     h_flex,
     input::clear_button,
     list::{List, ListDelegate, ListItem},
+    tag::Tag,
     v_flex, ActiveTheme, Disableable as _, Icon, IconName, Sizable, Size, StyleSized, StyledExt,
 };
 
+actions!(dropdown, [SelectAll, ClearAll]);
+
 #[derive(Clone)]
 pub enum ListEvent {
     /// Single click or move to selected row.
@@ -37,6 +43,8 @@ pub fn init(cx: &mut App) {
             Some(CONTEXT),
         ),
         KeyBinding::new("escape", Cancel, Some(CONTEXT)),
+        KeyBinding::new("secondary-a", SelectAll, Some(CONTEXT)),
+        KeyBinding::new("secondary-shift-a", ClearAll, Some(CONTEXT)),
     ])
 }
 
@@ -88,6 +96,27 @@ pub trait DropdownDelegate: Sized {
 
     fn get(&self, ix: usize) -> Option<&Self::Item>;
 
+    /// Return the number of sections, default is 1.
+    fn sections_count(&self) -> usize {
+        1
+    }
+
+    /// Return the number of items in the section, the items are indexed in order of the sections.
+    fn section_items_count(&self, _section: usize) -> usize {
+        self.len()
+    }
+
+    /// Return the title of the section header, default is None to hide the header.
+    fn section_title(&self, _section: usize) -> Option<SharedString> {
+        None
+    }
+
+    /// Return the byte offsets of the matched characters in the title of the item,
+    /// to highlight them in the list.
+    fn matched_positions(&self, _ix: usize) -> &[usize] {
+        &[]
+    }
+
     fn position<V>(&self, value: &V) -> Option<usize>
     where
         Self::Item: DropdownItem<Value = V>,
@@ -141,6 +170,18 @@ where
         self.delegate.len()
     }
 
+    fn sections_count(&self, _: &App) -> usize {
+        self.delegate.sections_count()
+    }
+
+    fn section_items_count(&self, section: usize, _: &App) -> usize {
+        self.delegate.section_items_count(section)
+    }
+
+    fn section_title(&self, section: usize, _: &App) -> Option<SharedString> {
+        self.delegate.section_title(section)
+    }
+
     fn render_item(
         &self,
         ix: usize,
@@ -150,18 +191,23 @@ where
         let selected = self
             .selected_index
             .map_or(false, |selected_index| selected_index == ix);
-        let size = self
-            .dropdown
-            .upgrade()
-            .map_or(Size::Medium, |dropdown| dropdown.read(cx).size);
 
         if let Some(item) = self.delegate.get(ix) {
+            let (size, checked) =
+                self.dropdown
+                    .upgrade()
+                    .map_or((Size::Medium, false), |dropdown| {
+                        let dropdown = dropdown.read(cx);
+                        (dropdown.size, dropdown.is_value_selected(item.value()))
+                    });
+
             let list_item = ListItem::new(("list-item", ix))
                 .check_icon(IconName::Check)
                 .selected(selected)
+                .confirmed(checked)
                 .input_text_size(size)
                 .list_size(size)
-                .child(div().whitespace_nowrap().child(item.title().to_string()));
+                .matched_title(item.title(), self.delegate.matched_positions(ix));
             Some(list_item)
         } else {
             None
@@ -179,18 +225,28 @@ where
     }
 
     fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<List<Self>>) {
-        let selected_value = self
+        let selected_item = self
             .selected_index
             .and_then(|ix| self.delegate.get(ix))
-            .map(|item| item.value().clone());
+            .map(|item| (item.value().clone(), item.title()));
+        let selected_value = selected_item.as_ref().map(|(value, _)| value.clone());
         let dropdown = self.dropdown.clone();
 
         cx.defer_in(window, move |_, window, cx| {
             _ = dropdown.update(cx, |this, cx| {
+                // Toggle the item and keep the menu open in multi-select mode.
+                if this.multiple.is_some() {
+                    if let Some((value, title)) = selected_item {
+                        this.toggle_value(value, title, window, cx);
+                    }
+                    return;
+                }
+
                 cx.emit(DropdownEvent::Confirm(selected_value.clone()));
                 this.selected_value = selected_value;
                 this.open = false;
                 this.focus(window, cx);
+                cx.notify();
             });
         });
     }
@@ -237,6 +293,12 @@ pub enum DropdownEvent<D: DropdownDelegate + 'static> {
     Confirm(Option<<D::Item as DropdownItem>::Value>),
 }
 
+/// The event of the multi-select mode, see [`DropdownState::multiple`].
+pub enum DropdownMultipleEvent<D: DropdownDelegate + 'static> {
+    /// The selected values have been changed.
+    Change(Vec<<D::Item as DropdownItem>::Value>),
+}
+
 /// State of the [`Dropdown`].
 pub struct DropdownState<D: DropdownDelegate + 'static> {
     focus_handle: FocusHandle,
@@ -246,7 +308,13 @@ pub struct DropdownState<D: DropdownDelegate + 'static> {
     /// Store the bounds of the input
     bounds: Bounds<Pixels>,
     open: bool,
+    invalid: bool,
     selected_value: Option<<D::Item as DropdownItem>::Value>,
+    /// The equality of the values, only set in multi-select mode.
+    multiple:
+        Option<fn(&<D::Item as DropdownItem>::Value, &<D::Item as DropdownItem>::Value) -> bool>,
+    /// The selected values and titles in multi-select mode.
+    selected_items: Vec<(<D::Item as DropdownItem>::Value, SharedString)>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -260,15 +328,18 @@ pub struct Dropdown<D: DropdownDelegate + 'static> {
     cleanable: bool,
     placeholder: Option<SharedString>,
     title_prefix: Option<SharedString>,
+    max_visible_tags: usize,
     empty: Option<AnyElement>,
     width: Length,
     menu_width: Length,
     disabled: bool,
 }
 
+/// A searchable list of items, the items are fuzzy matched and sorted by the score.
 pub struct SearchableVec<T> {
     items: Vec<T>,
     matched_items: Vec<T>,
+    matched_positions: Vec<Vec<usize>>,
 }
 
 impl<T: DropdownItem + Clone> SearchableVec<T> {
@@ -277,6 +348,7 @@ impl<T: DropdownItem + Clone> SearchableVec<T> {
         Self {
             items: items.clone(),
             matched_items: items,
+            matched_positions: vec![],
         }
     }
 }
@@ -306,16 +378,126 @@ impl<T: DropdownItem + Clone> DropdownDelegate for SearchableVec<T> {
         None
     }
 
+    fn matched_positions(&self, ix: usize) -> &[usize] {
+        self.matched_positions
+            .get(ix)
+            .map_or(&[], |positions| positions)
+    }
+
+    fn can_search(&self) -> bool {
+        true
+    }
+
+    fn perform_search(&mut self, query: &str, _window: &mut Window, _: &mut App) -> Task<()> {
+        (self.matched_items, self.matched_positions) =
+            fuzzy_search(query, self.items.iter(), |item| item.title())
+                .into_iter()
+                .map(|(item, matched)| (item.clone(), matched.positions))
+                .unzip();
+
+        Task::ready(())
+    }
+}
+
+/// A group of the dropdown items, shown under a section header.
+#[derive(Clone)]
+pub struct DropdownItemGroup<T> {
+    pub title: SharedString,
+    pub items: Vec<T>,
+}
+
+impl<T> DropdownItemGroup<T> {
+    pub fn new(title: impl Into<SharedString>, items: impl Into<Vec<T>>) -> Self {
+        Self {
+            title: title.into(),
+            items: items.into(),
+        }
+    }
+}
+
+/// A searchable list of grouped items, the matched items are kept in their groups.
+pub struct SearchableGroups<T> {
+    groups: Vec<DropdownItemGroup<T>>,
+    matched_groups: Vec<DropdownItemGroup<T>>,
+    /// The matched positions of the items in order of the groups.
+    matched_positions: Vec<Vec<usize>>,
+}
+
+impl<T: DropdownItem + Clone> SearchableGroups<T> {
+    pub fn new(groups: impl Into<Vec<DropdownItemGroup<T>>>) -> Self {
+        let groups = groups.into();
+        Self {
+            groups: groups.clone(),
+            matched_groups: groups,
+            matched_positions: vec![],
+        }
+    }
+}
+
+impl<T: DropdownItem + Clone> DropdownDelegate for SearchableGroups<T> {
+    type Item = T;
+
+    fn len(&self) -> usize {
+        self.matched_groups
+            .iter()
+            .map(|group| group.items.len())
+            .sum()
+    }
+
+    fn get(&self, mut ix: usize) -> Option<&Self::Item> {
+        for group in &self.matched_groups {
+            if ix < group.items.len() {
+                return group.items.get(ix);
+            }
+            ix -= group.items.len();
+        }
+
+        None
+    }
+
+    fn sections_count(&self) -> usize {
+        self.matched_groups.len()
+    }
+
+    fn section_items_count(&self, section: usize) -> usize {
+        self.matched_groups
+            .get(section)
+            .map_or(0, |group| group.items.len())
+    }
+
+    fn section_title(&self, section: usize) -> Option<SharedString> {
+        self.matched_groups
+            .get(section)
+            .map(|group| group.title.clone())
+    }
+
+    fn matched_positions(&self, ix: usize) -> &[usize] {
+        self.matched_positions
+            .get(ix)
+            .map_or(&[], |positions| positions)
+    }
+
     fn can_search(&self) -> bool {
         true
     }
 
     fn perform_search(&mut self, query: &str, _window: &mut Window, _: &mut App) -> Task<()> {
-        self.matched_items = self
-            .items
+        self.matched_positions.clear();
+        self.matched_groups = self
+            .groups
             .iter()
-            .filter(|item| item.title().to_lowercase().contains(&query.to_lowercase()))
-            .cloned()
+            .map(|group| {
+                let (items, positions): (Vec<_>, Vec<_>) =
+                    fuzzy_search(query, group.items.iter(), |item| item.title())
+                        .into_iter()
+                        .map(|(item, matched)| (item.clone(), matched.positions))
+                        .unzip();
+                self.matched_positions.extend(positions);
+                DropdownItemGroup {
+                    title: group.title.clone(),
+                    items,
+                }
+            })
             .collect();
 
         Task::ready(())
@@ -327,6 +509,7 @@ impl From<Vec<SharedString>> for SearchableVec<SharedString> {
         Self {
             items: items.clone(),
             matched_items: items,
+            matched_positions: vec![],
         }
     }
 }
@@ -370,7 +553,10 @@ where
             list,
             size: Size::Medium,
             selected_value: None,
+            multiple: None,
+            selected_items: vec![],
             open: false,
+            invalid: false,
             bounds: Bounds::default(),
             empty: None,
             _subscriptions,
@@ -388,6 +574,21 @@ where
         self
     }
 
+    /// Set to multi-select mode, the items are toggled without closing the menu,
+    /// and the selected values are shown as tags.
+    pub fn multiple(mut self) -> Self
+    where
+        <D::Item as DropdownItem>::Value: PartialEq,
+    {
+        self.multiple = Some(PartialEq::eq);
+        self
+    }
+
+    /// Returns true if in multi-select mode.
+    pub fn is_multiple(&self) -> bool {
+        self.multiple.is_some()
+    }
+
     pub fn set_selected_index(
         &mut self,
         selected_index: Option<usize>,
@@ -398,6 +599,7 @@ where
             list.set_selected_index(selected_index, window, cx);
         });
         self.update_selected_value(window, cx);
+        cx.notify();
     }
 
     pub fn set_selected_value(
@@ -428,6 +630,104 @@ where
         self.selected_value.as_ref()
     }
 
+    /// Returns the selected values, or the single selected value if not in multi-select mode.
+    pub fn selected_values(&self) -> Vec<<D::Item as DropdownItem>::Value> {
+        if self.multiple.is_some() {
+            self.selected_items
+                .iter()
+                .map(|(value, _)| value.clone())
+                .collect()
+        } else {
+            self.selected_value.iter().cloned().collect()
+        }
+    }
+
+    /// Set the selected values in multi-select mode, the values not in the items are ignored.
+    pub fn set_selected_values(
+        &mut self,
+        values: &[<D::Item as DropdownItem>::Value],
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(eq) = self.multiple else {
+            return;
+        };
+
+        let delegate = &self.list.read(cx).delegate().delegate;
+        self.selected_items = values
+            .iter()
+            .filter_map(|value| {
+                (0..delegate.len())
+                    .filter_map(|ix| delegate.get(ix))
+                    .find(|item| eq(item.value(), value))
+                    .map(|item| (value.clone(), item.title()))
+            })
+            .collect();
+        self.notify_selected_items(cx);
+    }
+
+    /// Select all the items in multi-select mode.
+    pub fn select_all(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(eq) = self.multiple else {
+            return;
+        };
+
+        let delegate = &self.list.read(cx).delegate().delegate;
+        extend_selected(
+            &mut self.selected_items,
+            (0..delegate.len())
+                .filter_map(|ix| delegate.get(ix))
+                .map(|item| (item.value().clone(), item.title())),
+            eq,
+        );
+        self.notify_selected_items(cx);
+    }
+
+    /// Clear all the selected values in multi-select mode.
+    pub fn clear_all(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        if self.multiple.is_none() {
+            return;
+        }
+
+        self.selected_items.clear();
+        self.notify_selected_items(cx);
+    }
+
+    fn is_value_selected(&self, value: &<D::Item as DropdownItem>::Value) -> bool {
+        self.multiple.map_or(false, |eq| {
+            self.selected_items.iter().any(|(v, _)| eq(v, value))
+        })
+    }
+
+    fn toggle_value(
+        &mut self,
+        value: <D::Item as DropdownItem>::Value,
+        title: SharedString,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(eq) = self.multiple else {
+            return;
+        };
+
+        toggle_selected(&mut self.selected_items, value, title, eq);
+        self.notify_selected_items(cx);
+    }
+
+    fn notify_selected_items(&mut self, cx: &mut Context<Self>) {
+        cx.emit(DropdownMultipleEvent::Change(self.selected_values()));
+        self.list.update(cx, |_, cx| cx.notify());
+        cx.notify();
+    }
+
+    fn on_select_all(&mut self, _: &SelectAll, window: &mut Window, cx: &mut Context<Self>) {
+        self.select_all(window, cx);
+    }
+
+    fn on_clear_all(&mut self, _: &ClearAll, window: &mut Window, cx: &mut Context<Self>) {
+        self.clear_all(window, cx);
+    }
+
     pub fn focus(&self, window: &mut Window, _: &mut App) {
         self.focus_handle.focus(window);
     }
@@ -492,6 +792,11 @@ where
     }
 
     fn clean(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        if self.multiple.is_some() {
+            self.clear_all(window, cx);
+            return;
+        }
+
         self.set_selected_index(None, window, cx);
         cx.emit(DropdownEvent::Confirm(None));
     }
@@ -529,6 +834,7 @@ where
             icon: None,
             cleanable: false,
             title_prefix: None,
+            max_visible_tags: 3,
             empty: None,
             width: Length::Auto,
             menu_width: Length::Auto,
@@ -570,6 +876,14 @@ where
         self
     }
 
+    /// Set the max number of the tags to show in multi-select mode, default: 3
+    ///
+    /// The rest of the selected values are shown as `+N more`.
+    pub fn max_visible_tags(mut self, max_visible_tags: usize) -> Self {
+        self.max_visible_tags = max_visible_tags;
+        self
+    }
+
     /// Set true to show the clear button when the input field is not empty.
     pub fn cleanable(mut self) -> Self {
         self.cleanable = true;
@@ -600,7 +914,39 @@ where
                 this.text_color(cx.theme().muted_foreground)
             });
 
-        let Some(selected_index) = &self.state.read(cx).selected_index(cx) else {
+        let state = self.state.read(cx);
+        if state.multiple.is_some() {
+            if state.selected_items.is_empty() {
+                return default_title;
+            }
+
+            let more = state
+                .selected_items
+                .len()
+                .saturating_sub(self.max_visible_tags);
+            return div().child(
+                h_flex()
+                    .gap_1()
+                    .overflow_hidden()
+                    .children(
+                        state
+                            .selected_items
+                            .iter()
+                            .take(self.max_visible_tags)
+                            .map(|(_, title)| Tag::secondary().small().child(title.clone())),
+                    )
+                    .when(more > 0, |this| {
+                        this.child(
+                            div()
+                                .flex_none()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(t!("Dropdown.more", count = more).to_string()),
+                        )
+                    }),
+            );
+        }
+
+        let Some(selected_index) = &state.selected_index(cx) else {
             return default_title;
         };
 
@@ -646,6 +992,10 @@ where
 }
 
 impl<D> EventEmitter<DropdownEvent<D>> for DropdownState<D> where D: DropdownDelegate + 'static {}
+impl<D> EventEmitter<DropdownMultipleEvent<D>> for DropdownState<D> where
+    D: DropdownDelegate + 'static
+{
+}
 impl<D> EventEmitter<DismissEvent> for DropdownState<D> where D: DropdownDelegate + 'static {}
 impl<D> Focusable for DropdownState<D>
 where
@@ -659,6 +1009,72 @@ where
         }
     }
 }
+
+impl<D> FormControl for DropdownState<D>
+where
+    D: DropdownDelegate + 'static,
+    <D::Item as DropdownItem>::Value: PartialEq + serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn field_value(&self, _: &App) -> serde_json::Value {
+        if self.multiple.is_some() {
+            return serde_json::to_value(self.selected_values()).unwrap_or_default();
+        }
+
+        self.selected_value
+            .as_ref()
+            .and_then(|value| serde_json::to_value(value).ok())
+            .unwrap_or_default()
+    }
+
+    fn set_field_value(
+        &mut self,
+        value: &serde_json::Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.multiple.is_some() {
+            let values: Vec<_> = serde_json::from_value(value.clone()).unwrap_or_default();
+            self.set_selected_values(&values, window, cx);
+            return;
+        }
+
+        match serde_json::from_value(value.clone()) {
+            Ok(Some(value)) => self.set_selected_value(&value, window, cx),
+            _ => self.set_selected_index(None, window, cx),
+        }
+    }
+
+    fn set_invalid(&mut self, invalid: bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.invalid = invalid;
+        cx.notify();
+    }
+
+    fn subscribe_change(
+        control: &Entity<Self>,
+        window: &mut Window,
+        cx: &mut Context<FormState>,
+        on_change: impl Fn(&mut FormState, &mut Window, &mut Context<FormState>) + 'static,
+    ) -> Subscription {
+        if control.read(cx).multiple.is_some() {
+            return cx.subscribe_in(
+                control,
+                window,
+                move |form, _, _: &DropdownMultipleEvent<D>, window, cx| {
+                    on_change(form, window, cx);
+                },
+            );
+        }
+
+        cx.subscribe_in(
+            control,
+            window,
+            move |form, _, _: &DropdownEvent<D>, window, cx| {
+                on_change(form, window, cx);
+            },
+        )
+    }
+}
+
 impl<D> Focusable for Dropdown<D>
 where
     D: DropdownDelegate,
@@ -688,10 +1104,17 @@ where
         }
 
         let state = self.state.read(cx);
-        let show_clean = self.cleanable && state.selected_index(cx).is_some();
+        let multiple = state.multiple.is_some();
+        let show_clean = self.cleanable
+            && if multiple {
+                !state.selected_items.is_empty()
+            } else {
+                state.selected_index(cx).is_some()
+            };
         let bounds = state.bounds;
         let allow_open = !(state.open || self.disabled);
         let outline_visible = state.open || is_focused && !self.disabled;
+        let invalid = state.invalid;
         let popup_radius = cx.theme().radius.min(px(8.));
 
         div()
@@ -702,6 +1125,10 @@ where
             .on_action(window.listener_for(&self.state, DropdownState::down))
             .on_action(window.listener_for(&self.state, DropdownState::enter))
             .on_action(window.listener_for(&self.state, DropdownState::escape))
+            .when(multiple, |this| {
+                this.on_action(window.listener_for(&self.state, DropdownState::on_select_all))
+                    .on_action(window.listener_for(&self.state, DropdownState::on_clear_all))
+            })
             .size_full()
             .relative()
             .input_text_size(self.size)
@@ -725,6 +1152,7 @@ where
                         Length::Auto => this.w_full(),
                     })
                     .when(outline_visible, |this| this.focused_border(cx))
+                    .when(invalid, |this| this.border_color(cx.theme().danger))
                     .input_size(self.size)
                     .when(allow_open, |this| {
                         this.on_click(window.listener_for(&self.state, DropdownState::toggle_menu))
@@ -803,7 +1231,40 @@ where
                                         .border_color(cx.theme().border)
                                         .rounded(popup_radius)
                                         .shadow_md()
-                                        .child(state.list.clone()),
+                                        .child(state.list.clone())
+                                        .when(multiple, |this| {
+                                            this.child(
+                                                h_flex()
+                                                    .justify_between()
+                                                    .p_1()
+                                                    .border_t_1()
+                                                    .border_color(cx.theme().border)
+                                                    .child(
+                                                        Button::new("select-all")
+                                                            .ghost()
+                                                            .xsmall()
+                                                            .label(t!("Dropdown.select_all"))
+                                                            .on_click(window.listener_for(
+                                                                &self.state,
+                                                                |this, _, window, cx| {
+                                                                    this.select_all(window, cx)
+                                                                },
+                                                            )),
+                                                    )
+                                                    .child(
+                                                        Button::new("clear-all")
+                                                            .ghost()
+                                                            .xsmall()
+                                                            .label(t!("Dropdown.clear_all"))
+                                                            .on_click(window.listener_for(
+                                                                &self.state,
+                                                                |this, _, window, cx| {
+                                                                    this.clear_all(window, cx)
+                                                                },
+                                                            )),
+                                                    ),
+                                            )
+                                        }),
                                 )
                                 .on_mouse_down_out(window.listener_for(
                                     &self.state,
@@ -818,3 +1279,79 @@ where
             })
     }
 }
+
+/// Toggle the value in the selected items, remove it if it is selected, otherwise append it.
+fn toggle_selected<V>(
+    selected: &mut Vec<(V, SharedString)>,
+    value: V,
+    title: SharedString,
+    eq: fn(&V, &V) -> bool,
+) {
+    match selected.iter().position(|(v, _)| eq(v, &value)) {
+        Some(ix) => {
+            selected.remove(ix);
+        }
+        None => selected.push((value, title)),
+    }
+}
+
+/// Append the items that are not selected yet to the selected items, keep the order of them.
+fn extend_selected<V>(
+    selected: &mut Vec<(V, SharedString)>,
+    items: impl IntoIterator<Item = (V, SharedString)>,
+    eq: fn(&V, &V) -> bool,
+) {
+    for (value, title) in items {
+        if !selected.iter().any(|(v, _)| eq(v, &value)) {
+            selected.push((value, title));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::SharedString;
+
+    use super::{extend_selected, toggle_selected};
+
+    fn values(selected: &[(i32, SharedString)]) -> Vec<i32> {
+        selected.iter().map(|(value, _)| *value).collect()
+    }
+
+    #[test]
+    fn test_toggle_selected() {
+        let mut selected = vec![];
+        toggle_selected(&mut selected, 2, "Two".into(), PartialEq::eq);
+        toggle_selected(&mut selected, 1, "One".into(), PartialEq::eq);
+        assert_eq!(values(&selected), vec![2, 1]);
+        assert_eq!(selected[0].1, "Two");
+
+        toggle_selected(&mut selected, 2, "Two".into(), PartialEq::eq);
+        assert_eq!(values(&selected), vec![1]);
+        toggle_selected(&mut selected, 1, "One".into(), PartialEq::eq);
+        assert!(selected.is_empty());
+    }
+
+    #[test]
+    fn test_extend_selected() {
+        let mut selected = vec![(3, SharedString::from("Three"))];
+        let items = (1..=4).map(|value| (value, SharedString::from(value.to_string())));
+        extend_selected(&mut selected, items.clone(), PartialEq::eq);
+        assert_eq!(values(&selected), vec![3, 1, 2, 4]);
+        // The title of the selected value is kept.
+        assert_eq!(selected[0].1, "Three");
+
+        // Nothing changed if all the items are selected.
+        extend_selected(&mut selected, items, PartialEq::eq);
+        assert_eq!(values(&selected), vec![3, 1, 2, 4]);
+
+        // Compare the values by the custom equality.
+        let mut selected = vec![(-2, SharedString::from("Minus Two"))];
+        extend_selected(
+            &mut selected,
+            [(2, "Two".into()), (1, "One".into())],
+            |a: &i32, b: &i32| a.abs() == b.abs(),
+        );
+        assert_eq!(values(&selected), vec![-2, 1]);
+    }
+}
@@ -2,12 +2,14 @@ use std::rc::{Rc, Weak};
 
 use gpui::{
     div, prelude::FluentBuilder as _, px, AlignItems, AnyElement, AnyView, App, Axis, Div, Element,
-    ElementId, FocusHandle, InteractiveElement as _, IntoElement, ParentElement, Pixels, Rems,
-    RenderOnce, SharedString, Styled, Window,
+    ElementId, Entity, FocusHandle, InteractiveElement as _, IntoElement, ParentElement, Pixels,
+    Rems, RenderOnce, SharedString, Styled, Window,
 };
 
 use crate::{h_flex, v_flex, ActiveTheme as _, AxisExt, FocusableCycle, Sizable, Size, StyledExt};
 
+use super::FormState;
+
 /// Create a new form with a vertical layout.
 pub fn v_form() -> Form {
     Form::vertical()
@@ -183,6 +185,9 @@ pub struct FormField {
     child: Div,
     visible: bool,
     required: bool,
+    error: Option<SharedString>,
+    /// The form state and the field name to show the validation error.
+    bind: Option<(Entity<FormState>, SharedString)>,
     /// Alignment of the form field.
     align_items: Option<AlignItems>,
     props: FieldProps,
@@ -198,6 +203,8 @@ impl FormField {
             child: div(),
             visible: true,
             required: false,
+            error: None,
+            bind: None,
             no_label_indent: false,
             focus_handle: None,
             align_items: None,
@@ -263,6 +270,21 @@ impl FormField {
         self
     }
 
+    /// Set the error message to show under the form field.
+    pub fn error(mut self, error: impl Into<SharedString>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+
+    /// Bind the form field to the field of the [`FormState`] by name.
+    ///
+    /// The validation error of the field will be shown under the form field,
+    /// and the `required` mark will be shown if it has the [`super::Rule::required`] rule.
+    pub fn bind(mut self, form: &Entity<FormState>, name: impl Into<SharedString>) -> Self {
+        self.bind = Some((form.clone(), name.into()));
+        self
+    }
+
     /// Set the focus handle for the form field.
     ///
     /// If not set, the form field will not be focusable.
@@ -320,6 +342,14 @@ impl RenderOnce for FormField {
         };
         let has_label = !self.no_label_indent;
 
+        let mut required = self.required;
+        let mut error = self.error;
+        if let Some((form, name)) = &self.bind {
+            let form = form.read(cx);
+            required |= form.is_required(name);
+            error = error.or_else(|| form.error(name).cloned());
+        }
+
         #[inline]
         fn wrap_div(layout: Axis) -> Div {
             if layout.is_vertical() {
@@ -379,14 +409,12 @@ impl RenderOnce for FormField {
                                 .gap_1()
                                 .items_center()
                                 .when_some(self.label, |this, builder| {
-                                    this.child(builder.render(window, cx)).when(
-                                        self.required,
-                                        |this| {
+                                    this.child(builder.render(window, cx))
+                                        .when(required, |this| {
                                             this.child(
                                                 div().text_color(cx.theme().danger).child("*"),
                                             )
-                                        },
-                                    )
+                                        })
                                 }),
                         )
                     })
@@ -402,12 +430,21 @@ impl RenderOnce for FormField {
                             wrap_label(label_width),
                         )
                     })
-                    .when_some(self.description, |this, builder| {
+                    .when(error.is_some() || self.description.is_some(), |this| {
                         this.child(
-                            div()
+                            v_flex()
+                                .gap_0p5()
                                 .text_xs()
-                                .text_color(cx.theme().muted_foreground)
-                                .child(builder.render(window, cx)),
+                                .when_some(error, |this, error| {
+                                    this.child(div().text_color(cx.theme().danger).child(error))
+                                })
+                                .when_some(self.description, |this, builder| {
+                                    this.child(
+                                        div()
+                                            .text_color(cx.theme().muted_foreground)
+                                            .child(builder.render(window, cx)),
+                                    )
+                                }),
                         )
                     }),
             )
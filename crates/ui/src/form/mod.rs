@@ -0,0 +1,10 @@
+mod form;
+mod schema;
+mod schema_form;
+mod state;
+mod validation;
+
+pub use form::*;
+pub use schema_form::*;
+pub use state::*;
+pub use validation::*;
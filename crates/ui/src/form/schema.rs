@@ -0,0 +1,446 @@
+use gpui::SharedString;
+use schemars::schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+
+/// The max depth of the nested fields, to stop the recursive types.
+const MAX_DEPTH: usize = 16;
+
+/// The widget kind of a schema field.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum FieldKind {
+    Text,
+    Number {
+        min: Option<f64>,
+        max: Option<f64>,
+        /// The value must be greater than it, only for the non-integer number.
+        exclusive_min: Option<f64>,
+        /// The value must be less than it, only for the non-integer number.
+        exclusive_max: Option<f64>,
+        step: Option<f64>,
+        integer: bool,
+    },
+    Boolean,
+    /// The values and the titles of the options.
+    Enum(Vec<(Value, SharedString)>),
+    Date,
+    Object(Vec<SchemaField>),
+    Array(Box<SchemaField>),
+}
+
+/// A field parsed from the JSON Schema.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct SchemaField {
+    pub(super) name: SharedString,
+    pub(super) title: SharedString,
+    pub(super) description: Option<SharedString>,
+    pub(super) default: Option<Value>,
+    /// The field is in the `required` list of the parent object.
+    pub(super) required: bool,
+    pub(super) min_length: Option<usize>,
+    pub(super) max_length: Option<usize>,
+    pub(super) pattern: Option<String>,
+    pub(super) kind: FieldKind,
+}
+
+impl SchemaField {
+    /// Parse the root schema, return `None` if the schema is not supported.
+    ///
+    /// The `$ref` to the definitions are resolved, and the unsupported properties are skipped.
+    pub(super) fn parse(root: &RootSchema) -> Option<Self> {
+        Parser { root }.parse_field("", &root.schema, true, 0)
+    }
+}
+
+struct Parser<'a> {
+    root: &'a RootSchema,
+}
+
+impl Parser<'_> {
+    fn parse_schema(
+        &self,
+        name: &str,
+        schema: &Schema,
+        required: bool,
+        depth: usize,
+    ) -> Option<SchemaField> {
+        match schema {
+            Schema::Object(object) => self.parse_field(name, object, required, depth),
+            Schema::Bool(_) => None,
+        }
+    }
+
+    fn parse_field(
+        &self,
+        name: &str,
+        outer: &SchemaObject,
+        required: bool,
+        depth: usize,
+    ) -> Option<SchemaField> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let object = self.resolve(outer)?;
+
+        // The metadata of the property takes precedence over the referenced definition.
+        let metadata = |f: fn(&schemars::schema::Metadata) -> Option<&String>| {
+            [outer, object]
+                .into_iter()
+                .find_map(|schema| schema.metadata.as_deref().and_then(f))
+                .map(|text| SharedString::from(text.trim().to_string()))
+        };
+        let default = [outer, object]
+            .into_iter()
+            .find_map(|schema| schema.metadata.as_ref().and_then(|m| m.default.clone()));
+
+        let string = object.string.as_deref();
+        Some(SchemaField {
+            name: name.to_string().into(),
+            title: metadata(|m| m.title.as_ref()).unwrap_or_else(|| title_case(name).into()),
+            description: metadata(|m| m.description.as_ref()),
+            default,
+            required,
+            min_length: string.and_then(|s| s.min_length).map(|len| len as usize),
+            max_length: string.and_then(|s| s.max_length).map(|len| len as usize),
+            pattern: string.and_then(|s| s.pattern.clone()),
+            kind: self.parse_kind(object, depth)?,
+        })
+    }
+
+    fn parse_kind(&self, object: &SchemaObject, depth: usize) -> Option<FieldKind> {
+        if let Some(options) = self.parse_options(object) {
+            return Some(FieldKind::Enum(options));
+        }
+
+        Some(match instance_type(object)? {
+            InstanceType::Boolean => FieldKind::Boolean,
+            InstanceType::Integer | InstanceType::Number => {
+                let integer = instance_type(object) == Some(InstanceType::Integer);
+                let number = object.number.as_deref();
+                let mut min = number.and_then(|n| n.minimum);
+                let mut max = number.and_then(|n| n.maximum);
+                let mut exclusive_min = number.and_then(|n| n.exclusive_minimum);
+                let mut exclusive_max = number.and_then(|n| n.exclusive_maximum);
+                // The exclusive bound of the integer is the next integer, e.g. `> 0` is `>= 1`.
+                if integer {
+                    if let Some(bound) = exclusive_min.take() {
+                        min =
+                            Some(min.map_or(bound.floor() + 1., |min| min.max(bound.floor() + 1.)));
+                    }
+                    if let Some(bound) = exclusive_max.take() {
+                        max = Some(max.map_or(bound.ceil() - 1., |max| max.min(bound.ceil() - 1.)));
+                    }
+                }
+
+                FieldKind::Number {
+                    min,
+                    max,
+                    exclusive_min,
+                    exclusive_max,
+                    step: number.and_then(|n| n.multiple_of).or(integer.then_some(1.)),
+                    integer,
+                }
+            }
+            InstanceType::String => match object.format.as_deref() {
+                Some("date") => FieldKind::Date,
+                _ => FieldKind::Text,
+            },
+            InstanceType::Object => {
+                let validation = object.object.as_deref()?;
+                FieldKind::Object(
+                    validation
+                        .properties
+                        .iter()
+                        .filter_map(|(name, schema)| {
+                            let required = validation.required.contains(name);
+                            self.parse_schema(name, schema, required, depth + 1)
+                        })
+                        .collect(),
+                )
+            }
+            InstanceType::Array => {
+                let items = object.array.as_deref()?.items.as_ref()?;
+                let SingleOrVec::Single(item) = items else {
+                    return None;
+                };
+                FieldKind::Array(Box::new(self.parse_schema("", item, true, depth + 1)?))
+            }
+            InstanceType::Null => return None,
+        })
+    }
+
+    /// Parse the options of the `enum`, or the `oneOf` of the `const` or `enum` variants,
+    /// e.g. the unit variants with doc comments.
+    ///
+    /// The options are in the order of the schema, note that the derived schema of `schemars`
+    /// groups the unit variants without doc comments before the variants with doc comments.
+    fn parse_options(&self, object: &SchemaObject) -> Option<Vec<(Value, SharedString)>> {
+        if let Some(values) = &object.enum_values {
+            let options: Vec<_> = values
+                .iter()
+                .filter(|value| !value.is_null())
+                .map(|value| (value.clone(), value_title(value)))
+                .collect();
+            return (!options.is_empty()).then_some(options);
+        }
+
+        let variants = object.subschemas.as_deref()?.one_of.as_ref()?;
+        let mut options = vec![];
+        for schema in variants.iter().filter(|schema| !is_null(schema)) {
+            let Schema::Object(variant) = schema else {
+                return None;
+            };
+            let values = match (&variant.const_value, &variant.enum_values) {
+                (Some(value), _) => std::slice::from_ref(value),
+                (None, Some(values)) => values.as_slice(),
+                _ => return None,
+            };
+
+            // The title of the variant is only for the single value.
+            let title = variant
+                .metadata
+                .as_ref()
+                .and_then(|m| m.title.clone())
+                .filter(|_| values.len() == 1);
+            for value in values {
+                let title = match &title {
+                    Some(title) => title.clone().into(),
+                    None => value_title(value),
+                };
+                options.push((value.clone(), title));
+            }
+        }
+        (!options.is_empty()).then_some(options)
+    }
+
+    /// Follow the `$ref`, and unwrap the single `allOf`, or the `anyOf` with `null` of the `Option<T>`.
+    fn resolve<'b>(&'b self, mut object: &'b SchemaObject) -> Option<&'b SchemaObject> {
+        for _ in 0..MAX_DEPTH {
+            if let Some(reference) = &object.reference {
+                let name = reference.rsplit('/').next()?;
+                let Schema::Object(definition) = self.root.definitions.get(name)? else {
+                    return None;
+                };
+                object = definition;
+                continue;
+            }
+
+            if let Some(subschemas) = object.subschemas.as_deref() {
+                if let Some(schemas) = subschemas.all_of.as_ref().or(subschemas.any_of.as_ref()) {
+                    let mut schemas = schemas.iter().filter(|schema| !is_null(schema));
+                    if let (Some(Schema::Object(inner)), None) = (schemas.next(), schemas.next()) {
+                        object = inner;
+                        continue;
+                    }
+                }
+            }
+
+            return Some(object);
+        }
+
+        None
+    }
+}
+
+/// Return the type of the schema, the `null` of the `Option<T>` is ignored.
+fn instance_type(object: &SchemaObject) -> Option<InstanceType> {
+    match object.instance_type.as_ref()? {
+        SingleOrVec::Single(ty) => Some(**ty),
+        SingleOrVec::Vec(types) => {
+            let mut types = types.iter().filter(|ty| **ty != InstanceType::Null);
+            match (types.next(), types.next()) {
+                (Some(ty), None) => Some(*ty),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn is_null(schema: &Schema) -> bool {
+    match schema {
+        Schema::Object(object) => {
+            object.instance_type == Some(SingleOrVec::Single(Box::new(InstanceType::Null)))
+        }
+        Schema::Bool(_) => false,
+    }
+}
+
+fn value_title(value: &Value) -> SharedString {
+    match value {
+        Value::String(text) => text.clone().into(),
+        value => value.to_string().into(),
+    }
+}
+
+/// Convert the field name to the title, e.g. `max_connections` to `Max connections`.
+fn title_case(name: &str) -> String {
+    let name = name.replace(['_', '-'], " ");
+    let mut chars = name.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::{schema_for, JsonSchema};
+    use serde_json::json;
+
+    use super::{title_case, FieldKind, SchemaField};
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    enum Level {
+        Debug,
+        /// The default level.
+        Info,
+        Error,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct Server {
+        /// Server Host
+        ///
+        /// The host name or IP address.
+        host: String,
+        #[validate(range(min = 1, max = 65535))]
+        port: u16,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct Config {
+        #[validate(length(min = 1, max = 20))]
+        name: String,
+        nickname: Option<String>,
+        enabled: bool,
+        #[schemars(default = "default_ratio")]
+        ratio: f64,
+        level: Level,
+        server: Server,
+        tags: Vec<String>,
+    }
+
+    fn default_ratio() -> f64 {
+        0.5
+    }
+
+    fn field<'a>(fields: &'a [SchemaField], name: &str) -> &'a SchemaField {
+        fields
+            .iter()
+            .find(|field| field.name.as_ref() == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let root = SchemaField::parse(&schema_for!(Config)).unwrap();
+        let FieldKind::Object(fields) = &root.kind else {
+            panic!("expected object");
+        };
+
+        let name = field(fields, "name");
+        assert_eq!(name.kind, FieldKind::Text);
+        assert_eq!(name.title.as_ref(), "Name");
+        assert!(name.required);
+        assert_eq!((name.min_length, name.max_length), (Some(1), Some(20)));
+
+        let nickname = field(fields, "nickname");
+        assert_eq!(nickname.kind, FieldKind::Text);
+        assert!(!nickname.required);
+
+        assert_eq!(field(fields, "enabled").kind, FieldKind::Boolean);
+
+        let ratio = field(fields, "ratio");
+        assert!(matches!(
+            ratio.kind,
+            FieldKind::Number { integer: false, .. }
+        ));
+        assert_eq!(ratio.default, Some(json!(0.5)));
+
+        // The `Info` with the doc comment is after the other variants in the derived schema.
+        assert_eq!(
+            field(fields, "level").kind,
+            FieldKind::Enum(vec![
+                (json!("Debug"), "Debug".into()),
+                (json!("Error"), "Error".into()),
+                (json!("Info"), "Info".into()),
+            ])
+        );
+
+        let server = field(fields, "server");
+        let FieldKind::Object(server_fields) = &server.kind else {
+            panic!("expected object");
+        };
+        let host = field(server_fields, "host");
+        assert_eq!(host.title.as_ref(), "Server Host");
+        assert_eq!(
+            host.description.as_ref().map(|s| s.as_ref()),
+            Some("The host name or IP address.")
+        );
+        assert_eq!(
+            field(server_fields, "port").kind,
+            FieldKind::Number {
+                min: Some(1.),
+                max: Some(65535.),
+                exclusive_min: None,
+                exclusive_max: None,
+                step: Some(1.),
+                integer: true,
+            }
+        );
+
+        let FieldKind::Array(item) = &field(fields, "tags").kind else {
+            panic!("expected array");
+        };
+        assert_eq!(item.kind, FieldKind::Text);
+    }
+
+    #[test]
+    fn test_parse_exclusive_bounds() {
+        let root = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "count": { "type": "integer", "exclusiveMinimum": 0, "exclusiveMaximum": 10.5 },
+                "ratio": { "type": "number", "minimum": 0, "exclusiveMaximum": 1 },
+            },
+        }))
+        .unwrap();
+        let root = SchemaField::parse(&root).unwrap();
+        let FieldKind::Object(fields) = &root.kind else {
+            panic!("expected object");
+        };
+
+        assert_eq!(
+            field(fields, "count").kind,
+            FieldKind::Number {
+                min: Some(1.),
+                max: Some(10.),
+                exclusive_min: None,
+                exclusive_max: None,
+                step: Some(1.),
+                integer: true,
+            }
+        );
+        assert_eq!(
+            field(fields, "ratio").kind,
+            FieldKind::Number {
+                min: Some(0.),
+                max: None,
+                exclusive_min: None,
+                exclusive_max: Some(1.),
+                step: None,
+                integer: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_title_case() {
+        assert_eq!(title_case("max_connections"), "Max connections");
+        assert_eq!(title_case("dry-run"), "Dry run");
+        assert_eq!(title_case("name"), "Name");
+        assert_eq!(title_case(""), "");
+    }
+}
@@ -0,0 +1,573 @@
+use std::{collections::HashSet, rc::Rc};
+
+use gpui::{
+    div, prelude::FluentBuilder as _, AnyElement, App, AppContext as _, Axis, Context, Entity,
+    EventEmitter, IntoElement, ParentElement as _, Render, SharedString, Styled as _, Subscription,
+    Window,
+};
+use regex::Regex;
+use rust_i18n::t;
+use schemars::schema::RootSchema;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    accordion::Accordion,
+    button::{Button, ButtonVariants as _},
+    date_picker::{DatePicker, DatePickerState},
+    dropdown::{Dropdown, DropdownState},
+    h_flex,
+    input::{InputState, NumberInput, TextInput},
+    switch::Switch,
+    v_flex, IconName, Sizable as _,
+};
+
+use super::{
+    form_field,
+    schema::{FieldKind, SchemaField},
+    state::is_same_value,
+    v_form, FieldError, FormControl, FormEvent, FormField, FormState, Rule,
+};
+
+enum Control {
+    Text(Entity<InputState>),
+    Number(Entity<InputState>),
+    /// The value is kept by the value field of the [`FormState`].
+    Switch,
+    Dropdown(Entity<DropdownState<Vec<SharedString>>>),
+    Date(Entity<DatePickerState>),
+    Object(Vec<Node>),
+    Array {
+        rows: Vec<Node>,
+        next_id: usize,
+    },
+}
+
+struct Node {
+    /// The path of the field, e.g. `server.port`, `tags.0`,
+    /// it is also the field name in the [`FormState`].
+    path: SharedString,
+    field: Rc<SchemaField>,
+    control: Control,
+}
+
+impl Node {
+    fn find_mut(&mut self, path: &str) -> Option<&mut Node> {
+        if self.path.as_ref() == path {
+            return Some(self);
+        }
+
+        match &mut self.control {
+            Control::Object(nodes) | Control::Array { rows: nodes, .. } => {
+                nodes.iter_mut().find_map(|node| node.find_mut(path))
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the value of the node, `None` if the value is empty and the field is not required,
+    /// so the default value of the serde field is used.
+    fn value(&self, form: &FormState, cx: &App) -> Option<Value> {
+        let value = match &self.control {
+            Control::Text(_) | Control::Number(_) | Control::Switch | Control::Date(_) => {
+                form.value(&self.path, cx).unwrap_or_default()
+            }
+            Control::Dropdown(state) => {
+                let FieldKind::Enum(options) = &self.field.kind else {
+                    return None;
+                };
+                state
+                    .read(cx)
+                    .selected_index(cx)
+                    .and_then(|ix| options.get(ix))
+                    .map(|(value, _)| value.clone())
+                    .unwrap_or_default()
+            }
+            Control::Object(nodes) => Value::Object(
+                nodes
+                    .iter()
+                    .filter_map(|node| Some((node.field.name.to_string(), node.value(form, cx)?)))
+                    .collect::<Map<_, _>>(),
+            ),
+            Control::Array { rows, .. } => Value::Array(
+                rows.iter()
+                    .map(|row| row.value(form, cx).unwrap_or_default())
+                    .collect(),
+            ),
+        };
+
+        let empty = value.is_null() || value.as_str() == Some("");
+        (!empty || self.field.required).then_some(value)
+    }
+}
+
+/// A form generated from the JSON Schema, e.g. of a config struct deriving `JsonSchema`.
+///
+/// The widget of the field is chosen by the schema type:
+///
+/// - `string`: [`TextInput`], or [`DatePicker`] with `format: date`.
+/// - `integer`, `number`: [`NumberInput`] with the `minimum` and `maximum`.
+/// - `boolean`: [`Switch`].
+/// - `enum`: [`Dropdown`].
+/// - `object`: a section in the [`Accordion`].
+/// - `array`: the rows can be added and removed.
+///
+/// The `title` and `description` of the schema are used for the label and description of the field.
+///
+/// ```ignore
+/// let form = cx.new(|cx| SchemaForm::new(&schema_for!(Config), window, cx));
+/// form.update(cx, |form, cx| form.load(&config, window, cx))?;
+///
+/// let config: Config = form.read(cx).values_as(cx)?;
+/// ```
+///
+/// The properties are sorted by name, unless the `preserve_order` feature of `schemars` is enabled.
+pub struct SchemaForm {
+    form: Entity<FormState>,
+    root: Option<Node>,
+    initial_value: Value,
+    /// The paths of the collapsed object sections.
+    collapsed: HashSet<SharedString>,
+    layout: Axis,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SchemaForm {
+    pub fn new(schema: &RootSchema, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let form = cx.new(|cx| FormState::new(window, cx));
+        let _subscriptions = vec![cx.subscribe(&form, |_, _, event: &FormEvent, cx| {
+            cx.emit(event.clone());
+            cx.notify();
+        })];
+
+        let root = SchemaField::parse(schema)
+            .map(|field| Self::build_node(&form, "".into(), Rc::new(field), window, cx));
+
+        let mut this = Self {
+            form,
+            root,
+            initial_value: Value::Null,
+            collapsed: HashSet::new(),
+            layout: Axis::Vertical,
+            _subscriptions,
+        };
+        this.mark_clean(cx);
+        this
+    }
+
+    /// Set the layout of the form, default is [`Axis::Vertical`].
+    pub fn layout(mut self, layout: Axis) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Return the [`FormState`] of the fields, the field names are the paths, e.g. `server.port`.
+    pub fn form_state(&self) -> &Entity<FormState> {
+        &self.form
+    }
+
+    /// Return the values of the form, in the structure of the schema.
+    pub fn values(&self, cx: &App) -> Value {
+        let form = self.form.read(cx);
+        self.root
+            .as_ref()
+            .and_then(|root| root.value(form, cx))
+            .unwrap_or_default()
+    }
+
+    /// Deserialize the values of the form to `T`.
+    pub fn values_as<T: DeserializeOwned>(&self, cx: &App) -> serde_json::Result<T> {
+        serde_json::from_value(self.values(cx))
+    }
+
+    /// Load the values from the serializable data, the loaded values become the initial values.
+    pub fn load<T: Serialize>(
+        &mut self,
+        data: &T,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> serde_json::Result<()> {
+        let value = serde_json::to_value(data)?;
+        self.set_values(&value, window, cx);
+        self.mark_clean(cx);
+        Ok(())
+    }
+
+    /// Return true if the values are different from the initial values.
+    pub fn is_dirty(&self, cx: &App) -> bool {
+        !is_same_value(&self.values(cx), &self.initial_value)
+    }
+
+    /// Restore the initial values, and clear the errors.
+    pub fn reset(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let value = self.initial_value.clone();
+        self.set_values(&value, window, cx);
+    }
+
+    /// Use the current values as the initial values, e.g. after the values are saved.
+    pub fn mark_clean(&mut self, cx: &mut Context<Self>) {
+        self.initial_value = self.values(cx);
+        self.form.update(cx, |form, cx| form.mark_clean(cx));
+        cx.notify();
+    }
+
+    /// Validate all fields and return the errors.
+    pub fn validate(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Vec<FieldError> {
+        self.form.update(cx, |form, cx| form.validate(window, cx))
+    }
+
+    fn set_values(&mut self, value: &Value, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(root) = self.root.as_mut() {
+            Self::set_node_value(&self.form, root, value, window, cx);
+        }
+        self.form
+            .update(cx, |form, cx| form.clear_errors(window, cx));
+        cx.notify();
+    }
+
+    fn build_node(
+        form: &Entity<FormState>,
+        path: SharedString,
+        field: Rc<SchemaField>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Node {
+        let control = match &field.kind {
+            FieldKind::Text => {
+                let state = cx.new(|cx| InputState::new(window, cx));
+                Self::bind_field(form, &path, &state, &field, window, cx);
+                Control::Text(state)
+            }
+            FieldKind::Number {
+                min,
+                max,
+                step,
+                integer,
+                ..
+            } => {
+                let state = cx.new(|cx| {
+                    let mut state = InputState::new(window, cx);
+                    if let Some(min) = min {
+                        state = state.min_value(*min);
+                    }
+                    if let Some(max) = max {
+                        state = state.max_value(*max);
+                    }
+                    if let Some(step) = step {
+                        state = state.step(*step);
+                    }
+                    if *integer {
+                        state = state.precision(0);
+                    }
+                    state
+                });
+                Self::bind_field(form, &path, &state, &field, window, cx);
+                Control::Number(state)
+            }
+            FieldKind::Boolean => {
+                form.update(cx, |form, cx| {
+                    form.add_value_field(path.clone(), false, [], cx);
+                });
+                Control::Switch
+            }
+            FieldKind::Enum(options) => {
+                let titles: Vec<SharedString> =
+                    options.iter().map(|(_, title)| title.clone()).collect();
+                let state = cx.new(|cx| DropdownState::new(titles, None, window, cx));
+                Self::bind_field(form, &path, &state, &field, window, cx);
+                Control::Dropdown(state)
+            }
+            FieldKind::Date => {
+                let state = cx.new(|cx| DatePickerState::new(window, cx));
+                Self::bind_field(form, &path, &state, &field, window, cx);
+                Control::Date(state)
+            }
+            FieldKind::Object(fields) => Control::Object(
+                fields
+                    .iter()
+                    .map(|child| {
+                        let path = join_path(&path, &child.name);
+                        Self::build_node(form, path, Rc::new(child.clone()), window, cx)
+                    })
+                    .collect(),
+            ),
+            FieldKind::Array(_) => Control::Array {
+                rows: vec![],
+                next_id: 0,
+            },
+        };
+
+        let mut node = Node {
+            path,
+            field,
+            control,
+        };
+        if let Some(default) = node.field.default.clone() {
+            Self::set_node_value(form, &mut node, &default, window, cx);
+        }
+        node
+    }
+
+    fn bind_field<T: FormControl>(
+        form: &Entity<FormState>,
+        path: &SharedString,
+        state: &Entity<T>,
+        field: &SchemaField,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut rules = vec![];
+        let required = match field.kind {
+            // The empty text is a valid string, only required with the min length.
+            FieldKind::Text => field.min_length.is_some_and(|len| len > 0),
+            _ => field.required,
+        };
+        if required {
+            rules.push(Rule::required());
+        }
+        if let Some(len) = field.min_length {
+            rules.push(Rule::min_length(len));
+        }
+        if let Some(len) = field.max_length {
+            rules.push(Rule::max_length(len));
+        }
+        if let Some(pattern) = field.pattern.as_ref().and_then(|p| Regex::new(p).ok()) {
+            rules.push(Rule::pattern(pattern));
+        }
+        // The input only clamps the value to the inclusive bounds, check the exclusive bounds here.
+        if let FieldKind::Number {
+            exclusive_min,
+            exclusive_max,
+            ..
+        } = field.kind
+        {
+            if let Some(bound) = exclusive_min {
+                rules.push(Rule::custom(move |value| match value.as_f64() {
+                    Some(value) if value <= bound => {
+                        Err(t!("Form.greater_than", value = bound).into())
+                    }
+                    _ => Ok(()),
+                }));
+            }
+            if let Some(bound) = exclusive_max {
+                rules.push(Rule::custom(move |value| match value.as_f64() {
+                    Some(value) if value >= bound => {
+                        Err(t!("Form.less_than", value = bound).into())
+                    }
+                    _ => Ok(()),
+                }));
+            }
+        }
+
+        form.update(cx, |form, cx| {
+            form.add_field(path.clone(), state, rules, window, cx);
+        });
+    }
+
+    fn set_node_value(
+        form: &Entity<FormState>,
+        node: &mut Node,
+        value: &Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match &mut node.control {
+            Control::Text(_) | Control::Number(_) | Control::Date(_) | Control::Switch => {
+                let value = match (&node.control, value) {
+                    (Control::Switch, Value::Null) => Value::Bool(false),
+                    _ => value.clone(),
+                };
+                form.update(cx, |form, cx| form.set_value(&node.path, value, window, cx));
+            }
+            Control::Dropdown(state) => {
+                let FieldKind::Enum(options) = &node.field.kind else {
+                    return;
+                };
+                let ix = options.iter().position(|(option, _)| option == value);
+                state.update(cx, |state, cx| state.set_selected_index(ix, window, cx));
+            }
+            Control::Object(nodes) => {
+                for node in nodes.iter_mut() {
+                    let value = value.get(node.field.name.as_ref()).unwrap_or(&Value::Null);
+                    Self::set_node_value(form, node, value, window, cx);
+                }
+            }
+            Control::Array { rows, next_id } => {
+                for row in rows.drain(..) {
+                    Self::remove_fields(form, &row, window, cx);
+                }
+
+                let FieldKind::Array(item) = &node.field.kind else {
+                    return;
+                };
+                for value in value.as_array().into_iter().flatten() {
+                    let path = join_path(&node.path, &next_id.to_string());
+                    *next_id += 1;
+                    let mut row =
+                        Self::build_node(form, path, Rc::new((**item).clone()), window, cx);
+                    Self::set_node_value(form, &mut row, value, window, cx);
+                    rows.push(row);
+                }
+            }
+        }
+    }
+
+    /// Remove the fields of the node and the children from the [`FormState`].
+    fn remove_fields(form: &Entity<FormState>, node: &Node, window: &mut Window, cx: &mut App) {
+        match &node.control {
+            Control::Object(nodes) | Control::Array { rows: nodes, .. } => {
+                for node in nodes {
+                    Self::remove_fields(form, node, window, cx);
+                }
+            }
+            _ => form.update(cx, |form, cx| form.remove_field(&node.path, window, cx)),
+        }
+    }
+
+    fn add_row(&mut self, path: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(node) = self.root.as_mut().and_then(|root| root.find_mut(path)) else {
+            return;
+        };
+        let (FieldKind::Array(item), Control::Array { rows, next_id }) =
+            (&node.field.kind, &mut node.control)
+        else {
+            return;
+        };
+
+        let path = join_path(&node.path, &next_id.to_string());
+        *next_id += 1;
+        let row = Self::build_node(&self.form, path, Rc::new((**item).clone()), window, cx);
+        rows.push(row);
+
+        cx.emit(FormEvent::Change(node.path.clone()));
+        cx.notify();
+    }
+
+    fn remove_row(&mut self, path: &str, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(node) = self.root.as_mut().and_then(|root| root.find_mut(path)) else {
+            return;
+        };
+        let Control::Array { rows, .. } = &mut node.control else {
+            return;
+        };
+        if ix >= rows.len() {
+            return;
+        }
+
+        let row = rows.remove(ix);
+        Self::remove_fields(&self.form, &row, window, cx);
+
+        cx.emit(FormEvent::Change(node.path.clone()));
+        cx.notify();
+    }
+
+    /// Render the widget of the node without the label.
+    fn render_control(&self, node: &Node, cx: &mut Context<Self>) -> AnyElement {
+        match &node.control {
+            Control::Text(state) => TextInput::new(state).into_any_element(),
+            Control::Number(state) => NumberInput::new(state).into_any_element(),
+            Control::Switch => Switch::new(SharedString::from(format!("switch-{}", node.path)))
+                .bind(&self.form, node.path.clone())
+                .into_any_element(),
+            Control::Dropdown(state) => Dropdown::new(state).into_any_element(),
+            Control::Date(state) => DatePicker::new(state).into_any_element(),
+            Control::Object(nodes) => v_form()
+                .layout(self.layout)
+                .children(nodes.iter().map(|node| self.render_field(node, cx)))
+                .into_any_element(),
+            Control::Array { rows, .. } => {
+                let path = node.path.clone();
+                v_flex()
+                    .w_full()
+                    .gap_2()
+                    .children(rows.iter().enumerate().map(|(ix, row)| {
+                        let path = path.clone();
+                        h_flex()
+                            .gap_2()
+                            .items_start()
+                            .child(div().flex_1().child(self.render_control(row, cx)))
+                            .child(
+                                Button::new(SharedString::from(format!("remove-{}", row.path)))
+                                    .ghost()
+                                    .small()
+                                    .icon(IconName::Minus)
+                                    .on_click(cx.listener(move |this, _, window, cx| {
+                                        this.remove_row(&path, ix, window, cx);
+                                    })),
+                            )
+                    }))
+                    .child(
+                        div().child(
+                            Button::new(SharedString::from(format!("add-{}", path)))
+                                .small()
+                                .icon(IconName::Plus)
+                                .label(t!("Form.add_item"))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.add_row(&path, window, cx);
+                                })),
+                        ),
+                    )
+                    .into_any_element()
+            }
+        }
+    }
+
+    fn render_field(&self, node: &Node, cx: &mut Context<Self>) -> FormField {
+        let field = &node.field;
+        let form_field = form_field().when_some(field.description.clone(), |this, description| {
+            this.description(description)
+        });
+
+        if !matches!(node.control, Control::Object(_)) {
+            return form_field
+                .label(field.title.clone())
+                .bind(&self.form, node.path.clone())
+                .child(self.render_control(node, cx));
+        }
+
+        let path = node.path.clone();
+        let open = !self.collapsed.contains(&path);
+        form_field.no_label_indent().child(
+            Accordion::new(SharedString::from(format!("section-{}", path)))
+                .item(|item| {
+                    item.open(open)
+                        .title(field.title.clone())
+                        .content(self.render_control(node, cx))
+                })
+                .on_toggle_click(cx.listener(move |this, open_ixs: &[usize], _, cx| {
+                    if open_ixs.is_empty() {
+                        this.collapsed.insert(path.clone());
+                    } else {
+                        this.collapsed.remove(&path);
+                    }
+                    cx.notify();
+                })),
+        )
+    }
+}
+
+impl EventEmitter<FormEvent> for SchemaForm {}
+
+impl Render for SchemaForm {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let form = v_form().layout(self.layout);
+        let Some(root) = &self.root else {
+            return form;
+        };
+
+        match &root.control {
+            Control::Object(nodes) => {
+                form.children(nodes.iter().map(|node| self.render_field(node, cx)))
+            }
+            _ => form.child(self.render_field(root, cx)),
+        }
+    }
+}
+
+fn join_path(parent: &str, name: &str) -> SharedString {
+    if parent.is_empty() {
+        name.to_string().into()
+    } else {
+        format!("{}.{}", parent, name).into()
+    }
+}
@@ -0,0 +1,635 @@
+use std::rc::Rc;
+
+use gpui::{
+    App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable, SharedString,
+    Subscription, Task, Window,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use super::{FieldError, Rule, ValidateMode};
+
+/// A control state that can be bound to a field of the [`FormState`],
+/// e.g. [`crate::input::InputState`], [`crate::dropdown::DropdownState`].
+pub trait FormControl: Focusable + 'static {
+    /// Return the value of the control, `Value::Null` if empty.
+    fn field_value(&self, cx: &App) -> Value;
+
+    /// Set the value of the control, e.g. by [`FormState::load`] or [`FormState::reset`].
+    fn set_field_value(&mut self, value: &Value, window: &mut Window, cx: &mut Context<Self>);
+
+    /// Set the invalid state to show the error style.
+    fn set_invalid(&mut self, invalid: bool, window: &mut Window, cx: &mut Context<Self>);
+
+    /// Subscribe to the change event of the control, e.g. [`crate::input::InputEvent::Change`],
+    /// the `on_change` is called to read the value again by [`Self::field_value`].
+    fn subscribe_change(
+        control: &Entity<Self>,
+        window: &mut Window,
+        cx: &mut Context<FormState>,
+        on_change: impl Fn(&mut FormState, &mut Window, &mut Context<FormState>) + 'static,
+    ) -> Subscription
+    where
+        Self: Sized;
+}
+
+/// The type erased [`FormControl`] entity.
+trait AnyControl {
+    fn focus_handle(&self, cx: &App) -> FocusHandle;
+    fn value(&self, cx: &App) -> Value;
+    fn set_value(&self, value: &Value, window: &mut Window, cx: &mut App);
+    fn set_invalid(&self, invalid: bool, window: &mut Window, cx: &mut App);
+    fn observe(
+        &self,
+        name: SharedString,
+        window: &mut Window,
+        cx: &mut Context<FormState>,
+    ) -> Subscription;
+}
+
+impl<T: FormControl> AnyControl for Entity<T> {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.read(cx).focus_handle(cx)
+    }
+
+    fn value(&self, cx: &App) -> Value {
+        self.read(cx).field_value(cx)
+    }
+
+    fn set_value(&self, value: &Value, window: &mut Window, cx: &mut App) {
+        self.update(cx, |control, cx| control.set_field_value(value, window, cx));
+    }
+
+    fn set_invalid(&self, invalid: bool, window: &mut Window, cx: &mut App) {
+        self.update(cx, |control, cx| control.set_invalid(invalid, window, cx));
+    }
+
+    fn observe(
+        &self,
+        name: SharedString,
+        window: &mut Window,
+        cx: &mut Context<FormState>,
+    ) -> Subscription {
+        T::subscribe_change(self, window, cx, move |form, window, cx| {
+            form.on_field_change(&name, window, cx);
+        })
+    }
+}
+
+/// Emitted by the [`FormState`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormEvent {
+    /// The value of the field has been changed.
+    Change(SharedString),
+}
+
+struct Field {
+    name: SharedString,
+    /// The bound control, `None` for the field added by [`FormState::add_value_field`].
+    control: Option<Rc<dyn AnyControl>>,
+    rules: Vec<Rule>,
+    /// The last known value, the value of the value field.
+    value: Value,
+    /// The value to compare with for the dirty state, and to restore by [`FormState::reset`].
+    initial_value: Value,
+    error: Option<SharedString>,
+    /// The value of the last validation, to skip the validation if not changed.
+    validated_value: Option<Value>,
+    _validate_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl Field {
+    fn current_value(&self, cx: &App) -> Value {
+        match &self.control {
+            Some(control) => control.value(cx),
+            None => self.value.clone(),
+        }
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        !is_same_value(&self.current_value(cx), &self.initial_value)
+    }
+}
+
+/// Return true if the values are equal, the numbers are compared as `f64`,
+/// e.g. `5` from a number input is the same as `5.0` loaded from a `f64` field.
+pub(super) fn is_same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| is_same_value(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| is_same_value(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+/// The state of a form, to collect the values of the bound controls, validate them and show the errors.
+///
+/// The [`Checkbox`](crate::checkbox::Checkbox) and [`Switch`](crate::switch::Switch) are stateless,
+/// use [`Self::value_field`] to keep the value, and `bind` them to the field.
+///
+/// ```ignore
+/// let form = cx.new(|cx| {
+///     FormState::new(window, cx)
+///         .field("name", &name_input, [Rule::required(), Rule::max_length(20)], window, cx)
+///         .value_field("subscribe", false, [], cx)
+/// });
+///
+/// v_form()
+///     .child(form_field().label("Name").bind(&form, "name").child(TextInput::new(&name_input)))
+///     .child(form_field().child(Switch::new("subscribe").bind(&form, "subscribe")))
+/// ```
+///
+/// The values are a JSON object of the field names, use [`Self::load`] and [`Self::values_as`]
+/// to bind a serde struct.
+pub struct FormState {
+    mode: ValidateMode,
+    fields: Vec<Field>,
+}
+
+impl FormState {
+    pub fn new(_: &mut Window, _: &mut Context<Self>) -> Self {
+        Self {
+            mode: ValidateMode::default(),
+            fields: vec![],
+        }
+    }
+
+    /// Set the validate mode, default is [`ValidateMode::OnBlur`].
+    pub fn validate_mode(mut self, mode: ValidateMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Bind a control to the field with the validation rules.
+    pub fn field<T: FormControl>(
+        mut self,
+        name: impl Into<SharedString>,
+        control: &Entity<T>,
+        rules: impl IntoIterator<Item = Rule>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        self.add_field(name, control, rules, window, cx);
+        self
+    }
+
+    /// Bind a control to the field with the validation rules,
+    /// the field with the same name will be replaced.
+    pub fn add_field<T: FormControl>(
+        &mut self,
+        name: impl Into<SharedString>,
+        control: &Entity<T>,
+        rules: impl IntoIterator<Item = Rule>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let name: SharedString = name.into();
+        let control: Rc<dyn AnyControl> = Rc::new(control.clone());
+        let focus_handle = control.focus_handle(cx);
+        let value = control.value(cx);
+
+        let _subscriptions = vec![
+            control.observe(name.clone(), window, cx),
+            cx.on_focus_out(&focus_handle, window, {
+                let name = name.clone();
+                move |form, _, window, cx| {
+                    if form.mode == ValidateMode::OnBlur {
+                        form.validate_field(&name, window, cx);
+                    }
+                }
+            }),
+        ];
+
+        self.insert_field(Field {
+            name,
+            control: Some(control),
+            rules: rules.into_iter().collect(),
+            initial_value: value.clone(),
+            value,
+            error: None,
+            validated_value: None,
+            _validate_task: Task::ready(()),
+            _subscriptions,
+        });
+        cx.notify();
+    }
+
+    /// Add a field without control to keep the value, e.g. the checked state of a
+    /// [`Switch`](crate::switch::Switch), the value can be changed by [`Self::set_value`].
+    pub fn value_field(
+        mut self,
+        name: impl Into<SharedString>,
+        value: impl Into<Value>,
+        rules: impl IntoIterator<Item = Rule>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        self.add_value_field(name, value, rules, cx);
+        self
+    }
+
+    /// Add a field without control to keep the value,
+    /// the field with the same name will be replaced.
+    pub fn add_value_field(
+        &mut self,
+        name: impl Into<SharedString>,
+        value: impl Into<Value>,
+        rules: impl IntoIterator<Item = Rule>,
+        cx: &mut Context<Self>,
+    ) {
+        let value = value.into();
+        self.insert_field(Field {
+            name: name.into(),
+            control: None,
+            rules: rules.into_iter().collect(),
+            initial_value: value.clone(),
+            value,
+            error: None,
+            validated_value: None,
+            _validate_task: Task::ready(()),
+            _subscriptions: vec![],
+        });
+        cx.notify();
+    }
+
+    fn insert_field(&mut self, field: Field) {
+        match self.position(&field.name) {
+            Some(ix) => self.fields[ix] = field,
+            None => self.fields.push(field),
+        }
+    }
+
+    /// Remove the field, and reset the invalid state of the control.
+    pub fn remove_field(&mut self, name: &str, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.position(name) {
+            let field = self.fields.remove(ix);
+            if let Some(control) = field.control.filter(|_| field.error.is_some()) {
+                control.set_invalid(false, window, cx);
+            }
+            cx.notify();
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|field| field.name.as_ref() == name)
+    }
+
+    /// Return true if the field has the [`Rule::required`] rule.
+    pub fn is_required(&self, name: &str) -> bool {
+        self.position(name)
+            .is_some_and(|ix| self.fields[ix].rules.iter().any(|rule| rule.is_required()))
+    }
+
+    /// Return the error message of the field.
+    pub fn error(&self, name: &str) -> Option<&SharedString> {
+        self.position(name)
+            .and_then(|ix| self.fields[ix].error.as_ref())
+    }
+
+    /// Return the errors of all fields, in the order they were added.
+    pub fn errors(&self) -> Vec<FieldError> {
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                field.error.clone().map(|message| FieldError {
+                    name: field.name.clone(),
+                    message,
+                })
+            })
+            .collect()
+    }
+
+    /// Return the value of the field.
+    pub fn value(&self, name: &str, cx: &App) -> Option<Value> {
+        self.position(name)
+            .map(|ix| self.fields[ix].current_value(cx))
+    }
+
+    /// Set the value of the field, the bound control will be updated.
+    pub fn set_value(
+        &mut self,
+        name: &str,
+        value: impl Into<Value>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.position(name) else {
+            return;
+        };
+
+        let value = value.into();
+        match self.fields[ix].control.clone() {
+            Some(control) => {
+                control.set_value(&value, window, cx);
+                self.on_field_change(name, window, cx);
+            }
+            None => self.update_value(ix, value, window, cx),
+        }
+    }
+
+    /// Return the values of all fields as a JSON object.
+    pub fn values(&self, cx: &App) -> Value {
+        Value::Object(
+            self.fields
+                .iter()
+                .map(|field| (field.name.to_string(), field.current_value(cx)))
+                .collect(),
+        )
+    }
+
+    /// Deserialize the values of all fields to `T`.
+    pub fn values_as<T: DeserializeOwned>(&self, cx: &App) -> serde_json::Result<T> {
+        serde_json::from_value(self.values(cx))
+    }
+
+    /// Load the values from the serializable data, e.g. a struct with the fields of the same names.
+    ///
+    /// The loaded values become the initial values, so the form is not dirty after that,
+    /// and the fields not in the data are unchanged.
+    pub fn load<T: Serialize>(
+        &mut self,
+        data: &T,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> serde_json::Result<()> {
+        let Value::Object(mut values) = serde_json::to_value(data)? else {
+            return Err(serde::de::Error::custom("the data must be an object"));
+        };
+
+        for ix in 0..self.fields.len() {
+            let name = self.fields[ix].name.clone();
+            if let Some(value) = values.remove(name.as_ref()) {
+                self.fields[ix].initial_value = value.clone();
+                self.set_value(&name, value, window, cx);
+            }
+        }
+        self.clear_errors(window, cx);
+        Ok(())
+    }
+
+    /// Return true if any field value is different from the initial value.
+    pub fn is_dirty(&self, cx: &App) -> bool {
+        self.fields.iter().any(|field| field.is_dirty(cx))
+    }
+
+    /// Return true if the field value is different from the initial value.
+    pub fn is_field_dirty(&self, name: &str, cx: &App) -> bool {
+        self.position(name)
+            .is_some_and(|ix| self.fields[ix].is_dirty(cx))
+    }
+
+    /// Restore the initial values of all fields, and clear the errors.
+    pub fn reset(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        for ix in 0..self.fields.len() {
+            let name = self.fields[ix].name.clone();
+            let value = self.fields[ix].initial_value.clone();
+            self.set_value(&name, value, window, cx);
+        }
+        self.clear_errors(window, cx);
+    }
+
+    /// Use the current values as the initial values, e.g. after the values are saved.
+    pub fn mark_clean(&mut self, cx: &mut Context<Self>) {
+        for field in self.fields.iter_mut() {
+            field.initial_value = field.current_value(cx);
+        }
+        cx.notify();
+    }
+
+    /// Return the checked state and the click handler of a checkbox or switch bound to the field.
+    pub(crate) fn bind_checked(
+        form: &Entity<Self>,
+        name: SharedString,
+        on_click: Option<Rc<dyn Fn(&bool, &mut Window, &mut App)>>,
+        cx: &App,
+    ) -> (bool, Rc<dyn Fn(&bool, &mut Window, &mut App)>) {
+        let checked = form
+            .read(cx)
+            .value(&name, cx)
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+
+        let form = form.clone();
+        let on_click = Rc::new(move |checked: &bool, window: &mut Window, cx: &mut App| {
+            form.update(cx, |form, cx| form.set_value(&name, *checked, window, cx));
+            if let Some(on_click) = &on_click {
+                on_click(checked, window, cx);
+            }
+        });
+
+        (checked, on_click)
+    }
+
+    /// Return true if no field has an error.
+    ///
+    /// The fields are not validated by this, call [`Self::validate`] first.
+    pub fn is_valid(&self) -> bool {
+        self.fields.iter().all(|field| field.error.is_none())
+    }
+
+    /// Clear the errors of all fields.
+    pub fn clear_errors(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        for ix in 0..self.fields.len() {
+            self.fields[ix]._validate_task = Task::ready(());
+            self.fields[ix].validated_value = None;
+            self.set_error(ix, None, window, cx);
+        }
+    }
+
+    /// Validate all fields and return the errors.
+    ///
+    /// The errors of the async rules are not included, they will be shown when done,
+    /// use [`Self::validate_async`] to wait for them.
+    pub fn validate(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Vec<FieldError> {
+        for ix in 0..self.fields.len() {
+            self.validate_field_at(ix, window, cx);
+        }
+        self.errors()
+    }
+
+    /// Validate all fields including the async rules, and return the errors.
+    pub fn validate_async(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<FieldError>> {
+        let mut pending = vec![];
+        for ix in 0..self.fields.len() {
+            self.fields[ix]._validate_task = Task::ready(());
+            if let Some(task) = self.check_field(ix, window, cx) {
+                pending.push((self.fields[ix].name.clone(), task));
+            }
+        }
+
+        cx.spawn_in(window, async move |this, cx| {
+            for (name, task) in pending {
+                let error = task.await;
+                _ = this.update_in(cx, |this, window, cx| {
+                    if let Some(ix) = this.position(&name) {
+                        this.set_error(ix, error, window, cx);
+                    }
+                });
+            }
+
+            this.update(cx, |this, _| this.errors()).unwrap_or_default()
+        })
+    }
+
+    /// Validate the field, the error will be shown under the bound [`super::FormField`].
+    pub fn validate_field(&mut self, name: &str, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.position(name) {
+            self.validate_field_at(ix, window, cx);
+        }
+    }
+
+    fn validate_field_at(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(task) = self.check_field(ix, window, cx) else {
+            self.fields[ix]._validate_task = Task::ready(());
+            return;
+        };
+
+        let name = self.fields[ix].name.clone();
+        self.fields[ix]._validate_task = cx.spawn_in(window, async move |this, cx| {
+            let error = task.await;
+            _ = this.update_in(cx, |this, window, cx| {
+                if let Some(ix) = this.position(&name) {
+                    this.set_error(ix, error, window, cx);
+                }
+            });
+        });
+    }
+
+    /// Check the field by the sync rules and set the error,
+    /// return the task of the async rules if all the sync rules are passed.
+    fn check_field(
+        &mut self,
+        ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<Option<SharedString>>> {
+        let value = self.fields[ix].current_value(cx);
+        let field = &mut self.fields[ix];
+        field.validated_value = Some(value.clone());
+
+        let error = field.rules.iter().find_map(|rule| rule.check(&value).err());
+        let has_async = field.rules.iter().any(|rule| rule.is_async());
+        if error.is_some() || !has_async {
+            self.set_error(ix, error, window, cx);
+            return None;
+        }
+
+        let tasks: Vec<_> = self.fields[ix]
+            .rules
+            .iter()
+            .filter_map(|rule| rule.check_async(&value, cx))
+            .collect();
+        Some(cx.spawn(async move |_, _| {
+            for task in tasks {
+                if let Err(err) = task.await {
+                    return Some(err);
+                }
+            }
+            None
+        }))
+    }
+
+    fn set_error(
+        &mut self,
+        ix: usize,
+        error: Option<SharedString>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let field = &mut self.fields[ix];
+        if field.error == error {
+            return;
+        }
+
+        let invalid = error.is_some();
+        if let Some(control) = field.control.clone() {
+            if field.error.is_some() != invalid {
+                control.set_invalid(invalid, window, cx);
+            }
+        }
+        field.error = error;
+        cx.notify();
+    }
+
+    fn on_field_change(&mut self, name: &str, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.position(name) {
+            let value = self.fields[ix].current_value(cx);
+            self.update_value(ix, value, window, cx);
+        }
+    }
+
+    /// Emit the change event if the value changed, and validate the field again
+    /// if it has an error, to clear the error once it is fixed.
+    fn update_value(
+        &mut self,
+        ix: usize,
+        value: Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if is_same_value(&self.fields[ix].value, &value) {
+            return;
+        }
+        self.fields[ix].value = value;
+        cx.emit(FormEvent::Change(self.fields[ix].name.clone()));
+        cx.notify();
+
+        // The value field has no blur event, so validate it on change.
+        let field = &self.fields[ix];
+        let validate =
+            field.error.is_some() || field.control.is_none() && self.mode == ValidateMode::OnBlur;
+        if validate && field.validated_value.as_ref() != Some(&field.value) {
+            self.validate_field_at(ix, window, cx);
+        }
+    }
+}
+
+impl EventEmitter<FormEvent> for FormState {}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::{json, Value};
+
+    use super::is_same_value;
+
+    #[test]
+    fn test_is_same_value() {
+        #[derive(Serialize)]
+        struct Data {
+            age: f64,
+            name: String,
+        }
+
+        // The loaded value of the number input is the same as the value it returns.
+        let loaded = serde_json::to_value(Data {
+            age: 5.,
+            name: "Jason".into(),
+        })
+        .unwrap();
+        let current = json!({ "age": 5, "name": "Jason" });
+        assert!(is_same_value(&loaded, &current));
+        assert!(is_same_value(&loaded["age"], &Value::from(5)));
+        assert!(is_same_value(&json!([1, 2.5]), &json!([1.0, 2.5])));
+
+        assert!(!is_same_value(&loaded["age"], &json!(5.5)));
+        assert!(!is_same_value(&loaded["age"], &json!("5")));
+        assert!(!is_same_value(&json!([1, 2]), &json!([1])));
+        assert!(!is_same_value(
+            &current,
+            &json!({ "age": 5, "title": "Jason" })
+        ));
+    }
+}
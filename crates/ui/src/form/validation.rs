@@ -0,0 +1,248 @@
+use std::rc::Rc;
+
+use gpui::{App, SharedString, Task};
+use regex::Regex;
+use rust_i18n::t;
+use serde_json::Value;
+
+/// When to validate the fields of the [`super::FormState`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValidateMode {
+    /// Validate the field when it loses focus, and on submit.
+    #[default]
+    OnBlur,
+    /// Only validate the fields on submit, by [`super::FormState::validate`].
+    OnSubmit,
+}
+
+/// The validation error of a form field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    /// The name of the field.
+    pub name: SharedString,
+    pub message: SharedString,
+}
+
+type CheckFn = Rc<dyn Fn(&Value) -> Result<(), SharedString>>;
+type AsyncCheckFn = Rc<dyn Fn(&Value, &mut App) -> Task<Result<(), SharedString>>>;
+
+#[derive(Clone)]
+enum RuleKind {
+    Required,
+    MinLength(usize),
+    MaxLength(usize),
+    Pattern(Regex),
+    Custom(CheckFn),
+    Async(AsyncCheckFn),
+}
+
+/// A validation rule of the form field.
+///
+/// Except [`Rule::required`], the rules are skipped for the empty value.
+#[derive(Clone)]
+pub struct Rule {
+    kind: RuleKind,
+    message: Option<SharedString>,
+}
+
+impl Rule {
+    fn new(kind: RuleKind) -> Self {
+        Self {
+            kind,
+            message: None,
+        }
+    }
+
+    /// The value must not be empty, e.g. an empty text, `false` or no selection.
+    pub fn required() -> Self {
+        Self::new(RuleKind::Required)
+    }
+
+    /// The text must have at least `len` characters, or the array must have at least `len` items.
+    pub fn min_length(len: usize) -> Self {
+        Self::new(RuleKind::MinLength(len))
+    }
+
+    /// The text must have at most `len` characters, or the array must have at most `len` items.
+    pub fn max_length(len: usize) -> Self {
+        Self::new(RuleKind::MaxLength(len))
+    }
+
+    /// The text must match the regular expression.
+    pub fn pattern(pattern: Regex) -> Self {
+        Self::new(RuleKind::Pattern(pattern))
+    }
+
+    /// Check the value with a function, return `Err` with the error message if invalid.
+    pub fn custom(f: impl Fn(&Value) -> Result<(), SharedString> + 'static) -> Self {
+        Self::new(RuleKind::Custom(Rc::new(f)))
+    }
+
+    /// Check the value asynchronously, e.g. to check the username is available on the server.
+    ///
+    /// The async rules are only run when all the other rules are passed.
+    pub fn custom_async(
+        f: impl Fn(&Value, &mut App) -> Task<Result<(), SharedString>> + 'static,
+    ) -> Self {
+        Self::new(RuleKind::Async(Rc::new(f)))
+    }
+
+    /// Set the error message to replace the default message.
+    pub fn message(mut self, message: impl Into<SharedString>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub(super) fn is_required(&self) -> bool {
+        matches!(self.kind, RuleKind::Required)
+    }
+
+    pub(super) fn is_async(&self) -> bool {
+        matches!(self.kind, RuleKind::Async(_))
+    }
+
+    /// Check the value by the rule, the async rule is always passed here.
+    pub(super) fn check(&self, value: &Value) -> Result<(), SharedString> {
+        if !self.is_required() && is_empty(value) {
+            return Ok(());
+        }
+
+        let result = match &self.kind {
+            RuleKind::Required => {
+                if is_empty(value) {
+                    Err(t!("Form.required").into())
+                } else {
+                    Ok(())
+                }
+            }
+            RuleKind::MinLength(len) => match value_len(value) {
+                Some(n) if n < *len => Err(t!("Form.min_length", count = len).into()),
+                _ => Ok(()),
+            },
+            RuleKind::MaxLength(len) => match value_len(value) {
+                Some(n) if n > *len => Err(t!("Form.max_length", count = len).into()),
+                _ => Ok(()),
+            },
+            RuleKind::Pattern(pattern) => match value_text(value) {
+                Some(text) if !pattern.is_match(&text) => Err(t!("Form.invalid").into()),
+                _ => Ok(()),
+            },
+            RuleKind::Custom(f) => f(value),
+            RuleKind::Async(_) => Ok(()),
+        };
+
+        result.map_err(|err| self.message.clone().unwrap_or(err))
+    }
+
+    /// Start the async check, return `None` if not an async rule or the value is empty.
+    pub(super) fn check_async(
+        &self,
+        value: &Value,
+        cx: &mut App,
+    ) -> Option<Task<Result<(), SharedString>>> {
+        let RuleKind::Async(f) = &self.kind else {
+            return None;
+        };
+        if is_empty(value) {
+            return None;
+        }
+
+        let task = f(value, cx);
+        match self.message.clone() {
+            Some(message) => Some(cx.spawn(async move |_| task.await.map_err(|_| message))),
+            None => Some(task),
+        }
+    }
+}
+
+/// Return true if the value is `null`, `false`, or an empty text, array or object.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(value) => !value,
+        Value::String(text) => text.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        Value::Number(_) => false,
+    }
+}
+
+fn value_len(value: &Value) -> Option<usize> {
+    match value {
+        Value::String(text) => Some(text.chars().count()),
+        Value::Array(items) => Some(items.len()),
+        _ => None,
+    }
+}
+
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use serde_json::json;
+
+    use super::Rule;
+
+    #[test]
+    fn test_required() {
+        let rule = Rule::required();
+        assert!(rule.check(&json!(null)).is_err());
+        assert!(rule.check(&json!("")).is_err());
+        assert!(rule.check(&json!("  ")).is_err());
+        assert!(rule.check(&json!(false)).is_err());
+        assert!(rule.check(&json!([])).is_err());
+        assert!(rule.check(&json!("a")).is_ok());
+        assert!(rule.check(&json!(0)).is_ok());
+        assert!(rule.check(&json!(true)).is_ok());
+        assert!(rule.check(&json!(["a"])).is_ok());
+
+        let rule = Rule::required().message("Name is required");
+        assert_eq!(rule.check(&json!("")), Err("Name is required".into()));
+    }
+
+    #[test]
+    fn test_length() {
+        let rule = Rule::min_length(3);
+        assert!(rule.check(&json!("ab")).is_err());
+        assert!(rule.check(&json!("abc")).is_ok());
+        assert!(rule.check(&json!("你好吗")).is_ok());
+        assert!(rule.check(&json!([1, 2])).is_err());
+        // The empty value is checked by the required rule.
+        assert!(rule.check(&json!("")).is_ok());
+
+        let rule = Rule::max_length(3);
+        assert!(rule.check(&json!("abcd")).is_err());
+        assert!(rule.check(&json!("abc")).is_ok());
+        assert!(rule.check(&json!(12345)).is_ok());
+    }
+
+    #[test]
+    fn test_pattern_and_custom() {
+        let rule = Rule::pattern(Regex::new(r"^\S+@\S+$").unwrap()).message("Invalid email");
+        assert_eq!(rule.check(&json!("foo")), Err("Invalid email".into()));
+        assert!(rule.check(&json!("foo@bar.com")).is_ok());
+        assert!(rule.check(&json!("")).is_ok());
+
+        let rule = Rule::pattern(Regex::new(r"^\d{3}$").unwrap());
+        assert!(rule.check(&json!(123)).is_ok());
+        assert!(rule.check(&json!(1234)).is_err());
+
+        let rule = Rule::custom(|value| match value.as_str() {
+            Some("admin") => Err("The name is reserved".into()),
+            _ => Ok(()),
+        });
+        assert_eq!(
+            rule.check(&json!("admin")),
+            Err("The name is reserved".into())
+        );
+        assert!(rule.check(&json!("jason")).is_ok());
+        assert!(!rule.is_async());
+    }
+}
@@ -0,0 +1,222 @@
+//! Fuzzy matching for the searchable lists, like the command palette of the editors.
+//!
+//! The query matches a candidate if the characters of the query are a subsequence of the candidate
+//! (case insensitive), the matches at the word boundaries and the consecutive matches score higher.
+
+use std::ops::Range;
+
+const SCORE_MATCH: i32 = 16;
+const BONUS_BOUNDARY: i32 = 8;
+const BONUS_CAMEL: i32 = 6;
+const BONUS_CONSECUTIVE: i32 = 6;
+const PENALTY_GAP_START: i32 = 3;
+const PENALTY_GAP_EXTENSION: i32 = 1;
+const NONE: i32 = i32::MIN / 2;
+
+/// The result of a fuzzy match.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// The score of the match, the higher the better.
+    pub score: i32,
+    /// The byte offsets of the matched characters in the candidate.
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyMatch {
+    /// Return the byte ranges of the matched characters in the candidate, see [`match_ranges`].
+    pub fn ranges(&self, candidate: &str) -> Vec<Range<usize>> {
+        match_ranges(candidate, &self.positions)
+    }
+}
+
+/// Match the query with the candidate, return `None` if not matched.
+///
+/// The whitespaces in the query are ignored, an empty query matches any candidate with zero score.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch::default());
+    }
+
+    let chars: Vec<(usize, char)> = candidate.char_indices().collect();
+    let (n, m) = (query.len(), chars.len());
+    if n > m {
+        return None;
+    }
+
+    let lower: Vec<char> = chars.iter().map(|(_, c)| lowercase(*c)).collect();
+    let bonus: Vec<i32> = (0..m)
+        .map(|j| boundary_bonus(j.checked_sub(1).map(|k| chars[k].1), chars[j].1))
+        .collect();
+
+    // The best score of matching `query[..=i]` with `query[i]` at `chars[j]`,
+    // and the position of the previous matched character.
+    let mut scores = vec![NONE; n * m];
+    let mut prevs = vec![0; n * m];
+    for i in 0..n {
+        // The best of `scores[i - 1][k] + PENALTY_GAP_EXTENSION * k` with `k <= j - 2`.
+        let mut best_gap = (NONE, 0);
+        for j in i..m {
+            if i > 0 && j >= 2 {
+                let k = j - 2;
+                let score = scores[(i - 1) * m + k];
+                if score > NONE && score + PENALTY_GAP_EXTENSION * k as i32 > best_gap.0 {
+                    best_gap = (score + PENALTY_GAP_EXTENSION * k as i32, k);
+                }
+            }
+            if lower[j] != query[i] {
+                continue;
+            }
+
+            let score = SCORE_MATCH + bonus[j];
+            let ix = i * m + j;
+            if i == 0 {
+                scores[ix] = score - gap_penalty(j);
+                continue;
+            }
+
+            let consecutive = scores[(i - 1) * m + j - 1];
+            if consecutive > NONE {
+                scores[ix] = score + consecutive + BONUS_CONSECUTIVE;
+                prevs[ix] = j - 1;
+            }
+            if best_gap.0 > NONE {
+                let gap_score =
+                    best_gap.0 - PENALTY_GAP_START - PENALTY_GAP_EXTENSION * (j as i32 - 2) + score;
+                if gap_score > scores[ix] {
+                    scores[ix] = gap_score;
+                    prevs[ix] = best_gap.1;
+                }
+            }
+        }
+    }
+
+    let (mut j, score) = (0..m)
+        .map(|j| (j, scores[(n - 1) * m + j]))
+        .filter(|(_, score)| *score > NONE)
+        .fold(None, |best: Option<(usize, i32)>, (j, score)| match best {
+            Some((_, best_score)) if best_score >= score => best,
+            _ => Some((j, score)),
+        })?;
+
+    let mut positions = vec![0; n];
+    for i in (0..n).rev() {
+        positions[i] = chars[j].0;
+        j = prevs[i * m + j];
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+/// Match the items with the query, return the matched items sorted by the score,
+/// the items with the same score keep the original order.
+pub fn fuzzy_search<T, S>(
+    query: &str,
+    items: impl IntoIterator<Item = T>,
+    text: impl Fn(&T) -> S,
+) -> Vec<(T, FuzzyMatch)>
+where
+    S: AsRef<str>,
+{
+    let mut matches: Vec<(T, FuzzyMatch)> = items
+        .into_iter()
+        .filter_map(|item| {
+            let matched = fuzzy_match(query, text(&item).as_ref())?;
+            Some((item, matched))
+        })
+        .collect();
+    matches.sort_by(|(_, a), (_, b)| b.score.cmp(&a.score));
+    matches
+}
+
+/// Merge the byte offsets of the matched characters into the byte ranges, to highlight the text.
+pub fn match_ranges(text: &str, positions: &[usize]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for &start in positions {
+        let Some(c) = text.get(start..).and_then(|s| s.chars().next()) else {
+            continue;
+        };
+        let end = start + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn gap_penalty(len: usize) -> i32 {
+    match len {
+        0 => 0,
+        len => PENALTY_GAP_START + PENALTY_GAP_EXTENSION * (len as i32 - 1),
+    }
+}
+
+fn boundary_bonus(prev: Option<char>, c: char) -> i32 {
+    match prev {
+        None => BONUS_BOUNDARY,
+        Some(prev) if prev.is_whitespace() || "_-/\\.:,()[]".contains(prev) => BONUS_BOUNDARY,
+        Some(prev) if prev.is_lowercase() && c.is_uppercase() => BONUS_CAMEL,
+        Some(prev) if !prev.is_ascii_digit() && c.is_ascii_digit() => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fuzzy_match, fuzzy_search, match_ranges};
+
+    fn positions(query: &str, candidate: &str) -> Option<Vec<usize>> {
+        fuzzy_match(query, candidate).map(|m| m.positions)
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(positions("abc", "a_b_c"), Some(vec![0, 2, 4]));
+        assert_eq!(positions("ABC", "xabcx"), Some(vec![1, 2, 3]));
+        assert_eq!(positions("acb", "abc"), None);
+        assert_eq!(positions("abcd", "abc"), None);
+        assert_eq!(positions("", "abc"), Some(vec![]));
+        assert_eq!(positions("g c", "git commit"), Some(vec![0, 4]));
+
+        // Prefer the consecutive matches.
+        assert_eq!(positions("ab", "xaxab"), Some(vec![3, 4]));
+        // Prefer the word boundaries.
+        assert_eq!(positions("fb", "foo bar"), Some(vec![0, 4]));
+        assert_eq!(positions("fb", "fooBar"), Some(vec![0, 3]));
+        assert_eq!(positions("ob", "fooBar"), Some(vec![2, 3]));
+
+        assert_eq!(positions("世界", "你好，世界"), Some(vec![9, 12]));
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let items = ["xaxb", "Apple Banana", "ab", "bcd"];
+        let matches = fuzzy_search("ab", items, |item| *item);
+        assert_eq!(
+            matches.iter().map(|(item, _)| *item).collect::<Vec<_>>(),
+            vec!["ab", "Apple Banana", "xaxb"]
+        );
+
+        let matches = fuzzy_search("", items, |item| *item);
+        assert_eq!(
+            matches.iter().map(|(item, _)| *item).collect::<Vec<_>>(),
+            items.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_match_ranges() {
+        assert_eq!(match_ranges("abcdef", &[0, 1, 2, 4]), vec![0..3, 4..5]);
+        assert_eq!(match_ranges("你好世界", &[3, 6]), vec![3..9]);
+        assert_eq!(match_ranges("abc", &[5]), vec![]);
+    }
+}
@@ -58,6 +58,15 @@ impl HighlightTheme {
     }
 }
 
+/// The state of the parser at the start of a line, used to highlight the text line by line.
+///
+/// Two lines that start with the same state will be highlighted the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineState {
+    parse_state: parsing::ParseState,
+    scope_stack: parsing::ScopeStack,
+}
+
 /// Inspired by the `iced` crate's `Highlighter` struct.
 ///
 /// https://github.com/iced-rs/iced/blob/master/highlighter/src/lib.rs#L24
@@ -81,16 +90,62 @@ impl<'a> Highlighter<'a> {
         }
     }
 
+    /// Return the parse state at the start of the text.
+    pub fn start_state(&self) -> LineState {
+        LineState {
+            parse_state: parsing::ParseState::new(self.syntax),
+            scope_stack: parsing::ScopeStack::new(),
+        }
+    }
+
+    /// Return the line comment token of the syntax, e.g. `//` for Rust, None if the syntax has no line comment.
+    ///
+    /// The token is read from the `TM_COMMENT_START` of the syntax metadata.
+    pub fn line_comment(&self) -> Option<SharedString> {
+        let metadata = SYNTAXES.metadata().metadata_for_scope(&[self.syntax.scope]);
+        metadata
+            .line_comment()
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(|token| SharedString::from(token.to_string()))
+    }
+
     /// Highlight a line and returns a vector of ranges and highlight styles
     pub fn highlight(&self, line: &str) -> Vec<(Range<usize>, HighlightStyle)> {
-        let mut parser = parsing::ParseState::new(self.syntax);
-        let mut stack = parsing::ScopeStack::new();
+        self.highlight_with_state(line, &mut self.start_state())
+    }
+
+    /// Highlight a line of text that starts with the `state`, the `state` will be advanced to the end of the line.
+    ///
+    /// The `line` should not contain the `\n`, it is added for the parser.
+    pub fn highlight_line(
+        &self,
+        line: &str,
+        state: &mut LineState,
+    ) -> Vec<(Range<usize>, HighlightStyle)> {
+        let mut styles = self.highlight_with_state(&format!("{}\n", line), state);
+        // Remove the range of the `\n`.
+        styles.retain_mut(|(range, _)| {
+            range.end = range.end.min(line.len());
+            !range.is_empty()
+        });
+        styles
+    }
 
-        let ops = parser.parse_line(line, &SYNTAXES).unwrap_or_default();
+    fn highlight_with_state(
+        &self,
+        text: &str,
+        state: &mut LineState,
+    ) -> Vec<(Range<usize>, HighlightStyle)> {
+        let ops = state
+            .parse_state
+            .parse_line(text, &SYNTAXES)
+            .unwrap_or_default();
+        let stack = &mut state.scope_stack;
 
         ScopeRangeIterator {
             ops,
-            line_length: line.len(),
+            line_length: text.len(),
             index: 0,
             last_str_index: 0,
         }
@@ -27,6 +27,8 @@ pub struct History<I: HistoryItem> {
     pub(crate) ignore: bool,
     max_undo: usize,
     group_interval: Option<Duration>,
+    /// The depth of the nested groups, see [`History::start_grouping`].
+    group_depth: usize,
     unique: bool,
 }
 
@@ -43,6 +45,7 @@ where
             version: 0,
             max_undo: 1000,
             group_interval: None,
+            group_depth: 0,
             unique: false,
         }
     }
@@ -69,7 +72,7 @@ where
     /// Increment the version number if the last change was made more than `GROUP_INTERVAL` milliseconds ago.
     fn inc_version(&mut self) -> usize {
         let t = Instant::now();
-        if Some(self.last_changed_at.elapsed()) > self.group_interval {
+        if self.group_depth == 0 && Some(self.last_changed_at.elapsed()) > self.group_interval {
             self.version += 1;
         }
 
@@ -82,6 +85,25 @@ where
         self.version
     }
 
+    /// Start a group, all changes pushed before [`History::end_grouping`] will have the same version,
+    /// so they will be undone or redone together.
+    ///
+    /// The group is never merged with the changes before or after it, and can be nested.
+    pub fn start_grouping(&mut self) {
+        if self.group_depth == 0 {
+            self.version += 1;
+        }
+        self.group_depth += 1;
+    }
+
+    /// End the group started by [`History::start_grouping`].
+    pub fn end_grouping(&mut self) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+        if self.group_depth == 0 {
+            self.version += 1;
+        }
+    }
+
     pub fn push(&mut self, item: I) {
         let version = self.inc_version();
 
@@ -236,6 +258,43 @@ mod tests {
         assert_eq!(history.undo().is_none(), true);
     }
 
+    #[test]
+    fn test_history_grouping() {
+        let mut history: History<TabIndex> = History::new().group_interval(Duration::from_secs(60));
+        history.push(0.into());
+
+        history.start_grouping();
+        history.push(1.into());
+        history.start_grouping();
+        history.push(2.into());
+        history.end_grouping();
+        history.push(3.into());
+        history.end_grouping();
+
+        history.push(4.into());
+
+        let changes = history.undo().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].tab_index, 4);
+
+        let changes = history.undo().unwrap();
+        assert_eq!(
+            changes.iter().map(|c| c.tab_index).collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+
+        let changes = history.redo().unwrap();
+        assert_eq!(
+            changes.iter().map(|c| c.tab_index).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        history.undo();
+        let changes = history.undo().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].tab_index, 0);
+    }
+
     #[test]
     fn test_unique_history() {
         let mut history: History<TabIndex> = History::new().max_undo(100).unique();
@@ -0,0 +1,458 @@
+use std::ops::Range;
+
+use gpui::{Context, Window};
+
+use super::{mode::TabSize, rope::Rope, InputState};
+
+/// The max number of chars to scan for the matching bracket.
+const MAX_BRACKET_SCAN_CHARS: usize = 50_000;
+
+/// A pair of brackets (or quotes) for auto-closing and bracket matching in the code editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BracketPair {
+    pub start: char,
+    pub end: char,
+}
+
+impl BracketPair {
+    pub const fn new(start: char, end: char) -> Self {
+        Self { start, end }
+    }
+
+    /// The quotes has the same start and end, they are not used for bracket matching.
+    #[inline]
+    fn is_quote(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// The default bracket pairs: `()`, `[]`, `{}`, `""`, `''` and ``` `` ```.
+pub(super) const DEFAULT_BRACKET_PAIRS: [BracketPair; 6] = [
+    BracketPair::new('(', ')'),
+    BracketPair::new('[', ']'),
+    BracketPair::new('{', '}'),
+    BracketPair::new('"', '"'),
+    BracketPair::new('\'', '\''),
+    BracketPair::new('`', '`'),
+];
+
+/// The edit of typing a char with the bracket pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BracketEdit {
+    /// Insert the start and end of the pair, and move the cursor between them.
+    Insert(BracketPair),
+    /// Wrap the selected text with the pair.
+    Wrap(BracketPair),
+    /// Skip the end of the pair after the cursor.
+    SkipEnd,
+}
+
+/// Return the edit of typing the char `c`, the `prev` and `next` are the chars around the cursor.
+fn bracket_edit(
+    c: char,
+    prev: Option<char>,
+    next: Option<char>,
+    has_selection: bool,
+    pairs: &[BracketPair],
+) -> Option<BracketEdit> {
+    if has_selection {
+        return pairs
+            .iter()
+            .find(|pair| pair.start == c)
+            .map(|pair| BracketEdit::Wrap(*pair));
+    }
+
+    if next == Some(c) && pairs.iter().any(|pair| pair.end == c) {
+        return Some(BracketEdit::SkipEnd);
+    }
+
+    let pair = pairs.iter().find(|pair| pair.start == c)?;
+    let next_allowed = next.map_or(true, |next| {
+        next.is_whitespace()
+            || matches!(next, ',' | ';' | ':' | '.')
+            || pairs
+                .iter()
+                .any(|pair| !pair.is_quote() && pair.end == next)
+    });
+    // Avoid to close the quote after a word, e.g. `don't`.
+    let prev_allowed =
+        !pair.is_quote() || prev.map_or(true, |prev| !prev.is_alphanumeric() && prev != '_');
+
+    (next_allowed && prev_allowed).then_some(BracketEdit::Insert(*pair))
+}
+
+/// Find the bracket next to the `offset` (after it first) and its matching bracket.
+///
+/// Returns the ranges of the two brackets, sorted by the offset.
+fn find_matching_brackets(
+    text: &Rope,
+    offset: usize,
+    pairs: &[BracketPair],
+) -> Option<[Range<usize>; 2]> {
+    let after = text.char_at(offset).map(|c| (offset, c));
+    let before = text
+        .reversed_chars_at(offset)
+        .next()
+        .map(|c| (offset - c.len_utf8(), c));
+
+    for (pos, c) in after.into_iter().chain(before) {
+        for pair in pairs.iter().filter(|pair| !pair.is_quote()) {
+            let found = if c == pair.start {
+                let start = pos + c.len_utf8();
+                scan_matching_bracket(text.chars_at(start), pair.start, pair.end).map(|len| {
+                    let end = start + len;
+                    [pos..pos + c.len_utf8(), end..end + pair.end.len_utf8()]
+                })
+            } else if c == pair.end {
+                scan_matching_bracket(text.reversed_chars_at(pos), pair.end, pair.start).map(
+                    |len| {
+                        let start = pos - len - pair.start.len_utf8();
+                        [
+                            start..start + pair.start.len_utf8(),
+                            pos..pos + c.len_utf8(),
+                        ]
+                    },
+                )
+            } else {
+                continue;
+            };
+
+            if found.is_some() {
+                return found;
+            }
+        }
+    }
+
+    None
+}
+
+/// Scan the `chars` for the `target` bracket that closes the `open` bracket,
+/// returns the bytes length of the chars before the target.
+fn scan_matching_bracket(
+    chars: impl Iterator<Item = char>,
+    open: char,
+    target: char,
+) -> Option<usize> {
+    let mut depth = 0;
+    let mut len = 0;
+    for c in chars.take(MAX_BRACKET_SCAN_CHARS) {
+        if c == target {
+            if depth == 0 {
+                return Some(len);
+            }
+            depth -= 1;
+        } else if c == open {
+            depth += 1;
+        }
+        len += c.len_utf8();
+    }
+
+    None
+}
+
+/// Convert the `indent` to the tab size, e.g. the spaces are converted to `\t` for hard tabs.
+fn normalize_indent(indent: &str, tab: &TabSize) -> String {
+    let tab_size = tab.tab_size.max(1);
+    let columns = indent.chars().fold(0, |col, c| match c {
+        '\t' => col + tab_size - col % tab_size,
+        _ => col + 1,
+    });
+
+    if tab.hard_tabs {
+        "\t".repeat(columns / tab_size) + &" ".repeat(columns % tab_size)
+    } else {
+        " ".repeat(columns)
+    }
+}
+
+/// Return the text to insert for a new line and the cursor offset in it.
+///
+/// The `line` is the text from the start of the line to the cursor, the `next` is the char after the cursor.
+fn new_line_with_indent(
+    line: &str,
+    next: Option<char>,
+    tab: &TabSize,
+    pairs: &[BracketPair],
+) -> (String, usize) {
+    let indent = line
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect::<String>();
+    let indent = normalize_indent(&indent, tab);
+
+    let open_pair = line.trim_end().chars().last().and_then(|last| {
+        pairs
+            .iter()
+            .find(|pair| !pair.is_quote() && pair.start == last)
+    });
+
+    let Some(pair) = open_pair else {
+        let text = format!("\n{}", indent);
+        let cursor = text.len();
+        return (text, cursor);
+    };
+
+    let inner = format!("\n{}{}", indent, tab.to_string());
+    let cursor = inner.len();
+    if next == Some(pair.end) {
+        (format!("{}\n{}", inner, indent), cursor)
+    } else {
+        (inner, cursor)
+    }
+}
+
+impl InputState {
+    /// Set the bracket pairs for auto-closing and bracket matching in the code editor.
+    ///
+    /// Default is `()`, `[]`, `{}`, `""`, `''` and ``` `` ```, set empty to disable.
+    pub fn bracket_pairs(mut self, pairs: impl Into<Vec<BracketPair>>) -> Self {
+        self.bracket_pairs = pairs.into();
+        self
+    }
+
+    /// Set the bracket pairs for auto-closing and bracket matching in the code editor.
+    pub fn set_bracket_pairs(
+        &mut self,
+        pairs: impl Into<Vec<BracketPair>>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.bracket_pairs = pairs.into();
+        cx.notify();
+    }
+
+    /// Handle the typed `new_text` with the bracket pairs, returns true if it has been handled.
+    pub(super) fn handle_bracket_input(
+        &mut self,
+        new_text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if !self.mode.is_code_editor() || self.bracket_pairs.is_empty() {
+            return false;
+        }
+
+        let mut chars = new_text.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return false;
+        };
+
+        let range = self.selected_range.clone();
+        let prev = self.text.reversed_chars_at(range.start).next();
+        let next = self.text.char_at(range.end);
+        let Some(edit) = bracket_edit(c, prev, next, !range.is_empty(), &self.bracket_pairs) else {
+            return false;
+        };
+
+        match edit {
+            BracketEdit::Insert(pair) => {
+                let text = format!("{}{}", pair.start, pair.end);
+                self.replace_text_in_range(Some(self.range_to_utf16(&range)), &text, window, cx);
+                let offset = range.start + pair.start.len_utf8();
+                self.selected_range = offset..offset;
+            }
+            BracketEdit::Wrap(pair) => {
+                let selected_text = self.text.slice(range.clone());
+                let text = format!("{}{}{}", pair.start, selected_text, pair.end);
+                self.replace_text_in_range(Some(self.range_to_utf16(&range)), &text, window, cx);
+                let start = range.start + pair.start.len_utf8();
+                self.selected_range = start..start + selected_text.len();
+            }
+            BracketEdit::SkipEnd => {
+                let offset = range.end + c.len_utf8();
+                self.selected_range = offset..offset;
+                self.update_preferred_x_offset(cx);
+            }
+        }
+
+        self.selection_reversed = false;
+        cx.notify();
+        true
+    }
+
+    /// Return the range of the empty bracket pair around the `offset`, e.g. `(|)`.
+    ///
+    /// Used to delete the pair together by backspace.
+    pub(super) fn empty_bracket_pair_range(&self, offset: usize) -> Option<Range<usize>> {
+        if !self.mode.is_code_editor() {
+            return None;
+        }
+
+        let prev = self.text.reversed_chars_at(offset).next()?;
+        let next = self.text.char_at(offset)?;
+        self.bracket_pairs
+            .iter()
+            .find(|pair| pair.start == prev && pair.end == next)
+            .map(|_| offset - prev.len_utf8()..offset + next.len_utf8())
+    }
+
+    /// Return the ranges of the bracket at the cursor and its matching bracket.
+    pub(super) fn matching_brackets(&self) -> Option<[Range<usize>; 2]> {
+        if !self.mode.is_code_editor() || !self.selected_range.is_empty() {
+            return None;
+        }
+
+        find_matching_brackets(&self.text, self.cursor_offset(), &self.bracket_pairs)
+    }
+
+    /// Insert a new line with the indent of the current line, and one more level after an opening bracket.
+    ///
+    /// If the cursor is between a pair of brackets, the closing bracket is moved to the next line.
+    pub(super) fn insert_new_line_with_indent(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let tab = self.mode.tab_size().copied().unwrap_or_default();
+        let range = self.selected_range.clone();
+        let line_start = self
+            .text
+            .line_start_offset(self.text.offset_to_point(range.start).row);
+        let line = self.text.slice(line_start..range.start);
+        let next = self.text.char_at(range.end);
+
+        let (text, cursor) = new_line_with_indent(&line, next, &tab, &self.bracket_pairs);
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), &text, window, cx);
+        self.selected_range = range.start + cursor..range.start + cursor;
+        self.selection_reversed = false;
+        self.update_preferred_x_offset(cx);
+        cx.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bracket_edit() {
+        let pairs = DEFAULT_BRACKET_PAIRS;
+        let insert = |c| {
+            Some(BracketEdit::Insert(
+                *pairs.iter().find(|pair| pair.start == c).unwrap(),
+            ))
+        };
+
+        assert_eq!(bracket_edit('(', None, None, false, &pairs), insert('('));
+        assert_eq!(
+            bracket_edit('{', Some(' '), Some(')'), false, &pairs),
+            insert('{')
+        );
+        assert_eq!(bracket_edit('(', Some('a'), Some('b'), false, &pairs), None);
+        assert_eq!(
+            bracket_edit('"', Some(' '), None, false, &pairs),
+            insert('"')
+        );
+        // Not close the quote after a word.
+        assert_eq!(
+            bracket_edit('\'', Some('n'), Some('t'), false, &pairs),
+            None
+        );
+        assert_eq!(bracket_edit('\'', Some('n'), None, false, &pairs), None);
+        // Not close before a quote.
+        assert_eq!(bracket_edit('(', Some(' '), Some('"'), false, &pairs), None);
+
+        assert_eq!(
+            bracket_edit(')', Some('('), Some(')'), false, &pairs),
+            Some(BracketEdit::SkipEnd)
+        );
+        assert_eq!(
+            bracket_edit('"', Some('a'), Some('"'), false, &pairs),
+            Some(BracketEdit::SkipEnd)
+        );
+        assert_eq!(bracket_edit(')', Some('a'), None, false, &pairs), None);
+
+        assert_eq!(
+            bracket_edit('[', Some('a'), Some('b'), true, &pairs),
+            Some(BracketEdit::Wrap(BracketPair::new('[', ']')))
+        );
+        assert_eq!(bracket_edit('a', None, None, true, &pairs), None);
+        assert_eq!(bracket_edit('(', None, None, false, &[]), None);
+    }
+
+    #[test]
+    fn test_find_matching_brackets() {
+        let pairs = DEFAULT_BRACKET_PAIRS;
+        let text = Rope::from("fn a(b: [u8; 2]) {\n    (1, \"中\")\n}");
+
+        assert_eq!(
+            find_matching_brackets(&text, 4, &pairs),
+            Some([4..5, 15..16])
+        );
+        // The bracket before the cursor.
+        assert_eq!(
+            find_matching_brackets(&text, 16, &pairs),
+            Some([4..5, 15..16])
+        );
+        assert_eq!(
+            find_matching_brackets(&text, 8, &pairs),
+            Some([8..9, 14..15])
+        );
+        assert_eq!(
+            find_matching_brackets(&text, 17, &pairs),
+            Some([17..18, 34..35])
+        );
+        assert_eq!(
+            find_matching_brackets(&text, 35, &pairs),
+            Some([17..18, 34..35])
+        );
+        assert_eq!(
+            find_matching_brackets(&text, 24, &pairs),
+            Some([23..24, 32..33])
+        );
+        assert_eq!(find_matching_brackets(&text, 1, &pairs), None);
+
+        let text = Rope::from("(()");
+        assert_eq!(find_matching_brackets(&text, 0, &pairs), None);
+        assert_eq!(find_matching_brackets(&text, 1, &pairs), Some([1..2, 2..3]));
+    }
+
+    #[test]
+    fn test_normalize_indent() {
+        let soft = TabSize {
+            tab_size: 4,
+            hard_tabs: false,
+        };
+        let hard = TabSize {
+            tab_size: 4,
+            hard_tabs: true,
+        };
+
+        assert_eq!(normalize_indent("", &soft), "");
+        assert_eq!(normalize_indent("\t  ", &soft), "      ");
+        assert_eq!(normalize_indent("  \t", &soft), "    ");
+        assert_eq!(normalize_indent("      ", &hard), "\t  ");
+        assert_eq!(normalize_indent("\t\t", &hard), "\t\t");
+    }
+
+    #[test]
+    fn test_new_line_with_indent() {
+        let pairs = DEFAULT_BRACKET_PAIRS;
+        let tab = TabSize::default();
+        let hard = TabSize {
+            tab_size: 4,
+            hard_tabs: true,
+        };
+
+        assert_eq!(
+            new_line_with_indent("  let a = 1;", None, &tab, &pairs),
+            ("\n  ".to_string(), 3)
+        );
+        assert_eq!(
+            new_line_with_indent("  fn a() {", None, &tab, &pairs),
+            ("\n    ".to_string(), 5)
+        );
+        assert_eq!(
+            new_line_with_indent("  fn a() { ", Some('}'), &tab, &pairs),
+            ("\n    \n  ".to_string(), 5)
+        );
+        assert_eq!(
+            new_line_with_indent("\tfoo(", Some(')'), &hard, &pairs),
+            ("\n\t\t\n\t".to_string(), 3)
+        );
+        assert_eq!(
+            new_line_with_indent("let s = \"", None, &tab, &pairs),
+            ("\n".to_string(), 1)
+        );
+    }
+}
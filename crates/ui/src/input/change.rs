@@ -8,6 +8,8 @@ pub struct Change {
     pub(crate) old_text: String,
     pub(crate) new_range: Range<usize>,
     pub(crate) new_text: String,
+    /// The selections before the change, only for the first change of the multiple cursors editing.
+    pub(crate) selections: Vec<Range<usize>>,
     version: usize,
 }
 
@@ -23,6 +25,7 @@ impl Change {
             old_text: old_text.to_string(),
             new_range,
             new_text: new_text.to_string(),
+            selections: vec![],
             version: 0,
         }
     }
@@ -1,18 +1,46 @@
-use std::{collections::HashMap, ops::Range, rc::Rc};
+use std::{ops::Range, rc::Rc, sync::Arc};
 
-use gpui::{App, HighlightStyle, SharedString, TextRun, TextStyle};
+use gpui::{Context, HighlightStyle, TextRun, TextStyle};
 
-use crate::highlighter::Highlighter;
+use super::{
+    mode::InputMode,
+    rope::{LinesEdit, Rope},
+    InputState,
+};
+use crate::highlighter::{Highlighter, LineState};
 
-#[derive(Debug, Clone)]
+/// The max number of lines to highlight in one background batch.
+const MAX_BATCH_ROWS: usize = 1000;
+
+#[derive(Debug, Clone, Default)]
 pub(crate) struct LineHighlightStyle {
-    offset: usize,
     pub(crate) styles: Rc<Vec<(Range<usize>, HighlightStyle)>>,
 }
 
 impl LineHighlightStyle {
+    /// The length of the line (exclude the `\n`).
+    pub(super) fn len(&self) -> usize {
+        self.styles.last().map(|(range, _)| range.end).unwrap_or(0)
+    }
+
+    /// Return the styles resized to the line of `len`, the last style is extended to the end of the line.
+    ///
+    /// Used to keep the stale styles of a changed line before it has been highlighted again.
+    fn resized(&self, len: usize) -> Self {
+        let mut styles = resize_styles(&self.styles, len);
+        if styles.is_empty() && len > 0 {
+            styles.push((0..len, HighlightStyle::default()));
+        }
+
+        Self {
+            styles: Rc::new(styles),
+        }
+    }
+
+    /// Convert to the text runs, the `offset` is the start offset of the line in the text.
     pub(super) fn to_run(
         &self,
+        offset: usize,
         text_style: &TextStyle,
         marked_range: &Option<Range<usize>>,
         marked_run: &TextRun,
@@ -22,8 +50,8 @@ impl LineHighlightStyle {
             .map(|(range, style)| {
                 let mut run = text_style.clone().highlight(*style).to_run(range.len());
                 if let Some(marked_range) = marked_range {
-                    if self.offset + range.start >= marked_range.start
-                        && self.offset + range.end <= marked_range.end
+                    if offset + range.start >= marked_range.start
+                        && offset + range.end <= marked_range.end
                     {
                         run.color = marked_run.color;
                         run.strikethrough = marked_run.strikethrough;
@@ -38,66 +66,326 @@ impl LineHighlightStyle {
     }
 }
 
+/// Resize the contiguous styles of a line to `len`, the styles out of the line are removed,
+/// and the last style is extended to the end of the line.
+fn resize_styles<T: Clone>(styles: &[(Range<usize>, T)], len: usize) -> Vec<(Range<usize>, T)> {
+    let mut styles = styles
+        .iter()
+        .filter(|(range, _)| range.start < len)
+        .map(|(range, style)| (range.start..range.end.min(len), style.clone()))
+        .collect::<Vec<_>>();
+    if let Some((range, _)) = styles.last_mut() {
+        range.end = len;
+    }
+    styles
+}
+
+/// Map the `rows` to the text after the `edit`, and extend it to cover the new rows of the edit.
+fn merge_invalid_rows(rows: Option<Range<usize>>, edit: &LinesEdit) -> Range<usize> {
+    let edit_end = edit.start_row + edit.new_rows;
+    let Some(rows) = rows else {
+        return edit.start_row..edit_end;
+    };
+
+    let end = if rows.end <= edit.start_row {
+        rows.end
+    } else if rows.end >= edit.start_row + edit.old_rows {
+        rows.end + edit.new_rows - edit.old_rows
+    } else {
+        edit_end
+    };
+
+    rows.start.min(edit.start_row)..end.max(edit_end)
+}
+
 #[derive(Clone)]
 pub(super) struct CodeHighlighter {
-    pub(super) highlighter: Rc<Highlighter<'static>>,
-    pub(super) text: SharedString,
+    pub(super) highlighter: Arc<Highlighter<'static>>,
     /// The lines by split \n
     pub(super) lines: Vec<LineHighlightStyle>,
-    pub(super) cache: HashMap<u64, LineHighlightStyle>,
+    /// The parse state at the start of each line.
+    states: Vec<LineState>,
+    /// The rows must be highlighted again, the lines after them are highlighted again
+    /// until the parse states converge.
+    invalid_rows: Option<Range<usize>>,
+    /// Increased on every change, to drop the outdated background results.
+    version: usize,
+}
+
+/// A batch of lines to highlight in the background.
+pub(super) struct HighlightBatch {
+    highlighter: Arc<Highlighter<'static>>,
+    version: usize,
+    start_row: usize,
+    /// The parse state at the start of `start_row`.
+    state: LineState,
+    lines: Vec<String>,
+    /// The current states at the start of the lines after `start_row`.
+    old_states: Vec<LineState>,
+    /// Only check the convergence from this row, the rows before it have been changed.
+    check_from: usize,
+}
+
+pub(super) struct HighlightResult {
+    version: usize,
+    start_row: usize,
+    lines: Vec<Vec<(Range<usize>, HighlightStyle)>>,
+    /// The states at the start of the lines after `start_row`.
+    states: Vec<LineState>,
+    converged: bool,
+}
+
+impl HighlightBatch {
+    pub(super) fn run(self) -> HighlightResult {
+        let mut state = self.state;
+        let mut lines = Vec::with_capacity(self.lines.len());
+        let mut states = Vec::with_capacity(self.lines.len());
+        let mut converged = false;
+
+        for (ix, line) in self.lines.iter().enumerate() {
+            lines.push(self.highlighter.highlight_line(line, &mut state));
+            states.push(state.clone());
+
+            let next_row = self.start_row + ix + 1;
+            if next_row >= self.check_from && self.old_states.get(ix) == Some(&state) {
+                converged = true;
+                break;
+            }
+        }
+
+        HighlightResult {
+            version: self.version,
+            start_row: self.start_row,
+            lines,
+            states,
+            converged,
+        }
+    }
 }
 
 impl CodeHighlighter {
-    pub(super) fn new(highlighter: Rc<Highlighter<'static>>) -> Self {
+    pub(super) fn new(highlighter: Arc<Highlighter<'static>>) -> Self {
         Self {
             highlighter,
-            text: SharedString::default(),
             lines: vec![],
-            cache: HashMap::new(),
+            states: vec![],
+            invalid_rows: None,
+            version: 0,
         }
     }
 
-    pub fn set_highlighter(&mut self, highlighter: Rc<Highlighter<'static>>, cx: &mut App) {
+    pub fn set_highlighter(&mut self, highlighter: Arc<Highlighter<'static>>, text: &Rope) {
         self.highlighter = highlighter;
-        self.lines.clear();
-        self.cache.clear();
-        self.update(self.text.clone(), true, cx);
+        self.highlight_all(text);
     }
 
-    pub fn update(&mut self, text: SharedString, force: bool, _: &mut App) {
-        if self.text == text && !force {
-            return;
+    /// Highlight the whole text if it has not been highlighted yet.
+    ///
+    /// Returns true if the text need to be highlighted.
+    pub fn prepare(&mut self, text: &Rope) -> bool {
+        if self.lines.is_empty() {
+            self.highlight_all(text);
+            return true;
+        }
+
+        false
+    }
+
+    /// Mark the whole text to be highlighted, the lines are plain text until highlighted.
+    pub fn highlight_all(&mut self, text: &Rope) {
+        self.lines = text
+            .lines()
+            .map(|line| LineHighlightStyle::default().resized(line.len()))
+            .collect();
+        self.states = vec![self.highlighter.start_state(); self.lines.len()];
+        self.invalid_rows = Some(0..self.lines.len());
+        self.version += 1;
+    }
+
+    /// Update the changed lines with the stale styles, and mark them to be highlighted again.
+    pub fn update(&mut self, text: &Rope, edit: &LinesEdit) {
+        if self.lines.is_empty() && edit.old_rows > 0 {
+            return self.highlight_all(text);
         }
 
-        let mut lines = vec![];
-        let mut offset = 0;
-        let mut new_cache = HashMap::new();
-        for line in text.split('\n') {
-            let cache_key = gpui::hash(&line);
-
-            // cache hit
-            if let Some(line_style) = self.cache.get(&cache_key) {
-                let new_style = LineHighlightStyle {
-                    offset,
-                    styles: line_style.styles.clone(),
-                };
-                new_cache.insert(cache_key, new_style.clone());
-                lines.push(new_style);
-            } else {
-                // cache miss
-                let styles = Rc::new(self.highlighter.highlight(line));
-                let line_style = LineHighlightStyle { offset, styles };
-                new_cache.insert(cache_key, line_style.clone());
-                lines.push(line_style);
+        let old_end = (edit.start_row + edit.old_rows).min(self.lines.len());
+        let start = edit.start_row.min(old_end);
+
+        let new_lines = text
+            .lines_at(edit.start_row)
+            .take(edit.new_rows)
+            .enumerate()
+            .map(|(ix, line)| match self.lines.get(start + ix) {
+                Some(old) if start + ix < old_end => old.resized(line.len()),
+                _ => LineHighlightStyle::default().resized(line.len()),
+            })
+            .collect::<Vec<_>>();
+        self.lines.splice(start..old_end, new_lines);
+
+        // The state at the start of the first changed line is not affected by the edit.
+        let state = self
+            .states
+            .get(start)
+            .cloned()
+            .unwrap_or_else(|| self.highlighter.start_state());
+        self.states
+            .splice(start..old_end, std::iter::repeat(state).take(edit.new_rows));
+
+        let rows = merge_invalid_rows(self.invalid_rows.take(), edit);
+        self.invalid_rows = Some(rows.start..rows.end.min(self.lines.len()));
+        self.version += 1;
+    }
+
+    /// Return the next batch of lines to highlight, None if all lines are highlighted.
+    pub(super) fn next_batch(&self, text: &Rope) -> Option<HighlightBatch> {
+        let rows = self.invalid_rows.clone()?;
+        if rows.start >= self.lines.len() {
+            return None;
+        }
+
+        let lines = text
+            .lines_at(rows.start)
+            .take(MAX_BATCH_ROWS)
+            .collect::<Vec<_>>();
+        let states_end = (rows.start + 1 + lines.len()).min(self.states.len());
+        let old_states = self
+            .states
+            .get(rows.start + 1..states_end)
+            .map(|states| states.to_vec())
+            .unwrap_or_default();
+
+        Some(HighlightBatch {
+            highlighter: self.highlighter.clone(),
+            version: self.version,
+            start_row: rows.start,
+            state: self.states[rows.start].clone(),
+            lines,
+            old_states,
+            check_from: rows.end,
+        })
+    }
+
+    /// Apply the result of a background batch, the outdated result is ignored.
+    ///
+    /// Returns false if the result is outdated.
+    pub(super) fn apply(&mut self, result: HighlightResult) -> bool {
+        if result.version != self.version {
+            return false;
+        }
+
+        let start_row = result.start_row;
+        let end_row = start_row + result.lines.len();
+        for (ix, styles) in result.lines.into_iter().enumerate() {
+            if let Some(line) = self.lines.get_mut(start_row + ix) {
+                line.styles = Rc::new(styles);
+            }
+        }
+        for (ix, state) in result.states.into_iter().enumerate() {
+            if let Some(old) = self.states.get_mut(start_row + ix + 1) {
+                *old = state;
+            }
+        }
+
+        self.invalid_rows = match self.invalid_rows.take() {
+            Some(rows) if !result.converged && end_row < self.lines.len() => {
+                Some(end_row..rows.end.max(end_row))
             }
+            _ => None,
+        };
+        true
+    }
+}
+
+impl InputState {
+    /// Return the highlighted lines of the `rows` for the [`InputMode::CodeEditor`] mode.
+    ///
+    /// The stale styles are returned until the background highlighting is done.
+    pub(super) fn highlight_lines(
+        &mut self,
+        rows: &Range<usize>,
+        cx: &mut Context<Self>,
+    ) -> Option<Vec<LineHighlightStyle>> {
+        let InputMode::CodeEditor { highlighter, .. } = &mut self.mode else {
+            return None;
+        };
 
-            // +1 for '\n'
-            offset += line.len() + 1;
+        let prepared = highlighter.prepare(&self.text);
+        let len = highlighter.lines.len();
+        let lines = highlighter.lines[rows.start.min(len)..rows.end.min(len)].to_vec();
+        if prepared {
+            self.highlight_in_background(cx);
         }
 
-        // Ensure to recreate cache to remove unused caches.
-        self.cache = new_cache;
-        self.lines = lines;
-        self.text = text;
+        Some(lines)
+    }
+
+    /// Highlight the changed lines in a background task, batch by batch until the parse states converge.
+    pub(super) fn highlight_in_background(&mut self, cx: &mut Context<Self>) {
+        if !matches!(self.mode, InputMode::CodeEditor { .. }) {
+            return;
+        }
+
+        self._highlight_task = cx.spawn(async move |this, cx| loop {
+            let Ok(Some(batch)) = this.update(cx, |state, _| match &state.mode {
+                InputMode::CodeEditor { highlighter, .. } => highlighter.next_batch(&state.text),
+                _ => None,
+            }) else {
+                break;
+            };
+
+            let result = cx
+                .background_executor()
+                .spawn(async move { batch.run() })
+                .await;
+
+            let applied = this.update(cx, |state, cx| match &mut state.mode {
+                InputMode::CodeEditor { highlighter, .. } => {
+                    cx.notify();
+                    highlighter.apply(result)
+                }
+                _ => false,
+            });
+            if !matches!(applied, Ok(true)) {
+                break;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resize_styles() {
+        let styles = vec![(0..3, 'a'), (3..5, 'b'), (5..10, 'c')];
+        assert_eq!(resize_styles(&styles, 10), styles);
+        assert_eq!(resize_styles(&styles, 4), vec![(0..3, 'a'), (3..4, 'b')]);
+        assert_eq!(
+            resize_styles(&styles, 12),
+            vec![(0..3, 'a'), (3..5, 'b'), (5..12, 'c')]
+        );
+        assert!(resize_styles(&styles, 0).is_empty());
+    }
+
+    #[test]
+    fn test_merge_invalid_rows() {
+        let edit = LinesEdit {
+            start_row: 5,
+            old_rows: 1,
+            new_rows: 3,
+        };
+        assert_eq!(merge_invalid_rows(None, &edit), 5..8);
+        // The invalid rows before the edit.
+        assert_eq!(merge_invalid_rows(Some(1..2), &edit), 1..8);
+        // The invalid rows after the edit are moved.
+        assert_eq!(merge_invalid_rows(Some(10..12), &edit), 5..14);
+        // The invalid rows end inside the edit.
+        let edit = LinesEdit {
+            start_row: 5,
+            old_rows: 4,
+            new_rows: 1,
+        };
+        assert_eq!(merge_invalid_rows(Some(2..7), &edit), 2..6);
     }
 }
@@ -0,0 +1,589 @@
+use std::{ops::Range, rc::Rc, time::Duration};
+
+use anyhow::Result;
+use gpui::{
+    div, prelude::FluentBuilder as _, px, App, AppContext as _, Context, Entity,
+    EntityInputHandler as _, ParentElement as _, Pixels, Point, SharedString, Styled as _, Task,
+    WeakEntity, Window,
+};
+use smol::Timer;
+
+use super::{rope::Rope, InputState};
+use crate::{
+    fuzzy::fuzzy_search,
+    h_flex,
+    list::{List, ListDelegate, ListItem},
+    ActiveTheme as _,
+};
+
+/// The delay before requesting the completions on typing.
+const COMPLETION_DEBOUNCE: Duration = Duration::from_millis(100);
+/// The width of the completion menu.
+pub(super) const COMPLETION_MENU_WIDTH: Pixels = px(300.);
+
+/// An item of the completions returned by the [`CompletionProvider`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    /// The label to show in the completion menu.
+    pub label: SharedString,
+    /// The detail to show after the label, e.g. the type of a symbol.
+    pub detail: Option<SharedString>,
+    /// The text to insert, default is the `label`.
+    ///
+    /// The snippet syntax is supported: `$1`, `${1:placeholder}` for the tabstops
+    /// and `$0` for the final cursor position, use `\$` to insert a `$`.
+    pub insert_text: Option<SharedString>,
+    /// The text to filter the item while typing, default is the `label`.
+    pub filter_text: Option<SharedString>,
+}
+
+impl CompletionItem {
+    pub fn new(label: impl Into<SharedString>) -> Self {
+        Self {
+            label: label.into(),
+            detail: None,
+            insert_text: None,
+            filter_text: None,
+        }
+    }
+
+    /// Set the detail of the item.
+    pub fn detail(mut self, detail: impl Into<SharedString>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set the text (or snippet) to insert.
+    pub fn insert_text(mut self, insert_text: impl Into<SharedString>) -> Self {
+        self.insert_text = Some(insert_text.into());
+        self
+    }
+
+    /// Set the text to filter the item.
+    pub fn filter_text(mut self, filter_text: impl Into<SharedString>) -> Self {
+        self.filter_text = Some(filter_text.into());
+        self
+    }
+
+    fn filter_text_or_label(&self) -> &str {
+        self.filter_text.as_ref().unwrap_or(&self.label)
+    }
+}
+
+/// A provider of the completions for the [`InputState`].
+///
+/// The completions are requested on typing a trigger character, or a word character if
+/// [`CompletionProvider::complete_on_word`] is true, and filtered by the current word while typing.
+pub trait CompletionProvider {
+    /// Return the completions at the cursor `offset` (in bytes) of the `text`.
+    ///
+    /// The items are filtered by the word before the cursor, so all candidates can be returned.
+    fn completions(
+        &self,
+        text: &Rope,
+        offset: usize,
+        window: &mut Window,
+        cx: &mut Context<InputState>,
+    ) -> Task<Result<Vec<CompletionItem>>>;
+
+    /// The characters to request the completions, e.g. `.` in code or `@` for mentions.
+    fn trigger_characters(&self) -> &[char] {
+        &[]
+    }
+
+    /// Return false to request the completions only after the trigger characters.
+    ///
+    /// Default is true.
+    fn complete_on_word(&self) -> bool {
+        true
+    }
+}
+
+pub(super) struct CompletionMenu {
+    pub(super) list: Entity<List<CompletionListDelegate>>,
+    /// The start offset of the word to complete.
+    start: usize,
+}
+
+pub(super) struct CompletionListDelegate {
+    input: WeakEntity<InputState>,
+    items: Vec<CompletionItem>,
+    /// The indices of the items matched by the current word, sorted by the score.
+    matches: Vec<usize>,
+    selected_index: Option<usize>,
+}
+
+impl CompletionListDelegate {
+    fn set_items(&mut self, items: Vec<CompletionItem>, query: &str) {
+        self.items = items;
+        self.set_query(query);
+    }
+
+    fn set_query(&mut self, query: &str) {
+        self.matches = filter_completions(&self.items, query);
+        self.selected_index = (!self.matches.is_empty()).then_some(0);
+    }
+
+    fn selected_item(&self) -> Option<&CompletionItem> {
+        self.selected_index
+            .and_then(|ix| self.matches.get(ix))
+            .and_then(|ix| self.items.get(*ix))
+    }
+}
+
+impl ListDelegate for CompletionListDelegate {
+    type Item = ListItem;
+
+    fn items_count(&self, _: &App) -> usize {
+        self.matches.len()
+    }
+
+    fn render_item(
+        &self,
+        ix: usize,
+        _: &mut Window,
+        cx: &mut Context<List<Self>>,
+    ) -> Option<Self::Item> {
+        let item = self.items.get(*self.matches.get(ix)?)?;
+
+        Some(
+            ListItem::new(("completion-item", ix))
+                .selected(self.selected_index == Some(ix))
+                .child(
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .justify_between()
+                        .child(div().truncate().child(item.label.clone()))
+                        .when_some(item.detail.clone(), |this, detail| {
+                            this.child(
+                                div()
+                                    .truncate()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(detail),
+                            )
+                        }),
+                ),
+        )
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: Option<usize>,
+        _: &mut Window,
+        _: &mut Context<List<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<List<Self>>) {
+        let Some(item) = self.selected_item().cloned() else {
+            return;
+        };
+
+        let input = self.input.clone();
+        cx.defer_in(window, move |_, window, cx| {
+            _ = input.update(cx, |state, cx| {
+                state.apply_completion(&item, window, cx);
+                state.focus(window, cx);
+            });
+        });
+    }
+
+    fn cancel(&mut self, window: &mut Window, cx: &mut Context<List<Self>>) {
+        let input = self.input.clone();
+        cx.defer_in(window, move |_, _, cx| {
+            _ = input.update(cx, |state, cx| {
+                state.hide_completion_menu(cx);
+            });
+        });
+    }
+}
+
+#[inline]
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Return the indices of the items matched by the `query`, sorted by the score.
+fn filter_completions(items: &[CompletionItem], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return (0..items.len()).collect();
+    }
+
+    fuzzy_search(query, 0..items.len(), |ix| {
+        items[*ix].filter_text_or_label()
+    })
+    .into_iter()
+    .map(|(ix, _)| ix)
+    .collect()
+}
+
+/// Parse the snippet, returns the text to insert and the range to select after inserting.
+///
+/// The first tabstop (`$1` or `${1:placeholder}`) is selected, or the cursor moves to the `$0`,
+/// otherwise the cursor moves to the end of the text.
+fn parse_snippet(snippet: &str) -> (String, Range<usize>) {
+    let mut text = String::with_capacity(snippet.len());
+    let mut tabstops: Vec<(usize, Range<usize>)> = vec![];
+    let mut chars = snippet.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), Some('$' | '}' | '\\')) => {
+                text.extend(chars.next());
+            }
+            '$' if chars.peek().map_or(false, |c| c.is_ascii_digit()) => {
+                let mut n = 0;
+                while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    n = n * 10 + digit as usize;
+                    chars.next();
+                }
+                tabstops.push((n, text.len()..text.len()));
+            }
+            '$' if chars.peek() == Some(&'{') => {
+                let mut rest = chars.clone();
+                rest.next();
+                let mut n = None;
+                while let Some(digit) = rest.peek().and_then(|c| c.to_digit(10)) {
+                    n = Some(n.unwrap_or(0) * 10 + digit as usize);
+                    rest.next();
+                }
+                let Some(n) = n.filter(|_| matches!(rest.peek(), Some(':' | '}'))) else {
+                    text.push(c);
+                    continue;
+                };
+
+                let start = text.len();
+                if rest.next() == Some(':') {
+                    while let Some(c) = rest.next() {
+                        match c {
+                            '}' => break,
+                            '\\' if matches!(rest.peek(), Some('$' | '}' | '\\')) => {
+                                text.extend(rest.next());
+                            }
+                            _ => text.push(c),
+                        }
+                    }
+                }
+                tabstops.push((n, start..text.len()));
+                chars = rest;
+            }
+            _ => text.push(c),
+        }
+    }
+
+    let selection = tabstops
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .min_by_key(|(n, _)| *n)
+        .or_else(|| tabstops.iter().find(|(n, _)| *n == 0))
+        .map(|(_, range)| range.clone())
+        .unwrap_or(text.len()..text.len());
+
+    (text, selection)
+}
+
+impl InputState {
+    /// Set the [`CompletionProvider`] to show the completions while typing.
+    pub fn completion_provider(mut self, provider: impl CompletionProvider + 'static) -> Self {
+        self.completion_provider = Some(Rc::new(provider));
+        self
+    }
+
+    /// Set the [`CompletionProvider`], None to disable the completions.
+    pub fn set_completion_provider(
+        &mut self,
+        provider: Option<Rc<dyn CompletionProvider>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.completion_provider = provider;
+        self.hide_completion_menu(cx);
+    }
+
+    /// Return the start offset of the word before the `offset`.
+    fn completion_word_start(&self, offset: usize) -> usize {
+        offset
+            - self
+                .text
+                .reversed_chars_at(offset)
+                .take_while(|c| is_word_char(*c))
+                .map(char::len_utf8)
+                .sum::<usize>()
+    }
+
+    /// Return the range of the word at the `offset` to replace with the completion.
+    fn completion_word_range(&self, offset: usize) -> Range<usize> {
+        let end = offset
+            + self
+                .text
+                .chars_at(offset)
+                .take_while(|c| is_word_char(*c))
+                .map(char::len_utf8)
+                .sum::<usize>();
+        self.completion_word_start(offset)..end
+    }
+
+    /// Update the completions after the text has been replaced with the `new_text`.
+    ///
+    /// The `typing` is true if the text is typed at the cursor.
+    pub(super) fn update_completions(
+        &mut self,
+        new_text: &str,
+        typing: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(provider) = self.completion_provider.clone() else {
+            return;
+        };
+        if !typing || !self.extra_selections.is_empty() {
+            self.hide_completion_menu(cx);
+            return;
+        }
+
+        let mut chars = new_text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if provider.trigger_characters().contains(&c) => {
+                self.hide_completion_menu(cx);
+                self.request_completions(window, cx);
+            }
+            (Some(c), None) if is_word_char(c) => {
+                if self.completion_menu.is_some() {
+                    self.filter_completion_menu(window, cx);
+                } else if provider.complete_on_word() {
+                    self.request_completions(window, cx);
+                }
+            }
+            // Deleted a character, e.g. by backspace.
+            (None, _) if self.completion_menu.is_some() => self.filter_completion_menu(window, cx),
+            _ => self.hide_completion_menu(cx),
+        }
+    }
+
+    /// Request the completions from the provider after the debounce delay.
+    fn request_completions(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(provider) = self.completion_provider.clone() else {
+            return;
+        };
+
+        self._completion_task = cx.spawn_in(window, async move |this, cx| {
+            Timer::after(COMPLETION_DEBOUNCE).await;
+
+            let Ok(task) = this.update_in(cx, |state, window, cx| {
+                provider.completions(&state.text, state.cursor_offset(), window, cx)
+            }) else {
+                return;
+            };
+
+            let items = task.await.unwrap_or_default();
+            _ = this.update_in(cx, |state, window, cx| {
+                state.show_completion_menu(items, window, cx);
+            });
+        });
+    }
+
+    fn show_completion_menu(
+        &mut self,
+        items: Vec<CompletionItem>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cursor = self.cursor_offset();
+        let start = self.completion_word_start(cursor);
+        let query = self.text.slice(start..cursor);
+
+        if let Some(menu) = self.completion_menu.as_mut() {
+            menu.start = start;
+            menu.list.update(cx, |list, cx| {
+                list.delegate_mut().set_items(items, &query);
+                list.set_selected_index(list.delegate().selected_index, window, cx);
+                cx.notify();
+            });
+        } else {
+            let delegate = CompletionListDelegate {
+                input: cx.entity().downgrade(),
+                items: vec![],
+                matches: vec![],
+                selected_index: None,
+            };
+            let list = cx.new(|cx| {
+                let mut list = List::new(delegate, window, cx).no_query().max_h(px(240.));
+                list.delegate_mut().set_items(items, &query);
+                list.set_selected_index(list.delegate().selected_index, window, cx);
+                list
+            });
+            self.completion_menu = Some(CompletionMenu { list, start });
+        }
+
+        if self.completion_matches_count(cx) == 0 {
+            self.hide_completion_menu(cx);
+        }
+        cx.notify();
+    }
+
+    /// Filter the items of the completion menu by the word before the cursor.
+    fn filter_completion_menu(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(menu) = self.completion_menu.as_ref() else {
+            return;
+        };
+
+        let cursor = self.cursor_offset();
+        if cursor < menu.start || self.completion_word_start(cursor) > menu.start {
+            self.hide_completion_menu(cx);
+            return;
+        }
+
+        let query = self.text.slice(menu.start..cursor);
+        menu.list.update(cx, |list, cx| {
+            list.delegate_mut().set_query(&query);
+            list.set_selected_index(list.delegate().selected_index, window, cx);
+            cx.notify();
+        });
+
+        if self.completion_matches_count(cx) == 0 {
+            self.hide_completion_menu(cx);
+        }
+    }
+
+    fn completion_matches_count(&self, cx: &App) -> usize {
+        self.completion_menu
+            .as_ref()
+            .map_or(0, |menu| menu.list.read(cx).delegate().matches.len())
+    }
+
+    /// Hide the completion menu and cancel the pending request.
+    ///
+    /// Returns true if the menu was visible.
+    pub(super) fn hide_completion_menu(&mut self, cx: &mut Context<Self>) -> bool {
+        self._completion_task = Task::ready(());
+        if self.completion_menu.take().is_some() {
+            cx.notify();
+            return true;
+        }
+
+        false
+    }
+
+    /// Select the previous (`direction` < 0) or next item of the completion menu.
+    ///
+    /// Returns true if the completion menu is visible.
+    pub(super) fn select_completion(
+        &mut self,
+        direction: isize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(menu) = self.completion_menu.as_ref() else {
+            return false;
+        };
+
+        menu.list.update(cx, |list, cx| {
+            let count = list.delegate().matches.len();
+            if count == 0 {
+                return;
+            }
+
+            let ix = list.selected_index().unwrap_or(0);
+            let ix = if direction < 0 {
+                (ix + count - 1) % count
+            } else {
+                (ix + 1) % count
+            };
+            list.set_selected_index(Some(ix), window, cx);
+            cx.notify();
+        });
+        true
+    }
+
+    /// Insert the selected item of the completion menu.
+    ///
+    /// Returns true if the completion menu is visible.
+    pub(super) fn confirm_completion(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(menu) = self.completion_menu.as_ref() else {
+            return false;
+        };
+
+        let Some(item) = menu.list.read(cx).delegate().selected_item().cloned() else {
+            return false;
+        };
+
+        self.apply_completion(&item, window, cx);
+        true
+    }
+
+    /// Replace the current word with the completion item, and select the first tabstop of the snippet.
+    fn apply_completion(
+        &mut self,
+        item: &CompletionItem,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.hide_completion_menu(cx);
+
+        let range = self.completion_word_range(self.cursor_offset());
+        let (text, selection) = parse_snippet(item.insert_text.as_ref().unwrap_or(&item.label));
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), &text, window, cx);
+
+        let len = self.text.len();
+        self.selected_range =
+            (range.start + selection.start).min(len)..(range.start + selection.end).min(len);
+        self.selection_reversed = false;
+        cx.notify();
+    }
+
+    /// Return the position to show the completion menu, under the start of the current word.
+    pub(super) fn completion_menu_origin(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Point<Pixels>> {
+        let start = self.completion_menu.as_ref()?.start;
+        let bounds = self.last_bounds?;
+        let range = self.range_to_utf16(&(start..start));
+        self.bounds_for_range(range, bounds, window, cx)
+            .map(|bounds| bounds.bottom_left())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_completions() {
+        let items = vec![
+            CompletionItem::new("println"),
+            CompletionItem::new("print"),
+            CompletionItem::new("eprintln"),
+            CompletionItem::new("format").filter_text("fmt"),
+        ];
+
+        assert_eq!(filter_completions(&items, ""), vec![0, 1, 2, 3]);
+        assert_eq!(filter_completions(&items, "pri"), vec![0, 1, 2]);
+        assert_eq!(filter_completions(&items, "pln"), vec![0, 2]);
+        assert_eq!(filter_completions(&items, "fmt"), vec![3]);
+        assert!(filter_completions(&items, "xyz").is_empty());
+    }
+
+    #[test]
+    fn test_parse_snippet() {
+        assert_eq!(parse_snippet("foo"), ("foo".to_string(), 3..3));
+        assert_eq!(parse_snippet("foo($0)"), ("foo()".to_string(), 4..4));
+        assert_eq!(
+            parse_snippet("foo(${1:a}, ${2:b})$0"),
+            ("foo(a, b)".to_string(), 4..5)
+        );
+        assert_eq!(
+            parse_snippet("if $2 {\n    $1\n}"),
+            ("if  {\n    \n}".to_string(), 10..10)
+        );
+        assert_eq!(parse_snippet("\\$0 ${x}"), ("$0 ${x}".to_string(), 7..7));
+        assert_eq!(parse_snippet("${1:\\}}"), ("}".to_string(), 0..1));
+    }
+}
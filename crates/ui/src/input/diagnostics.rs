@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+use gpui::{px, AnyView, App, Context, Hsla, Pixels, Point, SharedString, Window};
+
+use super::{multi_cursor::adjust_range_for_edit, InputState};
+use crate::{tooltip::Tooltip, ActiveTheme as _, IconName};
+
+/// The width of the diagnostic markers in the gutter.
+pub(super) const DIAGNOSTIC_MARKER_WIDTH: Pixels = px(16.);
+
+/// The severity of a [`Diagnostic`], sorted from the most severe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticSeverity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl DiagnosticSeverity {
+    pub(super) fn color(&self, cx: &App) -> Hsla {
+        match self {
+            Self::Error => cx.theme().danger,
+            Self::Warning => cx.theme().warning,
+            Self::Info => cx.theme().info,
+            Self::Hint => cx.theme().muted_foreground,
+        }
+    }
+
+    pub(super) fn icon(&self) -> IconName {
+        match self {
+            Self::Error => IconName::CircleX,
+            Self::Warning => IconName::TriangleAlert,
+            Self::Info | Self::Hint => IconName::Info,
+        }
+    }
+}
+
+/// A diagnostic (e.g. the result of a linter or compiler) of the text in the [`InputState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The UTF-8 byte range of the text.
+    pub range: Range<usize>,
+    pub severity: DiagnosticSeverity,
+    pub message: SharedString,
+}
+
+impl Diagnostic {
+    /// Create a new [`DiagnosticSeverity::Error`] diagnostic.
+    pub fn new(range: Range<usize>, message: impl Into<SharedString>) -> Self {
+        Self {
+            range,
+            severity: DiagnosticSeverity::default(),
+            message: message.into(),
+        }
+    }
+
+    /// Set the severity of the diagnostic, default is [`DiagnosticSeverity::Error`].
+    pub fn severity(mut self, severity: DiagnosticSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+/// The tooltip of the hovered diagnostic.
+pub(super) struct DiagnosticTooltip {
+    /// The index of the diagnostic.
+    ix: usize,
+    pub(super) position: Point<Pixels>,
+    pub(super) view: AnyView,
+}
+
+/// Adjust the diagnostics after the `edit` range of the text has been replaced with a text of `new_len`.
+///
+/// The diagnostics that have been deleted are removed.
+pub(super) fn adjust_diagnostics(
+    diagnostics: &mut Vec<Diagnostic>,
+    edit: &Range<usize>,
+    new_len: usize,
+) {
+    diagnostics.retain_mut(|diagnostic| {
+        let was_empty = diagnostic.range.is_empty();
+        adjust_range_for_edit(&mut diagnostic.range, edit, new_len);
+        was_empty || !diagnostic.range.is_empty()
+    });
+}
+
+impl InputState {
+    /// Set the diagnostics of the text, the old diagnostics will be replaced.
+    ///
+    /// The diagnostics are rendered as wavy underlines and gutter markers (if line number is enabled),
+    /// their ranges are moved with the edits.
+    pub fn set_diagnostics(&mut self, mut diagnostics: Vec<Diagnostic>, cx: &mut Context<Self>) {
+        let len = self.text.len();
+        for diagnostic in diagnostics.iter_mut() {
+            diagnostic.range.end = diagnostic.range.end.min(len);
+            diagnostic.range.start = diagnostic.range.start.min(diagnostic.range.end);
+        }
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.range.start, diagnostic.severity));
+
+        self.diagnostics = diagnostics;
+        self.diagnostic_tooltip = None;
+        cx.notify();
+    }
+
+    /// Return the diagnostics of the text.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Show the tooltip of the diagnostic under the mouse position.
+    pub(super) fn update_diagnostic_tooltip(
+        &mut self,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.diagnostics.is_empty() && self.diagnostic_tooltip.is_none() {
+            return;
+        }
+
+        let hovered_ix = if self.input_bounds.contains(&position) {
+            let offset = self.index_for_mouse_position(position, window, cx);
+            self.diagnostics
+                .iter()
+                .position(|d| d.range.start <= offset && offset < d.range.end)
+        } else {
+            None
+        };
+
+        match hovered_ix {
+            Some(ix) if self.diagnostic_tooltip.as_ref().map(|t| t.ix) == Some(ix) => {}
+            Some(ix) => {
+                let view = Tooltip::new(self.diagnostics[ix].message.clone()).build(window, cx);
+                self.diagnostic_tooltip = Some(DiagnosticTooltip { ix, position, view });
+                cx.notify();
+            }
+            None => {
+                if self.diagnostic_tooltip.take().is_some() {
+                    cx.notify();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjust_diagnostics() {
+        let mut diagnostics = vec![
+            Diagnostic::new(0..3, "a"),
+            Diagnostic::new(10..15, "b").severity(DiagnosticSeverity::Warning),
+            Diagnostic::new(20..20, "c"),
+        ];
+
+        // Insert before the diagnostics.
+        adjust_diagnostics(&mut diagnostics, &(5..5), 2);
+        assert_eq!(diagnostics[0].range, 0..3);
+        assert_eq!(diagnostics[1].range, 12..17);
+        assert_eq!(diagnostics[2].range, 22..22);
+
+        // Delete the text of the first diagnostic.
+        adjust_diagnostics(&mut diagnostics, &(0..4), 0);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].range, 8..13);
+        assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+        assert_eq!(diagnostics[1].range, 18..18);
+    }
+}
@@ -1,17 +1,123 @@
 use gpui::{
     fill, point, px, relative, size, App, Bounds, Corners, Element, ElementId, ElementInputHandler,
-    Entity, GlobalElementId, IntoElement, LayoutId, MouseButton, MouseMoveEvent, PaintQuad, Path,
-    Pixels, Point, SharedString, Style, TextAlign, TextRun, UnderlineStyle, Window, WrappedLine,
+    Entity, GlobalElementId, Hsla, IntoElement, LayoutId, MouseButton, MouseMoveEvent, PaintQuad,
+    Path, Pixels, Point, SharedString, Size, Style, TextAlign, TextRun, TransformationMatrix,
+    UnderlineStyle, Window, WrappedLine,
 };
-use smallvec::SmallVec;
+use std::{ops::Range, rc::Rc};
 
-use crate::{ActiveTheme as _, Root};
+use crate::{ActiveTheme as _, Colorize as _, IconName, Root};
 
-use super::{code_highlighter::LineHighlightStyle, mode::InputMode, InputState};
+use super::{
+    code_highlighter::LineHighlightStyle,
+    diagnostics::{DiagnosticSeverity, DIAGNOSTIC_MARKER_WIDTH},
+    folding::FOLD_MARKER_WIDTH,
+    InputState,
+};
 
 const RIGHT_MARGIN: Pixels = px(5.);
 const BOTTOM_MARGIN_ROWS: usize = 1;
-const LINE_NUMBER_MARGIN_RIGHT: Pixels = px(10.);
+/// The number of rows to layout before and after the visible rows, to avoid blank lines while scrolling.
+const OVERSCAN_ROWS: usize = 2;
+pub(super) const LINE_NUMBER_MARGIN_RIGHT: Pixels = px(10.);
+
+/// The layout of a line (split by `\n`) of the text.
+#[derive(Clone)]
+pub(super) struct LineLayout {
+    /// The row of the line in the text.
+    pub(super) row: usize,
+    /// The start offset of the line in the text.
+    pub(super) offset: usize,
+    /// The y offset of the line, relative to the top of the text.
+    pub(super) top: Pixels,
+    pub(super) line: WrappedLine,
+}
+
+impl LineLayout {
+    /// The end offset of the line in the text (exclude the `\n`).
+    #[inline]
+    pub(super) fn end(&self) -> usize {
+        self.offset + self.line.len()
+    }
+
+    /// Return the position of the `offset` relative to the top left of the text,
+    /// None if the offset is not in this line.
+    pub(super) fn position_for_offset(
+        &self,
+        offset: usize,
+        line_height: Pixels,
+    ) -> Option<Point<Pixels>> {
+        if offset < self.offset || offset > self.end() {
+            return None;
+        }
+
+        self.line
+            .position_for_index(offset - self.offset, line_height)
+            .map(|pos| point(pos.x, self.top + pos.y))
+    }
+}
+
+/// The layout of the last paint.
+///
+/// Only the lines in the scroll viewport (and a few overscan rows) are shaped,
+/// the lines hidden by folds are excluded.
+#[derive(Clone)]
+pub(super) struct LastLayout {
+    /// The laid out lines, sorted by the row.
+    pub(super) lines: Rc<Vec<LineLayout>>,
+}
+
+impl LastLayout {
+    /// Return the index of the line that contains the `offset`, None if the line is not laid out.
+    pub(super) fn line_index_for_offset(&self, offset: usize) -> Option<usize> {
+        line_index_for_offset(&self.lines, offset)
+    }
+
+    /// Return the position of the `offset` relative to the top left of the text,
+    /// None if the line of the offset is not laid out.
+    pub(super) fn position_for_offset(
+        &self,
+        offset: usize,
+        line_height: Pixels,
+    ) -> Option<Point<Pixels>> {
+        position_for_offset(&self.lines, offset, line_height)
+    }
+}
+
+fn line_index_for_offset(lines: &[LineLayout], offset: usize) -> Option<usize> {
+    let ix = lines.partition_point(|line| line.end() < offset);
+    lines
+        .get(ix)
+        .filter(|line| line.offset <= offset)
+        .map(|_| ix)
+}
+
+fn position_for_offset(
+    lines: &[LineLayout],
+    offset: usize,
+    line_height: Pixels,
+) -> Option<Point<Pixels>> {
+    let ix = line_index_for_offset(lines, offset)?;
+    lines[ix].position_for_offset(offset, line_height)
+}
+
+/// Return the laid out lines that overlap with the `range`.
+fn lines_in_range<'a>(
+    lines: &'a [LineLayout],
+    range: &Range<usize>,
+) -> impl Iterator<Item = &'a LineLayout> {
+    let ix = lines.partition_point(|line| line.end() < range.start);
+    let end = range.end;
+    lines[ix..]
+        .iter()
+        .take_while(move |line| line.offset <= end)
+}
+
+/// Return the line number label of the `row`, padded to the width of the max line number.
+fn line_number_label(row: usize, total_lines: usize) -> String {
+    let width = total_lines.to_string().len().max(3);
+    format!("{:>width$}", row + 1)
+}
 
 pub(super) struct TextElement {
     input: Entity<InputState>,
@@ -41,159 +147,421 @@ impl TextElement {
                     input.update(cx, |input, cx| {
                         input.on_drag_move(event, window, cx);
                     });
+                } else {
+                    input.update(cx, |input, cx| {
+                        input.update_diagnostic_tooltip(event.position, window, cx);
+                    });
                 }
             }
         });
     }
 
-    fn layout_cursor(
-        &self,
-        lines: &[WrappedLine],
+    /// Return the width of the gutter (the line numbers and the markers), and the x offset of the line numbers.
+    fn layout_gutter_width(&self, window: &mut Window, cx: &mut App) -> (Pixels, Pixels) {
+        let input = self.input.read(cx);
+        if !input.mode.line_number() {
+            return (px(0.), px(0.));
+        }
+
+        let style = window.text_style();
+        let font_size = style.font_size.to_pixels(window.rem_size());
+        // Use the max line number to measure the width, to avoid shaping the line numbers of all lines.
+        let total_lines = input.text.lines_len();
+        let label = line_number_label(total_lines - 1, total_lines);
+        let run = TextRun {
+            len: label.len(),
+            font: style.font(),
+            color: cx.theme().muted_foreground,
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+        };
+        let mut width = window
+            .text_system()
+            .shape_text(label.into(), font_size, &[run], None, None)
+            .ok()
+            .and_then(|lines| lines.into_iter().next())
+            .map(|line| line.width())
+            .unwrap_or_default()
+            + LINE_NUMBER_MARGIN_RIGHT;
+
+        let mut offset = px(0.);
+        if input.mode.is_code_editor() {
+            offset = DIAGNOSTIC_MARKER_WIDTH;
+            width += DIAGNOSTIC_MARKER_WIDTH + FOLD_MARKER_WIDTH;
+        }
+
+        (width, offset)
+    }
+
+    /// Return the scroll offset to keep the cursor in view, if the cursor has been moved.
+    fn layout_scroll_offset(
+        &mut self,
+        bounds: &Bounds<Pixels>,
         line_height: Pixels,
-        bounds: &mut Bounds<Pixels>,
-        line_number_width: Pixels,
+        wrap_width: Option<Pixels>,
         window: &mut Window,
         cx: &mut App,
-    ) -> (Option<PaintQuad>, Point<Pixels>, usize) {
+    ) -> Point<Pixels> {
         let input = self.input.read(cx);
+        let mut scroll_offset = input.scroll_handle.offset();
         let mut selected_range = input.selected_range.clone();
         if let Some(marked_range) = &input.marked_range {
             selected_range = marked_range.end..marked_range.end;
         }
 
         let cursor_offset = input.cursor_offset();
-        let mut current_line_index = 0;
-        let mut scroll_offset = input.scroll_handle.offset();
-        let mut cursor = None;
+        let cursor_moved = input.last_cursor_offset != Some(cursor_offset);
+        let selection_changed = input.last_selected_range != Some(selected_range);
+        if !cursor_moved && !selection_changed {
+            return scroll_offset;
+        }
+
+        // Only layout the line of the cursor, it may be out of the visible lines.
+        let row = input.text.offset_to_point(cursor_offset).row;
+        let rows = row..row + 1;
+        let highlight_styles = self.highlight_lines(&rows, cx);
+        let lines = self.layout_lines(rows, highlight_styles, wrap_width, line_height, window, cx);
+        let Some(cursor_pos) = position_for_offset(&lines, cursor_offset, line_height) else {
+            return scroll_offset;
+        };
 
         // If the input has a fixed height (Otherwise is auto-grow), we need to add a bottom margin to the input.
-        let bottom_margin = if input.is_auto_grow() {
+        let bottom_margin = if self.input.read(cx).is_auto_grow() {
             px(0.) + line_height
         } else {
             BOTTOM_MARGIN_ROWS * line_height + line_height
         };
-        // The cursor corresponds to the current cursor position in the text no only the line.
-        let mut cursor_pos = None;
-        let mut cursor_start = None;
-        let mut cursor_end = None;
 
-        let mut prev_lines_offset = 0;
-        let mut offset_y = px(0.);
-        for line in lines.iter() {
-            // break loop if all cursor positions are found
-            if cursor_pos.is_some() && cursor_start.is_some() && cursor_end.is_some() {
-                break;
-            }
+        scroll_offset.x = if scroll_offset.x + cursor_pos.x > (bounds.size.width - RIGHT_MARGIN) {
+            // cursor is out of right
+            bounds.size.width - RIGHT_MARGIN - cursor_pos.x
+        } else if scroll_offset.x + cursor_pos.x < px(0.) {
+            // cursor is out of left
+            -cursor_pos.x
+        } else {
+            scroll_offset.x
+        };
+        scroll_offset.y =
+            if scroll_offset.y + cursor_pos.y + line_height > bounds.size.height - bottom_margin {
+                // cursor is out of bottom
+                bounds.size.height - bottom_margin - cursor_pos.y
+            } else if scroll_offset.y + cursor_pos.y < px(0.) {
+                // cursor is out of top
+                -cursor_pos.y
+            } else {
+                scroll_offset.y
+            };
 
-            let line_origin = point(px(0.), offset_y);
-            if cursor_pos.is_none() {
-                let offset = cursor_offset.saturating_sub(prev_lines_offset);
-                if let Some(pos) = line.position_for_index(offset, line_height) {
-                    cursor_pos = Some(line_origin + pos);
-                }
-            }
-            if cursor_start.is_none() {
-                let offset = selected_range.start.saturating_sub(prev_lines_offset);
-                if let Some(pos) = line.position_for_index(offset, line_height) {
-                    cursor_start = Some(line_origin + pos);
-                }
+        scroll_offset
+    }
+
+    /// Return the rows in the scroll viewport, with the overscan rows before and after them.
+    fn visible_rows(
+        &self,
+        bounds: &Bounds<Pixels>,
+        scroll_offset: Point<Pixels>,
+        line_height: Pixels,
+        cx: &App,
+    ) -> Range<usize> {
+        let input = self.input.read(cx);
+        let first_line = (-scroll_offset.y / line_height).floor().max(0.) as usize;
+        let last_line = ((bounds.size.height - scroll_offset.y) / line_height)
+            .ceil()
+            .max(0.) as usize;
+
+        let wrapper = &input.text_wrapper;
+        let start = wrapper
+            .row_for_wrapped_line(first_line, &input.folds)
+            .saturating_sub(OVERSCAN_ROWS);
+        let end = wrapper.row_for_wrapped_line(last_line, &input.folds) + 1 + OVERSCAN_ROWS;
+        start..end.min(input.text.lines_len())
+    }
+
+    /// Shape the lines of the `rows`, the rows hidden by folds are skipped.
+    ///
+    /// The `highlight_styles` are the styles of the `rows`, if the text is empty (to show the placeholder)
+    /// or masked, all lines of the display text are shaped.
+    fn layout_lines(
+        &self,
+        rows: Range<usize>,
+        highlight_styles: Option<Vec<LineHighlightStyle>>,
+        wrap_width: Option<Pixels>,
+        line_height: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<LineLayout> {
+        let input = self.input.read(cx);
+        let text_style = window.text_style();
+        let font_size = text_style.font_size.to_pixels(window.rem_size());
+        let is_empty = input.text.is_empty();
+        let text_color = if is_empty {
+            cx.theme().muted_foreground
+        } else {
+            cx.theme().foreground
+        };
+
+        let run = TextRun {
+            len: 0,
+            font: text_style.font(),
+            color: text_color,
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+        };
+        let marked_run = TextRun {
+            underline: Some(UnderlineStyle {
+                thickness: px(1.),
+                color: Some(text_color),
+                wavy: false,
+            }),
+            ..run.clone()
+        };
+
+        if is_empty || input.masked {
+            let display_text: SharedString = if is_empty {
+                self.placeholder.clone()
+            } else {
+                "*".repeat(input.text.chars_at(0).count()).into()
+            };
+            let run = TextRun {
+                len: display_text.len(),
+                ..run
+            };
+
+            let runs = match &input.marked_range {
+                // IME marked text
+                Some(marked_range) if is_empty => vec![
+                    TextRun {
+                        len: marked_range.start,
+                        ..run.clone()
+                    },
+                    TextRun {
+                        len: marked_range.end - marked_range.start,
+                        underline: marked_run.underline,
+                        ..run.clone()
+                    },
+                    TextRun {
+                        len: display_text.len() - marked_range.end,
+                        ..run.clone()
+                    },
+                ]
+                .into_iter()
+                .filter(|run| run.len > 0)
+                .collect(),
+                _ => vec![run],
+            };
+
+            let mut offset = 0;
+            let mut top = px(0.);
+            return window
+                .text_system()
+                .shape_text(display_text, font_size, &runs, wrap_width, None)
+                .unwrap_or_default()
+                .into_iter()
+                .enumerate()
+                .map(|(row, line)| {
+                    let layout = LineLayout {
+                        row,
+                        offset,
+                        top,
+                        line,
+                    };
+                    offset = layout.end() + 1;
+                    top += layout.line.size(line_height).height;
+                    layout
+                })
+                .collect();
+        }
+
+        let mut lines = vec![];
+        let mut row = rows.start;
+        let mut offset = input.text.line_start_offset(row);
+        let mut top = input.text_wrapper.wrapped_line_for_row(row, &input.folds) * line_height;
+        let mut text_lines = input.text.lines_at(row);
+        while row < rows.end {
+            if let Some(hidden) = input.folds.hidden_range(row) {
+                row = hidden.end;
+                offset = input.text.line_start_offset(row);
+                text_lines = input.text.lines_at(row);
+                continue;
             }
-            if cursor_end.is_none() {
-                let offset = selected_range.end.saturating_sub(prev_lines_offset);
-                if let Some(pos) = line.position_for_index(offset, line_height) {
-                    cursor_end = Some(line_origin + pos);
+
+            let Some(line_text) = text_lines.next() else {
+                break;
+            };
+
+            let len = line_text.len();
+            let runs = match highlight_styles
+                .as_ref()
+                .and_then(|styles| styles.get(row - rows.start))
+                .filter(|style| style.len() == len)
+            {
+                Some(style) => {
+                    let mut runs =
+                        style.to_run(offset, &text_style, &input.marked_range, &marked_run);
+                    // Remove the run of the `\n`.
+                    runs.pop();
+                    runs
                 }
+                None => vec![TextRun { len, ..run.clone() }],
+            };
+            let runs = runs
+                .into_iter()
+                .filter(|run| run.len > 0)
+                .collect::<Vec<_>>();
+
+            if let Some(line) = window
+                .text_system()
+                .shape_text(line_text.into(), font_size, &runs, wrap_width, None)
+                .ok()
+                .and_then(|lines| lines.into_iter().next())
+            {
+                let height = line.size(line_height).height;
+                lines.push(LineLayout {
+                    row,
+                    offset,
+                    top,
+                    line,
+                });
+                top += height;
             }
 
-            offset_y += line.size(line_height).height;
             // +1 for skip the last `\n`
-            prev_lines_offset += line.len() + 1;
-        }
-
-        if let (Some(cursor_pos), Some(cursor_start), Some(cursor_end)) =
-            (cursor_pos, cursor_start, cursor_end)
-        {
-            let cursor_moved = input.last_cursor_offset != Some(cursor_offset);
-            let selection_changed = input.last_selected_range != Some(selected_range.clone());
-
-            if cursor_moved || selection_changed {
-                scroll_offset.x =
-                    if scroll_offset.x + cursor_pos.x > (bounds.size.width - RIGHT_MARGIN) {
-                        // cursor is out of right
-                        bounds.size.width - RIGHT_MARGIN - cursor_pos.x
-                    } else if scroll_offset.x + cursor_pos.x < px(0.) {
-                        // cursor is out of left
-                        scroll_offset.x - cursor_pos.x
-                    } else {
-                        scroll_offset.x
-                    };
-                scroll_offset.y = if scroll_offset.y + cursor_pos.y + line_height
-                    > bounds.size.height - bottom_margin
-                {
-                    // cursor is out of bottom
-                    bounds.size.height - bottom_margin - cursor_pos.y
-                } else if scroll_offset.y + cursor_pos.y < px(0.) {
-                    // cursor is out of top
-                    scroll_offset.y - cursor_pos.y
+            offset += len + 1;
+            row += 1;
+        }
+
+        lines
+    }
+
+    /// Layout the line numbers of the `lines`, returns the y offset and the shaped line number of each line.
+    fn layout_line_numbers(
+        &self,
+        lines: &[LineLayout],
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Vec<(Pixels, WrappedLine)>> {
+        let input = self.input.read(cx);
+        if !input.mode.line_number() {
+            return None;
+        }
+
+        let style = window.text_style();
+        let font_size = style.font_size.to_pixels(window.rem_size());
+        let total_lines = input.text.lines_len();
+        let current_row = input.text.offset_to_point(input.cursor_offset()).row;
+
+        let line_numbers = lines
+            .iter()
+            .filter_map(|layout| {
+                let label = line_number_label(layout.row, total_lines);
+                let color = if layout.row == current_row {
+                    cx.theme().foreground
                 } else {
-                    scroll_offset.y
+                    cx.theme().muted_foreground
+                };
+                let run = TextRun {
+                    len: label.len(),
+                    font: style.font(),
+                    color,
+                    background_color: None,
+                    underline: None,
+                    strikethrough: None,
                 };
 
-                if input.selection_reversed {
-                    if scroll_offset.x + cursor_start.x < px(0.) {
-                        // selection start is out of left
-                        scroll_offset.x = -cursor_start.x;
-                    }
-                    if scroll_offset.y + cursor_start.y < px(0.) {
-                        // selection start is out of top
-                        scroll_offset.y = -cursor_start.y;
-                    }
-                } else {
-                    if scroll_offset.x + cursor_end.x <= px(0.) {
-                        // selection end is out of left
-                        scroll_offset.x = -cursor_end.x;
-                    }
-                    if scroll_offset.y + cursor_end.y <= px(0.) {
-                        // selection end is out of top
-                        scroll_offset.y = -cursor_end.y;
-                    }
-                }
-            }
+                let line = window
+                    .text_system()
+                    .shape_text(label.into(), font_size, &[run], None, None)
+                    .ok()?
+                    .into_iter()
+                    .next()?;
+                Some((layout.top, line))
+            })
+            .collect();
+
+        Some(line_numbers)
+    }
 
-            bounds.origin = bounds.origin + scroll_offset;
+    /// Layout the cursor, returns the cursor and the y offset of the wrapped line of the cursor.
+    fn layout_cursor(
+        &self,
+        lines: &[LineLayout],
+        line_height: Pixels,
+        bounds: &Bounds<Pixels>,
+        line_number_width: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (Option<PaintQuad>, Option<Pixels>) {
+        let input = self.input.read(cx);
+        let Some(cursor_pos) = position_for_offset(lines, input.cursor_offset(), line_height)
+        else {
+            return (None, None);
+        };
+
+        let mut cursor = None;
+        if input.show_cursor(window, cx) {
+            // cursor blink
+            let cursor_height = window.text_style().font_size.to_pixels(window.rem_size()) + px(2.);
+            cursor = Some(fill(
+                Bounds::new(
+                    point(
+                        bounds.left() + cursor_pos.x + line_number_width,
+                        bounds.top() + cursor_pos.y + ((line_height - cursor_height) / 2.),
+                    ),
+                    size(px(1.), cursor_height),
+                ),
+                cx.theme().caret,
+            ))
+        };
+
+        (cursor, Some(cursor_pos.y))
+    }
+
+    /// Layout the cursors of the extra selections (multiple cursors).
+    fn layout_extra_cursors(
+        &self,
+        lines: &[LineLayout],
+        line_height: Pixels,
+        bounds: &Bounds<Pixels>,
+        line_number_width: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<PaintQuad> {
+        let input = self.input.read(cx);
+        if input.extra_selections.is_empty() || !input.show_cursor(window, cx) {
+            return vec![];
+        }
 
-            if input.show_cursor(window, cx) {
-                // cursor blink
-                let cursor_height =
-                    window.text_style().font_size.to_pixels(window.rem_size()) + px(2.);
-                cursor = Some(fill(
+        let cursor_height = window.text_style().font_size.to_pixels(window.rem_size()) + px(2.);
+        input
+            .extra_selections
+            .iter()
+            .filter_map(|range| position_for_offset(lines, range.end, line_height))
+            .map(|pos| {
+                fill(
                     Bounds::new(
                         point(
-                            bounds.left() + cursor_pos.x + line_number_width,
-                            bounds.top() + cursor_pos.y + ((line_height - cursor_height) / 2.),
+                            bounds.left() + pos.x + line_number_width,
+                            bounds.top() + pos.y + ((line_height - cursor_height) / 2.),
                         ),
                         size(px(1.), cursor_height),
                     ),
                     cx.theme().caret,
-                ))
-            };
-
-            // Calculate the current line index
-            current_line_index = (cursor_pos.y.0 / line_height.0) as usize;
-        }
-
-        (cursor, scroll_offset, current_line_index)
+                )
+            })
+            .collect()
     }
 
     fn layout_selections(
         &self,
-        lines: &[WrappedLine],
+        lines: &[LineLayout],
         line_height: Pixels,
-        bounds: &mut Bounds<Pixels>,
+        bounds: &Bounds<Pixels>,
         line_number_width: Pixels,
         _: &mut Window,
         cx: &mut App,
-    ) -> Option<Path<Pixels>> {
+    ) -> Vec<Path<Pixels>> {
         let input = self.input.read(cx);
         let mut selected_range = input.selected_range.clone();
         if let Some(marked_range) = &input.marked_range {
@@ -201,6 +569,22 @@ impl TextElement {
                 selected_range = marked_range.end..marked_range.end;
             }
         }
+
+        std::iter::once(selected_range)
+            .chain(input.extra_selections.iter().cloned())
+            .filter_map(|range| {
+                Self::layout_selection(range, lines, line_height, bounds, line_number_width)
+            })
+            .collect()
+    }
+
+    fn layout_selection(
+        selected_range: Range<usize>,
+        lines: &[LineLayout],
+        line_height: Pixels,
+        bounds: &Bounds<Pixels>,
+        line_number_width: Pixels,
+    ) -> Option<Path<Pixels>> {
         if selected_range.is_empty() {
             return None;
         }
@@ -211,71 +595,56 @@ impl TextElement {
             (selected_range.end, selected_range.start)
         };
 
-        let mut prev_lines_offset = 0;
         let mut line_corners = vec![];
-
-        let mut offset_y = px(0.);
-        for line in lines.iter() {
+        for layout in lines_in_range(lines, &(start_ix..end_ix)) {
+            let line = &layout.line;
             let line_size = line.size(line_height);
             let line_wrap_width = line_size.width;
 
-            let line_origin = point(px(0.), offset_y);
-
-            let line_cursor_start =
-                line.position_for_index(start_ix.saturating_sub(prev_lines_offset), line_height);
-            let line_cursor_end =
-                line.position_for_index(end_ix.saturating_sub(prev_lines_offset), line_height);
+            let line_origin = point(px(0.), layout.top);
 
-            if line_cursor_start.is_some() || line_cursor_end.is_some() {
-                let start = line_cursor_start
-                    .unwrap_or_else(|| line.position_for_index(0, line_height).unwrap());
+            // The selection may start before this line, or end after this line.
+            let (Some(start), Some(end)) = (
+                line.position_for_index(start_ix.max(layout.offset) - layout.offset, line_height),
+                line.position_for_index(end_ix.min(layout.end()) - layout.offset, line_height),
+            ) else {
+                continue;
+            };
 
-                let end = line_cursor_end
-                    .unwrap_or_else(|| line.position_for_index(line.len(), line_height).unwrap());
+            // Split the selection into multiple items
+            let wrapped_lines =
+                (end.y / line_height).ceil() as usize - (start.y / line_height).ceil() as usize;
 
-                // Split the selection into multiple items
-                let wrapped_lines =
-                    (end.y / line_height).ceil() as usize - (start.y / line_height).ceil() as usize;
+            let mut end_x = end.x;
+            if wrapped_lines > 0 {
+                end_x = line_wrap_width;
+            }
 
-                let mut end_x = end.x;
-                if wrapped_lines > 0 {
-                    end_x = line_wrap_width;
+            // Ensure at least 6px width for the selection for empty lines.
+            end_x = end_x.max(start.x + px(6.));
+
+            line_corners.push(Corners {
+                top_left: line_origin + point(start.x, start.y),
+                top_right: line_origin + point(end_x, start.y),
+                bottom_left: line_origin + point(start.x, start.y + line_height),
+                bottom_right: line_origin + point(end_x, start.y + line_height),
+            });
+
+            // wrapped lines
+            for i in 1..=wrapped_lines {
+                let start = point(px(0.), start.y + i as f32 * line_height);
+                let mut end = point(end.x, end.y + i as f32 * line_height);
+                if i < wrapped_lines {
+                    end.x = line_size.width;
                 }
 
-                // Ensure at least 6px width for the selection for empty lines.
-                end_x = end_x.max(start.x + px(6.));
-
                 line_corners.push(Corners {
                     top_left: line_origin + point(start.x, start.y),
-                    top_right: line_origin + point(end_x, start.y),
+                    top_right: line_origin + point(end.x, start.y),
                     bottom_left: line_origin + point(start.x, start.y + line_height),
-                    bottom_right: line_origin + point(end_x, start.y + line_height),
+                    bottom_right: line_origin + point(end.x, start.y + line_height),
                 });
-
-                // wrapped lines
-                for i in 1..=wrapped_lines {
-                    let start = point(px(0.), start.y + i as f32 * line_height);
-                    let mut end = point(end.x, end.y + i as f32 * line_height);
-                    if i < wrapped_lines {
-                        end.x = line_size.width;
-                    }
-
-                    line_corners.push(Corners {
-                        top_left: line_origin + point(start.x, start.y),
-                        top_right: line_origin + point(end.x, start.y),
-                        bottom_left: line_origin + point(start.x, start.y + line_height),
-                        bottom_right: line_origin + point(end.x, start.y + line_height),
-                    });
-                }
             }
-
-            if line_cursor_start.is_some() && line_cursor_end.is_some() {
-                break;
-            }
-
-            offset_y += line_size.height;
-            // +1 for skip the last `\n`
-            prev_lines_offset += line.len() + 1;
         }
 
         let mut points = vec![];
@@ -320,26 +689,276 @@ impl TextElement {
         builder.build().ok()
     }
 
-    fn highlight_lines(&mut self, cx: &mut App) -> Option<Vec<LineHighlightStyle>> {
-        self.input.update(cx, |state, cx| match &mut state.mode {
-            InputMode::CodeEditor { highlighter, .. } => {
-                highlighter.update(state.text.clone(), false, cx);
-                Some(highlighter.lines.clone())
+    /// Layout the search matches, returns the quads of each wrapped line of the matches.
+    fn layout_search_matches(
+        &self,
+        lines: &[LineLayout],
+        line_height: Pixels,
+        bounds: &Bounds<Pixels>,
+        line_number_width: Pixels,
+        cx: &mut App,
+    ) -> Vec<PaintQuad> {
+        let input = self.input.read(cx);
+        let Some(panel) = input.search_panel.as_ref().map(|panel| panel.read(cx)) else {
+            return vec![];
+        };
+        if !panel.open || panel.matches.is_empty() {
+            return vec![];
+        }
+        let (Some(first_line), Some(last_line)) = (lines.first(), lines.last()) else {
+            return vec![];
+        };
+
+        let match_color = cx.theme().warning.opacity(0.25);
+        let current_match_color = cx.theme().warning.opacity(0.5);
+        let origin = bounds.origin + point(line_number_width, px(0.));
+
+        let mut quads = vec![];
+        // Only the matches in the laid out lines.
+        let start_ix = panel
+            .matches
+            .partition_point(|range| range.end < first_line.offset);
+        for (ix, range) in panel.matches.iter().enumerate().skip(start_ix) {
+            if range.start > last_line.end() {
+                break;
             }
-            _ => None,
-        })
+
+            let color = if panel.current_match_ix == Some(ix) {
+                current_match_color
+            } else {
+                match_color
+            };
+
+            for layout in lines_in_range(lines, range) {
+                let start = range.start.max(layout.offset) - layout.offset;
+                let end = range.end.min(layout.end()) - layout.offset;
+                for segment in line_range_bounds(&layout.line, start..end, line_height) {
+                    let y = layout.top + segment.top();
+                    quads.push(fill(
+                        Bounds::from_corners(
+                            origin + point(segment.left(), y),
+                            origin
+                                + point(
+                                    segment.right().max(segment.left() + px(2.)),
+                                    y + line_height,
+                                ),
+                        ),
+                        color,
+                    ));
+                }
+            }
+        }
+
+        quads
+    }
+
+    /// Layout the highlights of the bracket at the cursor and its matching bracket.
+    fn layout_bracket_matches(
+        &self,
+        lines: &[LineLayout],
+        line_height: Pixels,
+        bounds: &Bounds<Pixels>,
+        line_number_width: Pixels,
+        cx: &mut App,
+    ) -> Vec<PaintQuad> {
+        let input = self.input.read(cx);
+        let Some(brackets) = input.matching_brackets() else {
+            return vec![];
+        };
+
+        let color = cx.theme().muted_foreground.opacity(0.25);
+        let origin = bounds.origin + point(line_number_width, px(0.));
+
+        let mut quads = vec![];
+        for range in brackets.iter() {
+            for layout in lines_in_range(lines, range) {
+                if range.start < layout.offset || range.end > layout.end() {
+                    continue;
+                }
+
+                let range = range.start - layout.offset..range.end - layout.offset;
+                for segment in line_range_bounds(&layout.line, range, line_height) {
+                    let origin = origin + point(segment.left(), layout.top + segment.top());
+                    quads.push(fill(Bounds::new(origin, segment.size), color));
+                }
+            }
+        }
+
+        quads
+    }
+
+    /// Layout the wavy underlines of the diagnostics, and the severity markers in the gutter.
+    fn layout_diagnostics(
+        &self,
+        lines: &[LineLayout],
+        line_height: Pixels,
+        bounds: &Bounds<Pixels>,
+        line_number_width: Pixels,
+        cx: &mut App,
+    ) -> (Vec<DiagnosticUnderline>, Vec<DiagnosticMarker>) {
+        let input = self.input.read(cx);
+        if input.diagnostics.is_empty() {
+            return (vec![], vec![]);
+        }
+
+        let show_markers = input.mode.is_code_editor() && input.mode.line_number();
+        let origin = bounds.origin + point(line_number_width, px(0.));
+        let marker_size = px(12.);
+
+        let mut underlines = vec![];
+        let mut markers: Vec<DiagnosticMarker> = vec![];
+        for diagnostic in input.diagnostics.iter() {
+            let color = diagnostic.severity.color(cx);
+
+            // The first line of the diagnostic, if it has been laid out.
+            let mut start_line = None;
+            for layout in lines_in_range(lines, &diagnostic.range) {
+                if layout.offset <= diagnostic.range.start {
+                    start_line = Some(layout);
+                }
+
+                let start = diagnostic.range.start.max(layout.offset) - layout.offset;
+                let end = diagnostic.range.end.min(layout.end()) - layout.offset;
+                for segment in line_range_bounds(&layout.line, start..end, line_height) {
+                    underlines.push(DiagnosticUnderline {
+                        origin: origin
+                            + point(segment.left(), layout.top + segment.bottom() - px(3.)),
+                        width: segment.size.width.max(px(4.)),
+                        color,
+                    });
+                }
+            }
+
+            let Some(start_line) = start_line.filter(|_| show_markers) else {
+                continue;
+            };
+            match markers.last_mut() {
+                Some(marker) if marker.row == start_line.row => {
+                    marker.severity = marker.severity.min(diagnostic.severity);
+                }
+                _ => markers.push(DiagnosticMarker {
+                    row: start_line.row,
+                    bounds: Bounds::new(
+                        point(
+                            bounds.left() + (DIAGNOSTIC_MARKER_WIDTH - marker_size) / 2.,
+                            bounds.top() + start_line.top + (line_height - marker_size) / 2.,
+                        ),
+                        size(marker_size, marker_size),
+                    ),
+                    severity: diagnostic.severity,
+                }),
+            }
+        }
+
+        (underlines, markers)
+    }
+
+    /// Layout the fold markers in the gutter, and the placeholder origins after the folded lines.
+    fn layout_folds(
+        &self,
+        lines: &[LineLayout],
+        line_height: Pixels,
+        bounds: &Bounds<Pixels>,
+        line_number_width: Pixels,
+        cx: &mut App,
+    ) -> (Vec<FoldMarker>, Vec<Point<Pixels>>) {
+        if !self.input.read(cx).mode.is_code_editor() {
+            return (vec![], vec![]);
+        }
+
+        let foldable_ranges = self
+            .input
+            .update(cx, |input, _| {
+                input.mode.line_number().then(|| input.foldable_ranges())
+            })
+            .unwrap_or_default();
+
+        let input = self.input.read(cx);
+        let mut markers = vec![];
+        let mut placeholders = vec![];
+        for layout in lines {
+            let folded = input.folds.is_folded(layout.row);
+            if folded
+                || foldable_ranges
+                    .binary_search_by_key(&layout.row, |range| range.start)
+                    .is_ok()
+            {
+                markers.push(FoldMarker {
+                    bounds: Bounds::new(
+                        point(
+                            bounds.left() + line_number_width
+                                - LINE_NUMBER_MARGIN_RIGHT
+                                - FOLD_MARKER_WIDTH,
+                            bounds.top() + layout.top + (line_height - FOLD_MARKER_WIDTH) / 2.,
+                        ),
+                        size(FOLD_MARKER_WIDTH, FOLD_MARKER_WIDTH),
+                    ),
+                    folded,
+                });
+            }
+
+            if folded {
+                let line = &layout.line;
+                if let Some(pos) = line.position_for_index(line.len(), line_height) {
+                    placeholders.push(point(
+                        bounds.left() + line_number_width + pos.x + px(4.),
+                        bounds.top() + layout.top + pos.y,
+                    ));
+                }
+            }
+        }
+
+        (markers, placeholders)
+    }
+
+    fn highlight_lines(
+        &mut self,
+        rows: &Range<usize>,
+        cx: &mut App,
+    ) -> Option<Vec<LineHighlightStyle>> {
+        self.input
+            .update(cx, |state, cx| state.highlight_lines(rows, cx))
     }
 }
 
+struct DiagnosticUnderline {
+    origin: Point<Pixels>,
+    width: Pixels,
+    color: Hsla,
+}
+
+struct DiagnosticMarker {
+    row: usize,
+    bounds: Bounds<Pixels>,
+    severity: DiagnosticSeverity,
+}
+
+struct FoldMarker {
+    bounds: Bounds<Pixels>,
+    folded: bool,
+}
+
 pub(super) struct PrepaintState {
-    lines: SmallVec<[WrappedLine; 1]>,
-    line_numbers: Option<SmallVec<[WrappedLine; 1]>>,
+    lines: Rc<Vec<LineLayout>>,
+    line_numbers: Option<Vec<(Pixels, WrappedLine)>>,
     line_number_width: Pixels,
+    /// The x offset of the line numbers, to leave space for the diagnostic markers.
+    line_number_offset: Pixels,
     cursor: Option<PaintQuad>,
     cursor_scroll_offset: Point<Pixels>,
-    current_line_index: usize,
-    selection_path: Option<Path<Pixels>>,
+    /// The y offset of the wrapped line of the cursor.
+    current_line_top: Option<Pixels>,
+    selection_paths: Vec<Path<Pixels>>,
+    extra_cursors: Vec<PaintQuad>,
+    search_matches: Vec<PaintQuad>,
+    bracket_matches: Vec<PaintQuad>,
+    fold_markers: Vec<FoldMarker>,
+    fold_placeholders: Vec<Point<Pixels>>,
+    fold_placeholder_text: Option<WrappedLine>,
+    diagnostic_underlines: Vec<DiagnosticUnderline>,
+    diagnostic_markers: Vec<DiagnosticMarker>,
     bounds: Bounds<Pixels>,
+    scroll_size: Size<Pixels>,
 }
 
 impl IntoElement for TextElement {
@@ -350,6 +969,35 @@ impl IntoElement for TextElement {
     }
 }
 
+/// Return the bounds of the `range` (the offsets in the line) in each wrapped line, relative to the line origin.
+fn line_range_bounds(
+    line: &WrappedLine,
+    range: Range<usize>,
+    line_height: Pixels,
+) -> Vec<Bounds<Pixels>> {
+    let (Some(start), Some(end)) = (
+        line.position_for_index(range.start, line_height),
+        line.position_for_index(range.end, line_height),
+    ) else {
+        return vec![];
+    };
+
+    let line_width = line.size(line_height).width;
+    let wrapped_lines = ((end.y - start.y) / line_height).round() as usize;
+    (0..=wrapped_lines)
+        .map(|i| {
+            let y = start.y + i as f32 * line_height;
+            let start_x = if i == 0 { start.x } else { px(0.) };
+            let end_x = if i == wrapped_lines {
+                end.x
+            } else {
+                line_width
+            };
+            Bounds::from_corners(point(start_x, y), point(end_x, y + line_height))
+        })
+        .collect()
+}
+
 /// A debug function to print points as SVG path.
 #[allow(unused)]
 fn print_points_as_svg_path(
@@ -422,154 +1070,43 @@ impl Element for TextElement {
         window: &mut Window,
         cx: &mut App,
     ) -> Self::PrepaintState {
-        let highlight_lines = self.highlight_lines(cx);
         let multi_line = self.input.read(cx).is_multi_line();
         let line_height = window.line_height();
-        let input = self.input.read(cx);
-        let text = input.text.clone();
-        let is_empty = text.is_empty();
-        let placeholder = self.placeholder.clone();
         let style = window.text_style();
         let font_size = style.font_size.to_pixels(window.rem_size());
         let mut bounds = bounds;
 
-        let (display_text, text_color) = if is_empty {
-            (placeholder, cx.theme().muted_foreground)
-        } else if input.masked {
-            (
-                "*".repeat(text.chars().count()).into(),
-                cx.theme().foreground,
-            )
-        } else {
-            (text, cx.theme().foreground)
-        };
-
-        let text_style = window.text_style();
-
-        // Calculate the width of the line numbers
-        let mut line_number_width = px(0.);
-        let line_numbers = if input.mode.line_number() {
-            let mut line_numbers = SmallVec::new();
-            let total_lines = input.text_wrapper.lines.len();
-            let run_len = if total_lines > 999 { 4 } else { 3 };
-
-            let other_line_runs = vec![TextRun {
-                len: run_len,
-                font: style.font(),
-                color: cx.theme().muted_foreground,
-                background_color: None,
-                underline: None,
-                strikethrough: None,
-            }];
-            let current_line_runs = vec![TextRun {
-                len: run_len,
-                font: style.font(),
-                color: cx.theme().foreground,
-                background_color: None,
-                underline: None,
-                strikethrough: None,
-            }];
-
-            for (i, line_wrap) in input.text_wrapper.lines.iter().enumerate() {
-                let line_no = if run_len == 4 {
-                    format!("{:>4}", i + 1).into()
-                } else {
-                    format!("{:>3}", i + 1).into()
-                };
-
-                let runs = if input.current_line_index == Some(i) {
-                    &current_line_runs
-                } else {
-                    &other_line_runs
-                };
-
-                let line = window
-                    .text_system()
-                    .shape_text(line_no, font_size, &runs, None, None)
-                    .unwrap();
-                line_number_width = (line.last().unwrap().width() + LINE_NUMBER_MARGIN_RIGHT)
-                    .max(line_number_width);
-                line_numbers.extend(line);
-
-                for _ in 0..line_wrap.wrap_lines {
-                    // Empty line no for wrapped lines
-                    let line = window
-                        .text_system()
-                        .shape_text("    ".into(), font_size, &runs, None, None)
-                        .unwrap();
-                    line_numbers.extend(line);
-                }
-            }
-            Some(line_numbers)
-        } else {
-            None
-        };
-
-        let run = TextRun {
-            len: display_text.len(),
-            font: style.font(),
-            color: text_color,
-            background_color: None,
-            underline: None,
-            strikethrough: None,
-        };
-        let marked_run = TextRun {
-            len: 0,
-            font: style.font(),
-            color: text_color,
-            background_color: None,
-            underline: Some(UnderlineStyle {
-                thickness: px(1.),
-                color: Some(text_color),
-                wavy: false,
-            }),
-            strikethrough: None,
-        };
-
-        let runs = if !is_empty {
-            if let Some(highlight_lines) = highlight_lines {
-                let mut runs = vec![];
-                for style in highlight_lines {
-                    runs.extend(style.to_run(&text_style, &input.marked_range, &marked_run));
-                }
-                runs.into_iter().filter(|run| run.len > 0).collect()
-            } else {
-                vec![run]
-            }
-        } else if let Some(marked_range) = &input.marked_range {
-            // IME marked text
-            vec![
-                TextRun {
-                    len: marked_range.start,
-                    ..run.clone()
-                },
-                TextRun {
-                    len: marked_range.end - marked_range.start,
-                    underline: marked_run.underline,
-                    ..run.clone()
-                },
-                TextRun {
-                    len: display_text.len() - marked_range.end,
-                    ..run.clone()
-                },
-            ]
-            .into_iter()
-            .filter(|run| run.len > 0)
-            .collect()
-        } else {
-            vec![run]
-        };
-
+        let (line_number_width, line_number_offset) = self.layout_gutter_width(window, cx);
         let wrap_width = if multi_line {
             Some(bounds.size.width - line_number_width - RIGHT_MARGIN)
         } else {
             None
         };
 
-        let lines = window
-            .text_system()
-            .shape_text(display_text, font_size, &runs, wrap_width, None)
-            .unwrap();
+        // Keep the wrapped lines in sync with the wrap width, to find the visible lines by them.
+        self.input.update(cx, |input, cx| {
+            input
+                .text_wrapper
+                .set_wrap_width(wrap_width, &input.text, cx);
+            input.mode.update_auto_grow(&input.text_wrapper);
+        });
+
+        // Calculate the scroll offset to keep the cursor in view,
+        // then only layout the lines in the scroll viewport.
+        let cursor_scroll_offset =
+            self.layout_scroll_offset(&bounds, line_height, wrap_width, window, cx);
+        let visible_rows = self.visible_rows(&bounds, cursor_scroll_offset, line_height, cx);
+        bounds.origin = bounds.origin + cursor_scroll_offset;
+
+        let highlight_styles = self.highlight_lines(&visible_rows, cx);
+        let lines = self.layout_lines(
+            visible_rows,
+            highlight_styles,
+            wrap_width,
+            line_height,
+            window,
+            cx,
+        );
 
         // `position_for_index` for example
         //
@@ -600,35 +1137,73 @@ impl Element for TextElement {
         // | 114   | (429.85938 px, 20.0)  | 1    |
         // | 115   | (11.3125 px, 40.0)    | 2    |
 
-        // Calculate the scroll offset to keep the cursor in view
-
-        let (cursor, cursor_scroll_offset, current_line_index) = self.layout_cursor(
-            &lines,
-            line_height,
-            &mut bounds,
-            line_number_width,
-            window,
-            cx,
-        );
+        let (cursor, current_line_top) =
+            self.layout_cursor(&lines, line_height, &bounds, line_number_width, window, cx);
+        let line_numbers = self.layout_line_numbers(&lines, window, cx);
+
+        let selection_paths =
+            self.layout_selections(&lines, line_height, &bounds, line_number_width, window, cx);
+
+        let extra_cursors =
+            self.layout_extra_cursors(&lines, line_height, &bounds, line_number_width, window, cx);
+        let search_matches =
+            self.layout_search_matches(&lines, line_height, &bounds, line_number_width, cx);
+        let bracket_matches =
+            self.layout_bracket_matches(&lines, line_height, &bounds, line_number_width, cx);
+        let (fold_markers, fold_placeholders) =
+            self.layout_folds(&lines, line_height, &bounds, line_number_width, cx);
+        let fold_placeholder_text = if fold_placeholders.is_empty() {
+            None
+        } else {
+            let run = TextRun {
+                len: "⋯".len(),
+                font: style.font(),
+                color: cx.theme().muted_foreground,
+                background_color: None,
+                underline: None,
+                strikethrough: None,
+            };
+            window
+                .text_system()
+                .shape_text("⋯".into(), font_size, &[run], None, None)
+                .ok()
+                .and_then(|lines| lines.into_iter().next())
+        };
+        let (diagnostic_underlines, diagnostic_markers) =
+            self.layout_diagnostics(&lines, line_height, &bounds, line_number_width, cx);
 
-        let selection_path = self.layout_selections(
-            &lines,
-            line_height,
-            &mut bounds,
-            line_number_width,
-            window,
-            cx,
-        );
+        // The height is calculated by the wrapped lines, as only the visible lines are laid out.
+        let input = self.input.read(cx);
+        let lines_bottom = lines
+            .last()
+            .map(|layout| layout.top + layout.line.size(line_height).height)
+            .unwrap_or_default();
+        let height = (input.text_wrapper.visible_len(&input.folds) * line_height).max(lines_bottom);
+        let width = lines
+            .iter()
+            .map(|layout| layout.line.width())
+            .max()
+            .unwrap_or_default();
 
         PrepaintState {
             bounds,
-            lines,
+            lines: Rc::new(lines),
             line_numbers,
             line_number_width,
+            line_number_offset,
             cursor,
             cursor_scroll_offset,
-            current_line_index,
-            selection_path,
+            current_line_top,
+            selection_paths,
+            extra_cursors,
+            search_matches,
+            bracket_matches,
+            fold_markers,
+            fold_placeholders,
+            fold_placeholder_text,
+            diagnostic_underlines,
+            diagnostic_markers,
+            scroll_size: size(width, height),
         }
     }
 
@@ -691,71 +1266,150 @@ impl Element for TextElement {
         }
 
         if let Some(line_numbers) = prepaint.line_numbers.as_ref() {
-            for (ix, line) in line_numbers.iter().enumerate() {
-                let p = point(origin.x, origin.y + offset_y);
-                let line_size = line.size(line_height);
-
-                // Paint the current line background
-                if prepaint.current_line_index == ix {
-                    if let Some(bg_color) = self
-                        .input
-                        .read(cx)
-                        .mode
-                        .highlighter()
-                        .and_then(|h| h.theme.settings().line_highlight)
-                        .map(crate::highlighter::color_to_hsla)
-                    {
-                        window.paint_quad(fill(
-                            Bounds::new(p, size(bounds.size.width, line_height)),
-                            bg_color,
-                        ));
-                    }
+            // Paint the current line background
+            if let Some(top) = prepaint.current_line_top {
+                if let Some(bg_color) = self
+                    .input
+                    .read(cx)
+                    .mode
+                    .highlighter()
+                    .and_then(|h| h.theme.settings().line_highlight)
+                    .map(crate::highlighter::color_to_hsla)
+                {
+                    window.paint_quad(fill(
+                        Bounds::new(
+                            point(origin.x, origin.y + offset_y + top),
+                            size(bounds.size.width, line_height),
+                        ),
+                        bg_color,
+                    ));
                 }
+            }
 
-                _ = line.paint(p, line_height, TextAlign::Left, None, window, cx);
-                offset_y += line_size.height;
+            for (top, line) in line_numbers.iter() {
+                let number_origin = point(
+                    origin.x + prepaint.line_number_offset,
+                    origin.y + offset_y + *top,
+                );
+                _ = line.paint(
+                    number_origin,
+                    line_height,
+                    TextAlign::Left,
+                    None,
+                    window,
+                    cx,
+                );
             }
         }
 
+        // Paint diagnostic markers
+        for marker in prepaint.diagnostic_markers.drain(..) {
+            _ = window.paint_svg(
+                marker.bounds,
+                marker.severity.icon().path(),
+                TransformationMatrix::unit(),
+                marker.severity.color(cx),
+                cx,
+            );
+        }
+
+        // Paint search matches
+        for quad in prepaint.search_matches.drain(..) {
+            window.paint_quad(quad);
+        }
+
+        // Paint matching brackets
+        for quad in prepaint.bracket_matches.drain(..) {
+            window.paint_quad(quad);
+        }
+
         // Paint selections
-        if let Some(path) = prepaint.selection_path.take() {
+        for path in prepaint.selection_paths.drain(..) {
             window.paint_path(path, cx.theme().selection);
         }
 
+        // Paint fold markers
+        for marker in prepaint.fold_markers.drain(..) {
+            let icon = if marker.folded {
+                IconName::ChevronRight
+            } else {
+                IconName::ChevronDown
+            };
+            _ = window.paint_svg(
+                marker.bounds,
+                icon.path(),
+                TransformationMatrix::unit(),
+                cx.theme().muted_foreground,
+                cx,
+            );
+        }
+
         // Paint text
-        let mut offset_y = px(0.);
-        for line in prepaint.lines.iter() {
-            let p = point(origin.x + prepaint.line_number_width, origin.y + offset_y);
-            let line_size = line.size(line_height);
-            _ = line.paint(p, line_height, TextAlign::Left, None, window, cx);
-            offset_y += line_size.height;
+        for layout in prepaint.lines.iter() {
+            let p = point(origin.x + prepaint.line_number_width, origin.y + layout.top);
+            _ = layout
+                .line
+                .paint(p, line_height, TextAlign::Left, None, window, cx);
+        }
+
+        // Paint diagnostic underlines
+        for underline in prepaint.diagnostic_underlines.drain(..) {
+            window.paint_underline(
+                underline.origin,
+                underline.width,
+                &UnderlineStyle {
+                    thickness: px(1.),
+                    color: Some(underline.color),
+                    wavy: true,
+                },
+            );
+        }
+
+        // Paint the placeholders of the folded lines
+        if let Some(text) = prepaint.fold_placeholder_text.as_ref() {
+            for origin in prepaint.fold_placeholders.iter() {
+                let text_width = text.width();
+                window.paint_quad(
+                    fill(
+                        Bounds::new(
+                            *origin + point(px(0.), px(2.)),
+                            size(text_width + px(8.), line_height - px(4.)),
+                        ),
+                        cx.theme().muted,
+                    )
+                    .corner_radii(px(3.)),
+                );
+                _ = text.paint(
+                    *origin + point(px(4.), px(0.)),
+                    line_height,
+                    TextAlign::Left,
+                    None,
+                    window,
+                    cx,
+                );
+            }
         }
 
         if focused {
             if let Some(cursor) = prepaint.cursor.take() {
                 window.paint_quad(cursor);
             }
+            for cursor in prepaint.extra_cursors.drain(..) {
+                window.paint_quad(cursor);
+            }
         }
 
-        let width = prepaint
-            .lines
-            .iter()
-            .map(|l| l.width())
-            .max()
-            .unwrap_or_default();
-        let height = offset_y;
-        let scroll_size = size(width, height);
-
         self.input.update(cx, |input, cx| {
-            input.last_layout = Some(prepaint.lines.clone());
+            input.last_layout = Some(LastLayout {
+                lines: prepaint.lines.clone(),
+            });
             input.last_bounds = Some(bounds);
             input.last_cursor_offset = Some(input.cursor_offset());
             input.last_line_height = line_height;
             input.set_input_bounds(input_bounds, cx);
             input.last_selected_range = Some(selected_range);
-            input.scroll_size = scroll_size;
+            input.scroll_size = prepaint.scroll_size;
             input.line_number_width = prepaint.line_number_width;
-            input.current_line_index = Some(prepaint.current_line_index);
             input
                 .scroll_handle
                 .set_offset(prepaint.cursor_scroll_offset);
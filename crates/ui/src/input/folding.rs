@@ -0,0 +1,520 @@
+use std::{ops::Range, rc::Rc};
+
+use gpui::{actions, px, App, Context, KeyBinding, Pixels, Point, Window};
+use smallvec::SmallVec;
+
+use super::{
+    element::LINE_NUMBER_MARGIN_RIGHT,
+    rope::{LinesEdit, Rope},
+    state::CONTEXT,
+    InputState,
+};
+
+actions!(input, [Fold, Unfold, FoldAll, UnfoldAll]);
+
+/// The width of the fold markers in the gutter.
+pub(super) const FOLD_MARKER_WIDTH: Pixels = px(14.);
+
+pub(super) fn init(cx: &mut App) {
+    cx.bind_keys([
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-[", Fold, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-[", Fold, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-]", Unfold, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-]", Unfold, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-k cmd-0", FoldAll, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-k ctrl-0", FoldAll, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-k cmd-j", UnfoldAll, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-k ctrl-j", UnfoldAll, Some(CONTEXT)),
+    ]);
+}
+
+/// The folded regions of the text.
+///
+/// Each fold is a range of rows, the first row is kept visible, and the rest rows are hidden.
+#[derive(Debug, Default, Clone)]
+pub(super) struct FoldMap {
+    /// Sorted by the start row, the folds may be nested.
+    folds: Vec<Range<usize>>,
+    /// The merged hidden rows of all folds, sorted and not overlapped.
+    hidden: Vec<Range<usize>>,
+}
+
+impl FoldMap {
+    #[inline]
+    pub(super) fn is_empty(&self) -> bool {
+        self.folds.is_empty()
+    }
+
+    /// Return true if the `row` is the start row of a fold.
+    pub(super) fn is_folded(&self, row: usize) -> bool {
+        self.folds
+            .binary_search_by_key(&row, |fold| fold.start)
+            .is_ok()
+    }
+
+    /// Return true if the `row` is hidden by a fold.
+    #[inline]
+    pub(super) fn is_hidden(&self, row: usize) -> bool {
+        self.hidden_range(row).is_some()
+    }
+
+    /// Return the merged hidden rows of all folds, sorted by the start row.
+    #[inline]
+    pub(super) fn hidden_rows(&self) -> &[Range<usize>] {
+        &self.hidden
+    }
+
+    /// Return the hidden rows that contain the `row`.
+    pub(super) fn hidden_range(&self, row: usize) -> Option<Range<usize>> {
+        let ix = self.hidden.partition_point(|range| range.end <= row);
+        self.hidden
+            .get(ix)
+            .filter(|range| range.start <= row)
+            .cloned()
+    }
+
+    /// Fold the rows, the fold with the same start row will be replaced.
+    pub(super) fn fold(&mut self, rows: Range<usize>) {
+        if rows.end <= rows.start + 1 {
+            return;
+        }
+
+        match self
+            .folds
+            .binary_search_by_key(&rows.start, |fold| fold.start)
+        {
+            Ok(ix) => self.folds[ix] = rows,
+            Err(ix) => self.folds.insert(ix, rows),
+        }
+        self.rebuild();
+    }
+
+    /// Unfold the folds that contain the `row`, returns false if there is no fold.
+    pub(super) fn unfold(&mut self, row: usize) -> bool {
+        let len = self.folds.len();
+        self.folds.retain(|fold| !fold.contains(&row));
+        self.rebuild();
+        self.folds.len() != len
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.folds.clear();
+        self.hidden.clear();
+    }
+
+    /// Update the folds after the text has been changed.
+    ///
+    /// The folds after the edit are moved, the folds that overlap with the edit are removed,
+    /// except the edit is only in the first row of the fold.
+    pub(super) fn update(&mut self, edit: &LinesEdit) {
+        if self.folds.is_empty() {
+            return;
+        }
+
+        let old_end = edit.start_row + edit.old_rows;
+        self.folds.retain_mut(|fold| {
+            if old_end <= fold.start {
+                fold.start = fold.start + edit.new_rows - edit.old_rows;
+                fold.end = fold.end + edit.new_rows - edit.old_rows;
+                true
+            } else if edit.start_row >= fold.end {
+                true
+            } else {
+                edit.start_row == fold.start && edit.old_rows == 1 && edit.new_rows == 1
+            }
+        });
+        self.rebuild();
+    }
+
+    fn rebuild(&mut self) {
+        self.hidden.clear();
+        for fold in self.folds.iter() {
+            let rows = fold.start + 1..fold.end;
+            match self.hidden.last_mut() {
+                Some(last) if rows.start <= last.end => last.end = last.end.max(rows.end),
+                _ => self.hidden.push(rows),
+            }
+        }
+    }
+}
+
+/// Return the indent width of the line, a tab is counted as 4 spaces.
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// Return the open bracket of the close bracket.
+fn open_bracket(close: char) -> char {
+    match close {
+        ')' => '(',
+        ']' => '[',
+        _ => '{',
+    }
+}
+
+/// The summary of a line to find the foldable regions, so only the changed lines are scanned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FoldLine {
+    /// The indent width, None if the line is blank.
+    indent: Option<usize>,
+    /// The brackets not paired in the line, the close brackets are before the open brackets.
+    brackets: SmallVec<[char; 4]>,
+}
+
+impl FoldLine {
+    fn new(line: &str) -> Self {
+        let mut brackets: SmallVec<[char; 4]> = SmallVec::new();
+        for c in line.chars() {
+            match c {
+                '(' | '[' | '{' => brackets.push(c),
+                ')' | ']' | '}' => match brackets.last() {
+                    Some(last) if *last == open_bracket(c) => {
+                        brackets.pop();
+                    }
+                    // Not paired with the open bracket in the line, ignored.
+                    Some('(' | '[' | '{') => {}
+                    // May be paired with the open bracket in the previous lines.
+                    _ => brackets.push(c),
+                },
+                _ => {}
+            }
+        }
+
+        Self {
+            indent: (!line.trim().is_empty()).then(|| indent_width(line)),
+            brackets,
+        }
+    }
+}
+
+/// Find the foldable regions of the lines, by the indentation and the bracket pairs.
+///
+/// Returns the ranges of rows sorted by the start row, the first row of each range is kept
+/// visible when folded. For the bracket pairs, the row of the close bracket is also kept visible.
+fn foldable_ranges(lines: &[FoldLine]) -> Vec<Range<usize>> {
+    let mut ends = vec![0; lines.len()];
+
+    // The bracket pairs.
+    let mut brackets: Vec<(char, usize)> = vec![];
+    for (row, line) in lines.iter().enumerate() {
+        for c in line.brackets.iter().copied() {
+            match c {
+                '(' | '[' | '{' => brackets.push((c, row)),
+                _ => {
+                    if brackets.last().map(|(c, _)| *c) == Some(open_bracket(c)) {
+                        let (_, start_row) = brackets.pop().unwrap();
+                        ends[start_row] = ends[start_row].max(row);
+                    }
+                }
+            }
+        }
+    }
+
+    // The indentation, the rows with larger indent than the previous row are folded into it.
+    let mut stack: Vec<(usize, usize)> = vec![];
+    let mut last_row = 0;
+    for (row, line) in lines.iter().enumerate() {
+        let Some(indent) = line.indent else {
+            continue;
+        };
+
+        while let Some((start_row, _)) = stack.last().filter(|(_, i)| *i >= indent) {
+            ends[*start_row] = ends[*start_row].max(last_row + 1);
+            stack.pop();
+        }
+        stack.push((row, indent));
+        last_row = row;
+    }
+    for (start_row, _) in stack {
+        ends[start_row] = ends[start_row].max(last_row + 1);
+    }
+
+    ends.into_iter()
+        .enumerate()
+        .filter(|(start, end)| *end > start + 1)
+        .map(|(start, end)| start..end)
+        .collect()
+}
+
+/// The foldable regions of the text.
+///
+/// The lines are scanned on the first use, then only the changed lines of each edit are scanned
+/// again, and the regions are found from the lines until the next edit.
+#[derive(Debug, Default, Clone)]
+pub(super) struct FoldableMap {
+    lines: Vec<FoldLine>,
+    ranges: Option<Rc<Vec<Range<usize>>>>,
+}
+
+impl FoldableMap {
+    pub(super) fn clear(&mut self) {
+        self.lines.clear();
+        self.ranges = None;
+    }
+
+    /// Scan the changed lines again, the lines after them are moved.
+    pub(super) fn update(&mut self, text: &Rope, edit: &LinesEdit) {
+        self.ranges = None;
+        if self.lines.is_empty() {
+            return;
+        }
+
+        let old_end = (edit.start_row + edit.old_rows).min(self.lines.len());
+        let start = edit.start_row.min(old_end);
+        let new_lines = text
+            .lines_at(edit.start_row)
+            .take(edit.new_rows)
+            .map(|line| FoldLine::new(&line));
+        self.lines.splice(start..old_end, new_lines);
+    }
+
+    /// Return the foldable ranges sorted by the start row, see [`foldable_ranges`].
+    pub(super) fn ranges(&mut self, text: &Rope) -> Rc<Vec<Range<usize>>> {
+        if self.lines.is_empty() {
+            self.lines = text.lines().map(|line| FoldLine::new(&line)).collect();
+        }
+
+        let lines = &self.lines;
+        self.ranges
+            .get_or_insert_with(|| Rc::new(foldable_ranges(lines)))
+            .clone()
+    }
+}
+
+impl InputState {
+    /// Return the foldable region that contains the `row` and has not been folded, the innermost one first.
+    fn foldable_range_at(&mut self, row: usize) -> Option<Range<usize>> {
+        self.foldable_ranges()
+            .iter()
+            .rev()
+            .find(|range| range.contains(&row) && !self.folds.is_folded(range.start))
+            .cloned()
+    }
+
+    /// Move the offset out of the folded rows.
+    ///
+    /// If `forward` is true, move to the start of the next visible row, otherwise move to the end
+    /// of the first row of the fold.
+    pub(super) fn skip_folded(&self, offset: usize, forward: bool) -> usize {
+        if self.folds.is_empty() {
+            return offset;
+        }
+
+        let row = self.text.offset_to_point(offset).row;
+        let Some(hidden) = self.folds.hidden_range(row) else {
+            return offset;
+        };
+
+        if forward && hidden.end < self.text.lines_len() {
+            self.text.line_start_offset(hidden.end)
+        } else {
+            self.text.line_end_offset(hidden.start - 1)
+        }
+    }
+
+    /// Unfold the folds that hide the `offset`, to make sure it is visible.
+    pub(super) fn unfold_offset(&mut self, offset: usize) {
+        if self.folds.is_empty() {
+            return;
+        }
+
+        let row = self.text.offset_to_point(offset).row;
+        while let Some(hidden) = self.folds.hidden_range(row) {
+            self.folds.unfold(hidden.start - 1);
+        }
+    }
+
+    /// Toggle the fold of the `row`, returns false if the row is not foldable.
+    pub(super) fn toggle_fold(&mut self, row: usize, cx: &mut Context<Self>) -> bool {
+        if self.folds.is_folded(row) {
+            self.folds.unfold(row);
+        } else {
+            let ranges = self.foldable_ranges();
+            let Ok(ix) = ranges.binary_search_by_key(&row, |range| range.start) else {
+                return false;
+            };
+            self.folds.fold(ranges[ix].clone());
+            self.move_cursor_out_of_folds(cx);
+        }
+
+        cx.notify();
+        true
+    }
+
+    /// Move the cursor to the first row of the fold if it is hidden, the extra cursors are cleared.
+    fn move_cursor_out_of_folds(&mut self, cx: &mut Context<Self>) {
+        let cursor = self.cursor_offset();
+        let offset = self.skip_folded(cursor, false);
+        if offset != cursor {
+            self.selected_range = offset..offset;
+            self.selection_reversed = false;
+        }
+        self.clear_extra_selections(cx);
+    }
+
+    /// Return the row of the fold marker at the mouse position in the gutter.
+    pub(super) fn fold_marker_row_for_position(&self, position: Point<Pixels>) -> Option<usize> {
+        if !self.mode.is_code_editor() || !self.mode.line_number() {
+            return None;
+        }
+
+        let (Some(bounds), Some(layout)) = (self.last_bounds.as_ref(), self.last_layout.as_ref())
+        else {
+            return None;
+        };
+
+        let marker_end = self.line_number_width - LINE_NUMBER_MARGIN_RIGHT;
+        let x = position.x - bounds.origin.x;
+        if x < marker_end - FOLD_MARKER_WIDTH || x > marker_end {
+            return None;
+        }
+
+        let line_height = self.last_line_height;
+        layout
+            .lines
+            .iter()
+            .find(|line| {
+                let top = bounds.origin.y + line.top;
+                position.y >= top && position.y < top + line.line.size(line_height).height
+            })
+            .map(|line| line.row)
+    }
+
+    /// Return the foldable ranges sorted by the start row, cached until the text is changed.
+    pub(super) fn foldable_ranges(&mut self) -> Rc<Vec<Range<usize>>> {
+        self.foldables.ranges(&self.text)
+    }
+
+    pub(super) fn fold(&mut self, _: &Fold, _: &mut Window, cx: &mut Context<Self>) {
+        let row = self.text.offset_to_point(self.cursor_offset()).row;
+        let Some(range) = self.foldable_range_at(row) else {
+            return;
+        };
+
+        self.folds.fold(range);
+        self.move_cursor_out_of_folds(cx);
+        cx.notify();
+    }
+
+    pub(super) fn unfold(&mut self, _: &Unfold, _: &mut Window, cx: &mut Context<Self>) {
+        let row = self.text.offset_to_point(self.cursor_offset()).row;
+        if self.folds.unfold(row) {
+            cx.notify();
+        }
+    }
+
+    pub(super) fn fold_all(&mut self, _: &FoldAll, _: &mut Window, cx: &mut Context<Self>) {
+        for range in self.foldable_ranges().iter() {
+            self.folds.fold(range.clone());
+        }
+
+        self.move_cursor_out_of_folds(cx);
+        cx.notify();
+    }
+
+    pub(super) fn unfold_all(&mut self, _: &UnfoldAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.folds.clear();
+        cx.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn foldable_ranges(text: &str) -> Vec<Range<usize>> {
+        let lines = text.split('\n').map(FoldLine::new).collect::<Vec<_>>();
+        super::foldable_ranges(&lines)
+    }
+
+    #[test]
+    fn test_foldable_ranges() {
+        let text = "fn main() {\n    let a = [\n        1,\n        2,\n    ];\n}\n";
+        assert_eq!(foldable_ranges(text), vec![0..5, 1..4]);
+
+        let text = "def foo():\n    if a:\n        pass\n\n    return 1\nfoo()";
+        assert_eq!(foldable_ranges(text), vec![0..5, 1..3]);
+
+        // The brackets in the same row are not foldable.
+        assert!(foldable_ranges("foo(1, 2)\nbar()").is_empty());
+        // The close bracket is paired with the open bracket in the previous lines.
+        assert_eq!(foldable_ranges("foo(\n1,\n2)"), vec![0..2]);
+        // The close bracket after another open bracket is ignored.
+        assert!(foldable_ranges("foo(\n1,\n[)]").is_empty());
+    }
+
+    #[test]
+    fn test_foldable_map_update() {
+        let mut text = Rope::from("fn main() {\n    let a = [\n        1,\n    ];\n}\n");
+        let mut foldables = FoldableMap::default();
+        assert_eq!(*foldables.ranges(&text), vec![0..4, 1..3]);
+
+        for (range, new_text) in [
+            (36..36, "        2,\n"),
+            (11..11, "\n    if a {\n        b();\n    }"),
+            (0..12, ""),
+            (20..60, "x"),
+        ] {
+            let edit = text.replace(range, new_text);
+            foldables.update(&text, &edit);
+            let mut expected = FoldableMap::default();
+            assert_eq!(foldables.ranges(&text), expected.ranges(&text));
+        }
+    }
+
+    #[test]
+    fn test_fold_map() {
+        let mut folds = FoldMap::default();
+        folds.fold(2..6);
+        folds.fold(3..5);
+        folds.fold(10..12);
+        assert!(folds.is_folded(2));
+        assert!(!folds.is_hidden(2));
+        assert!(folds.is_hidden(3));
+        assert!(folds.is_hidden(5));
+        assert!(!folds.is_hidden(6));
+        assert_eq!(folds.hidden_range(4), Some(3..6));
+        assert_eq!(folds.hidden_range(11), Some(11..12));
+
+        // Edit in the first row of the fold.
+        folds.update(&LinesEdit {
+            start_row: 2,
+            old_rows: 1,
+            new_rows: 1,
+        });
+        assert!(folds.is_folded(2));
+
+        // Insert rows before the folds.
+        folds.update(&LinesEdit {
+            start_row: 0,
+            old_rows: 1,
+            new_rows: 3,
+        });
+        assert!(folds.is_folded(4));
+        assert!(folds.is_folded(12));
+        assert!(folds.is_hidden(13));
+
+        // Edit in the hidden rows removes the fold.
+        folds.update(&LinesEdit {
+            start_row: 13,
+            old_rows: 1,
+            new_rows: 1,
+        });
+        assert!(!folds.is_folded(12));
+        assert!(folds.is_folded(4));
+
+        assert!(folds.unfold(6));
+        assert!(folds.is_empty());
+    }
+}
@@ -0,0 +1,542 @@
+use std::ops::Range;
+
+use gpui::{
+    actions, div, px, App, AppContext as _, Context, Entity, InteractiveElement as _, IntoElement,
+    KeyBinding, MouseButton, ParentElement as _, Styled as _, Subscription, Window,
+};
+use rust_i18n::t;
+
+use super::{
+    multi_cursor::adjust_range_for_edit, rope::Rope, state::CONTEXT, InputEvent, InputState,
+    SelectAll, TextInput,
+};
+use crate::{ActiveTheme as _, Sizable as _};
+
+actions!(
+    input,
+    [
+        MoveLineUp,
+        MoveLineDown,
+        DuplicateLine,
+        DeleteLine,
+        JoinLines,
+        ToggleLineComment,
+        GoToLine,
+        SelectLine
+    ]
+);
+
+pub(super) fn init(cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("alt-up", MoveLineUp, Some(CONTEXT)),
+        KeyBinding::new("alt-down", MoveLineDown, Some(CONTEXT)),
+        KeyBinding::new("alt-shift-down", DuplicateLine, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-k", DeleteLine, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-k", DeleteLine, Some(CONTEXT)),
+        KeyBinding::new("ctrl-j", JoinLines, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-/", ToggleLineComment, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-/", ToggleLineComment, Some(CONTEXT)),
+        KeyBinding::new("ctrl-g", GoToLine, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-l", SelectLine, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-l", SelectLine, Some(CONTEXT)),
+    ]);
+}
+
+/// Return the rows of the `range`, the last row is excluded if a non-empty range ends at the start of it.
+fn selected_rows(text: &Rope, range: &Range<usize>) -> Range<usize> {
+    let start = text.offset_to_point(range.start).row;
+    let end = text.offset_to_point(range.end);
+    if end.row > start && end.column == 0 {
+        start..end.row
+    } else {
+        start..end.row + 1
+    }
+}
+
+/// Move the first line of the `text` to the end (`up`), or the last line to the start.
+fn move_lines(text: &str, up: bool) -> String {
+    let mut lines = text.split('\n').collect::<Vec<_>>();
+    if up {
+        lines.rotate_left(1);
+    } else {
+        lines.rotate_right(1);
+    }
+    lines.join("\n")
+}
+
+/// Join the lines of the `text` with a single space, the indentation of the joined lines is removed.
+///
+/// Returns the new text and the offset of the last joint.
+fn join_lines(text: &str) -> (String, usize) {
+    let mut lines = text.split('\n');
+    let mut new_text = lines.next().unwrap_or_default().trim_end().to_string();
+    let mut joint = new_text.len();
+
+    for line in lines {
+        let line = line.trim_start();
+        joint = new_text.len();
+        if line.trim_end().is_empty() {
+            continue;
+        }
+        if !new_text.is_empty() {
+            new_text.push(' ');
+        }
+        new_text.push_str(line);
+    }
+
+    (new_text, joint)
+}
+
+/// Return the edits to toggle the line comment of the lines in the `text`, the blank lines are skipped.
+///
+/// If all lines are commented, the `token` (and a space after it) is removed,
+/// otherwise the `token` is inserted at the minimum indentation of the lines.
+fn toggle_line_comment(text: &str, token: &str) -> Vec<(Range<usize>, String)> {
+    let mut lines = vec![];
+    let mut offset = 0;
+    for line in text.split('\n') {
+        let indent = line.len() - line.trim_start().len();
+        if !line.trim().is_empty() {
+            lines.push((offset, indent, &line[indent..]));
+        }
+        offset += line.len() + 1;
+    }
+
+    if lines.iter().all(|(_, _, line)| line.starts_with(token)) {
+        lines
+            .iter()
+            .map(|(offset, indent, line)| {
+                let start = offset + indent;
+                let mut len = token.len();
+                if line[len..].starts_with(' ') {
+                    len += 1;
+                }
+                (start..start + len, String::new())
+            })
+            .collect()
+    } else {
+        let indent = lines
+            .iter()
+            .map(|(_, indent, _)| *indent)
+            .min()
+            .unwrap_or(0);
+        lines
+            .iter()
+            .map(|(offset, _, _)| (offset + indent..offset + indent, format!("{} ", token)))
+            .collect()
+    }
+}
+
+/// Parse the line position of `line` or `line:column` (1-based), returns the zero-based row and column.
+fn parse_line_position(input: &str) -> Option<(usize, usize)> {
+    let mut parts = input.trim().splitn(2, ':');
+    let row = parts.next()?.trim().parse::<usize>().ok()?;
+    let column = match parts.next().map(str::trim) {
+        Some(column) if !column.is_empty() => column.parse::<usize>().ok()?,
+        _ => 1,
+    };
+
+    Some((row.saturating_sub(1), column.saturating_sub(1)))
+}
+
+/// The prompt of [`GoToLine`], to input the `line:column` to go to.
+pub(super) struct GoToLinePrompt {
+    input: Entity<InputState>,
+    _subscription: Subscription,
+}
+
+impl GoToLinePrompt {
+    pub(super) fn render(&self, cx: &App) -> impl IntoElement {
+        div()
+            .occlude()
+            .cursor_default()
+            .w(px(240.))
+            .p_1()
+            .border_1()
+            .border_color(cx.theme().border)
+            .bg(cx.theme().popover)
+            .rounded(cx.theme().radius)
+            .shadow_md()
+            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+            .child(TextInput::new(&self.input).xsmall())
+    }
+}
+
+impl InputState {
+    /// Replace the `range` with the `new_text` as a single undo step, then select the `selection`.
+    fn replace_lines(
+        &mut self,
+        range: Range<usize>,
+        new_text: &str,
+        selection: Range<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.history.start_grouping();
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), new_text, window, cx);
+        self.history.end_grouping();
+
+        let len = self.text.len();
+        self.selected_range = selection.start.min(len)..selection.end.min(len);
+        self.selection_reversed = false;
+        self.update_preferred_x_offset(cx);
+        cx.notify();
+    }
+
+    /// Return the offset of the `column` (in bytes) in the `row`, clamped to the line and the char boundary.
+    pub(super) fn offset_for_column(&self, row: usize, column: usize) -> usize {
+        let line = self.text.line(row);
+        let mut column = column.min(line.len());
+        while !line.is_char_boundary(column) {
+            column -= 1;
+        }
+        self.text.line_start_offset(row) + column
+    }
+
+    pub(super) fn move_line_up(
+        &mut self,
+        action: &MoveLineUp,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.move_line_up(action, window, cx)
+        }) {
+            return;
+        }
+
+        let rows = selected_rows(&self.text, &self.selected_range);
+        if rows.start == 0 {
+            return;
+        }
+
+        let start = self.text.line_start_offset(rows.start - 1);
+        let end = self.text.line_end_offset(rows.end - 1);
+        let prev_line_len = self.text.line_end_offset(rows.start - 1) - start + 1;
+        let new_text = move_lines(&self.text.slice(start..end), true);
+        let selection =
+            self.selected_range.start - prev_line_len..self.selected_range.end - prev_line_len;
+        self.replace_lines(start..end, &new_text, selection, window, cx);
+    }
+
+    pub(super) fn move_line_down(
+        &mut self,
+        action: &MoveLineDown,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.move_line_down(action, window, cx)
+        }) {
+            return;
+        }
+
+        let rows = selected_rows(&self.text, &self.selected_range);
+        if rows.end >= self.text.lines_len() {
+            return;
+        }
+
+        let start = self.text.line_start_offset(rows.start);
+        let end = self.text.line_end_offset(rows.end);
+        let next_line_len = end - self.text.line_start_offset(rows.end) + 1;
+        let new_text = move_lines(&self.text.slice(start..end), false);
+        let selection =
+            self.selected_range.start + next_line_len..self.selected_range.end + next_line_len;
+        self.replace_lines(start..end, &new_text, selection, window, cx);
+    }
+
+    pub(super) fn duplicate_line(
+        &mut self,
+        action: &DuplicateLine,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.duplicate_line(action, window, cx)
+        }) {
+            return;
+        }
+
+        let rows = selected_rows(&self.text, &self.selected_range);
+        let start = self.text.line_start_offset(rows.start);
+        let end = self.text.line_end_offset(rows.end - 1);
+        let new_text = format!("\n{}", self.text.slice(start..end));
+        // Move the selection to the duplicated lines.
+        let selection =
+            self.selected_range.start + new_text.len()..self.selected_range.end + new_text.len();
+        self.replace_lines(end..end, &new_text, selection, window, cx);
+    }
+
+    pub(super) fn delete_line(
+        &mut self,
+        action: &DeleteLine,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.delete_line(action, window, cx)
+        }) {
+            return;
+        }
+
+        let rows = selected_rows(&self.text, &self.selected_range);
+        let column = self.text.offset_to_point(self.cursor_offset()).column;
+        let mut start = self.text.line_start_offset(rows.start);
+        let end = if rows.end < self.text.lines_len() {
+            self.text.line_start_offset(rows.end)
+        } else {
+            // Delete the `\n` before the last line.
+            start = start.saturating_sub(1);
+            self.text.len()
+        };
+
+        self.history.start_grouping();
+        self.replace_text_in_range(Some(self.range_to_utf16(&(start..end))), "", window, cx);
+        self.history.end_grouping();
+
+        let row = rows.start.min(self.text.lines_len().saturating_sub(1));
+        let offset = self.offset_for_column(row, column);
+        self.selected_range = offset..offset;
+        self.selection_reversed = false;
+        self.update_preferred_x_offset(cx);
+        cx.notify();
+    }
+
+    pub(super) fn join_lines(
+        &mut self,
+        action: &JoinLines,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.join_lines(action, window, cx)
+        }) {
+            return;
+        }
+
+        let mut rows = selected_rows(&self.text, &self.selected_range);
+        if rows.len() == 1 {
+            if rows.end >= self.text.lines_len() {
+                return;
+            }
+            rows.end += 1;
+        }
+
+        let start = self.text.line_start_offset(rows.start);
+        let end = self.text.line_end_offset(rows.end - 1);
+        let (new_text, joint) = join_lines(&self.text.slice(start..end));
+        let selection = if self.selected_range.is_empty() {
+            start + joint..start + joint
+        } else {
+            start..start + new_text.len()
+        };
+        self.replace_lines(start..end, &new_text, selection, window, cx);
+    }
+
+    pub(super) fn toggle_line_comment(
+        &mut self,
+        action: &ToggleLineComment,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(token) = self
+            .mode
+            .highlighter()
+            .and_then(|highlighter| highlighter.line_comment())
+        else {
+            return;
+        };
+
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.toggle_line_comment(action, window, cx)
+        }) {
+            return;
+        }
+
+        let rows = selected_rows(&self.text, &self.selected_range);
+        let start = self.text.line_start_offset(rows.start);
+        let end = self.text.line_end_offset(rows.end - 1);
+        let mut new_text = self.text.slice(start..end);
+        let edits = toggle_line_comment(&new_text, &token);
+        if edits.is_empty() {
+            return;
+        }
+
+        let mut selection = self.selected_range.start - start..self.selected_range.end - start;
+        for (range, text) in edits.iter().rev() {
+            new_text.replace_range(range.clone(), text);
+            adjust_range_for_edit(&mut selection, range, text.len());
+        }
+
+        let selection = start + selection.start..start + selection.end;
+        self.replace_lines(start..end, &new_text, selection, window, cx);
+    }
+
+    pub(super) fn select_line(
+        &mut self,
+        action: &SelectLine,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_line(action, window, cx)
+        }) {
+            return;
+        }
+
+        // Include the row of the selection end, to extend the selection to the next line when selected again.
+        let start_row = self.text.offset_to_point(self.selected_range.start).row;
+        let end_row = self.text.offset_to_point(self.selected_range.end).row + 1;
+        let start = self.text.line_start_offset(start_row);
+        let end = if end_row < self.text.lines_len() {
+            self.text.line_start_offset(end_row)
+        } else {
+            self.text.len()
+        };
+
+        self.unfold_offset(start);
+        self.unfold_offset(end);
+        self.selected_range = start..end;
+        self.selection_reversed = false;
+        cx.notify();
+    }
+
+    pub(super) fn go_to_line(&mut self, _: &GoToLine, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.is_multi_line() {
+            cx.propagate();
+            return;
+        }
+
+        let row = self.text.offset_to_point(self.cursor_offset()).row;
+        let input = cx
+            .new(|cx| InputState::new(window, cx).placeholder(t!("Input.go_to_line_placeholder")));
+        let _subscription = cx.subscribe_in(&input, window, Self::on_go_to_line_event);
+        input.update(cx, |input, cx| {
+            input.set_value((row + 1).to_string(), window, cx);
+            input.select_all(&SelectAll, window, cx);
+            input.focus(window, cx);
+        });
+
+        self.go_to_line_prompt = Some(GoToLinePrompt {
+            input,
+            _subscription,
+        });
+        cx.notify();
+    }
+
+    fn on_go_to_line_event(
+        &mut self,
+        input: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            InputEvent::PressEnter { .. } => {
+                if let Some((row, column)) = parse_line_position(&input.read(cx).value()) {
+                    let row = row.min(self.text.lines_len().saturating_sub(1));
+                    let column = self
+                        .text
+                        .line(row)
+                        .chars()
+                        .take(column)
+                        .map(char::len_utf8)
+                        .sum();
+                    let offset = self.offset_for_column(row, column);
+
+                    self.extra_selections.clear();
+                    self.unfold_offset(offset);
+                    self.selected_range = offset..offset;
+                    self.selection_reversed = false;
+                    self.update_preferred_x_offset(cx);
+                }
+                self.close_go_to_line_prompt(window, cx);
+            }
+            InputEvent::Blur => {
+                self.go_to_line_prompt = None;
+                cx.notify();
+            }
+            _ => {}
+        }
+    }
+
+    /// Close the go to line prompt and focus back to the editor, returns false if the prompt is not open.
+    pub(super) fn close_go_to_line_prompt(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if self.go_to_line_prompt.take().is_none() {
+            return false;
+        }
+
+        self.focus(window, cx);
+        cx.notify();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selected_rows() {
+        let text = Rope::from("aa\nbb\ncc\n");
+        assert_eq!(selected_rows(&text, &(0..0)), 0..1);
+        assert_eq!(selected_rows(&text, &(1..4)), 0..2);
+        // Ends at the start of the row 2.
+        assert_eq!(selected_rows(&text, &(1..6)), 0..2);
+        assert_eq!(selected_rows(&text, &(6..6)), 2..3);
+        assert_eq!(selected_rows(&text, &(9..9)), 3..4);
+    }
+
+    #[test]
+    fn test_move_lines() {
+        assert_eq!(move_lines("a\nb\nc", true), "b\nc\na");
+        assert_eq!(move_lines("a\nb\nc", false), "c\na\nb");
+        assert_eq!(move_lines("a\n", true), "\na");
+    }
+
+    #[test]
+    fn test_join_lines() {
+        assert_eq!(join_lines("foo  \n    bar"), ("foo bar".to_string(), 3));
+        assert_eq!(join_lines("a\n\n  b\n  c"), ("a b c".to_string(), 3));
+        assert_eq!(join_lines("a\n  "), ("a".to_string(), 1));
+        assert_eq!(join_lines("\nb"), ("b".to_string(), 0));
+    }
+
+    #[test]
+    fn test_toggle_line_comment() {
+        let apply = |text: &str, token: &str| {
+            let mut text = text.to_string();
+            for (range, new_text) in toggle_line_comment(&text, token).iter().rev() {
+                text.replace_range(range.clone(), new_text);
+            }
+            text
+        };
+
+        assert_eq!(apply("  a\n\n    b", "//"), "  // a\n\n  //   b");
+        assert_eq!(apply("  // a\n\n  //   b", "//"), "  a\n\n    b");
+        assert_eq!(apply("# a\nb", "#"), "# # a\n# b");
+        assert_eq!(apply("#a\n  #b", "#"), "a\n  b");
+        assert!(toggle_line_comment("  \n", "//").is_empty());
+    }
+
+    #[test]
+    fn test_parse_line_position() {
+        assert_eq!(parse_line_position("12"), Some((11, 0)));
+        assert_eq!(parse_line_position(" 12:5 "), Some((11, 4)));
+        assert_eq!(parse_line_position("12:"), Some((11, 0)));
+        assert_eq!(parse_line_position("0"), Some((0, 0)));
+        assert_eq!(parse_line_position("abc"), None);
+        assert_eq!(parse_line_position("1:x"), None);
+        assert_eq!(parse_line_position(""), None);
+    }
+}
@@ -1,3 +1,5 @@
+use std::{borrow::Cow, ops::Range};
+
 use gpui::SharedString;
 
 #[derive(Clone, PartialEq, Debug)]
@@ -14,10 +16,30 @@ pub enum MaskToken {
     Sep(char),
     /// Any character
     Any,
+    /// Custom character class, e.g. `[0-5]` or `[A-F]`
+    Class(Vec<(char, char)>),
+    /// Transform the letter to upper case, then match the inner token.
+    Upper(Box<MaskToken>),
+    /// Transform the letter to lower case, then match the inner token.
+    Lower(Box<MaskToken>),
+    /// The inner token can be left empty, see [`MaskPattern::extended`].
+    Optional(Box<MaskToken>),
 }
 
 #[allow(unused)]
 impl MaskToken {
+    /// Return the token of the char in the [`MaskPattern::new`] syntax.
+    fn from_char(ch: char) -> Self {
+        match ch {
+            // '0' => MaskToken::Digit0,
+            '9' => MaskToken::Digit,
+            'A' => MaskToken::Letter,
+            '#' => MaskToken::LetterOrDigit,
+            '*' => MaskToken::Any,
+            _ => MaskToken::Sep(ch),
+        }
+    }
+
     /// Check if the token is any character.
     pub fn is_any(&self) -> bool {
         matches!(self, MaskToken::Any)
@@ -33,12 +55,26 @@ impl MaskToken {
             MaskToken::LetterOrDigit => ch.is_ascii_alphanumeric(),
             MaskToken::Any => true,
             MaskToken::Sep(c) => *c == ch,
+            MaskToken::Class(ranges) => ranges
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&ch)),
+            MaskToken::Upper(token) => token.is_match(ch.to_ascii_uppercase()),
+            MaskToken::Lower(token) => token.is_match(ch.to_ascii_lowercase()),
+            MaskToken::Optional(token) => token.is_match(ch),
         }
     }
 
     /// Is the token a separator (Can be ignored)
     fn is_sep(&self) -> bool {
-        matches!(self, MaskToken::Sep(_))
+        match self {
+            MaskToken::Sep(_) => true,
+            MaskToken::Optional(token) => token.is_sep(),
+            _ => false,
+        }
+    }
+
+    fn is_optional(&self) -> bool {
+        matches!(self, MaskToken::Optional(_))
     }
 
     /// Check if the token is a number.
@@ -49,15 +85,29 @@ impl MaskToken {
     pub fn placeholder(&self) -> char {
         match self {
             MaskToken::Sep(c) => *c,
+            MaskToken::Optional(token) => token.placeholder(),
             _ => '_',
         }
     }
 
+    /// Wrap the token with the case transform, the separator is never transformed.
+    fn with_case(self, case: Option<MaskCase>) -> Self {
+        match case {
+            _ if self.is_sep() => self,
+            Some(MaskCase::Upper) => MaskToken::Upper(Box::new(self)),
+            Some(MaskCase::Lower) => MaskToken::Lower(Box::new(self)),
+            None => self,
+        }
+    }
+
     fn mask_char(&self, ch: char) -> char {
         match self {
             MaskToken::Digit | MaskToken::LetterOrDigit | MaskToken::Letter => ch,
             MaskToken::Sep(c) => *c,
-            MaskToken::Any => ch,
+            MaskToken::Any | MaskToken::Class(_) => ch,
+            MaskToken::Upper(_) => ch.to_ascii_uppercase(),
+            MaskToken::Lower(_) => ch.to_ascii_lowercase(),
+            MaskToken::Optional(token) => token.mask_char(ch),
         }
     }
 
@@ -67,11 +117,68 @@ impl MaskToken {
             MaskToken::Letter => Some(ch),
             MaskToken::LetterOrDigit => Some(ch),
             MaskToken::Any => Some(ch),
+            MaskToken::Class(_) | MaskToken::Upper(_) | MaskToken::Lower(_) => Some(ch),
+            MaskToken::Optional(token) => token.unmask_char(ch),
             _ => None,
         }
     }
 }
 
+/// Return the number of the tokens before the first optional token.
+fn required_len(tokens: &[MaskToken]) -> usize {
+    tokens
+        .iter()
+        .position(|token| token.is_optional())
+        .unwrap_or(tokens.len())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MaskCase {
+    Upper,
+    Lower,
+}
+
+/// Parse the char class after the `[`, e.g. `0-5]` or `A-Fa-f]`.
+///
+/// Return `None` if the class is not closed or is empty.
+fn parse_class(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Vec<(char, char)>> {
+    let mut ranges = vec![];
+    loop {
+        let start = match chars.next()? {
+            ']' => break,
+            '\\' => chars.next()?,
+            ch => ch,
+        };
+
+        let mut lookahead = chars.clone();
+        if lookahead.next() == Some('-') && lookahead.peek().is_some_and(|ch| *ch != ']') {
+            chars.next();
+            let end = match chars.next()? {
+                '\\' => chars.next()?,
+                ch => ch,
+            };
+            ranges.push((start.min(end), start.max(end)));
+        } else {
+            ranges.push((start, start));
+        }
+    }
+
+    (!ranges.is_empty()).then_some(ranges)
+}
+
+/// Parse the repeat count after the `{`, e.g. `3}`.
+fn parse_repeat(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+    let mut count = String::new();
+    loop {
+        match chars.next()? {
+            '}' => break,
+            ch if ch.is_ascii_digit() => count.push(ch),
+            _ => return None,
+        }
+    }
+    count.parse().ok().filter(|count| *count > 0)
+}
+
 #[derive(Clone, Default)]
 pub enum MaskPattern {
     #[default]
@@ -86,6 +193,14 @@ pub enum MaskPattern {
         /// Number of fraction digits, e.g. 2 for 123.45
         fraction: Option<usize>,
     },
+    /// The number with the locale decimal point, digit grouping, prefix and suffix.
+    FormattedNumber(NumberFormat),
+}
+
+impl From<NumberFormat> for MaskPattern {
+    fn from(format: NumberFormat) -> Self {
+        Self::FormattedNumber(format)
+    }
 }
 
 impl From<&str> for MaskPattern {
@@ -109,18 +224,87 @@ impl MaskPattern {
     /// - `99999-9999` - ZIP code: 12345-6789
     /// - `AAAA-99-####` - Custom pattern: ABCD-12-3AB4
     /// - `*999*` - Custom pattern: (123) or [123]
+    ///
+    /// See [`Self::extended`] for the char classes, repeats, optional parts and case transforms.
     pub fn new(pattern: &str) -> Self {
-        let tokens = pattern
-            .chars()
-            .map(|ch| match ch {
-                // '0' => MaskToken::Digit0,
-                '9' => MaskToken::Digit,
-                'A' => MaskToken::Letter,
-                '#' => MaskToken::LetterOrDigit,
-                '*' => MaskToken::Any,
-                _ => MaskToken::Sep(ch),
-            })
-            .collect();
+        let tokens = pattern.chars().map(MaskToken::from_char).collect();
+
+        Self::Pattern {
+            pattern: pattern.to_owned().into(),
+            tokens,
+        }
+    }
+
+    /// Create a new mask pattern with the extended syntax, in addition to the tokens of [`Self::new`]:
+    ///
+    /// - `[0-5]`, `[A-F]`, `[xyz]` - Custom character class
+    /// - `{n}` - Repeat the previous token `n` times, e.g. `9{3}` is `999`
+    /// - `?` - The rest of the pattern is optional
+    /// - `>` - Transform the following letters to upper case
+    /// - `<` - Transform the following letters to lower case
+    /// - `<>` - Stop transforming the case
+    /// - `\` - Escape the next character as a separator, e.g. `\9`
+    ///
+    /// For example:
+    ///
+    /// - `(999)999-9999? x9{4}` - US phone number with optional extension: (123)456-7890 x12
+    /// - `[0-2]9:[0-5]9` - Time: 23:59
+    /// - `>AA99 A{4} 9{4} 9{4} 9{4} 9{2}` - IBAN: GB82 WEST 1234 5698 7654 32
+    pub fn extended(pattern: &str) -> Self {
+        let mut tokens: Vec<MaskToken> = vec![];
+        let mut optional = false;
+        let mut case = None;
+        let mut chars = pattern.chars().peekable();
+        while let Some(ch) = chars.next() {
+            let token = match ch {
+                '[' => {
+                    let mut lookahead = chars.clone();
+                    match parse_class(&mut lookahead) {
+                        Some(ranges) => {
+                            chars = lookahead;
+                            MaskToken::Class(ranges)
+                        }
+                        None => MaskToken::Sep(ch),
+                    }
+                }
+                '{' => {
+                    let mut lookahead = chars.clone();
+                    match (tokens.last().cloned(), parse_repeat(&mut lookahead)) {
+                        (Some(last), Some(count)) => {
+                            chars = lookahead;
+                            tokens.extend(std::iter::repeat(last).take(count - 1));
+                            continue;
+                        }
+                        _ => MaskToken::Sep(ch),
+                    }
+                }
+                '?' if !optional => {
+                    optional = true;
+                    continue;
+                }
+                '>' => {
+                    case = Some(MaskCase::Upper);
+                    continue;
+                }
+                '<' => {
+                    if chars.next_if_eq(&'>').is_some() {
+                        case = None;
+                    } else {
+                        case = Some(MaskCase::Lower);
+                    }
+                    continue;
+                }
+                '\\' => MaskToken::Sep(chars.next().unwrap_or(ch)),
+                _ => MaskToken::from_char(ch),
+            };
+
+            let token = token.with_case(case);
+            if optional {
+                tokens.push(MaskToken::Optional(Box::new(token)));
+            } else {
+                tokens.push(token);
+            }
+        }
 
         Self::Pattern {
             pattern: pattern.to_owned().into(),
@@ -132,8 +316,23 @@ impl MaskPattern {
     fn tokens(&self) -> Option<&Vec<MaskToken>> {
         match self {
             Self::Pattern { tokens, .. } => Some(tokens),
-            Self::Number { .. } => None,
-            Self::None => None,
+            _ => None,
+        }
+    }
+
+    /// Return the number format of the number mask pattern.
+    fn number_format(&self) -> Option<Cow<'_, NumberFormat>> {
+        match self {
+            Self::Number {
+                separator,
+                fraction,
+            } => Some(Cow::Owned(NumberFormat {
+                separator: *separator,
+                fraction: *fraction,
+                ..Default::default()
+            })),
+            Self::FormattedNumber(format) => Some(Cow::Borrowed(format)),
+            _ => None,
         }
     }
 
@@ -145,13 +344,22 @@ impl MaskPattern {
         }
     }
 
+    /// Create a new number mask pattern follow the current locale.
+    ///
+    /// See also: [`NumberFormat::locale`]
+    pub fn locale_number() -> Self {
+        Self::FormattedNumber(NumberFormat::locale(&crate::locale()))
+    }
+
     pub fn placeholder(&self) -> Option<String> {
         match self {
-            Self::Pattern { tokens, .. } => {
-                Some(tokens.iter().map(|token| token.placeholder()).collect())
-            }
-            Self::Number { .. } => None,
-            Self::None => None,
+            Self::Pattern { tokens, .. } => Some(
+                tokens[..required_len(tokens)]
+                    .iter()
+                    .map(|token| token.placeholder())
+                    .collect(),
+            ),
+            _ => None,
         }
     }
 
@@ -159,7 +367,7 @@ impl MaskPattern {
     pub fn is_none(&self) -> bool {
         match self {
             Self::Pattern { tokens, .. } => tokens.is_empty(),
-            Self::Number { .. } => false,
+            Self::Number { .. } | Self::FormattedNumber(_) => false,
             Self::None => true,
         }
     }
@@ -171,6 +379,9 @@ impl MaskPattern {
         if self.is_none() {
             return true;
         }
+        if let Some(format) = self.number_format() {
+            return format.is_valid(mask_text);
+        }
 
         let mut text_index = 0;
         let mask_text_chars: Vec<char> = mask_text.chars().collect();
@@ -186,43 +397,26 @@ impl MaskPattern {
                         text_index += 1;
                     }
                 }
-                text_index == mask_text.len()
+                text_index == mask_text_chars.len()
             }
-            Self::Number { separator, .. } => {
-                if mask_text.is_empty() {
-                    return true;
-                }
-
-                // check if the text is valid number
-                let mut parts = mask_text.split('.');
-                let int_part = parts.next().unwrap_or("");
-                let frac_part = parts.next();
-
-                if int_part.is_empty() {
-                    return false;
-                }
-
-                // check if the integer part is valid
-                if !int_part
-                    .chars()
-                    .all(|ch| ch.is_ascii_digit() || Some(ch) == *separator)
-                {
-                    return false;
-                }
+            _ => true,
+        }
+    }
 
-                // check if the fraction part is valid
-                if let Some(frac) = frac_part {
-                    if !frac
-                        .chars()
-                        .all(|ch| ch.is_ascii_digit() || Some(ch) == *separator)
-                    {
-                        return false;
-                    }
-                }
+    /// Check if all the required tokens are filled, the optional tokens can be empty.
+    ///
+    /// The number mask pattern is complete if it is not empty.
+    pub fn is_complete(&self, mask_text: &str) -> bool {
+        if let Some(format) = self.number_format() {
+            return !format.unmask(mask_text).is_empty() && format.is_valid(mask_text);
+        }
 
-                true
+        match self {
+            Self::Pattern { tokens, .. } => {
+                let len = mask_text.chars().count();
+                (len >= required_len(tokens) && len <= tokens.len()) && self.is_valid(mask_text)
             }
-            Self::None => true,
+            _ => true,
         }
     }
 
@@ -251,8 +445,7 @@ impl MaskPattern {
 
                 false
             }
-            Self::Number { .. } => true,
-            Self::None => true,
+            _ => true,
         }
     }
 
@@ -267,51 +460,11 @@ impl MaskPattern {
         if self.is_none() {
             return text.to_owned().into();
         }
+        if let Some(format) = self.number_format() {
+            return format.mask(text).into();
+        }
 
         match self {
-            Self::Number {
-                separator,
-                fraction,
-            } => {
-                if let Some(sep) = *separator {
-                    // Remove the existing group separator
-                    let text = text.replace(sep, "");
-
-                    let mut parts = text.split('.');
-                    let int_part = parts.next().unwrap_or("");
-
-                    // Limit the fraction part to the given range, if not enough, pad with 0
-                    let frac_part = parts.next().map(|part| {
-                        part.chars()
-                            .take(fraction.unwrap_or(usize::MAX))
-                            .collect::<String>()
-                    });
-
-                    // Reverse the integer part for easier grouping
-                    let chars: Vec<char> = int_part.chars().rev().collect();
-                    let mut result = String::new();
-                    for (i, ch) in chars.iter().enumerate() {
-                        if i > 0 && i % 3 == 0 {
-                            result.push(sep);
-                        }
-                        result.push(*ch);
-                    }
-                    let int_with_sep: String = result.chars().rev().collect();
-
-                    let final_str = if let Some(frac) = frac_part {
-                        if fraction == &Some(0) {
-                            int_with_sep
-                        } else {
-                            format!("{}.{}", int_with_sep, frac)
-                        }
-                    } else {
-                        int_with_sep
-                    };
-                    return final_str.into();
-                }
-
-                text.to_owned().into()
-            }
             Self::Pattern { tokens, .. } => {
                 let mut result = String::new();
                 let mut text_index = 0;
@@ -327,38 +480,25 @@ impl MaskPattern {
                     }
                     let mask_ch = token.mask_char(ch);
                     result.push(mask_ch);
-                    if ch == mask_ch {
+                    // The separator is inserted without consuming the char, if not typed.
+                    if !token.is_sep() || ch == mask_ch {
                         text_index += 1;
                         continue;
                     }
                 }
                 result.into()
             }
-            Self::None => text.to_owned().into(),
+            _ => text.to_owned().into(),
         }
     }
 
     /// Extract original text from masked text
     pub fn unmask(&self, mask_text: &str) -> String {
-        match self {
-            Self::Number { separator, .. } => {
-                if let Some(sep) = *separator {
-                    let mut result = String::new();
-                    for ch in mask_text.chars() {
-                        if ch == sep {
-                            continue;
-                        }
-                        result.push(ch);
-                    }
-
-                    if result.contains('.') {
-                        result = result.trim_end_matches('0').to_string();
-                    }
-                    return result;
-                }
+        if let Some(format) = self.number_format() {
+            return format.unmask(mask_text);
+        }
 
-                return mask_text.to_owned();
-            }
+        match self {
             Self::Pattern { tokens, .. } => {
                 let mut result = String::new();
                 let mask_text_chars: Vec<char> = mask_text.chars().collect();
@@ -374,14 +514,281 @@ impl MaskPattern {
                 }
                 result
             }
-            Self::None => mask_text.to_owned(),
+            _ => mask_text.to_owned(),
+        }
+    }
+
+    /// Format the unmasked value (e.g. from [`Self::unmask`]) to the mask text.
+    ///
+    /// This is different from [`Self::mask`] for the number with a locale decimal point.
+    pub fn mask_value(&self, value: &str) -> SharedString {
+        match self.number_format() {
+            Some(format) => format.mask(&format.localize(value)).into(),
+            None => self.mask(value),
+        }
+    }
+
+    /// Return the range of the mask text that the cursor can be placed in,
+    /// the prefix and suffix of the number are excluded.
+    pub(super) fn editable_range(&self, mask_text: &str) -> Range<usize> {
+        match self.number_format() {
+            Some(format) => format.editable_range(mask_text),
+            None => 0..mask_text.len(),
+        }
+    }
+}
+
+/// The digit grouping style of the [`NumberFormat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NumberGrouping {
+    /// Group by every 3 digits, e.g. 1,234,567
+    #[default]
+    Thousands,
+    /// Group the last 3 digits, then every 2 digits, e.g. 12,34,567
+    Indian,
+}
+
+impl NumberGrouping {
+    fn is_boundary(&self, ix: usize) -> bool {
+        match self {
+            Self::Thousands => ix > 0 && ix % 3 == 0,
+            Self::Indian => ix == 3 || (ix > 3 && (ix - 3) % 2 == 0),
+        }
+    }
+}
+
+/// The format of the [`MaskPattern::FormattedNumber`].
+///
+/// The unmask value always uses `.` as the decimal point and excludes the group separator,
+/// prefix and suffix, so it can be parsed as a number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberFormat {
+    /// Group separator, e.g. "," or " "
+    pub separator: Option<char>,
+    /// Decimal point, e.g. "." or ","
+    pub decimal: char,
+    pub grouping: NumberGrouping,
+    /// Number of fraction digits, e.g. 2 for 123.45
+    pub fraction: Option<usize>,
+    /// The prefix to display before the number, e.g. "$"
+    pub prefix: Option<SharedString>,
+    /// The suffix to display after the number, e.g. "%" or " kg"
+    pub suffix: Option<SharedString>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            separator: None,
+            decimal: '.',
+            grouping: NumberGrouping::default(),
+            fraction: None,
+            prefix: None,
+            suffix: None,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Create a number format with the separators of the locale, e.g. `en`, `de-CH`, `hi_IN`.
+    ///
+    /// The unknown locale will use `1,234.5`.
+    pub fn locale(locale: &str) -> Self {
+        let mut parts = locale.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_lowercase();
+        let region = parts.next().unwrap_or_default().to_uppercase();
+
+        let (separator, decimal, grouping) = match (language.as_str(), region.as_str()) {
+            ("de", "CH" | "LI") | ("it", "CH") => ('\'', '.', NumberGrouping::Thousands),
+            ("es", "MX" | "US") | ("pt", "MO") => (',', '.', NumberGrouping::Thousands),
+            ("en", "IN") | ("hi" | "bn" | "mr" | "ta" | "te" | "gu" | "kn" | "ml", _) => {
+                (',', '.', NumberGrouping::Indian)
+            }
+            (
+                "de" | "es" | "it" | "pt" | "nl" | "id" | "tr" | "da" | "el" | "ro" | "vi" | "hr"
+                | "sl" | "sr",
+                _,
+            ) => ('.', ',', NumberGrouping::Thousands),
+            (
+                "fr" | "ru" | "pl" | "cs" | "sk" | "uk" | "fi" | "sv" | "nb" | "no" | "hu" | "bg"
+                | "lt" | "lv" | "et",
+                _,
+            ) => (' ', ',', NumberGrouping::Thousands),
+            _ => (',', '.', NumberGrouping::Thousands),
+        };
+
+        Self {
+            separator: Some(separator),
+            decimal,
+            grouping,
+            ..Default::default()
+        }
+    }
+
+    /// Set the number of fraction digits.
+    pub fn fraction(mut self, fraction: usize) -> Self {
+        self.fraction = Some(fraction);
+        self
+    }
+
+    /// Set the prefix, e.g. "$", it will be excluded from the unmask value.
+    pub fn prefix(mut self, prefix: impl Into<SharedString>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set the suffix, e.g. "%", it will be excluded from the unmask value.
+    pub fn suffix(mut self, suffix: impl Into<SharedString>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// Remove the prefix and suffix from the text, the minus sign is kept.
+    fn strip_affixes(&self, text: &str) -> String {
+        let (sign, mut text) = match text.strip_prefix('-') {
+            Some(text) => ("-", text.to_string()),
+            None => ("", text.to_string()),
+        };
+        if let Some(prefix) = self.prefix.as_ref().filter(|p| !p.is_empty()) {
+            if text.starts_with(prefix.as_ref()) {
+                text.drain(..prefix.len());
+            }
+        }
+        // The suffix may not be at the end, when typing after it.
+        if let Some(suffix) = self.suffix.as_ref().filter(|s| !s.is_empty()) {
+            if let Some(ix) = text.rfind(suffix.as_ref()) {
+                text.drain(ix..ix + suffix.len());
+            }
+        }
+        text.insert_str(0, sign);
+        text
+    }
+
+    /// Replace the `.` decimal point of the unmasked value with the locale decimal point.
+    fn localize(&self, value: &str) -> String {
+        if self.decimal == '.' {
+            return value.to_string();
+        }
+
+        value.replace('.', &self.decimal.to_string())
+    }
+
+    fn is_valid(&self, mask_text: &str) -> bool {
+        let text = self.strip_affixes(mask_text);
+        if text.is_empty() {
+            return true;
+        }
+
+        // Allow the leading minus sign, to type a negative number.
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(text) => (true, text),
+            None => (false, text.as_str()),
+        };
+
+        let (int_part, frac_part) = match text.split_once(self.decimal) {
+            Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+            None => (text, None),
+        };
+
+        if int_part.is_empty() {
+            return negative && frac_part.is_none();
+        }
+
+        let is_digit = |ch: char| ch.is_ascii_digit() || Some(ch) == self.separator;
+        int_part.chars().all(is_digit) && frac_part.map_or(true, |frac| frac.chars().all(is_digit))
+    }
+
+    fn mask(&self, text: &str) -> String {
+        let mut text = self.strip_affixes(text);
+        if text.is_empty() {
+            return text;
+        }
+
+        // Remove the existing group separator
+        if let Some(sep) = self.separator {
+            text = text.replace(sep, "");
+        }
+
+        // Keep the minus sign out of the grouping
+        let (sign, text) = match text.strip_prefix('-') {
+            Some(text) => ("-", text),
+            None => ("", text.as_str()),
+        };
+
+        let (int_part, frac_part) = match text.split_once(self.decimal) {
+            Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+            None => (text, None),
+        };
+
+        let mut result = String::from(sign);
+        if let Some(prefix) = &self.prefix {
+            result.push_str(prefix);
+        }
+
+        // Group the integer part from the right
+        let len = int_part.chars().count();
+        for (i, ch) in int_part.chars().enumerate() {
+            if let Some(sep) = self.separator {
+                if i > 0 && self.grouping.is_boundary(len - i) {
+                    result.push(sep);
+                }
+            }
+            result.push(ch);
+        }
+
+        // Limit the fraction part to the given digits
+        if let Some(frac) = frac_part {
+            if self.fraction != Some(0) {
+                result.push(self.decimal);
+                result.extend(frac.chars().take(self.fraction.unwrap_or(usize::MAX)));
+            }
+        }
+
+        if let Some(suffix) = &self.suffix {
+            result.push_str(suffix);
         }
+        result
+    }
+
+    fn unmask(&self, mask_text: &str) -> String {
+        let mut result = String::new();
+        for ch in self.strip_affixes(mask_text).chars() {
+            if Some(ch) == self.separator {
+                continue;
+            }
+            if ch == self.decimal {
+                result.push('.');
+            } else {
+                result.push(ch);
+            }
+        }
+
+        if result.contains('.') {
+            result = result.trim_end_matches('0').to_string();
+        }
+        result
+    }
+
+    fn editable_range(&self, mask_text: &str) -> Range<usize> {
+        let mut range = 0..mask_text.len();
+        if let Some(prefix) = &self.prefix {
+            if mask_text.starts_with(prefix.as_ref()) {
+                range.start = prefix.len();
+            }
+        }
+        if let Some(suffix) = &self.suffix {
+            if mask_text.len() >= range.start + suffix.len() && mask_text.ends_with(suffix.as_ref())
+            {
+                range.end = mask_text.len() - suffix.len();
+            }
+        }
+        range
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::input::mask_pattern::{MaskPattern, MaskToken};
+    use crate::input::mask_pattern::{MaskPattern, MaskToken, NumberFormat, NumberGrouping};
 
     #[test]
     fn test_is_match() {
@@ -502,6 +909,146 @@ mod tests {
         assert_eq!(mask.is_valid(&masked_text), true);
     }
 
+    #[test]
+    fn test_mask_pattern_class_and_repeat() {
+        let mask = MaskPattern::extended("[0-2]9:[0-5]9");
+        assert_eq!(
+            mask.tokens(),
+            Some(&vec![
+                MaskToken::Class(vec![('0', '2')]),
+                MaskToken::Digit,
+                MaskToken::Sep(':'),
+                MaskToken::Class(vec![('0', '5')]),
+                MaskToken::Digit,
+            ])
+        );
+        assert_eq!(mask.placeholder(), Some("__:__".to_string()));
+        assert_eq!(mask.mask("2359"), "23:59");
+        assert_eq!(mask.mask("2369"), "23:");
+        assert_eq!(mask.is_valid("23:59"), true);
+        assert_eq!(mask.is_valid("33:59"), false);
+        assert_eq!(mask.unmask("23:59"), "2359");
+
+        let mask = MaskPattern::extended("[A-Fa-f0-9x]{4}");
+        assert_eq!(
+            mask.tokens(),
+            Some(&vec![
+                MaskToken::Class(vec![
+                    ('A', 'F'),
+                    ('a', 'f'),
+                    ('0', '9'),
+                    ('x', 'x')
+                ]);
+                4
+            ])
+        );
+        assert_eq!(mask.mask("0xfF"), "0xfF");
+        assert_eq!(mask.is_valid("0xfG"), false);
+
+        // Escape and the invalid syntax are separators
+        let mask = MaskPattern::extended("\\9\\A-9[-{x}");
+        assert_eq!(
+            mask.tokens(),
+            Some(&vec![
+                MaskToken::Sep('9'),
+                MaskToken::Sep('A'),
+                MaskToken::Sep('-'),
+                MaskToken::Digit,
+                MaskToken::Sep('['),
+                MaskToken::Sep('-'),
+                MaskToken::Sep('{'),
+                MaskToken::Sep('x'),
+                MaskToken::Sep('}'),
+            ])
+        );
+        assert_eq!(mask.mask("5"), "9A-5");
+        assert_eq!(mask.unmask("9A-5"), "5");
+    }
+
+    #[test]
+    fn test_mask_pattern_optional() {
+        let mask = MaskPattern::extended("(999)999-9999? x9999");
+        assert_eq!(mask.placeholder(), Some("(___)___-____".to_string()));
+        assert_eq!(mask.mask("1234567890"), "(123)456-7890");
+        assert_eq!(mask.mask("123456789012"), "(123)456-7890 x12");
+        assert_eq!(mask.unmask("(123)456-7890 x12"), "123456789012");
+        assert_eq!(mask.is_complete("(123)456-789"), false);
+        assert_eq!(mask.is_complete("(123)456-7890"), true);
+        assert_eq!(mask.is_complete("(123)456-7890 x12"), true);
+        assert_eq!(mask.is_complete("(123)456-7890 x12345"), false);
+
+        let mask = MaskPattern::new("99-99");
+        assert_eq!(mask.is_complete("12-3"), false);
+        assert_eq!(mask.is_complete("12-34"), true);
+        assert_eq!(MaskPattern::None.is_complete(""), true);
+        assert_eq!(MaskPattern::number(None).is_complete(""), false);
+        assert_eq!(MaskPattern::number(None).is_complete("12"), true);
+    }
+
+    #[test]
+    fn test_mask_pattern_case() {
+        let mask = MaskPattern::extended(">AA-999-<AA<>A");
+        assert_eq!(
+            mask.tokens(),
+            Some(&vec![
+                MaskToken::Upper(Box::new(MaskToken::Letter)),
+                MaskToken::Upper(Box::new(MaskToken::Letter)),
+                MaskToken::Sep('-'),
+                MaskToken::Upper(Box::new(MaskToken::Digit)),
+                MaskToken::Upper(Box::new(MaskToken::Digit)),
+                MaskToken::Upper(Box::new(MaskToken::Digit)),
+                MaskToken::Sep('-'),
+                MaskToken::Lower(Box::new(MaskToken::Letter)),
+                MaskToken::Lower(Box::new(MaskToken::Letter)),
+                MaskToken::Letter,
+            ])
+        );
+        assert_eq!(mask.mask("ab123CDe"), "AB-123-cde");
+        assert_eq!(mask.mask("AB-123-CDE"), "AB-123-cdE");
+        assert_eq!(mask.is_valid("ab-123"), true);
+        assert_eq!(mask.unmask("AB-123-cde"), "AB123cde");
+
+        // Lower case letters can match the upper case class
+        let mask = MaskPattern::extended(">[A-F]{2}");
+        assert_eq!(mask.is_valid("af"), true);
+        assert_eq!(mask.mask("af"), "AF");
+        assert_eq!(mask.is_valid("ag"), false);
+
+        let mask = MaskPattern::extended(">AA99 A{4} 9{4}");
+        assert_eq!(mask.placeholder(), Some("____ ____ ____".to_string()));
+        assert_eq!(mask.mask("gb82west1234"), "GB82 WEST 1234");
+    }
+
+    #[test]
+    fn test_mask_pattern_new_keeps_separators() {
+        // The extended syntax chars are separators in `MaskPattern::new`.
+        let mask = MaskPattern::new("[9]{2}? <A>\\");
+        assert_eq!(
+            mask.tokens(),
+            Some(&vec![
+                MaskToken::Sep('['),
+                MaskToken::Digit,
+                MaskToken::Sep(']'),
+                MaskToken::Sep('{'),
+                MaskToken::Sep('2'),
+                MaskToken::Sep('}'),
+                MaskToken::Sep('?'),
+                MaskToken::Sep(' '),
+                MaskToken::Sep('<'),
+                MaskToken::Letter,
+                MaskToken::Sep('>'),
+                MaskToken::Sep('\\'),
+            ])
+        );
+        assert_eq!(mask.placeholder(), Some("[_]{2}? <_>\\".to_string()));
+        assert_eq!(mask.mask("5a"), "[5]{2}? <a");
+        assert_eq!(mask.unmask("[5]{2}? <a>\\"), "5a");
+        assert_eq!(mask.is_complete("[5]{2}? <a"), false);
+
+        let mask: MaskPattern = "(999)999-9999? x9999".into();
+        assert_eq!(mask.placeholder(), Some("(___)___-____? x____".to_string()));
+    }
+
     #[test]
     fn test_number_with_group_separator() {
         // Use comma as group separator
@@ -559,4 +1106,74 @@ mod tests {
 
         assert_eq!(mask.mask("1234567.1234567"), "1,234,567");
     }
+
+    #[test]
+    fn test_negative_number() {
+        let mask = MaskPattern::number(Some(','));
+        assert_eq!(mask.is_valid("-"), true);
+        assert_eq!(mask.is_valid("-1,234"), true);
+        assert_eq!(mask.is_valid("-."), false);
+        assert_eq!(mask.is_valid("1-"), false);
+        assert_eq!(mask.mask("-123"), "-123");
+        assert_eq!(mask.mask("-1234567.5"), "-1,234,567.5");
+        assert_eq!(mask.unmask("-1,234,567.5"), "-1234567.5");
+    }
+
+    #[test]
+    fn test_number_with_locale() {
+        let mask: MaskPattern = NumberFormat::locale("de").into();
+        assert_eq!(mask.is_valid("1.234,5"), true);
+        assert_eq!(mask.is_valid("1,2,3"), false);
+        assert_eq!(mask.mask("1234567,891"), "1.234.567,891");
+        assert_eq!(mask.unmask("1.234.567,891"), "1234567.891");
+        assert_eq!(mask.mask_value("-1234567.5"), "-1.234.567,5");
+
+        let mask: MaskPattern = NumberFormat::locale("fr-FR").into();
+        assert_eq!(mask.mask("1234567,5"), "1 234 567,5");
+        assert_eq!(mask.unmask("1 234 567,5"), "1234567.5");
+
+        let mask: MaskPattern = NumberFormat::locale("de_CH").into();
+        assert_eq!(mask.mask("1234567.5"), "1'234'567.5");
+
+        let mask: MaskPattern = NumberFormat::locale("en-IN").into();
+        assert_eq!(mask.mask("123"), "123");
+        assert_eq!(mask.mask("1234"), "1,234");
+        assert_eq!(mask.mask("123456"), "1,23,456");
+        assert_eq!(mask.mask("12345678.9"), "1,23,45,678.9");
+        assert_eq!(mask.unmask("1,23,45,678.9"), "12345678.9");
+
+        let format = NumberFormat::locale("zh-CN");
+        assert_eq!(format.separator, Some(','));
+        assert_eq!(format.decimal, '.');
+        assert_eq!(format.grouping, NumberGrouping::Thousands);
+        assert_eq!(NumberFormat::locale(""), format);
+    }
+
+    #[test]
+    fn test_number_with_prefix_and_suffix() {
+        let mask: MaskPattern = NumberFormat::locale("en").prefix("$").fraction(2).into();
+        assert_eq!(mask.mask("1234.5"), "$1,234.5");
+        assert_eq!(mask.mask("$1,2345"), "$12,345");
+        assert_eq!(mask.mask("-12"), "-$12");
+        assert_eq!(mask.mask("$-12"), "-$12");
+        assert_eq!(mask.unmask("-$1,234"), "-1234");
+        assert_eq!(mask.mask("$"), "");
+        assert_eq!(mask.unmask("$1,234.50"), "1234.5");
+        assert_eq!(mask.is_valid("$1,234.5"), true);
+        assert_eq!(mask.is_valid("$1a"), false);
+        assert_eq!(mask.editable_range("$1,234"), 1..6);
+
+        let mask: MaskPattern = NumberFormat::locale("de").suffix(" kg").into();
+        assert_eq!(mask.mask("5"), "5 kg");
+        assert_eq!(mask.mask("5 kg6"), "56 kg");
+        assert_eq!(mask.mask("1234 kg"), "1.234 kg");
+        assert_eq!(mask.unmask("1.234,5 kg"), "1234.5");
+        assert_eq!(mask.is_valid("1.234,5 kg"), true);
+        assert_eq!(mask.editable_range("56 kg"), 0..2);
+        assert_eq!(mask.editable_range(""), 0..0);
+
+        let mask: MaskPattern = NumberFormat::default().suffix("%").into();
+        assert_eq!(mask.mask("12.5"), "12.5%");
+        assert_eq!(mask.unmask("12.5%"), "12.5");
+    }
 }
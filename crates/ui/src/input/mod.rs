@@ -1,20 +1,41 @@
 mod blink_cursor;
+mod brackets;
 mod change;
 mod clear_button;
 mod code_highlighter;
+mod completion;
+mod diagnostics;
 mod element;
+mod folding;
+mod line_edit;
 mod mask_pattern;
 mod mode;
+mod multi_cursor;
 mod number_input;
 mod otp_input;
+mod rope;
+mod search;
 mod state;
+mod tag_input;
 mod text_input;
 mod text_wrapper;
 
+pub use brackets::BracketPair;
 pub(crate) use clear_button::*;
-pub use mask_pattern::MaskPattern;
+pub use completion::{CompletionItem, CompletionProvider};
+pub use diagnostics::{Diagnostic, DiagnosticSeverity};
+pub use folding::{Fold, FoldAll, Unfold, UnfoldAll};
+pub use line_edit::{
+    DeleteLine, DuplicateLine, GoToLine, JoinLines, MoveLineDown, MoveLineUp, SelectLine,
+    ToggleLineComment,
+};
+pub use mask_pattern::{MaskPattern, NumberFormat, NumberGrouping};
 pub use mode::TabSize;
+pub use multi_cursor::SelectNextOccurrence;
 pub use number_input::{NumberInput, NumberInputEvent, StepAction};
 pub use otp_input::*;
+pub use rope::Rope;
+pub use search::{Find, FindNext, FindPrevious, Replace, ReplaceAll};
 pub use state::*;
+pub use tag_input::*;
 pub use text_input::*;
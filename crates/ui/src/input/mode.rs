@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use gpui::{DefiniteLength, SharedString};
 
@@ -89,8 +89,7 @@ impl InputMode {
     }
 
     pub(super) fn update_auto_grow(&mut self, text_wrapper: &TextWrapper) {
-        let wrapped_lines = text_wrapper.wrapped_lines.len();
-        self.set_rows(wrapped_lines);
+        self.set_rows(text_wrapper.len());
     }
 
     /// At least 1 row be return.
@@ -132,6 +131,11 @@ impl InputMode {
         }
     }
 
+    #[inline]
+    pub(super) fn is_code_editor(&self) -> bool {
+        matches!(self, InputMode::CodeEditor { .. })
+    }
+
     /// Return false if the mode is not [`InputMode::CodeEditor`].
     #[allow(unused)]
     #[inline]
@@ -151,7 +155,7 @@ impl InputMode {
         }
     }
 
-    pub(super) fn highlighter(&self) -> Option<Rc<crate::highlighter::Highlighter<'static>>> {
+    pub(super) fn highlighter(&self) -> Option<Arc<crate::highlighter::Highlighter<'static>>> {
         match &self {
             InputMode::CodeEditor { highlighter, .. } => Some(highlighter.highlighter.clone()),
             _ => None,
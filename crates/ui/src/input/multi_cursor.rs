@@ -0,0 +1,369 @@
+use std::ops::Range;
+
+use gpui::{actions, point, App, Context, KeyBinding, Pixels, Point, Window};
+
+use super::{rope::Rope, state::CONTEXT, InputState};
+
+actions!(input, [SelectNextOccurrence]);
+
+pub(super) fn init(cx: &mut App) {
+    cx.bind_keys([
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-d", SelectNextOccurrence, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-d", SelectNextOccurrence, Some(CONTEXT)),
+    ]);
+}
+
+/// The state of the column (box) selection by alt-drag.
+pub(super) struct ColumnSelection {
+    /// The mouse position of the drag start.
+    origin: Point<Pixels>,
+    /// The selections before the drag start, they will be kept.
+    base: Vec<Range<usize>>,
+}
+
+/// Adjust the `range` after the `edit` range of the text has been replaced with a text of `new_len`.
+pub(super) fn adjust_range_for_edit(range: &mut Range<usize>, edit: &Range<usize>, new_len: usize) {
+    let adjust = |offset: usize| {
+        if offset >= edit.end {
+            offset + new_len - edit.len()
+        } else if offset > edit.start {
+            // Inside the replaced text, move to the start of it.
+            edit.start
+        } else {
+            offset
+        }
+    };
+
+    range.start = adjust(range.start);
+    range.end = adjust(range.end);
+}
+
+/// Sort and merge the overlapping selections.
+///
+/// Returns the merged selections and the index of the selection that contains the `primary` selection.
+pub(super) fn merge_selections(
+    mut selections: Vec<Range<usize>>,
+    primary: &Range<usize>,
+) -> (Vec<Range<usize>>, usize) {
+    selections.sort_by_key(|range| (range.start, range.end));
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(selections.len());
+    for range in selections {
+        match merged.last_mut() {
+            Some(last) if range.start < last.end || range.start == last.start => {
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+
+    let primary_ix = merged
+        .iter()
+        .position(|range| range.start <= primary.start && primary.end <= range.end)
+        .unwrap_or(merged.len().saturating_sub(1));
+
+    (merged, primary_ix)
+}
+
+/// Find the next occurrence of the `query` in the `text` from the `offset`, wrap around to the start,
+/// the occurrences that overlap with the `selections` are skipped.
+pub(super) fn next_occurrence(
+    text: &Rope,
+    query: &str,
+    offset: usize,
+    selections: &[Range<usize>],
+) -> Option<Range<usize>> {
+    text.matches_at(query, offset)
+        .chain(text.matches_at(query, 0).take_while(|ix| *ix < offset))
+        .map(|ix| ix..ix + query.len())
+        .find(|range| {
+            !selections
+                .iter()
+                .any(|s| s.start < range.end && range.start < s.end)
+        })
+}
+
+/// Split the pasted text into a line for each selection, when there are multiple selections
+/// and the lines count is the same as the selections count.
+///
+/// Returns None to paste the whole text as usual.
+pub(super) fn split_paste_lines(text: &str, selections_count: usize) -> Option<Vec<&str>> {
+    if selections_count < 2 {
+        return None;
+    }
+
+    let lines = text.split('\n').collect::<Vec<_>>();
+    (lines.len() == selections_count).then_some(lines)
+}
+
+impl InputState {
+    /// Return all selections sorted by the position, include the primary `selected_range`.
+    pub(super) fn all_selections(&self) -> Vec<Range<usize>> {
+        let mut selections = self.extra_selections.clone();
+        selections.push(self.selected_range.clone());
+        selections.sort_by_key(|range| (range.start, range.end));
+        selections
+    }
+
+    /// Clear the extra cursors, only keep the primary cursor.
+    pub(super) fn clear_extra_selections(&mut self, cx: &mut Context<Self>) {
+        if self.extra_selections.is_empty() {
+            return;
+        }
+
+        self.extra_selections.clear();
+        cx.notify();
+    }
+
+    /// Set all the selections, the last one will be the primary selection.
+    pub(super) fn set_selections(&mut self, mut selections: Vec<Range<usize>>) {
+        let len = self.text.len();
+        for range in selections.iter_mut() {
+            range.start = range.start.min(len);
+            range.end = range.end.min(len);
+        }
+
+        let Some(primary) = selections.last().cloned() else {
+            return;
+        };
+
+        let (mut merged, primary_ix) = merge_selections(selections, &primary);
+        self.selected_range = merged.remove(primary_ix);
+        self.selection_reversed = false;
+        self.extra_selections = merged;
+    }
+
+    /// Run the `f` for each selection (from the first to the last), the `f` is called with
+    /// the index of the selection, and the `selected_range` is set to that selection.
+    ///
+    /// All changes are grouped in the history, so they can be undone together.
+    ///
+    /// Returns false if there is only one selection, then the `f` is not called.
+    pub(super) fn for_each_selection(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        mut f: impl FnMut(&mut Self, usize, &mut Window, &mut Context<Self>),
+    ) -> bool {
+        if self.extra_selections.is_empty() || self.editing_selections {
+            return false;
+        }
+
+        let primary = self.selected_range.clone();
+        let mut snapshot = self.extra_selections.clone();
+        snapshot.push(primary.clone());
+
+        let selections = self.all_selections();
+        let primary_ix = selections
+            .iter()
+            .position(|range| *range == primary)
+            .unwrap_or(selections.len() - 1);
+
+        // Keep all selections in `extra_selections` when editing, so they can be adjusted by each edit.
+        self.extra_selections = selections;
+        self.editing_selections = true;
+        self.pending_selections = Some(snapshot);
+        self.history.start_grouping();
+
+        for ix in 0..self.extra_selections.len() {
+            self.selected_range = self.extra_selections[ix].clone();
+            self.selection_reversed = false;
+            f(self, ix, window, cx);
+            if let Some(range) = self.extra_selections.get_mut(ix) {
+                *range = self.selected_range.clone();
+            }
+        }
+
+        self.history.end_grouping();
+        self.pending_selections = None;
+        self.editing_selections = false;
+
+        let mut selections = std::mem::take(&mut self.extra_selections);
+        if primary_ix < selections.len() {
+            // Move the primary selection to the last.
+            let primary = selections.remove(primary_ix);
+            selections.push(primary);
+        }
+        self.set_selections(selections);
+        cx.notify();
+        true
+    }
+
+    /// Add a cursor at the offset, the current selection will be kept as an extra selection.
+    pub(super) fn add_cursor(&mut self, offset: usize, cx: &mut Context<Self>) {
+        let mut selections = self.extra_selections.clone();
+        selections.push(self.selected_range.clone());
+        selections.push(offset..offset);
+        self.set_selections(selections);
+        cx.notify();
+    }
+
+    pub(super) fn select_next_occurrence(
+        &mut self,
+        _: &SelectNextOccurrence,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.selected_range.is_empty() {
+            self.select_word(self.cursor_offset(), window, cx);
+            self.selected_word_range = None;
+            return;
+        }
+
+        if !self.is_multi_line() {
+            return;
+        }
+
+        let query = self.text.slice(self.selected_range.clone());
+        let selections = self.all_selections();
+        let Some(range) = next_occurrence(&self.text, &query, self.selected_range.end, &selections)
+        else {
+            return;
+        };
+
+        let mut selections = selections;
+        selections.push(range);
+        self.set_selections(selections);
+        cx.notify();
+    }
+
+    /// Start the column selection at the mouse position, called on alt + mouse down.
+    pub(super) fn start_column_selection(&mut self, position: Point<Pixels>) {
+        self.column_selection = Some(ColumnSelection {
+            origin: position,
+            base: self.all_selections(),
+        });
+    }
+
+    /// Update the column selection to the mouse position when dragging.
+    ///
+    /// Returns false if there is no column selection.
+    pub(super) fn update_column_selection(
+        &mut self,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(column_selection) = self.column_selection.as_ref() else {
+            return false;
+        };
+
+        let line_height = self.last_line_height;
+        let origin = column_selection.origin;
+        let mut selections = column_selection.base.clone();
+
+        let (top, bottom) = if origin.y < position.y {
+            (origin.y, position.y)
+        } else {
+            (position.y, origin.y)
+        };
+        let rows = ((bottom - top) / line_height).round() as usize;
+        let mut rows_selections = (0..=rows)
+            .map(|row| {
+                let y = top + line_height * row as f32;
+                let start = self.index_for_mouse_position(point(origin.x, y), window, cx);
+                let end = self.index_for_mouse_position(point(position.x, y), window, cx);
+                start.min(end)..start.max(end)
+            })
+            .collect::<Vec<_>>();
+
+        // Let the row of the mouse position to be the primary selection.
+        if origin.y > position.y {
+            rows_selections.reverse();
+        }
+        selections.extend(rows_selections);
+
+        self.set_selections(selections);
+        cx.notify();
+        true
+    }
+
+    /// Return the text of all selections joined by `\n`.
+    pub(super) fn selections_text(&self) -> String {
+        self.all_selections()
+            .into_iter()
+            .map(|range| self.text.slice(range))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjust_range_for_edit() {
+        // Insert before the range.
+        let mut range = 10..15;
+        adjust_range_for_edit(&mut range, &(5..5), 3);
+        assert_eq!(range, 13..18);
+
+        // Insert at the range start.
+        let mut range = 10..15;
+        adjust_range_for_edit(&mut range, &(10..10), 2);
+        assert_eq!(range, 12..17);
+
+        // Delete before the range.
+        let mut range = 10..15;
+        adjust_range_for_edit(&mut range, &(2..6), 0);
+        assert_eq!(range, 6..11);
+
+        // Edit after the range.
+        let mut range = 10..15;
+        adjust_range_for_edit(&mut range, &(20..22), 5);
+        assert_eq!(range, 10..15);
+
+        // Delete the text covering the range start.
+        let mut range = 10..15;
+        adjust_range_for_edit(&mut range, &(8..12), 1);
+        assert_eq!(range, 8..12);
+    }
+
+    #[test]
+    fn test_merge_selections() {
+        let (merged, ix) = merge_selections(vec![10..12, 0..2, 11..15, 20..20, 20..20], &(11..15));
+        assert_eq!(merged, vec![0..2, 10..15, 20..20]);
+        assert_eq!(ix, 1);
+
+        // Touched ranges are not merged.
+        let (merged, ix) = merge_selections(vec![0..5, 5..8], &(0..5));
+        assert_eq!(merged, vec![0..5, 5..8]);
+        assert_eq!(ix, 0);
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        let text = &Rope::from("foo bar foo baz foo");
+        assert_eq!(next_occurrence(text, "foo", 3, &[0..3]), Some(8..11));
+        assert_eq!(
+            next_occurrence(text, "foo", 11, &[0..3, 8..11]),
+            Some(16..19)
+        );
+        // Wrap around to the start.
+        assert_eq!(
+            next_occurrence(text, "foo", 19, &[8..11, 16..19]),
+            Some(0..3)
+        );
+        // All occurrences are selected.
+        assert_eq!(
+            next_occurrence(text, "foo", 19, &[0..3, 8..11, 16..19]),
+            None
+        );
+        assert_eq!(next_occurrence(text, "", 0, &[]), None);
+        assert_eq!(next_occurrence(text, "qux", 0, &[]), None);
+    }
+
+    #[test]
+    fn test_split_paste_lines() {
+        // A single line with one cursor is pasted as usual.
+        assert_eq!(split_paste_lines("hello", 1), None);
+        assert_eq!(split_paste_lines("foo\nbar", 1), None);
+
+        assert_eq!(split_paste_lines("foo\nbar", 2), Some(vec!["foo", "bar"]));
+        assert_eq!(split_paste_lines("foo\nbar", 3), None);
+        assert_eq!(split_paste_lines("hello", 2), None);
+    }
+}
@@ -1,7 +1,10 @@
+use std::time::Duration;
+
 use gpui::{
     actions, prelude::FluentBuilder as _, px, App, Context, ElementId, Entity, EventEmitter,
-    FocusHandle, Focusable, InteractiveElement, IntoElement, KeyBinding, ParentElement, RenderOnce,
-    SharedString, Styled, Window,
+    FocusHandle, Focusable, InteractiveElement, IntoElement, KeyBinding, MouseButton,
+    MouseDownEvent, ParentElement, RenderOnce, ScrollWheelEvent, SharedString, Styled, Task, Timer,
+    Window,
 };
 
 use crate::{
@@ -11,17 +14,101 @@ use crate::{
 
 use super::{InputState, TextInput};
 
-actions!(number_input, [Increment, Decrement]);
+actions!(
+    number_input,
+    [Increment, Decrement, LargeIncrement, LargeDecrement]
+);
 
 const KEY_CONTENT: &str = "NumberInput";
+/// The multiplier of the step when holding `shift`.
+const LARGE_STEP: f64 = 10.;
+/// The delay before repeating the step when holding the +/- button.
+const REPEAT_DELAY: Duration = Duration::from_millis(400);
+const REPEAT_INTERVAL: Duration = Duration::from_millis(60);
 
 pub fn init(cx: &mut App) {
     cx.bind_keys(vec![
         KeyBinding::new("up", Increment, Some(KEY_CONTENT)),
         KeyBinding::new("down", Decrement, Some(KEY_CONTENT)),
+        KeyBinding::new("shift-up", LargeIncrement, Some(KEY_CONTENT)),
+        KeyBinding::new("shift-down", LargeDecrement, Some(KEY_CONTENT)),
     ]);
 }
 
+/// The numeric options of a [`NumberInput`].
+///
+/// When set on the [`InputState`], the step actions will update the value by itself,
+/// otherwise only the [`NumberInputEvent::Step`] event is emitted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct NumberOptions {
+    pub(super) min: Option<f64>,
+    pub(super) max: Option<f64>,
+    pub(super) step: f64,
+    /// The number of decimal places, `None` to follow the step and the current value.
+    pub(super) precision: Option<usize>,
+}
+
+impl Default for NumberOptions {
+    fn default() -> Self {
+        Self {
+            min: None,
+            max: None,
+            step: 1.,
+            precision: None,
+        }
+    }
+}
+
+impl NumberOptions {
+    pub(super) fn clamp(&self, value: f64) -> f64 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+
+    /// Return the value after stepping, an empty value starts from the `min` or `0`.
+    pub(super) fn step_value(&self, value: Option<f64>, action: StepAction, large: bool) -> f64 {
+        let Some(value) = value else {
+            return self.clamp(self.min.unwrap_or(0.));
+        };
+
+        let step = if large {
+            self.step * LARGE_STEP
+        } else {
+            self.step
+        };
+        let value = match action {
+            StepAction::Increment => value + step,
+            StepAction::Decrement => value - step,
+        };
+        self.clamp(value)
+    }
+
+    /// Format the value with the precision, `current` is the text before the change.
+    pub(super) fn format(&self, value: f64, current: &str) -> String {
+        let precision = self
+            .precision
+            .unwrap_or_else(|| decimal_places(&self.step.to_string()).max(decimal_places(current)));
+        let text = format!("{:.*}", precision, value);
+        // Avoid the `-0` after rounding.
+        match text.parse::<f64>() {
+            Ok(v) if v == 0. => format!("{:.*}", precision, 0.),
+            _ => text,
+        }
+    }
+}
+
+/// Return the number of digits after the decimal point.
+fn decimal_places(text: &str) -> usize {
+    text.split_once('.')
+        .map(|(_, frac)| frac.chars().take_while(|c| c.is_ascii_digit()).count())
+        .unwrap_or(0)
+}
+
+/// Parse the unmasked text to a number.
+pub(super) fn parse_number(text: &str) -> Option<f64> {
+    text.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
 #[derive(IntoElement)]
 pub struct NumberInput {
     id: ElementId,
@@ -51,34 +138,218 @@ impl NumberInput {
         self
     }
 
+    /// Step the value of the [`InputState`] up once, same as clicking the `+` button.
     pub fn increment(state: &Entity<InputState>, window: &mut Window, cx: &mut App) {
         state.update(cx, |state, cx| {
             state.on_action_increment(&Increment, window, cx);
         })
     }
 
+    /// Step the value of the [`InputState`] down once, same as clicking the `-` button.
     pub fn decrement(state: &Entity<InputState>, window: &mut Window, cx: &mut App) {
         state.update(cx, |state, cx| {
             state.on_action_decrement(&Decrement, window, cx);
         })
     }
+
+    /// The +/- button, step once when clicking it, and step repeatedly when holding it.
+    fn step_button(
+        id: &'static str,
+        icon: IconName,
+        action: StepAction,
+        size: Size,
+        state: &Entity<InputState>,
+    ) -> impl IntoElement {
+        Button::new(id)
+            .ghost()
+            .with_size(size)
+            .icon(icon)
+            .on_click({
+                let state = state.clone();
+                move |_, window, cx| {
+                    // The value has been stepped by holding the button.
+                    if state.read(cx).number_step_repeated {
+                        return;
+                    }
+
+                    match action {
+                        StepAction::Increment => Self::increment(&state, window, cx),
+                        StepAction::Decrement => Self::decrement(&state, window, cx),
+                    }
+                }
+            })
+            .on_mouse_down(MouseButton::Left, {
+                let state = state.clone();
+                move |event: &MouseDownEvent, window, cx| {
+                    state.update(cx, |state, cx| {
+                        state.start_number_step_repeat(action, event.modifiers.shift, window, cx);
+                    })
+                }
+            })
+            .on_mouse_up(MouseButton::Left, {
+                let state = state.clone();
+                move |_, _, cx| state.update(cx, |state, _| state.stop_number_step_repeat())
+            })
+            .on_mouse_up_out(MouseButton::Left, {
+                let state = state.clone();
+                move |_, _, cx| state.update(cx, |state, _| state.stop_number_step_repeat())
+            })
+    }
 }
 
 impl InputState {
+    /// Set the minimum value of the [`NumberInput`].
+    pub fn min_value(mut self, min: f64) -> Self {
+        self.number_options.get_or_insert_default().min = Some(min);
+        self
+    }
+
+    /// Set the maximum value of the [`NumberInput`].
+    pub fn max_value(mut self, max: f64) -> Self {
+        self.number_options.get_or_insert_default().max = Some(max);
+        self
+    }
+
+    /// Set the step of the [`NumberInput`], default is `1`.
+    ///
+    /// Holding `shift` will step 10 times of it.
+    pub fn step(mut self, step: f64) -> Self {
+        self.number_options.get_or_insert_default().step = step.abs();
+        self
+    }
+
+    /// Set the number of decimal places of the [`NumberInput`] value.
+    ///
+    /// Default is follow the decimal places of the step and the current value.
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.number_options.get_or_insert_default().precision = Some(precision);
+        self
+    }
+
+    /// Return the numeric value, `None` if the input is empty or not a number.
+    pub fn number_value(&self) -> Option<f64> {
+        parse_number(&self.unmask_value())
+    }
+
+    /// Set the numeric value, it will be clamped and formatted by the number options.
+    pub fn set_number_value(&mut self, value: f64, window: &mut Window, cx: &mut Context<Self>) {
+        let options = self.number_options.unwrap_or_default();
+        let text = options.format(options.clamp(value), &self.unmask_value());
+        let text = self.mask_pattern.mask_value(&text);
+        if &text != self.value() {
+            self.set_value(text, window, cx);
+        }
+    }
+
+    /// Clamp and format the value when the input is blurred.
+    pub(super) fn clamp_number_value(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.number_options.is_none() {
+            return;
+        }
+
+        if let Some(value) = self.number_value() {
+            self.set_number_value(value, window, cx);
+        }
+    }
+
     fn on_action_increment(&mut self, _: &Increment, window: &mut Window, cx: &mut Context<Self>) {
-        self.on_number_input_step(StepAction::Increment, window, cx);
+        self.on_number_input_step(StepAction::Increment, false, window, cx);
     }
 
     fn on_action_decrement(&mut self, _: &Decrement, window: &mut Window, cx: &mut Context<Self>) {
-        self.on_number_input_step(StepAction::Decrement, window, cx);
+        self.on_number_input_step(StepAction::Decrement, false, window, cx);
+    }
+
+    fn on_action_large_increment(
+        &mut self,
+        _: &LargeIncrement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.on_number_input_step(StepAction::Increment, true, window, cx);
     }
 
-    fn on_number_input_step(&mut self, action: StepAction, _: &mut Window, cx: &mut Context<Self>) {
+    fn on_action_large_decrement(
+        &mut self,
+        _: &LargeDecrement,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.on_number_input_step(StepAction::Decrement, true, window, cx);
+    }
+
+    fn on_number_input_step(
+        &mut self,
+        action: StepAction,
+        large: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
         if self.disabled {
             return;
         }
 
         cx.emit(NumberInputEvent::Step(action));
+
+        if let Some(options) = self.number_options {
+            let value = options.step_value(self.number_value(), action, large);
+            self.set_number_value(value, window, cx);
+        }
+    }
+
+    /// Keep stepping after the [`REPEAT_DELAY`] until the mouse is released,
+    /// the first step is done by the click of the button.
+    fn start_number_step_repeat(
+        &mut self,
+        action: StepAction,
+        large: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.number_step_repeated = false;
+        self._number_step_task = cx.spawn_in(window, async move |this, cx| {
+            Timer::after(REPEAT_DELAY).await;
+            loop {
+                if this
+                    .update_in(cx, |state, window, cx| {
+                        state.number_step_repeated = true;
+                        state.on_number_input_step(action, large, window, cx);
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+                Timer::after(REPEAT_INTERVAL).await;
+            }
+        });
+    }
+
+    fn stop_number_step_repeat(&mut self) {
+        self._number_step_task = Task::ready(());
+    }
+
+    fn on_number_input_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.focus_handle.is_focused(window) {
+            return;
+        }
+
+        let line_height = self.last_line_height.max(px(1.));
+        self.number_wheel_delta += event.delta.pixel_delta(line_height).y;
+        while self.number_wheel_delta.abs() >= line_height {
+            let action = if self.number_wheel_delta > px(0.) {
+                self.number_wheel_delta -= line_height;
+                StepAction::Increment
+            } else {
+                self.number_wheel_delta += line_height;
+                StepAction::Decrement
+            };
+            self.on_number_input_step(action, event.modifiers.shift, window, cx);
+        }
     }
 }
 
@@ -118,6 +389,11 @@ impl RenderOnce for NumberInput {
             .key_context(KEY_CONTENT)
             .on_action(window.listener_for(&self.state, InputState::on_action_increment))
             .on_action(window.listener_for(&self.state, InputState::on_action_decrement))
+            .on_action(window.listener_for(&self.state, InputState::on_action_large_increment))
+            .on_action(window.listener_for(&self.state, InputState::on_action_large_decrement))
+            .on_scroll_wheel(
+                window.listener_for(&self.state, InputState::on_number_input_scroll_wheel),
+            )
             .flex_1()
             .input_size(self.size)
             .px(match self.size {
@@ -130,30 +406,102 @@ impl RenderOnce for NumberInput {
             .border_1()
             .rounded(cx.theme().radius)
             .when(focused, |this| this.focused_border(cx))
-            .child(
-                Button::new("minus")
-                    .ghost()
-                    .with_size(btn_size)
-                    .icon(IconName::Minus)
-                    .on_click({
-                        let state = self.state.clone();
-                        move |_, window, cx| {
-                            Self::decrement(&state, window, cx);
-                        }
-                    }),
-            )
+            .when(self.state.read(cx).invalid, |this| {
+                this.border_color(cx.theme().danger)
+            })
+            .child(Self::step_button(
+                "minus",
+                IconName::Minus,
+                StepAction::Decrement,
+                btn_size,
+                &self.state,
+            ))
             .child(TextInput::new(&self.state).appearance(false).no_gap())
-            .child(
-                Button::new("plus")
-                    .ghost()
-                    .with_size(btn_size)
-                    .icon(IconName::Plus)
-                    .on_click({
-                        let state = self.state.clone();
-                        move |_, window, cx| {
-                            Self::increment(&state, window, cx);
-                        }
-                    }),
-            )
+            .child(Self::step_button(
+                "plus",
+                IconName::Plus,
+                StepAction::Increment,
+                btn_size,
+                &self.state,
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_number, NumberOptions, StepAction};
+
+    #[test]
+    fn test_step_value() {
+        let options = NumberOptions {
+            min: Some(-5.),
+            max: Some(20.),
+            step: 0.5,
+            precision: None,
+        };
+
+        assert_eq!(
+            options.step_value(Some(1.), StepAction::Increment, false),
+            1.5
+        );
+        assert_eq!(
+            options.step_value(Some(1.), StepAction::Decrement, false),
+            0.5
+        );
+        assert_eq!(
+            options.step_value(Some(1.), StepAction::Increment, true),
+            6.
+        );
+        assert_eq!(
+            options.step_value(Some(19.), StepAction::Increment, true),
+            20.
+        );
+        assert_eq!(
+            options.step_value(Some(-4.), StepAction::Decrement, true),
+            -5.
+        );
+        assert_eq!(options.step_value(None, StepAction::Increment, false), -5.);
+        assert_eq!(options.clamp(100.), 20.);
+
+        let options = NumberOptions::default();
+        assert_eq!(options.step_value(None, StepAction::Decrement, false), 0.);
+        assert_eq!(
+            options.step_value(Some(-1.), StepAction::Decrement, false),
+            -2.
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let options = NumberOptions {
+            step: 0.1,
+            ..Default::default()
+        };
+        assert_eq!(options.format(0.1 + 0.2, "0.2"), "0.3");
+        assert_eq!(options.format(1.25, "1.15"), "1.25");
+        assert_eq!(options.format(-0.01, "0"), "0.0");
+
+        let options = NumberOptions::default();
+        assert_eq!(options.format(3., "2"), "3");
+        assert_eq!(options.format(3.5, "2.5"), "3.5");
+
+        let options = NumberOptions {
+            precision: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(options.format(3., "2"), "3.00");
+        assert_eq!(options.format(1. / 3., ""), "0.33");
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("12"), Some(12.));
+        assert_eq!(parse_number(" -1.5 "), Some(-1.5));
+        assert_eq!(parse_number("1234."), Some(1234.));
+        assert_eq!(parse_number("1,234.5"), None);
+        assert_eq!(parse_number(""), None);
+        assert_eq!(parse_number("-"), None);
+        assert_eq!(parse_number("abc"), None);
+        assert_eq!(parse_number("inf"), None);
     }
 }
@@ -0,0 +1,470 @@
+use std::{fmt::Display, ops::Range};
+
+/// The position of an offset in the text, zero-based.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Point {
+    pub row: usize,
+    /// The UTF-8 offset in the line.
+    pub column: usize,
+}
+
+/// The lines that changed by an edit, used to update line based caches incrementally.
+///
+/// The `old_rows` lines start at `start_row` in the old text have been replaced by
+/// the `new_rows` lines in the new text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinesEdit {
+    pub start_row: usize,
+    pub old_rows: usize,
+    pub new_rows: usize,
+}
+
+/// A rope to store the text of the [`super::InputState`].
+///
+/// The text is stored in a balanced tree of chunks by [`ropey`], each node caches the bytes,
+/// chars, UTF-16 and line counts of its children, so that the lookups and the edits are
+/// O(log n) of the text size.
+///
+/// The lines are only split by `\n`, and all the offsets are in UTF-8 bytes.
+#[derive(Debug, Clone, Default)]
+pub struct Rope {
+    tree: ropey::Rope,
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        Self {
+            tree: ropey::Rope::from_str(text),
+        }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+impl Display for Rope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in self.tree.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        self.tree == **other
+    }
+}
+
+#[allow(unused)]
+impl Rope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the length of the text in UTF-8 bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree.len_bytes()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of lines, at least 1 line.
+    ///
+    /// The text ends with `\n` has an empty last line.
+    #[inline]
+    pub fn lines_len(&self) -> usize {
+        self.tree.len_lines()
+    }
+
+    /// Return the number of `\n` in the text.
+    #[inline]
+    fn newlines(&self) -> usize {
+        self.tree.len_lines() - 1
+    }
+
+    /// Return the range clamped to the text.
+    fn clamp(&self, range: Range<usize>) -> Range<usize> {
+        let len = self.len();
+        range.start.min(len)..range.end.min(len)
+    }
+
+    /// Replace the text in the range with the new text, returns the changed lines.
+    pub fn replace(&mut self, range: Range<usize>, new_text: &str) -> LinesEdit {
+        let range = self.clamp(range);
+        let start_row = self.tree.byte_to_line(range.start);
+        let removed_newlines = self.tree.byte_to_line(range.end) - start_row;
+        let inserted_newlines = new_text.bytes().filter(|b| *b == b'\n').count();
+
+        let start = self.tree.byte_to_char(range.start);
+        let end = self.tree.byte_to_char(range.end);
+        self.tree.remove(start..end);
+        self.tree.insert(start, new_text);
+
+        LinesEdit {
+            start_row,
+            old_rows: removed_newlines + 1,
+            new_rows: inserted_newlines + 1,
+        }
+    }
+
+    /// Return the text in the range.
+    pub fn slice(&self, range: Range<usize>) -> String {
+        self.tree.byte_slice(self.clamp(range)).to_string()
+    }
+
+    /// Return the char at the offset.
+    pub fn char_at(&self, offset: usize) -> Option<char> {
+        self.chars_at(offset).next()
+    }
+
+    /// Iterate the chars from the offset to the end.
+    pub fn chars_at(&self, offset: usize) -> impl Iterator<Item = char> + '_ {
+        let offset = offset.min(self.len());
+        self.tree.chars_at(self.tree.byte_to_char(offset))
+    }
+
+    /// Iterate the chars from the offset to the start in reverse order.
+    pub fn reversed_chars_at(&self, offset: usize) -> impl Iterator<Item = char> + '_ {
+        let offset = offset.min(self.len());
+        self.tree
+            .chars_at(self.tree.byte_to_char(offset))
+            .reversed()
+    }
+
+    /// Convert the UTF-8 offset to the row and column.
+    pub fn offset_to_point(&self, offset: usize) -> Point {
+        let offset = offset.min(self.len());
+        let row = self.tree.byte_to_line(offset);
+        Point {
+            row,
+            column: offset - self.tree.line_to_byte(row),
+        }
+    }
+
+    /// Convert the row and column to the UTF-8 offset, the column will be clamped to the line.
+    pub fn point_to_offset(&self, point: Point) -> usize {
+        let row = point.row.min(self.newlines());
+        let start = self.line_start_offset(row);
+        let end = self.line_end_offset(row);
+        (start + point.column).min(end)
+    }
+
+    /// Return the start offset of the line, if the row is out of range, return the text length.
+    pub fn line_start_offset(&self, row: usize) -> usize {
+        if row > self.newlines() {
+            return self.len();
+        }
+        self.tree.line_to_byte(row)
+    }
+
+    /// Return the end offset of the line (exclude the `\n`).
+    pub fn line_end_offset(&self, row: usize) -> usize {
+        if row >= self.newlines() {
+            self.len()
+        } else {
+            self.tree.line_to_byte(row + 1) - 1
+        }
+    }
+
+    /// Return the text of the line (exclude the `\n`).
+    pub fn line(&self, row: usize) -> String {
+        self.slice(self.line_start_offset(row)..self.line_end_offset(row))
+    }
+
+    /// Iterate the lines start from the row, the lines are split by `\n`.
+    pub fn lines_at(&self, row: usize) -> Lines<'_> {
+        Lines {
+            lines: self.tree.lines_at(row.min(self.lines_len())),
+        }
+    }
+
+    /// Iterate all lines, the lines are split by `\n`.
+    pub fn lines(&self) -> Lines<'_> {
+        self.lines_at(0)
+    }
+
+    /// Iterate the start offsets of the non-overlapping matches of the `query` from the offset.
+    ///
+    /// The chunks are searched in turn, so the text is not copied.
+    pub fn matches_at<'a>(&'a self, query: &'a str, offset: usize) -> Matches<'a> {
+        let offset = offset.min(self.len());
+        let (mut chunks, chunk_start, _, _) = self.tree.chunks_at_byte(offset);
+        let buf = match chunks.next() {
+            Some(chunk) if !query.is_empty() => chunk.to_string(),
+            _ => String::new(),
+        };
+        Matches {
+            chunks,
+            query,
+            buf,
+            buf_start: chunk_start,
+            search_from: offset - chunk_start,
+        }
+    }
+
+    /// Convert the UTF-8 offset to UTF-16 offset.
+    pub fn offset_to_utf16(&self, offset: usize) -> usize {
+        let offset = offset.min(self.len());
+        self.tree.char_to_utf16_cu(self.tree.byte_to_char(offset))
+    }
+
+    /// Convert the UTF-16 offset to UTF-8 offset.
+    pub fn offset_from_utf16(&self, offset: usize) -> usize {
+        let offset = offset.min(self.tree.len_utf16_cu());
+        self.tree.char_to_byte(self.tree.utf16_cu_to_char(offset))
+    }
+}
+
+/// An iterator over the lines of a [`Rope`].
+pub struct Lines<'a> {
+    lines: ropey::iter::Lines<'a>,
+}
+
+impl Iterator for Lines<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = self.lines.next()?.to_string();
+        if line.ends_with('\n') {
+            line.pop();
+        }
+        Some(line)
+    }
+}
+
+/// An iterator over the match offsets of a query in a [`Rope`], see [`Rope::matches_at`].
+pub struct Matches<'a> {
+    chunks: ropey::iter::Chunks<'a>,
+    query: &'a str,
+    /// The text to search, the end of the last chunk and the current chunk.
+    buf: String,
+    /// The offset of the `buf` in the rope.
+    buf_start: usize,
+    /// The offset in the `buf` to search from.
+    search_from: usize,
+}
+
+impl Iterator for Matches<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.query.is_empty() {
+            return None;
+        }
+
+        loop {
+            if let Some(ix) = self.buf[self.search_from..].find(self.query) {
+                let start = self.search_from + ix;
+                self.search_from = start + self.query.len();
+                return Some(self.buf_start + start);
+            }
+
+            let chunk = self.chunks.next()?;
+            // Keep the end of the buffer that may be the start of a match across the chunks.
+            let mut keep = self.buf.len().saturating_sub(self.query.len() - 1);
+            while !self.buf.is_char_boundary(keep) {
+                keep -= 1;
+            }
+            let keep = keep.max(self.search_from);
+            self.buf.drain(..keep);
+            self.buf.push_str(chunk);
+            self.buf_start += keep;
+            self.search_from = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rope_from_str() {
+        let rope = Rope::from("");
+        assert_eq!(rope.len(), 0);
+        assert_eq!(rope.lines_len(), 1);
+        assert_eq!(rope.to_string(), "");
+
+        let rope = Rope::from("Hello\n世界\n");
+        assert_eq!(rope.len(), 13);
+        assert_eq!(rope.lines_len(), 3);
+        assert_eq!(rope, "Hello\n世界\n");
+
+        let text = "Hello 世界💝\n".repeat(500);
+        let rope = Rope::from(text.as_str());
+        assert!(rope.tree.chunks().count() > 1);
+        assert_eq!(rope.len(), text.len());
+        assert_eq!(rope.lines_len(), 501);
+        assert_eq!(rope.to_string(), text);
+    }
+
+    #[test]
+    fn test_rope_replace() {
+        let mut rope = Rope::from("Hello\nWorld");
+        let edit = rope.replace(5..5, " GPUI");
+        assert_eq!(rope, "Hello GPUI\nWorld");
+        assert_eq!(
+            edit,
+            LinesEdit {
+                start_row: 0,
+                old_rows: 1,
+                new_rows: 1
+            }
+        );
+
+        let edit = rope.replace(10..11, "");
+        assert_eq!(rope, "Hello GPUIWorld");
+        assert_eq!(rope.lines_len(), 1);
+        assert_eq!(
+            edit,
+            LinesEdit {
+                start_row: 0,
+                old_rows: 2,
+                new_rows: 1
+            }
+        );
+
+        let edit = rope.replace(15..15, "\nfoo\nbar");
+        assert_eq!(rope, "Hello GPUIWorld\nfoo\nbar");
+        assert_eq!(rope.lines_len(), 3);
+        assert_eq!(
+            edit,
+            LinesEdit {
+                start_row: 0,
+                old_rows: 1,
+                new_rows: 3
+            }
+        );
+
+        let mut rope = Rope::new();
+        rope.replace(0..0, "abc");
+        assert_eq!(rope, "abc");
+
+        let text = "0123456789\n".repeat(300);
+        let mut rope = Rope::from(text.as_str());
+        let mut expected = text.clone();
+        for (range, new_text) in [
+            (1020..1030, "💝"),
+            (0..2000, ""),
+            (100..100, "Hello\n世界\n"),
+            (500..1300, "a"),
+        ] {
+            let edit = rope.replace(range.clone(), new_text);
+            assert_eq!(
+                edit.old_rows,
+                expected[range.clone()].matches('\n').count() + 1
+            );
+            expected.replace_range(range, new_text);
+            assert_eq!(rope.to_string(), expected);
+            assert_eq!(rope.len(), expected.len());
+            assert_eq!(rope.lines_len(), expected.matches('\n').count() + 1);
+        }
+    }
+
+    #[test]
+    fn test_rope_lines() {
+        let rope = Rope::from("Hello\n\n世界\n");
+        assert_eq!(rope.line(0), "Hello");
+        assert_eq!(rope.line(1), "");
+        assert_eq!(rope.line(2), "世界");
+        assert_eq!(rope.line(3), "");
+        assert_eq!(rope.line_start_offset(2), 7);
+        assert_eq!(rope.line_end_offset(2), 13);
+        assert_eq!(rope.line_start_offset(4), rope.len());
+        assert_eq!(
+            rope.lines().collect::<Vec<_>>(),
+            vec!["Hello", "", "世界", ""]
+        );
+        assert_eq!(rope.lines_at(2).collect::<Vec<_>>(), vec!["世界", ""]);
+
+        let text = "Hello 世界💝\n".repeat(300) + "end";
+        let rope = Rope::from(text.as_str());
+        assert_eq!(
+            rope.lines().collect::<Vec<_>>(),
+            text.split('\n').collect::<Vec<_>>()
+        );
+        assert_eq!(rope.line(300), "end");
+    }
+
+    #[test]
+    fn test_rope_offset_to_point() {
+        let rope = Rope::from("Hello\n世界\nGPUI");
+        assert_eq!(rope.offset_to_point(0), Point { row: 0, column: 0 });
+        assert_eq!(rope.offset_to_point(5), Point { row: 0, column: 5 });
+        assert_eq!(rope.offset_to_point(6), Point { row: 1, column: 0 });
+        assert_eq!(rope.offset_to_point(12), Point { row: 1, column: 6 });
+        assert_eq!(rope.offset_to_point(17), Point { row: 2, column: 4 });
+        assert_eq!(rope.offset_to_point(100), Point { row: 2, column: 4 });
+
+        assert_eq!(rope.point_to_offset(Point { row: 1, column: 3 }), 9);
+        assert_eq!(
+            rope.point_to_offset(Point {
+                row: 0,
+                column: 100
+            }),
+            5
+        );
+        assert_eq!(rope.point_to_offset(Point { row: 10, column: 0 }), 13);
+    }
+
+    #[test]
+    fn test_rope_utf16() {
+        let text = "Hello 世界💝\n".repeat(200);
+        let rope = Rope::from(text.as_str());
+        for offset in [0, 6, 9, 12, 16, 17, 1000, text.len()] {
+            if !text.is_char_boundary(offset) {
+                continue;
+            }
+            let utf16: usize = text[..offset].chars().map(|c| c.len_utf16()).sum();
+            assert_eq!(rope.offset_to_utf16(offset), utf16);
+            assert_eq!(rope.offset_from_utf16(utf16), offset);
+        }
+    }
+
+    #[test]
+    fn test_rope_chars() {
+        let rope = Rope::from("Hello 世界");
+        assert_eq!(rope.char_at(6), Some('世'));
+        assert_eq!(rope.chars_at(6).collect::<String>(), "世界");
+        assert_eq!(rope.reversed_chars_at(5).collect::<String>(), "olleH");
+        assert_eq!(rope.char_at(rope.len()), None);
+        assert_eq!(rope.slice(3..9), "lo 世");
+        assert_eq!(rope.slice(9..100), "界");
+    }
+
+    #[test]
+    fn test_rope_matches_at() {
+        let rope = Rope::from("foo bar foo baz foo");
+        assert_eq!(
+            rope.matches_at("foo", 0).collect::<Vec<_>>(),
+            vec![0, 8, 16]
+        );
+        assert_eq!(rope.matches_at("foo", 9).collect::<Vec<_>>(), vec![16]);
+        assert_eq!(rope.matches_at("", 0).count(), 0);
+        assert_eq!(rope.matches_at("qux", 0).count(), 0);
+
+        // The matches across the chunks.
+        let text = "Hello 世界💝\n".repeat(500);
+        let rope = Rope::from(text.as_str());
+        for query in ["💝\nHello", "界💝", "\n"] {
+            let expected = text.match_indices(query).map(|(ix, _)| ix);
+            assert_eq!(
+                rope.matches_at(query, 0).collect::<Vec<_>>(),
+                expected.clone().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                rope.matches_at(query, 2992).collect::<Vec<_>>(),
+                expected.filter(|ix| *ix >= 2992).collect::<Vec<_>>()
+            );
+        }
+    }
+}
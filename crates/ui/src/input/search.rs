@@ -0,0 +1,807 @@
+use std::{ops::Range, time::Duration};
+
+use gpui::{
+    actions, div, prelude::FluentBuilder as _, px, App, AppContext as _, Context, Entity,
+    InteractiveElement as _, IntoElement, KeyBinding, MouseButton, ParentElement as _, Render,
+    SharedString, Styled as _, Subscription, WeakEntity, Window,
+};
+use regex::{Regex, RegexBuilder};
+use rust_i18n::t;
+use smol::Timer;
+
+use super::{state::CONTEXT, InputEvent, InputState, TextInput};
+use crate::{
+    button::{Button, ButtonVariants as _},
+    h_flex, v_flex, ActiveTheme, IconName, Selectable as _, Sizable as _,
+};
+
+actions!(input, [Find, FindNext, FindPrevious, Replace, ReplaceAll]);
+
+/// The delay to search again after the text changed.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+pub(super) fn init(cx: &mut App) {
+    cx.bind_keys([
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-f", Find, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-f", Find, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-g", FindNext, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("f3", FindNext, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-g", FindPrevious, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("shift-f3", FindPrevious, Some(CONTEXT)),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-alt-enter", ReplaceAll, Some(CONTEXT)),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-alt-enter", ReplaceAll, Some(CONTEXT)),
+    ]);
+}
+
+/// The options to match the search query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct SearchOptions {
+    /// Match with case sensitive.
+    pub case_sensitive: bool,
+    /// Only match the whole word.
+    pub whole_word: bool,
+    /// Use the query as a regular expression.
+    pub regex: bool,
+}
+
+impl SearchOptions {
+    /// Build the regex to match the query, returns None if the query is empty or is an invalid regex.
+    pub(super) fn build_regex(&self, query: &str) -> Option<Regex> {
+        if query.is_empty() {
+            return None;
+        }
+
+        let mut pattern = if self.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .multi_line(true)
+            .build()
+            .ok()
+    }
+}
+
+/// Return the ranges of all matches in the text, the empty matches are skipped.
+pub(super) fn find_matches(text: &str, regex: &Regex) -> Vec<Range<usize>> {
+    regex
+        .find_iter(text)
+        .filter(|m| !m.is_empty())
+        .map(|m| m.range())
+        .collect()
+}
+
+/// Adjust the matches for the edit that replaced the `edit` range with a text of `new_len`,
+/// the matches overlapping the edit are removed until the next search.
+pub(super) fn adjust_matches_for_edit(
+    matches: &mut Vec<Range<usize>>,
+    edit: &Range<usize>,
+    new_len: usize,
+) {
+    matches.retain(|m| m.end <= edit.start || m.start >= edit.end);
+    for m in matches.iter_mut().filter(|m| m.start >= edit.end) {
+        m.start = m.start + new_len - edit.len();
+        m.end = m.end + new_len - edit.len();
+    }
+}
+
+/// Return the replacement of the match at the `range`, or None if the `range` is not a match.
+///
+/// When `expand` is true, the `$1`, `$name` in the replacement will be expanded with the captures.
+pub(super) fn replacement_at(
+    text: &str,
+    regex: &Regex,
+    range: &Range<usize>,
+    replacement: &str,
+    expand: bool,
+) -> Option<String> {
+    let caps = regex.captures_at(text, range.start)?;
+    let m = caps.get(0)?;
+    if m.is_empty() || m.range() != *range {
+        return None;
+    }
+
+    if !expand {
+        return Some(replacement.to_string());
+    }
+
+    let mut new_text = String::new();
+    caps.expand(replacement, &mut new_text);
+    Some(new_text)
+}
+
+/// Replace all matches in the text.
+///
+/// Returns the range from the first match start to the last match end, and the new text of that range,
+/// so it can be applied as a single edit.
+pub(super) fn replace_all(
+    text: &str,
+    regex: &Regex,
+    replacement: &str,
+    expand: bool,
+) -> Option<(Range<usize>, String)> {
+    let mut range: Option<Range<usize>> = None;
+    let mut new_text = String::new();
+
+    for caps in regex.captures_iter(text) {
+        let Some(m) = caps.get(0).filter(|m| !m.is_empty()) else {
+            continue;
+        };
+
+        match range.as_mut() {
+            Some(range) => {
+                new_text.push_str(&text[range.end..m.start()]);
+                range.end = m.end();
+            }
+            None => range = Some(m.range()),
+        }
+
+        if expand {
+            caps.expand(replacement, &mut new_text);
+        } else {
+            new_text.push_str(replacement);
+        }
+    }
+
+    range.map(|range| (range, new_text))
+}
+
+/// The find and replace panel of the [`InputState`], only for multi-line and code editor.
+pub(super) struct SearchPanel {
+    editor: WeakEntity<InputState>,
+    query_input: Entity<InputState>,
+    replace_input: Entity<InputState>,
+    options: SearchOptions,
+    pub(super) open: bool,
+    show_replace: bool,
+    /// The ranges of all matches in the editor text.
+    pub(super) matches: Vec<Range<usize>>,
+    /// The index of the current match in `matches`.
+    pub(super) current_match_ix: Option<usize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SearchPanel {
+    fn regex(&self, cx: &App) -> Option<Regex> {
+        self.options.build_regex(&self.query_input.read(cx).value())
+    }
+
+    /// Update the matches by the editor text, and set the current match to the first match after the `offset`.
+    fn update_matches(&mut self, text: &str, offset: usize, cx: &mut Context<Self>) {
+        self.matches = self
+            .regex(cx)
+            .map(|regex| find_matches(text, &regex))
+            .unwrap_or_default();
+        self.current_match_ix = if self.matches.is_empty() {
+            None
+        } else {
+            Some(
+                self.matches
+                    .iter()
+                    .position(|m| m.start >= offset)
+                    .unwrap_or(0),
+            )
+        };
+        cx.notify();
+    }
+
+    /// Move to the next (or previous) match from the `offset`, wrap around at the end.
+    fn select_match(
+        &mut self,
+        offset: usize,
+        forward: bool,
+        cx: &mut Context<Self>,
+    ) -> Option<Range<usize>> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        let ix = if forward {
+            self.matches
+                .iter()
+                .position(|m| m.start >= offset)
+                .unwrap_or(0)
+        } else {
+            self.matches
+                .iter()
+                .rposition(|m| m.end <= offset)
+                .unwrap_or(self.matches.len() - 1)
+        };
+
+        self.current_match_ix = Some(ix);
+        cx.notify();
+        self.matches.get(ix).cloned()
+    }
+
+    fn render_toggle(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        tooltip: SharedString,
+        checked: bool,
+        toggle: fn(&mut SearchOptions),
+    ) -> Button {
+        let editor = self.editor.clone();
+        Button::new(id)
+            .label(label)
+            .ghost()
+            .xsmall()
+            .selected(checked)
+            .tooltip(tooltip)
+            .on_click(move |_, window, cx| {
+                _ = editor.update(cx, |editor, cx| {
+                    editor.update_search_options(toggle, window, cx);
+                });
+            })
+    }
+}
+
+impl Render for SearchPanel {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let editor = self.editor.clone();
+        let status: SharedString = match (self.current_match_ix, self.matches.len()) {
+            (_, 0) => t!("Input.no_results").into(),
+            (Some(ix), total) => format!("{}/{}", ix + 1, total).into(),
+            (None, total) => format!("?/{}", total).into(),
+        };
+
+        v_flex()
+            .id("search-panel")
+            .occlude()
+            .cursor_default()
+            .w(px(420.))
+            .gap_1()
+            .p_1()
+            .border_1()
+            .border_color(cx.theme().border)
+            .bg(cx.theme().popover)
+            .rounded(cx.theme().radius)
+            .shadow_md()
+            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Button::new("toggle-replace")
+                            .icon(if self.show_replace {
+                                IconName::ChevronDown
+                            } else {
+                                IconName::ChevronRight
+                            })
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("Input.toggle_replace"))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.show_replace = !this.show_replace;
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        div().flex_1().child(
+                            TextInput::new(&self.query_input).xsmall().suffix(
+                                h_flex()
+                                    .child(self.render_toggle(
+                                        "case-sensitive",
+                                        "Aa",
+                                        t!("Input.match_case").into(),
+                                        self.options.case_sensitive,
+                                        |o| o.case_sensitive = !o.case_sensitive,
+                                    ))
+                                    .child(self.render_toggle(
+                                        "whole-word",
+                                        "ab",
+                                        t!("Input.match_whole_word").into(),
+                                        self.options.whole_word,
+                                        |o| o.whole_word = !o.whole_word,
+                                    ))
+                                    .child(self.render_toggle(
+                                        "regex",
+                                        ".*",
+                                        t!("Input.use_regex").into(),
+                                        self.options.regex,
+                                        |o| o.regex = !o.regex,
+                                    )),
+                            ),
+                        ),
+                    )
+                    .child(
+                        div()
+                            .min_w(px(56.))
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(status),
+                    )
+                    .child(
+                        Button::new("find-previous")
+                            .icon(IconName::ArrowUp)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("Input.previous_match"))
+                            .on_click({
+                                let editor = editor.clone();
+                                move |_, window, cx| {
+                                    _ = editor.update(cx, |editor, cx| {
+                                        editor.find_previous(&FindPrevious, window, cx);
+                                    });
+                                }
+                            }),
+                    )
+                    .child(
+                        Button::new("find-next")
+                            .icon(IconName::ArrowDown)
+                            .ghost()
+                            .xsmall()
+                            .tooltip(t!("Input.next_match"))
+                            .on_click({
+                                let editor = editor.clone();
+                                move |_, window, cx| {
+                                    _ = editor.update(cx, |editor, cx| {
+                                        editor.find_next(&FindNext, window, cx);
+                                    });
+                                }
+                            }),
+                    )
+                    .child(
+                        Button::new("close-search")
+                            .icon(IconName::Close)
+                            .ghost()
+                            .xsmall()
+                            .on_click({
+                                let editor = editor.clone();
+                                move |_, window, cx| {
+                                    _ = editor.update(cx, |editor, cx| {
+                                        editor.close_search_panel(window, cx);
+                                    });
+                                }
+                            }),
+                    ),
+            )
+            .when(self.show_replace, |this| {
+                this.child(
+                    h_flex()
+                        .gap_1()
+                        .pl(px(26.))
+                        .child(
+                            div()
+                                .flex_1()
+                                .child(TextInput::new(&self.replace_input).xsmall()),
+                        )
+                        .child(
+                            Button::new("replace")
+                                .label(t!("Input.replace"))
+                                .ghost()
+                                .xsmall()
+                                .on_click({
+                                    let editor = editor.clone();
+                                    move |_, window, cx| {
+                                        _ = editor.update(cx, |editor, cx| {
+                                            editor.replace_search_match(&Replace, window, cx);
+                                        });
+                                    }
+                                }),
+                        )
+                        .child(
+                            Button::new("replace-all")
+                                .label(t!("Input.replace_all"))
+                                .ghost()
+                                .xsmall()
+                                .on_click({
+                                    let editor = editor.clone();
+                                    move |_, window, cx| {
+                                        _ = editor.update(cx, |editor, cx| {
+                                            editor.replace_all_search_matches(
+                                                &ReplaceAll,
+                                                window,
+                                                cx,
+                                            );
+                                        });
+                                    }
+                                }),
+                        ),
+                )
+            })
+    }
+}
+
+impl InputState {
+    pub(super) fn find(&mut self, _: &Find, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.is_multi_line() {
+            cx.propagate();
+            return;
+        }
+
+        let panel = match self.search_panel.clone() {
+            Some(panel) => panel,
+            None => {
+                let panel = self.new_search_panel(window, cx);
+                self.search_panel = Some(panel.clone());
+                panel
+            }
+        };
+
+        // Use the selected text as the query, if it is in a single line.
+        let selected_text = self.text.slice(self.selected_range.clone());
+        let query_input = panel.update(cx, |panel, cx| {
+            panel.open = true;
+            cx.notify();
+            panel.query_input.clone()
+        });
+        query_input.update(cx, |input, cx| {
+            if !selected_text.is_empty() && !selected_text.contains('\n') {
+                input.set_value(selected_text, window, cx);
+            }
+            input.select_all(&super::SelectAll, window, cx);
+            input.focus(window, cx);
+        });
+
+        self.update_search_matches(cx);
+        cx.notify();
+    }
+
+    fn new_search_panel(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<SearchPanel> {
+        let query_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder(t!("Input.find_placeholder")));
+        let replace_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder(t!("Input.replace_placeholder")));
+
+        let _subscriptions = vec![
+            cx.subscribe_in(&query_input, window, Self::on_search_query_event),
+            cx.subscribe_in(&replace_input, window, Self::on_search_replace_event),
+        ];
+
+        let editor = cx.entity().downgrade();
+        cx.new(|_| SearchPanel {
+            editor,
+            query_input,
+            replace_input,
+            options: SearchOptions::default(),
+            open: false,
+            show_replace: false,
+            matches: vec![],
+            current_match_ix: None,
+            _subscriptions,
+        })
+    }
+
+    fn on_search_query_event(
+        &mut self,
+        _: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            InputEvent::Change(_) => self.search_from_cursor(cx),
+            InputEvent::PressEnter { secondary: false } => self.find_next(&FindNext, window, cx),
+            InputEvent::PressEnter { secondary: true } => {
+                self.find_previous(&FindPrevious, window, cx)
+            }
+            _ => {}
+        }
+    }
+
+    fn on_search_replace_event(
+        &mut self,
+        _: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            InputEvent::PressEnter { secondary: false } => {
+                self.replace_search_match(&Replace, window, cx)
+            }
+            InputEvent::PressEnter { secondary: true } => {
+                self.replace_all_search_matches(&ReplaceAll, window, cx)
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the search panel if it is open.
+    fn open_search_panel(&self, cx: &App) -> Option<Entity<SearchPanel>> {
+        self.search_panel
+            .clone()
+            .filter(|panel| panel.read(cx).open)
+    }
+
+    /// Close the search panel and focus back to the editor, returns false if the panel is not open.
+    pub(super) fn close_search_panel(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(panel) = self.open_search_panel(cx) else {
+            return false;
+        };
+
+        panel.update(cx, |panel, cx| {
+            panel.open = false;
+            panel.matches.clear();
+            panel.current_match_ix = None;
+            cx.notify();
+        });
+        self.focus(window, cx);
+        cx.notify();
+        true
+    }
+
+    /// Re-run the search on the current text.
+    pub(super) fn update_search_matches(&mut self, cx: &mut Context<Self>) {
+        let Some(panel) = self.open_search_panel(cx) else {
+            return;
+        };
+
+        let text = self.value().clone();
+        let offset = self.selected_range.start;
+        panel.update(cx, |panel, cx| panel.update_matches(&text, offset, cx));
+    }
+
+    /// Adjust the matches for the edit, and re-run the search after the debounce delay,
+    /// to avoid searching the whole text on every keystroke.
+    pub(super) fn edit_search_matches(
+        &mut self,
+        edit: &Range<usize>,
+        new_len: usize,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(panel) = self.open_search_panel(cx) else {
+            return;
+        };
+
+        panel.update(cx, |panel, _| {
+            adjust_matches_for_edit(&mut panel.matches, edit, new_len);
+            panel.current_match_ix = panel
+                .current_match_ix
+                .filter(|ix| *ix < panel.matches.len());
+        });
+        self._search_task = cx.spawn(async move |this, cx| {
+            Timer::after(SEARCH_DEBOUNCE).await;
+            _ = this.update(cx, |this, cx| this.update_search_matches(cx));
+        });
+    }
+
+    /// Re-run the search and select the first match after the cursor, used when the query or options changed.
+    fn search_from_cursor(&mut self, cx: &mut Context<Self>) {
+        let Some(panel) = self.open_search_panel(cx) else {
+            return;
+        };
+
+        self.update_search_matches(cx);
+        let range = panel
+            .read(cx)
+            .current_match_ix
+            .and_then(|ix| panel.read(cx).matches.get(ix).cloned());
+        if let Some(range) = range {
+            self.select_search_match(range, cx);
+        }
+    }
+
+    fn update_search_options(
+        &mut self,
+        f: fn(&mut SearchOptions),
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(panel) = self.open_search_panel(cx) else {
+            return;
+        };
+
+        panel.update(cx, |panel, _| f(&mut panel.options));
+        self.search_from_cursor(cx);
+    }
+
+    fn select_search_match(&mut self, range: Range<usize>, cx: &mut Context<Self>) {
+        self.unfold_offset(range.start);
+        self.unfold_offset(range.end);
+        self.extra_selections.clear();
+        self.selected_range = range;
+        self.selection_reversed = false;
+        self.selected_word_range = None;
+        cx.notify();
+    }
+
+    fn search_next(&mut self, forward: bool, cx: &mut Context<Self>) {
+        let Some(panel) = self.open_search_panel(cx) else {
+            return;
+        };
+
+        let offset = if forward {
+            self.selected_range.end
+        } else {
+            self.selected_range.start
+        };
+        if let Some(range) = panel.update(cx, |panel, cx| panel.select_match(offset, forward, cx)) {
+            self.select_search_match(range, cx);
+        }
+    }
+
+    pub(super) fn find_next(&mut self, _: &FindNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.open_search_panel(cx).is_none() {
+            cx.propagate();
+            return;
+        }
+
+        self.search_next(true, cx);
+    }
+
+    pub(super) fn find_previous(
+        &mut self,
+        _: &FindPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.open_search_panel(cx).is_none() {
+            cx.propagate();
+            return;
+        }
+
+        self.search_next(false, cx);
+    }
+
+    /// Replace the current match (if it is selected) and move to the next match.
+    pub(super) fn replace_search_match(
+        &mut self,
+        _: &Replace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(panel) = self.open_search_panel(cx) else {
+            return;
+        };
+        if self.disabled {
+            return;
+        }
+
+        let panel = panel.read(cx);
+        let replacement = panel.replace_input.read(cx).value();
+        let expand = panel.options.regex;
+        let range = self.selected_range.clone();
+        let new_text = panel
+            .regex(cx)
+            .and_then(|regex| replacement_at(self.value(), &regex, &range, &replacement, expand));
+
+        if let Some(new_text) = new_text {
+            self.history.start_grouping();
+            self.replace_text_in_range(Some(self.range_to_utf16(&range)), &new_text, window, cx);
+            self.history.end_grouping();
+        }
+
+        self.search_next(true, cx);
+    }
+
+    /// Replace all matches as a single undoable change.
+    pub(super) fn replace_all_search_matches(
+        &mut self,
+        _: &ReplaceAll,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(panel) = self.open_search_panel(cx) else {
+            cx.propagate();
+            return;
+        };
+        if self.disabled {
+            return;
+        }
+
+        let panel = panel.read(cx);
+        let replacement = panel.replace_input.read(cx).value();
+        let expand = panel.options.regex;
+        let Some((range, new_text)) = panel
+            .regex(cx)
+            .and_then(|regex| replace_all(self.value(), &regex, &replacement, expand))
+        else {
+            return;
+        };
+
+        self.history.start_grouping();
+        self.replace_text_in_range(Some(self.range_to_utf16(&range)), &new_text, window, cx);
+        self.history.end_grouping();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matches() {
+        let text = "Hello world, hello World.\nhelloworld";
+        let options = SearchOptions::default();
+        let regex = options.build_regex("hello").unwrap();
+        assert_eq!(find_matches(text, &regex), vec![0..5, 13..18, 26..31]);
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let regex = options.build_regex("hello").unwrap();
+        assert_eq!(find_matches(text, &regex), vec![13..18, 26..31]);
+
+        let options = SearchOptions {
+            whole_word: true,
+            ..Default::default()
+        };
+        let regex = options.build_regex("world").unwrap();
+        assert_eq!(find_matches(text, &regex), vec![6..11, 19..24]);
+
+        // The query is escaped if not regex.
+        let regex = SearchOptions::default().build_regex("d.").unwrap();
+        assert_eq!(find_matches(text, &regex), vec![23..25]);
+
+        let options = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        let regex = options.build_regex(r"^hello\w*").unwrap();
+        assert_eq!(find_matches(text, &regex), vec![0..5, 26..36]);
+
+        // Empty matches are skipped.
+        let regex = options.build_regex("x*").unwrap();
+        assert_eq!(find_matches(text, &regex), vec![]);
+
+        assert!(options.build_regex("").is_none());
+        assert!(options.build_regex("(").is_none());
+    }
+
+    #[test]
+    fn test_adjust_matches_for_edit() {
+        let mut matches = vec![0..5, 10..15, 20..25];
+        // Replace "12" with "abcd" in the second match.
+        adjust_matches_for_edit(&mut matches, &(11..13), 4);
+        assert_eq!(matches, vec![0..5, 22..27]);
+
+        // Insert before the match, and delete after the match.
+        adjust_matches_for_edit(&mut matches, &(22..22), 1);
+        assert_eq!(matches, vec![0..5, 23..28]);
+        adjust_matches_for_edit(&mut matches, &(5..10), 0);
+        assert_eq!(matches, vec![0..5, 18..23]);
+    }
+
+    #[test]
+    fn test_replace() {
+        let text = "let foo = 1;\nlet bar = 2;";
+        let options = SearchOptions {
+            regex: true,
+            ..Default::default()
+        };
+        let regex = options.build_regex(r"let (\w+)").unwrap();
+
+        assert_eq!(
+            replacement_at(text, &regex, &(0..7), "const $1", true),
+            Some("const foo".to_string())
+        );
+        assert_eq!(
+            replacement_at(text, &regex, &(0..7), "const $1", false),
+            Some("const $1".to_string())
+        );
+        assert_eq!(replacement_at(text, &regex, &(1..7), "const", true), None);
+
+        assert_eq!(
+            replace_all(text, &regex, "const $1", true),
+            Some((0..20, "const foo = 1;\nconst bar".to_string()))
+        );
+
+        let regex = SearchOptions::default().build_regex("baz").unwrap();
+        assert_eq!(replace_all(text, &regex, "qux", false), None);
+    }
+}
@@ -4,10 +4,10 @@
 //! https://github.com/zed-industries/zed/blob/main/crates/gpui/examples/input.rs
 
 use serde::Deserialize;
-use smallvec::SmallVec;
-use std::cell::Cell;
+use std::cell::{Cell, OnceCell};
 use std::ops::Range;
 use std::rc::Rc;
+use std::sync::Arc;
 use unicode_segmentation::*;
 
 use gpui::{
@@ -15,8 +15,8 @@ use gpui::{
     AppContext, Bounds, ClipboardItem, Context, Entity, EntityInputHandler, EventEmitter,
     FocusHandle, Focusable, InteractiveElement as _, IntoElement, KeyBinding, KeyDownEvent,
     MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement as _, Pixels, Point,
-    Render, ScrollHandle, ScrollWheelEvent, SharedString, Styled as _, Subscription,
-    UTF16Selection, Window, WrappedLine,
+    Render, ScrollHandle, ScrollWheelEvent, SharedString, Styled as _, Subscription, Task,
+    UTF16Selection, Window,
 };
 
 // TODO:
@@ -24,15 +24,24 @@ use gpui::{
 
 use super::{
     blink_cursor::BlinkCursor,
+    brackets::{BracketPair, DEFAULT_BRACKET_PAIRS},
     change::Change,
     code_highlighter::CodeHighlighter,
-    element::TextElement,
+    completion::{CompletionMenu, CompletionProvider},
+    diagnostics::{adjust_diagnostics, Diagnostic, DiagnosticTooltip},
+    element::{LastLayout, TextElement},
+    folding::{self, FoldMap, FoldableMap},
+    line_edit::{self, GoToLinePrompt},
     mask_pattern::MaskPattern,
     mode::{InputMode, TabSize},
-    number_input,
+    multi_cursor::{self, adjust_range_for_edit, split_paste_lines, ColumnSelection},
+    number_input::{self, NumberOptions},
+    rope::{LinesEdit, Rope},
+    search::{self, SearchPanel},
     text_wrapper::TextWrapper,
 };
 use crate::{
+    form::{FormControl, FormState},
     highlighter::{HighlightTheme, Highlighter},
     history::History,
     scroll::ScrollbarState,
@@ -204,13 +213,19 @@ pub fn init(cx: &mut App) {
     ]);
 
     number_input::init(cx);
+    search::init(cx);
+    multi_cursor::init(cx);
+    folding::init(cx);
+    line_edit::init(cx);
 }
 
 /// InputState to keep editing state of the [`super::TextInput`].
 pub struct InputState {
     pub(super) focus_handle: FocusHandle,
     pub(super) mode: InputMode,
-    pub(super) text: SharedString,
+    pub(super) text: Rope,
+    /// The cached value of the text, cleared when the text is changed.
+    value: OnceCell<SharedString>,
     pub(super) text_wrapper: TextWrapper,
     pub(super) history: History<Change>,
     pub(super) blink_cursor: Entity<BlinkCursor>,
@@ -220,14 +235,29 @@ pub struct InputState {
     /// - "Hello 世界💝" = 16
     /// - "💝" = 4
     pub(super) selected_range: Range<usize>,
+    /// The other selections (cursors) besides the `selected_range`, for multiple cursors editing.
+    pub(super) extra_selections: Vec<Range<usize>>,
+    /// True if is applying an action to each selection, see [`Self::for_each_selection`].
+    pub(super) editing_selections: bool,
+    /// The selections before the multiple cursors editing, will be saved to the first change of the history.
+    pub(super) pending_selections: Option<Vec<Range<usize>>>,
+    pub(super) column_selection: Option<ColumnSelection>,
+    /// The folded regions, only for [`InputMode::CodeEditor`] mode.
+    pub(super) folds: FoldMap,
+    /// The foldable regions, updated by the changed lines.
+    pub(super) foldables: FoldableMap,
+    pub(super) diagnostics: Vec<Diagnostic>,
+    pub(super) diagnostic_tooltip: Option<DiagnosticTooltip>,
+    pub(super) bracket_pairs: Vec<BracketPair>,
+    pub(super) completion_provider: Option<Rc<dyn CompletionProvider>>,
+    pub(super) completion_menu: Option<CompletionMenu>,
     /// Range for save the selected word, use to keep word range when drag move.
     pub(super) selected_word_range: Option<Range<usize>>,
     pub(super) selection_reversed: bool,
-    /// The index of the current line, zero-based.
-    pub(super) current_line_index: Option<usize>,
     /// The marked range is the temporary insert text on IME typing.
     pub(super) marked_range: Option<Range<usize>>,
-    pub(super) last_layout: Option<SmallVec<[WrappedLine; 1]>>,
+    /// The layout of the visible lines of the last paint.
+    pub(super) last_layout: Option<LastLayout>,
     pub(super) last_cursor_offset: Option<usize>,
     /// The line_height of text layout, this will change will InputElement painted.
     pub(super) last_line_height: Pixels,
@@ -238,6 +268,8 @@ pub struct InputState {
     pub(super) last_selected_range: Option<Range<usize>>,
     pub(super) selecting: bool,
     pub(super) disabled: bool,
+    /// Show the error style, e.g. the validation of [`crate::form::FormState`] failed.
+    pub(super) invalid: bool,
     pub(super) masked: bool,
     pub(super) clean_on_escape: bool,
     pub(super) pattern: Option<regex::Regex>,
@@ -250,10 +282,23 @@ pub struct InputState {
 
     /// The mask pattern for formatting the input text
     pub(crate) mask_pattern: MaskPattern,
+    /// The numeric options for the [`super::NumberInput`].
+    pub(super) number_options: Option<NumberOptions>,
+    pub(super) number_wheel_delta: Pixels,
+    /// Whether the value has been stepped by holding the +/- button, to skip the step of the click.
+    pub(super) number_step_repeated: bool,
     pub(super) placeholder: SharedString,
+    /// The find and replace panel, only for multi-line mode.
+    pub(super) search_panel: Option<Entity<SearchPanel>>,
+    /// The go to line prompt, only for multi-line mode.
+    pub(super) go_to_line_prompt: Option<GoToLinePrompt>,
 
     /// To remember the horizontal column (x-coordinate) of the cursor position.
     preferred_x_offset: Option<Pixels>,
+    pub(super) _highlight_task: Task<()>,
+    pub(super) _completion_task: Task<()>,
+    pub(super) _search_task: Task<()>,
+    pub(super) _number_step_task: Task<()>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -290,7 +335,8 @@ impl InputState {
 
         Self {
             focus_handle: focus_handle.clone(),
-            text: "".into(),
+            text: Rope::new(),
+            value: OnceCell::new(),
             text_wrapper: TextWrapper::new(
                 text_style.font(),
                 text_style.font_size.to_pixels(window.rem_size()),
@@ -299,13 +345,17 @@ impl InputState {
             blink_cursor,
             history,
             selected_range: 0..0,
+            extra_selections: vec![],
+            editing_selections: false,
+            pending_selections: None,
+            column_selection: None,
             selected_word_range: None,
             selection_reversed: false,
-            current_line_index: None,
             marked_range: None,
             input_bounds: Bounds::default(),
             selecting: false,
             disabled: false,
+            invalid: false,
             masked: false,
             clean_on_escape: false,
             loading: false,
@@ -324,6 +374,22 @@ impl InputState {
             line_number_width: px(0.),
             placeholder: SharedString::default(),
             mask_pattern: MaskPattern::default(),
+            number_options: None,
+            number_wheel_delta: px(0.),
+            number_step_repeated: false,
+            search_panel: None,
+            go_to_line_prompt: None,
+            folds: FoldMap::default(),
+            foldables: FoldableMap::default(),
+            diagnostics: vec![],
+            diagnostic_tooltip: None,
+            bracket_pairs: DEFAULT_BRACKET_PAIRS.to_vec(),
+            completion_provider: None,
+            completion_menu: None,
+            _highlight_task: Task::ready(()),
+            _completion_task: Task::ready(()),
+            _search_task: Task::ready(()),
+            _number_step_task: Task::ready(()),
             _subscriptions,
         }
     }
@@ -367,7 +433,7 @@ impl InputState {
     /// - Auto Indent
     /// - Line Number
     pub fn code_editor(mut self, language: Option<&str>, theme: &'static HighlightTheme) -> Self {
-        let highlighter = Rc::new(Highlighter::new(language, theme));
+        let highlighter = Arc::new(Highlighter::new(language, theme));
         self.mode = InputMode::CodeEditor {
             rows: 2,
             tab: TabSize::default(),
@@ -435,13 +501,14 @@ impl InputState {
 
     /// Set highlighter, only for [`InputMode::CodeEditor`] mode.
     pub fn set_highlighter(&mut self, highlighter: Highlighter<'static>, cx: &mut Context<Self>) {
-        let new_highlighter = Rc::new(highlighter);
+        let new_highlighter = Arc::new(highlighter);
         match &mut self.mode {
             InputMode::CodeEditor { highlighter, .. } => {
-                highlighter.set_highlighter(new_highlighter, cx);
+                highlighter.set_highlighter(new_highlighter, &self.text);
             }
             _ => {}
         }
+        self.highlight_in_background(cx);
         cx.notify();
     }
 
@@ -457,16 +524,10 @@ impl InputState {
     }
 
     /// Called after moving the cursor. Updates preferred_x_offset if we know where the cursor now is.
-    fn update_preferred_x_offset(&mut self, _cx: &mut Context<Self>) {
-        if let (Some(lines), Some(bounds)) = (&self.last_layout, &self.last_bounds) {
+    pub(super) fn update_preferred_x_offset(&mut self, _cx: &mut Context<Self>) {
+        if let (Some(layout), Some(bounds)) = (&self.last_layout, &self.last_bounds) {
             let offset = self.cursor_offset();
-            let line_height = self.last_line_height;
-
-            // Find which line and sub-line the cursor is on and its position
-            let (_line_index, _sub_line_index, cursor_pos) =
-                self.line_and_position_for_offset(offset, lines, line_height);
-
-            if let Some(pos) = cursor_pos {
+            if let Some(pos) = layout.position_for_offset(offset, self.last_line_height) {
                 // Adjust by scroll offset
                 let scroll_offset = bounds.origin;
                 self.preferred_x_offset = Some(pos.x + scroll_offset.x);
@@ -474,29 +535,6 @@ impl InputState {
         }
     }
 
-    /// Find which line and sub-line the given offset belongs to, along with the position within that sub-line.
-    fn line_and_position_for_offset(
-        &self,
-        offset: usize,
-        lines: &[WrappedLine],
-        line_height: Pixels,
-    ) -> (usize, usize, Option<Point<Pixels>>) {
-        let mut prev_lines_offset = 0;
-        let mut y_offset = px(0.);
-        for (line_index, line) in lines.iter().enumerate() {
-            let local_offset = offset.saturating_sub(prev_lines_offset);
-            if let Some(pos) = line.position_for_index(local_offset, line_height) {
-                let sub_line_index = (pos.y.0 / line_height.0) as usize;
-                let adjusted_pos = point(pos.x, pos.y + y_offset);
-                return (line_index, sub_line_index, Some(adjusted_pos));
-            }
-
-            y_offset += line.size(line_height).height;
-            prev_lines_offset += line.len() + 1;
-        }
-        (0, 0, None)
-    }
-
     /// Move the cursor vertically by one line (up or down) while preserving the column if possible.
     /// direction: -1 for up, +1 for down
     fn move_vertical(&mut self, direction: i32, window: &mut Window, cx: &mut Context<Self>) {
@@ -504,18 +542,27 @@ impl InputState {
             return;
         }
 
-        let (Some(lines), Some(bounds)) = (&self.last_layout, &self.last_bounds) else {
+        let (Some(layout), Some(bounds)) = (&self.last_layout, self.last_bounds) else {
             return;
         };
 
         let offset = self.cursor_offset();
         let line_height = self.last_line_height;
-        let (current_line_index, current_sub_line, current_pos) =
-            self.line_and_position_for_offset(offset, lines, line_height);
-
-        let Some(current_pos) = current_pos else {
-            return;
+        let lines = layout.lines.clone();
+
+        // The line of the cursor may not be laid out, e.g. it has been scrolled out of the view.
+        let Some((current_line_index, current_pos)) =
+            layout.line_index_for_offset(offset).and_then(|ix| {
+                let line = &lines[ix];
+                let pos = line
+                    .line
+                    .position_for_index(offset - line.offset, line_height)?;
+                Some((ix, pos))
+            })
+        else {
+            return self.move_vertical_by_row(direction, window, cx);
         };
+        let current_sub_line = (current_pos.y.0 / line_height.0) as usize;
 
         let current_x = self
             .preferred_x_offset
@@ -526,26 +573,27 @@ impl InputState {
 
         new_sub_line += direction;
 
-        // Handle moving above the first line
-        if direction == -1 && new_line_index == 0 && new_sub_line < 0 {
-            // Move cursor to the beginning of the text
-            self.move_to(0, window, cx);
-            return;
-        }
-
         if new_sub_line < 0 {
             if new_line_index > 0 {
                 new_line_index -= 1;
-                new_sub_line = lines[new_line_index].wrap_boundaries.len() as i32;
+                new_sub_line = lines[new_line_index].line.wrap_boundaries.len() as i32;
+            } else if lines[new_line_index].row == 0 {
+                // Move cursor to the beginning of the text
+                self.move_to(0, window, cx);
+                return;
             } else {
-                new_sub_line = 0;
+                // The previous line has not been laid out.
+                return self.move_vertical_by_row(direction, window, cx);
             }
         } else {
-            let max_sub_line = lines[new_line_index].wrap_boundaries.len() as i32;
+            let max_sub_line = lines[new_line_index].line.wrap_boundaries.len() as i32;
             if new_sub_line > max_sub_line {
                 if new_line_index < lines.len() - 1 {
                     new_line_index += 1;
                     new_sub_line = 0;
+                } else if lines[new_line_index].row + 1 < self.text.lines_len() {
+                    // The next line has not been laid out.
+                    return self.move_vertical_by_row(direction, window, cx);
                 } else {
                     new_sub_line = max_sub_line;
                 }
@@ -562,22 +610,50 @@ impl InputState {
         let target_sub_line = new_sub_line as usize;
 
         let approx_pos = point(line_x, px(target_sub_line as f32 * line_height.0));
-        let index_res = target_line.index_for_position(approx_pos, line_height);
+        let index_res = target_line.line.index_for_position(approx_pos, line_height);
 
         let new_local_index = match index_res {
             Ok(i) => i + 1,
             Err(i) => i,
         };
 
-        let mut prev_lines_offset = 0;
-        for (i, l) in lines.iter().enumerate() {
-            if i == new_line_index {
+        let new_offset = (target_line.offset + new_local_index).min(self.text.len());
+        self.selected_range = new_offset..new_offset;
+        self.pause_blink_cursor(cx);
+        cx.notify();
+    }
+
+    /// Move the cursor to the previous or next row that is not hidden by folds, and keep the column if possible.
+    ///
+    /// Used when the lines around the cursor have not been laid out.
+    fn move_vertical_by_row(
+        &mut self,
+        direction: i32,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let point = self.text.offset_to_point(self.cursor_offset());
+        let mut row = point.row;
+        loop {
+            if direction < 0 {
+                let Some(prev_row) = row.checked_sub(1) else {
+                    self.move_to(0, window, cx);
+                    return;
+                };
+                row = prev_row;
+            } else {
+                row += 1;
+                if row >= self.text.lines_len() {
+                    return;
+                }
+            }
+
+            if !self.folds.is_hidden(row) {
                 break;
             }
-            prev_lines_offset += l.len() + 1;
         }
 
-        let new_offset = (prev_lines_offset + new_local_index).min(self.text.len());
+        let new_offset = self.offset_for_column(row, point.column);
         self.selected_range = new_offset..new_offset;
         self.pause_blink_cursor(cx);
         cx.notify();
@@ -611,6 +687,7 @@ impl InputState {
         cx: &mut Context<Self>,
     ) {
         self.history.ignore = true;
+        self.extra_selections.clear();
         self.replace_text(value, window, cx);
         self.history.ignore = false;
         // Ensure cursor to start when set text
@@ -655,7 +732,7 @@ impl InputState {
         cx: &mut Context<Self>,
     ) {
         let text: SharedString = text.into();
-        let range = 0..self.text.chars().map(|c| c.len_utf16()).sum();
+        let range = 0..self.text.offset_to_utf16(self.text.len());
         self.replace_text_in_range(Some(range), &text, window, cx);
     }
 
@@ -713,19 +790,30 @@ impl InputState {
 
     /// Set the default value of the input field.
     pub fn default_value(mut self, value: impl Into<SharedString>) -> Self {
-        self.text = value.into();
-        self.text_wrapper.text = self.text.clone();
+        let value: SharedString = value.into();
+        self.text = Rope::from(value.as_ref());
+        self.value = OnceCell::from(value);
+        // Clear the caches to let them be rebuilt with the new text on next render.
+        self.text_wrapper.clear();
+        self.foldables.clear();
+        if let InputMode::CodeEditor { highlighter, .. } = &mut self.mode {
+            highlighter.lines.clear();
+        }
         self
     }
 
     /// Return the value of the input field.
     pub fn value(&self) -> &SharedString {
-        &self.text
+        self.value.get_or_init(|| self.text.to_string().into())
     }
 
     /// Return the value without mask.
     pub fn unmask_value(&self) -> SharedString {
-        self.mask_pattern.unmask(&self.text).into()
+        if self.mask_pattern.is_none() {
+            return self.value().clone();
+        }
+
+        self.mask_pattern.unmask(self.value()).into()
     }
 
     pub fn disabled(&self) -> bool {
@@ -737,7 +825,13 @@ impl InputState {
         self.focus_handle.focus(window);
     }
 
-    pub(super) fn left(&mut self, _: &Left, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn left(&mut self, action: &Left, window: &mut Window, cx: &mut Context<Self>) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.left(action, window, cx)
+        }) {
+            return;
+        }
+
         self.pause_blink_cursor(cx);
         if self.selected_range.is_empty() {
             self.move_to(self.previous_boundary(self.cursor_offset()), window, cx);
@@ -746,7 +840,13 @@ impl InputState {
         }
     }
 
-    pub(super) fn right(&mut self, _: &Right, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn right(&mut self, action: &Right, window: &mut Window, cx: &mut Context<Self>) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.right(action, window, cx)
+        }) {
+            return;
+        }
+
         self.pause_blink_cursor(cx);
         if self.selected_range.is_empty() {
             self.move_to(self.next_boundary(self.selected_range.end), window, cx);
@@ -755,7 +855,18 @@ impl InputState {
         }
     }
 
-    pub(super) fn up(&mut self, _: &Up, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn up(&mut self, action: &Up, window: &mut Window, cx: &mut Context<Self>) {
+        if self.select_completion(-1, window, cx) {
+            return;
+        }
+
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.preferred_x_offset = None;
+            this.up(action, window, cx)
+        }) {
+            return;
+        }
+
         if self.is_single_line() {
             return;
         }
@@ -767,7 +878,18 @@ impl InputState {
         self.move_vertical(-1, window, cx);
     }
 
-    pub(super) fn down(&mut self, _: &Down, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn down(&mut self, action: &Down, window: &mut Window, cx: &mut Context<Self>) {
+        if self.select_completion(1, window, cx) {
+            return;
+        }
+
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.preferred_x_offset = None;
+            this.down(action, window, cx)
+        }) {
+            return;
+        }
+
         if self.is_single_line() {
             return;
         }
@@ -782,23 +904,46 @@ impl InputState {
 
     pub(super) fn select_left(
         &mut self,
-        _: &SelectLeft,
+        action: &SelectLeft,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_left(action, window, cx)
+        }) {
+            return;
+        }
+
         self.select_to(self.previous_boundary(self.cursor_offset()), window, cx);
     }
 
     pub(super) fn select_right(
         &mut self,
-        _: &SelectRight,
+        action: &SelectRight,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_right(action, window, cx)
+        }) {
+            return;
+        }
+
         self.select_to(self.next_boundary(self.cursor_offset()), window, cx);
     }
 
-    pub(super) fn select_up(&mut self, _: &SelectUp, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn select_up(
+        &mut self,
+        action: &SelectUp,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_up(action, window, cx)
+        }) {
+            return;
+        }
+
         if self.is_single_line() {
             return;
         }
@@ -808,10 +953,16 @@ impl InputState {
 
     pub(super) fn select_down(
         &mut self,
-        _: &SelectDown,
+        action: &SelectDown,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_down(action, window, cx)
+        }) {
+            return;
+        }
+
         if self.is_single_line() {
             return;
         }
@@ -825,17 +976,30 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_selections(cx);
         self.move_to(0, window, cx);
         self.select_to(self.text.len(), window, cx)
     }
 
-    pub(super) fn home(&mut self, _: &Home, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn home(&mut self, action: &Home, window: &mut Window, cx: &mut Context<Self>) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.home(action, window, cx)
+        }) {
+            return;
+        }
+
         self.pause_blink_cursor(cx);
         let offset = self.start_of_line(window, cx);
         self.move_to(offset, window, cx);
     }
 
-    pub(super) fn end(&mut self, _: &End, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn end(&mut self, action: &End, window: &mut Window, cx: &mut Context<Self>) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.end(action, window, cx)
+        }) {
+            return;
+        }
+
         self.pause_blink_cursor(cx);
         let offset = self.end_of_line(window, cx);
         self.move_to(offset, window, cx);
@@ -847,6 +1011,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_selections(cx);
         self.move_to(0, window, cx);
     }
 
@@ -856,26 +1021,39 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_selections(cx);
         let end = self.text.len();
         self.move_to(end, window, cx);
     }
 
     pub(super) fn move_to_previous_word(
         &mut self,
-        _: &MoveToPreviousWord,
+        action: &MoveToPreviousWord,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.move_to_previous_word(action, window, cx)
+        }) {
+            return;
+        }
+
         let offset = self.previous_start_of_word();
         self.move_to(offset, window, cx);
     }
 
     pub(super) fn move_to_next_word(
         &mut self,
-        _: &MoveToNextWord,
+        action: &MoveToNextWord,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.move_to_next_word(action, window, cx)
+        }) {
+            return;
+        }
+
         let offset = self.next_end_of_word();
         self.move_to(offset, window, cx);
     }
@@ -886,6 +1064,7 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_selections(cx);
         self.select_to(0, window, cx);
     }
 
@@ -895,46 +1074,71 @@ impl InputState {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_selections(cx);
         let end = self.text.len();
         self.select_to(end, window, cx);
     }
 
     pub(super) fn select_to_start_of_line(
         &mut self,
-        _: &SelectToStartOfLine,
+        action: &SelectToStartOfLine,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_to_start_of_line(action, window, cx)
+        }) {
+            return;
+        }
+
         let offset = self.start_of_line(window, cx);
         self.select_to(self.previous_boundary(offset), window, cx);
     }
 
     pub(super) fn select_to_end_of_line(
         &mut self,
-        _: &SelectToEndOfLine,
+        action: &SelectToEndOfLine,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_to_end_of_line(action, window, cx)
+        }) {
+            return;
+        }
+
         let offset = self.end_of_line(window, cx);
         self.select_to(self.next_boundary(offset), window, cx);
     }
 
     pub(super) fn select_to_previous_word(
         &mut self,
-        _: &SelectToPreviousWordStart,
+        action: &SelectToPreviousWordStart,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_to_previous_word(action, window, cx)
+        }) {
+            return;
+        }
+
         let offset = self.previous_start_of_word();
         self.select_to(offset, window, cx);
     }
 
     pub(super) fn select_to_next_word(
         &mut self,
-        _: &SelectToNextWordEnd,
+        action: &SelectToNextWordEnd,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.select_to_next_word(action, window, cx)
+        }) {
+            return;
+        }
+
         let offset = self.next_end_of_word();
         self.select_to(offset, window, cx);
     }
@@ -942,96 +1146,78 @@ impl InputState {
     /// Return the start offset of the previous word.
     fn previous_start_of_word(&mut self) -> usize {
         let offset = self.selected_range.start;
-        let prev_str = &self.text[..offset].to_string();
-        UnicodeSegmentation::split_word_bound_indices(prev_str as &str)
-            .filter(|(_, s)| !s.trim_start().is_empty())
-            .next_back()
-            .map(|(i, _)| i)
-            .unwrap_or(0)
+        // Only look back line by line, until a word is found.
+        let mut row = self.text.offset_to_point(offset).row;
+        loop {
+            let start = self.text.line_start_offset(row);
+            let prev_str = self.text.slice(start..offset);
+            if let Some(ix) = UnicodeSegmentation::split_word_bound_indices(prev_str.as_str())
+                .filter(|(_, s)| !s.trim_start().is_empty())
+                .next_back()
+                .map(|(i, _)| i)
+            {
+                return start + ix;
+            }
+
+            if row == 0 {
+                return 0;
+            }
+            row -= 1;
+        }
     }
 
     /// Return the next end offset of the next word.
     fn next_end_of_word(&mut self) -> usize {
         let offset = self.cursor_offset();
-        let next_str = &self.text[offset..].to_string();
-        UnicodeSegmentation::split_word_bound_indices(next_str as &str)
-            .find(|(_, s)| !s.trim_start().is_empty())
-            .map(|(i, s)| offset + i + s.len())
-            .unwrap_or(self.text.len())
+        // Only look forward line by line, until a word is found.
+        let mut row = self.text.offset_to_point(offset).row;
+        while row < self.text.lines_len() {
+            // +1 to include the `\n`
+            let end = (self.text.line_end_offset(row) + 1).min(self.text.len());
+            let next_str = self.text.slice(offset..end);
+            if let Some(end) = UnicodeSegmentation::split_word_bound_indices(next_str.as_str())
+                .find(|(_, s)| !s.trim_start().is_empty())
+                .map(|(i, s)| offset + i + s.len())
+            {
+                return end;
+            }
+            row += 1;
+        }
+
+        self.text.len()
     }
 
     /// Get start of line
-    fn start_of_line(&mut self, window: &mut Window, cx: &mut Context<Self>) -> usize {
+    fn start_of_line(&mut self, _: &mut Window, _: &mut Context<Self>) -> usize {
         if self.is_single_line() {
             return 0;
         }
 
-        let offset = self.previous_boundary(self.cursor_offset());
-        let line = self
-            .text_for_range(self.range_to_utf16(&(0..offset + 1)), &mut None, window, cx)
-            .unwrap_or_default()
-            .rfind('\n')
-            .map(|i| i + 1)
-            .unwrap_or(0);
-        line
+        let row = self.text.offset_to_point(self.cursor_offset()).row;
+        self.text.line_start_offset(row)
     }
 
     /// Get start line of selection start or end (The min value).
     ///
     /// This is means is always get the first line of selection.
-    fn start_of_line_of_selection(&mut self, window: &mut Window, cx: &mut Context<Self>) -> usize {
+    fn start_of_line_of_selection(&mut self, _: &mut Window, _: &mut Context<Self>) -> usize {
         if self.is_single_line() {
             return 0;
         }
 
-        let mut offset =
-            self.previous_boundary(self.selected_range.start.min(self.selected_range.end));
-        if self.text.chars().nth(offset) == Some('\r') {
-            offset += 1;
-        }
-
-        let line = self
-            .text_for_range(self.range_to_utf16(&(0..offset + 1)), &mut None, window, cx)
-            .unwrap_or_default()
-            .rfind('\n')
-            .map(|i| i + 1)
-            .unwrap_or(0);
-        line
+        let offset = self.selected_range.start.min(self.selected_range.end);
+        let row = self.text.offset_to_point(offset).row;
+        self.text.line_start_offset(row)
     }
 
     /// Get end of line
-    fn end_of_line(&mut self, window: &mut Window, cx: &mut Context<Self>) -> usize {
+    fn end_of_line(&mut self, _: &mut Window, _: &mut Context<Self>) -> usize {
         if self.is_single_line() {
             return self.text.len();
         }
 
-        let offset = self.next_boundary(self.cursor_offset());
-        // ignore if offset is "\n"
-        if self
-            .text_for_range(
-                self.range_to_utf16(&(offset - 1..offset)),
-                &mut None,
-                window,
-                cx,
-            )
-            .unwrap_or_default()
-            .eq("\n")
-        {
-            return offset;
-        }
-
-        let line = self
-            .text_for_range(
-                self.range_to_utf16(&(offset..self.text.len())),
-                &mut None,
-                window,
-                cx,
-            )
-            .unwrap_or_default()
-            .find('\n')
-            .map(|i| i + offset)
-            .unwrap_or(self.text.len());
-        line
+        let row = self.text.offset_to_point(self.cursor_offset()).row;
+        self.text.line_end_offset(row)
     }
 
     /// Get indent string of next line.
@@ -1050,7 +1236,7 @@ impl InputState {
         let mut next_indent = String::new();
         let current_line_start_pos = self.start_of_line(window, cx);
         let next_line_start_pos = self.end_of_line(window, cx);
-        for c in self.text.chars().skip(current_line_start_pos) {
+        for c in self.text.chars_at(current_line_start_pos) {
             if !c.is_whitespace() {
                 break;
             }
@@ -1060,7 +1246,7 @@ impl InputState {
             current_indent.push(c);
         }
 
-        for c in self.text.chars().skip(next_line_start_pos) {
+        for c in self.text.chars_at(next_line_start_pos) {
             if !c.is_whitespace() {
                 break;
             }
@@ -1077,15 +1263,36 @@ impl InputState {
         }
     }
 
-    pub(super) fn backspace(&mut self, _: &Backspace, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn backspace(
+        &mut self,
+        action: &Backspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.backspace(action, window, cx)
+        }) {
+            return;
+        }
+
         if self.selected_range.is_empty() {
-            self.select_to(self.previous_boundary(self.cursor_offset()), window, cx)
+            if let Some(range) = self.empty_bracket_pair_range(self.cursor_offset()) {
+                self.selected_range = range;
+            } else {
+                self.select_to(self.previous_boundary(self.cursor_offset()), window, cx)
+            }
         }
         self.replace_text_in_range(None, "", window, cx);
         self.pause_blink_cursor(cx);
     }
 
-    pub(super) fn delete(&mut self, _: &Delete, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn delete(&mut self, action: &Delete, window: &mut Window, cx: &mut Context<Self>) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.delete(action, window, cx)
+        }) {
+            return;
+        }
+
         if self.selected_range.is_empty() {
             self.select_to(self.next_boundary(self.cursor_offset()), window, cx)
         }
@@ -1095,10 +1302,16 @@ impl InputState {
 
     pub(super) fn delete_to_beginning_of_line(
         &mut self,
-        _: &DeleteToBeginningOfLine,
+        action: &DeleteToBeginningOfLine,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.delete_to_beginning_of_line(action, window, cx)
+        }) {
+            return;
+        }
+
         let mut offset = self.start_of_line(window, cx);
         if offset == self.cursor_offset() {
             offset = offset.saturating_sub(1);
@@ -1115,10 +1328,16 @@ impl InputState {
 
     pub(super) fn delete_to_end_of_line(
         &mut self,
-        _: &DeleteToEndOfLine,
+        action: &DeleteToEndOfLine,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.delete_to_end_of_line(action, window, cx)
+        }) {
+            return;
+        }
+
         let mut offset = self.end_of_line(window, cx);
         if offset == self.cursor_offset() {
             offset = (offset + 1).clamp(0, self.text.len());
@@ -1134,10 +1353,16 @@ impl InputState {
 
     pub(super) fn delete_previous_word(
         &mut self,
-        _: &DeleteToPreviousWordStart,
+        action: &DeleteToPreviousWordStart,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.delete_previous_word(action, window, cx)
+        }) {
+            return;
+        }
+
         let offset = self.previous_start_of_word();
         self.replace_text_in_range(
             Some(self.range_to_utf16(&(offset..self.cursor_offset()))),
@@ -1150,10 +1375,16 @@ impl InputState {
 
     pub(super) fn delete_next_word(
         &mut self,
-        _: &DeleteToNextWordEnd,
+        action: &DeleteToNextWordEnd,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.delete_next_word(action, window, cx)
+        }) {
+            return;
+        }
+
         let offset = self.next_end_of_word();
         self.replace_text_in_range(
             Some(self.range_to_utf16(&(self.cursor_offset()..offset))),
@@ -1165,27 +1396,16 @@ impl InputState {
     }
 
     pub(super) fn enter(&mut self, action: &Enter, window: &mut Window, cx: &mut Context<Self>) {
-        if self.is_multi_line() {
-            let is_eof = self.selected_range.end == self.text.len();
-
-            // Get current line indent
-            let indent = self.indent_of_next_line(window, cx);
-            self.replace_text_in_range(None, "\n", window, cx);
+        if self.confirm_completion(window, cx) {
+            return;
+        }
 
-            // Move cursor to the start of the next line
-            let mut new_offset = self.next_boundary(self.cursor_offset()) - 1;
-            if is_eof {
-                new_offset += 1;
+        if self.is_multi_line() {
+            if !self.for_each_selection(window, cx, |this, _, window, cx| {
+                this.insert_new_line(window, cx)
+            }) {
+                self.insert_new_line(window, cx);
             }
-            self.move_to(new_offset, window, cx);
-
-            // Add indent
-            self.replace_text_in_range(
-                Some(self.range_to_utf16(&(self.cursor_offset()..self.cursor_offset()))),
-                &indent,
-                window,
-                cx,
-            );
         }
 
         cx.emit(InputEvent::PressEnter {
@@ -1193,7 +1413,45 @@ impl InputState {
         });
     }
 
-    pub(super) fn indent(&mut self, _: &Indent, window: &mut Window, cx: &mut Context<Self>) {
+    /// Insert a new line with the indent of the current line.
+    fn insert_new_line(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.mode.is_code_editor() {
+            return self.insert_new_line_with_indent(window, cx);
+        }
+
+        let is_eof = self.selected_range.end == self.text.len();
+
+        // Get current line indent
+        let indent = self.indent_of_next_line(window, cx);
+        self.replace_text_in_range(None, "\n", window, cx);
+
+        // Move cursor to the start of the next line
+        let mut new_offset = self.next_boundary(self.cursor_offset()) - 1;
+        if is_eof {
+            new_offset += 1;
+        }
+        self.move_to(new_offset, window, cx);
+
+        // Add indent
+        self.replace_text_in_range(
+            Some(self.range_to_utf16(&(self.cursor_offset()..self.cursor_offset()))),
+            &indent,
+            window,
+            cx,
+        );
+    }
+
+    pub(super) fn indent(&mut self, action: &Indent, window: &mut Window, cx: &mut Context<Self>) {
+        if self.confirm_completion(window, cx) {
+            return;
+        }
+
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.indent(action, window, cx)
+        }) {
+            return;
+        }
+
         let Some(tab_size) = self.mode.tab_size() else {
             return;
         };
@@ -1243,7 +1501,18 @@ impl InputState {
         }
     }
 
-    pub(super) fn outdent(&mut self, _: &Outdent, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn outdent(
+        &mut self,
+        action: &Outdent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.for_each_selection(window, cx, |this, _, window, cx| {
+            this.outdent(action, window, cx)
+        }) {
+            return;
+        }
+
         let Some(tab_size) = self.mode.tab_size() else {
             return;
         };
@@ -1284,7 +1553,7 @@ impl InputState {
             // Selected none
             let start_offset = self.selected_range.start;
             let offset = self.start_of_line_of_selection(window, cx);
-            if self.text[offset..].starts_with(tab_indent.as_ref()) {
+            if self.text.slice(offset..offset + tab_indent.len()) == tab_indent.as_ref() {
                 self.replace_text_in_range(
                     Some(self.range_to_utf16(&(offset..offset + tab_indent.len()))),
                     "",
@@ -1306,6 +1575,18 @@ impl InputState {
         if self.marked_range.is_some() {
             self.unmark_text(window, cx);
         }
+        if self.hide_completion_menu(cx) {
+            return;
+        }
+        if self.close_search_panel(window, cx) {
+            return;
+        }
+        if self.close_go_to_line_prompt(window, cx) {
+            return;
+        }
+        if !self.extra_selections.is_empty() {
+            return self.clear_extra_selections(cx);
+        }
         if self.selected_range.len() > 0 {
             return self.unselect(window, cx);
         }
@@ -1331,8 +1612,23 @@ impl InputState {
             }
         }
 
+        // Click the fold marker in the gutter to toggle the fold.
+        if let Some(row) = self.fold_marker_row_for_position(event.position) {
+            if self.toggle_fold(row, cx) {
+                return;
+            }
+        }
+
         self.selecting = true;
         let offset = self.index_for_mouse_position(event.position, window, cx);
+        // Alt click to add a cursor, and alt drag to select a column.
+        if self.is_multi_line() && event.modifiers.alt {
+            self.start_column_selection(event.position);
+            self.add_cursor(offset, cx);
+            return;
+        }
+
+        self.clear_extra_selections(cx);
         // Double click to select word
         if event.button == MouseButton::Left && event.click_count == 2 {
             self.select_word(offset, window, cx);
@@ -1354,6 +1650,7 @@ impl InputState {
     ) {
         self.selecting = false;
         self.selected_word_range = None;
+        self.column_selection = None;
     }
 
     pub(super) fn on_scroll_wheel(
@@ -1390,20 +1687,33 @@ impl InputState {
     }
 
     pub(super) fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.extra_selections.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(self.selections_text()));
+            return;
+        }
+
         if self.selected_range.is_empty() {
             return;
         }
 
-        let selected_text = self.text[self.selected_range.clone()].to_string();
+        let selected_text = self.text.slice(self.selected_range.clone());
         cx.write_to_clipboard(ClipboardItem::new_string(selected_text));
     }
 
     pub(super) fn cut(&mut self, _: &Cut, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.extra_selections.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(self.selections_text()));
+            self.for_each_selection(window, cx, |this, _, window, cx| {
+                this.replace_text_in_range(None, "", window, cx)
+            });
+            return;
+        }
+
         if self.selected_range.is_empty() {
             return;
         }
 
-        let selected_text = self.text[self.selected_range.clone()].to_string();
+        let selected_text = self.text.slice(self.selected_range.clone());
         cx.write_to_clipboard(ClipboardItem::new_string(selected_text));
         self.replace_text_in_range(None, "", window, cx);
     }
@@ -1415,42 +1725,46 @@ impl InputState {
                 new_text = new_text.replace('\n', "");
             }
 
+            // Paste each line to each cursor, if the lines count is same as the cursors count.
+            if let Some(lines) = split_paste_lines(&new_text, self.extra_selections.len() + 1) {
+                if self.for_each_selection(window, cx, |this, ix, window, cx| {
+                    this.replace_text_in_range(None, lines[ix], window, cx)
+                }) {
+                    return;
+                }
+            }
+
             self.replace_text_in_range(None, &new_text, window, cx);
         }
     }
 
-    fn push_history(
-        &mut self,
-        range: &Range<usize>,
-        new_text: &str,
-        window: &mut Window,
-        cx: &mut Context<Self>,
-    ) {
+    fn push_history(&mut self, range: &Range<usize>, new_text: &str) {
         if self.history.ignore {
             return;
         }
 
-        let old_text = self
-            .text_for_range(self.range_to_utf16(&range), &mut None, window, cx)
-            .unwrap_or("".to_string());
+        let old_text = self.text.slice(range.clone());
 
         let new_range = range.start..range.start + new_text.len();
 
-        self.history.push(Change::new(
-            range.clone(),
-            &old_text,
-            new_range.clone(),
-            new_text,
-        ));
+        let mut change = Change::new(range.clone(), &old_text, new_range.clone(), new_text);
+        change.selections = self.pending_selections.take().unwrap_or_default();
+        self.history.push(change);
     }
 
     pub(super) fn undo(&mut self, _: &Undo, window: &mut Window, cx: &mut Context<Self>) {
         self.history.ignore = true;
         if let Some(changes) = self.history.undo() {
-            for change in changes {
+            self.extra_selections.clear();
+            for change in changes.iter() {
                 let range_utf16 = self.range_to_utf16(&change.new_range);
                 self.replace_text_in_range(Some(range_utf16), &change.old_text, window, cx);
             }
+
+            // Restore the cursors before the multiple cursors editing.
+            if let Some(change) = changes.last().filter(|c| !c.selections.is_empty()) {
+                self.set_selections(change.selections.clone());
+            }
         }
         self.history.ignore = false;
     }
@@ -1458,9 +1772,20 @@ impl InputState {
     pub(super) fn redo(&mut self, _: &Redo, window: &mut Window, cx: &mut Context<Self>) {
         self.history.ignore = true;
         if let Some(changes) = self.history.redo() {
+            self.extra_selections.clear();
+            let multi_cursors = changes.first().map_or(false, |c| !c.selections.is_empty());
             for change in changes {
                 let range_utf16 = self.range_to_utf16(&change.old_range);
                 self.replace_text_in_range(Some(range_utf16), &change.new_text, window, cx);
+                if multi_cursors {
+                    // Keep in `extra_selections` to let them be adjusted by the next changes.
+                    self.extra_selections.push(self.selected_range.clone());
+                }
+            }
+
+            if multi_cursors {
+                let cursors = std::mem::take(&mut self.extra_selections);
+                self.set_selections(cursors);
             }
         }
         self.history.ignore = false;
@@ -1473,7 +1798,9 @@ impl InputState {
     /// Ensure the offset use self.next_boundary or self.previous_boundary to get the correct offset.
     fn move_to(&mut self, offset: usize, _: &mut Window, cx: &mut Context<Self>) {
         let offset = offset.clamp(0, self.text.len());
+        let offset = self.skip_folded(offset, offset > self.cursor_offset());
         self.selected_range = offset..offset;
+        self.hide_completion_menu(cx);
         self.pause_blink_cursor(cx);
         self.update_preferred_x_offset(cx);
         cx.notify()
@@ -1491,7 +1818,7 @@ impl InputState {
         }
     }
 
-    fn index_for_mouse_position(
+    pub(super) fn index_for_mouse_position(
         &self,
         position: Point<Pixels>,
         _window: &Window,
@@ -1502,7 +1829,7 @@ impl InputState {
             return 0;
         }
 
-        let (Some(bounds), Some(lines)) = (self.last_bounds.as_ref(), self.last_layout.as_ref())
+        let (Some(bounds), Some(layout)) = (self.last_bounds.as_ref(), self.last_layout.as_ref())
         else {
             return 0;
         };
@@ -1521,72 +1848,34 @@ impl InputState {
         // - included the scroll offset.
         let inner_position = position - bounds.origin - point(self.line_number_width, px(0.));
 
-        let mut index = 0;
-        let mut y_offset = px(0.);
-
-        for (_, line) in lines.iter().enumerate() {
-            let line_origin = self.line_origin_with_y_offset(&mut y_offset, &line, line_height);
-            let pos = inner_position - line_origin;
-
-            // Return offset by use closest_index_for_x if is single line mode.
-            if self.is_single_line() {
-                return line.unwrapped_layout.closest_index_for_x(pos.x);
-            }
-
-            let index_result = line.closest_index_for_position(pos, line_height);
-            if let Ok(v) = index_result {
-                index += v;
-                break;
-            } else if let Ok(_) = line.index_for_position(point(px(0.), pos.y), line_height) {
-                // Click in the this line but not in the text, move cursor to the end of the line.
-                // The fallback index is saved in Err from `index_for_position` method.
-                index += index_result.unwrap_err();
-                break;
-            } else if line.text.trim_end_matches(|c| c == '\r').len() == 0 {
-                // empty line on Windows is `\r`, other is ''
-                let line_bounds = Bounds {
-                    origin: line_origin,
-                    size: gpui::size(bounds.size.width, line_height),
-                };
-                let pos = inner_position;
-                index += line.len();
-                if line_bounds.contains(&pos) {
-                    break;
-                }
-            } else {
-                index += line.len();
-            }
+        // Find the line at the position, the position out of the laid out lines is clamped to the first or last line.
+        let lines = &layout.lines;
+        let ix = lines.partition_point(|line| {
+            line.top + line.line.size(line_height).height <= inner_position.y
+        });
+        let Some(line) = lines.get(ix.min(lines.len().saturating_sub(1))) else {
+            return 0;
+        };
 
-            // +1 for revert `lines` split `\n`
-            index += 1;
+        // Return offset by use closest_index_for_x if is single line mode.
+        if self.is_single_line() {
+            return line
+                .line
+                .unwrapped_layout
+                .closest_index_for_x(inner_position.x);
         }
 
-        if index > self.text.len() {
-            self.text.len()
-        } else {
-            index
-        }
-    }
+        let line_bottom = line.line.size(line_height).height - px(1.);
+        let pos = point(
+            inner_position.x,
+            (inner_position.y - line.top).max(px(0.)).min(line_bottom),
+        );
+        // Click in the line but not in the text, the index of the line end is saved in Err.
+        let index = match line.line.closest_index_for_position(pos, line_height) {
+            Ok(ix) | Err(ix) => ix,
+        };
 
-    /// Returns a y offsetted point for the line origin.
-    fn line_origin_with_y_offset(
-        &self,
-        y_offset: &mut Pixels,
-        line: &WrappedLine,
-        line_height: Pixels,
-    ) -> Point<Pixels> {
-        // NOTE: About line.wrap_boundaries.len()
-        //
-        // If only 1 line, the value is 0
-        // If have 2 line, the value is 1
-        if self.is_multi_line() {
-            let p = point(px(0.), *y_offset);
-            let height = line_height + line.wrap_boundaries.len() as f32 * line_height;
-            *y_offset = *y_offset + height;
-            p
-        } else {
-            point(px(0.), px(0.))
-        }
+        (line.offset + index).min(self.text.len())
     }
 
     /// Select the text from the current cursor position to the given offset.
@@ -1596,6 +1885,7 @@ impl InputState {
     /// Ensure the offset use self.next_boundary or self.previous_boundary to get the correct offset.
     fn select_to(&mut self, offset: usize, _: &mut Window, cx: &mut Context<Self>) {
         let offset = offset.clamp(0, self.text.len());
+        let offset = self.skip_folded(offset, offset > self.cursor_offset());
         if self.selection_reversed {
             self.selected_range.start = offset
         } else {
@@ -1627,7 +1917,7 @@ impl InputState {
     /// The offset is the UTF-8 offset.
     ///
     /// FIXME: When click on a non-word character, the word is not selected.
-    fn select_word(&mut self, offset: usize, window: &mut Window, cx: &mut Context<Self>) {
+    pub(super) fn select_word(&mut self, offset: usize, _: &mut Window, cx: &mut Context<Self>) {
         #[inline(always)]
         fn is_word(c: char) -> bool {
             c.is_alphanumeric() || matches!(c, '_')
@@ -1635,33 +1925,16 @@ impl InputState {
 
         let mut start = offset;
         let mut end = start;
-        let prev_text = self
-            .text_for_range(self.range_to_utf16(&(0..start + 1)), &mut None, window, cx)
-            .unwrap_or_default();
-        let next_text = self
-            .text_for_range(
-                self.range_to_utf16(&(end..self.text.len())),
-                &mut None,
-                window,
-                cx,
-            )
-            .unwrap_or_default();
-
-        let prev_chars = prev_text.chars().rev();
-        let next_chars = next_text.chars();
 
-        let mut last_char_len = 0;
-        for (_, c) in prev_chars.enumerate() {
+        for c in self.text.reversed_chars_at(offset) {
             if !is_word(c) {
                 break;
             }
 
-            last_char_len = c.len_utf8();
-            start = start.saturating_sub(last_char_len);
+            start -= c.len_utf8();
         }
-        start += last_char_len;
 
-        for (_, c) in next_chars.enumerate() {
+        for c in self.text.chars_at(offset) {
             if !is_word(c) {
                 break;
             }
@@ -1671,16 +1944,10 @@ impl InputState {
 
         // Ensure at least one character is selected
         if start == end {
-            end = end + 1;
-
             // Avoid select empty range
-            match self.text.get(start..end) {
-                None => return,
-                Some(part) => {
-                    if part.trim().len() == 0 {
-                        return;
-                    }
-                }
+            match self.text.char_at(end) {
+                Some(c) if !c.is_whitespace() => end += c.len_utf8(),
+                _ => return,
             }
         }
 
@@ -1696,36 +1963,14 @@ impl InputState {
     }
 
     fn offset_from_utf16(&self, offset: usize) -> usize {
-        let mut utf8_offset = 0;
-        let mut utf16_count = 0;
-
-        for ch in self.text.chars() {
-            if utf16_count >= offset {
-                break;
-            }
-            utf16_count += ch.len_utf16();
-            utf8_offset += ch.len_utf8();
-        }
-
-        utf8_offset
+        self.text.offset_from_utf16(offset)
     }
 
     fn offset_to_utf16(&self, offset: usize) -> usize {
-        let mut utf16_offset = 0;
-        let mut utf8_count = 0;
-
-        for ch in self.text.chars() {
-            if utf8_count >= offset {
-                break;
-            }
-            utf8_count += ch.len_utf8();
-            utf16_offset += ch.len_utf16();
-        }
-
-        utf16_offset
+        self.text.offset_to_utf16(offset)
     }
 
-    fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
+    pub(super) fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
         self.offset_to_utf16(range.start)..self.offset_to_utf16(range.end)
     }
 
@@ -1733,19 +1978,28 @@ impl InputState {
         self.offset_from_utf16(range_utf16.start)..self.offset_from_utf16(range_utf16.end)
     }
 
+    /// Return the text around the offset (the line before and after) for grapheme boundary lookup,
+    /// and the start offset of it.
+    fn text_around(&self, offset: usize) -> (usize, String) {
+        let row = self.text.offset_to_point(offset).row;
+        let start = self.text.line_start_offset(row.saturating_sub(1));
+        let end = self.text.line_end_offset(row + 1);
+        (start, self.text.slice(start..end))
+    }
+
     fn previous_boundary(&self, offset: usize) -> usize {
-        self.text
-            .grapheme_indices(true)
+        let (start, text) = self.text_around(offset);
+        text.grapheme_indices(true)
             .rev()
-            .find_map(|(idx, _)| (idx < offset).then_some(idx))
+            .find_map(|(idx, _)| (start + idx < offset).then_some(start + idx))
             .unwrap_or(0)
     }
 
     fn next_boundary(&self, offset: usize) -> usize {
-        self.text
-            .grapheme_indices(true)
-            .find_map(|(idx, _)| (idx > offset).then_some(idx))
-            .unwrap_or(self.text.len())
+        let (start, text) = self.text_around(offset);
+        text.grapheme_indices(true)
+            .find_map(|(idx, _)| (start + idx > offset).then_some(start + idx))
+            .unwrap_or((start + text.len()).min(self.text.len()))
     }
 
     /// Returns the true to let InputElement to render cursor, when Input is focused and current BlinkCursor is visible.
@@ -1761,7 +2015,9 @@ impl InputState {
     }
 
     fn on_blur(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.clamp_number_value(window, cx);
         self.unselect(window, cx);
+        self.hide_completion_menu(cx);
         self.blink_cursor.update(cx, |cursor, cx| {
             cursor.stop(cx);
         });
@@ -1803,6 +2059,10 @@ impl InputState {
             return;
         }
 
+        if self.update_column_selection(event.position, window, cx) {
+            return;
+        }
+
         let offset = self.index_for_mouse_position(event.position, window, cx);
         self.select_to(offset, window, cx);
     }
@@ -1834,9 +2094,13 @@ impl InputState {
     /// - 9: Any digit or dot
     /// - A: Any letter
     /// - *: Any character
+    /// - [0-5]: Custom character class, `{n}` to repeat, `?` to start the optional part
+    /// - > or <: Transform the letters to upper or lower case
     /// - Other characters will be treated as literal mask characters
     ///
     /// Example: "(999)999-999" for phone numbers
+    ///
+    /// See also: [`MaskPattern::new`]
     pub fn mask_pattern(mut self, pattern: impl Into<MaskPattern>) -> Self {
         self.mask_pattern = pattern.into();
         if let Some(placeholder) = self.mask_pattern.placeholder() {
@@ -1858,15 +2122,65 @@ impl InputState {
         cx.notify();
     }
 
-    pub(super) fn set_input_bounds(&mut self, new_bounds: Bounds<Pixels>, cx: &mut Context<Self>) {
-        let wrap_width_changed = self.input_bounds.size.width != new_bounds.size.width;
+    pub(super) fn set_input_bounds(&mut self, new_bounds: Bounds<Pixels>, _: &mut Context<Self>) {
         self.input_bounds = new_bounds;
+    }
+
+    /// Return true if the input has any rule to validate the whole text, see [`Self::is_valid_input`].
+    fn has_input_rules(&self) -> bool {
+        self.validate.is_some() || self.pattern.is_some() || !self.mask_pattern.is_none()
+    }
+
+    /// Return the text after replacing the range with the new text.
+    fn pending_text(&self, range: &Range<usize>, new_text: &str) -> String {
+        self.text.slice(0..range.start) + new_text + &self.text.slice(range.end..self.text.len())
+    }
+
+    /// Apply the edit to the text, and only update the changed lines of the text wrapper and highlighter.
+    fn apply_edit(&mut self, range: Range<usize>, new_text: &str, cx: &mut Context<Self>) {
+        for selection in self.extra_selections.iter_mut() {
+            adjust_range_for_edit(selection, &range, new_text.len());
+        }
+        if !self.diagnostics.is_empty() {
+            adjust_diagnostics(&mut self.diagnostics, &range, new_text.len());
+            self.diagnostic_tooltip = None;
+        }
+        let edit = self.text.replace(range.clone(), new_text);
+        self.value.take();
+        self.update_lines(&edit, cx);
+        self.edit_search_matches(&range, new_text.len(), cx);
+    }
+
+    /// Replace the whole text, and update the text wrapper and highlighter.
+    fn apply_text(&mut self, text: &str, cx: &mut Context<Self>) {
+        let edit = LinesEdit {
+            start_row: 0,
+            old_rows: self.text.lines_len(),
+            new_rows: 0,
+        };
+        let old_len = self.text.len();
+        self.text = Rope::from(text);
+        self.value.take();
+        self.extra_selections.clear();
+        self.diagnostics.clear();
+        self.diagnostic_tooltip = None;
+        self.update_lines(
+            &LinesEdit {
+                new_rows: self.text.lines_len(),
+                ..edit
+            },
+            cx,
+        );
+        self.edit_search_matches(&(0..old_len), text.len(), cx);
+    }
 
-        // Update text_wrapper wrap_width if changed.
-        if wrap_width_changed {
-            self.text_wrapper
-                .set_wrap_width(Some(new_bounds.size.width), cx);
-            self.mode.update_auto_grow(&self.text_wrapper);
+    fn update_lines(&mut self, edit: &LinesEdit, cx: &mut Context<Self>) {
+        self.text_wrapper.update(&self.text, edit, cx);
+        self.folds.update(edit);
+        self.foldables.update(&self.text, edit);
+        if let InputMode::CodeEditor { highlighter, .. } = &mut self.mode {
+            highlighter.update(&self.text, edit);
+            self.highlight_in_background(cx);
         }
     }
 }
@@ -1881,7 +2195,7 @@ impl EntityInputHandler for InputState {
     ) -> Option<String> {
         let range = self.range_from_utf16(&range_utf16);
         adjusted_range.replace(self.range_to_utf16(&range));
-        Some(self.text[range].to_string())
+        Some(self.text.slice(range))
     }
 
     fn selected_text_range(
@@ -1925,31 +2239,64 @@ impl EntityInputHandler for InputState {
             return;
         }
 
+        // Typing with multiple cursors.
+        if range_utf16.is_none()
+            && self.marked_range.is_none()
+            && self.for_each_selection(window, cx, |this, _, window, cx| {
+                this.replace_text_in_range(None, new_text, window, cx)
+            })
+        {
+            return;
+        }
+
+        // Auto-closing the brackets on typing.
+        if range_utf16.is_none()
+            && self.marked_range.is_none()
+            && self.handle_bracket_input(new_text, window, cx)
+        {
+            return;
+        }
+
+        let typing = range_utf16.is_none();
         let range = range_utf16
             .as_ref()
             .map(|range_utf16| self.range_from_utf16(range_utf16))
             .or(self.marked_range.clone())
             .unwrap_or(self.selected_range.clone());
 
-        let pending_text: SharedString =
-            (self.text[0..range.start].to_owned() + new_text + &self.text[range.end..]).into();
-        // Check if the new text is valid
-        if !self.is_valid_input(&pending_text) {
-            return;
-        }
+        let new_pos = if self.has_input_rules() {
+            let pending_text = self.pending_text(&range, new_text);
+            // Check if the new text is valid
+            if !self.is_valid_input(&pending_text) {
+                return;
+            }
 
-        let mask_text = self.mask_pattern.mask(&pending_text);
-        let new_text_len = (new_text.len() + mask_text.len()).saturating_sub(pending_text.len());
-        let new_pos = (range.start + new_text_len).min(mask_text.len());
+            let mask_text = self.mask_pattern.mask(&pending_text);
+            let new_text_len =
+                (new_text.len() + mask_text.len()).saturating_sub(pending_text.len());
+            let editable_range = self.mask_pattern.editable_range(&mask_text);
+            let new_pos =
+                (range.start + new_text_len).clamp(editable_range.start, editable_range.end);
+
+            self.push_history(&range, &new_text);
+            if mask_text.as_ref() == pending_text {
+                self.apply_edit(range, new_text, cx);
+            } else {
+                self.apply_text(&mask_text, cx);
+            }
+            new_pos
+        } else {
+            self.push_history(&range, &new_text);
+            self.apply_edit(range.clone(), new_text, cx);
+            range.start + new_text.len()
+        };
 
-        self.push_history(&range, &new_text, window, cx);
-        self.text = mask_text;
-        self.text_wrapper.update(self.text.clone(), false, cx);
         self.selected_range = new_pos..new_pos;
         self.marked_range.take();
         self.update_preferred_x_offset(cx);
         self.update_scroll_offset(None, cx);
         self.mode.update_auto_grow(&self.text_wrapper);
+        self.update_completions(new_text, typing, window, cx);
         cx.emit(InputEvent::Change(self.unmask_value()));
         cx.notify();
     }
@@ -1960,7 +2307,7 @@ impl EntityInputHandler for InputState {
         range_utf16: Option<Range<usize>>,
         new_text: &str,
         new_selected_range_utf16: Option<Range<usize>>,
-        window: &mut Window,
+        _window: &mut Window,
         cx: &mut Context<Self>,
     ) {
         if self.disabled {
@@ -1972,15 +2319,12 @@ impl EntityInputHandler for InputState {
             .map(|range_utf16| self.range_from_utf16(range_utf16))
             .or(self.marked_range.clone())
             .unwrap_or(self.selected_range.clone());
-        let pending_text: SharedString =
-            (self.text[0..range.start].to_owned() + new_text + &self.text[range.end..]).into();
-        if !self.is_valid_input(&pending_text) {
+        if self.has_input_rules() && !self.is_valid_input(&self.pending_text(&range, new_text)) {
             return;
         }
 
-        self.push_history(&range, new_text, window, cx);
-        self.text = pending_text;
-        self.text_wrapper.update(self.text.clone(), false, cx);
+        self.push_history(&range, new_text);
+        self.apply_edit(range.clone(), new_text, cx);
         if new_text.is_empty() {
             // Cancel selection, when cancel IME input.
             self.selected_range = range.start..range.start;
@@ -2008,42 +2352,16 @@ impl EntityInputHandler for InputState {
         _cx: &mut Context<Self>,
     ) -> Option<Bounds<Pixels>> {
         let line_height = self.last_line_height;
-        let lines = self.last_layout.as_ref()?;
+        let layout = self.last_layout.as_ref()?;
         let range = self.range_from_utf16(&range_utf16);
 
-        let mut start_origin = None;
-        let mut end_origin = None;
         let line_number_origin = point(self.line_number_width, px(0.));
-        let mut y_offset = px(0.);
-        let mut index_offset = 0;
-
-        for line in lines.iter() {
-            if start_origin.is_some() && end_origin.is_some() {
-                break;
-            }
-
-            if start_origin.is_none() {
-                if let Some(p) =
-                    line.position_for_index(range.start.saturating_sub(index_offset), line_height)
-                {
-                    start_origin = Some(p + point(px(0.), y_offset));
-                }
-            }
-
-            if end_origin.is_none() {
-                if let Some(p) =
-                    line.position_for_index(range.end.saturating_sub(index_offset), line_height)
-                {
-                    end_origin = Some(p + point(px(0.), y_offset));
-                }
-            }
-
-            index_offset += line.len() + 1;
-            y_offset += line.size(line_height).height;
-        }
-
-        let start_origin = start_origin.unwrap_or_default();
-        let mut end_origin = end_origin.unwrap_or_default();
+        let start_origin = layout
+            .position_for_offset(range.start, line_height)
+            .unwrap_or_default();
+        let mut end_origin = layout
+            .position_for_offset(range.end, line_height)
+            .unwrap_or_default();
         // Ensure at same line.
         end_origin.y = start_origin.y;
 
@@ -2062,11 +2380,12 @@ impl EntityInputHandler for InputState {
     ) -> Option<usize> {
         let line_height = self.last_line_height;
         let line_point = self.last_bounds?.localize(&point)?;
-        let lines = self.last_layout.as_ref()?;
+        let layout = self.last_layout.as_ref()?;
 
-        for line in lines.iter() {
-            if let Ok(utf8_index) = line.index_for_position(line_point, line_height) {
-                return Some(self.offset_to_utf16(utf8_index));
+        for line in layout.lines.iter() {
+            let line_point = line_point - gpui::point(self.line_number_width, line.top);
+            if let Ok(utf8_index) = line.line.index_for_position(line_point, line_height) {
+                return Some(self.offset_to_utf16(line.offset + utf8_index));
             }
         }
 
@@ -2080,9 +2399,64 @@ impl Focusable for InputState {
     }
 }
 
+impl FormControl for InputState {
+    fn field_value(&self, _: &App) -> serde_json::Value {
+        if self.number_options.is_some() {
+            return match self.number_value() {
+                Some(value) if value.fract() == 0. && value.abs() < i64::MAX as f64 => {
+                    serde_json::Value::from(value as i64)
+                }
+                Some(value) => serde_json::Value::from(value),
+                None => serde_json::Value::Null,
+            };
+        }
+
+        serde_json::Value::String(self.unmask_value().to_string())
+    }
+
+    fn set_field_value(
+        &mut self,
+        value: &serde_json::Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let text = match value {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(text) => text.clone(),
+            value => value.to_string(),
+        };
+        let text = self.mask_pattern.mask_value(&text);
+        if &text != self.value() {
+            self.set_value(text, window, cx);
+        }
+    }
+
+    fn set_invalid(&mut self, invalid: bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.invalid = invalid;
+        cx.notify();
+    }
+
+    fn subscribe_change(
+        control: &Entity<Self>,
+        window: &mut Window,
+        cx: &mut Context<FormState>,
+        on_change: impl Fn(&mut FormState, &mut Window, &mut Context<FormState>) + 'static,
+    ) -> Subscription {
+        cx.subscribe_in(
+            control,
+            window,
+            move |form, _, event: &InputEvent, window, cx| {
+                if let InputEvent::Change(_) = event {
+                    on_change(form, window, cx);
+                }
+            },
+        )
+    }
+}
+
 impl Render for InputState {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        self.text_wrapper.update(self.text.clone(), false, cx);
+        self.text_wrapper.prepare(&self.text, cx);
 
         div()
             .id("text-element")
@@ -0,0 +1,654 @@
+use std::rc::Rc;
+
+use gpui::{
+    anchored, deferred, div, prelude::FluentBuilder as _, px, App, AppContext as _, ClickEvent,
+    Context, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement as _, IntoElement,
+    MouseButton, ParentElement as _, RenderOnce, SharedString, StatefulInteractiveElement as _,
+    Styled as _, Subscription, Window,
+};
+
+use super::{Backspace, Down, Enter, Escape, InputEvent, InputState, Paste, TextInput, Up};
+use crate::{
+    dropdown::DropdownItem,
+    form::{FormControl, FormState},
+    fuzzy::fuzzy_search,
+    h_flex,
+    tag::Tag,
+    v_flex, ActiveTheme as _, Icon, IconName, Sizable, Size, StyleSized as _,
+};
+
+/// The max number of the suggestions to show.
+const MAX_SUGGESTIONS: usize = 8;
+/// The separators to split the text into tags.
+const SEPARATORS: [char; 3] = [',', '\n', '\r'];
+
+/// A source of the suggestions for the [`TagInput`], like the
+/// [`DropdownDelegate`](crate::dropdown::DropdownDelegate) of the dropdown.
+pub trait TagSuggestions: 'static {
+    /// Return the suggestions matched the query text.
+    fn search(&self, query: &str, cx: &App) -> Vec<SharedString>;
+}
+
+/// The titles of the items are fuzzy matched, and sorted by the score.
+impl<T: DropdownItem + 'static> TagSuggestions for Vec<T> {
+    fn search(&self, query: &str, _: &App) -> Vec<SharedString> {
+        fuzzy_search(query, self.iter().map(|item| item.title()), |title| {
+            title.clone()
+        })
+        .into_iter()
+        .map(|(title, _)| title)
+        .collect()
+    }
+}
+
+pub enum TagInputEvent {
+    /// The tags have been changed.
+    Change(Vec<SharedString>),
+}
+
+/// The state of the [`TagInput`], to enter a list of values.
+///
+/// The text is split into tags by pressing `enter`, typing a comma or pasting text with
+/// commas or new lines, and `backspace` on the empty input removes the last tag.
+pub struct TagInputState {
+    input: Entity<InputState>,
+    tags: Vec<SharedString>,
+    allow_duplicates: bool,
+    max_tags: Option<usize>,
+    suggestions: Option<Rc<dyn TagSuggestions>>,
+    matched_suggestions: Vec<SharedString>,
+    selected_suggestion: Option<usize>,
+    invalid: bool,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl TagInputState {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let input = cx.new(|cx| InputState::new(window, cx));
+        let _subscriptions = vec![cx.subscribe_in(&input, window, Self::on_input_event)];
+
+        Self {
+            input,
+            tags: vec![],
+            allow_duplicates: false,
+            max_tags: None,
+            suggestions: None,
+            matched_suggestions: vec![],
+            selected_suggestion: None,
+            invalid: false,
+            _subscriptions,
+        }
+    }
+
+    /// Set the default tags.
+    pub fn default_tags(mut self, tags: impl IntoIterator<Item = impl Into<SharedString>>) -> Self {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set to allow the duplicate tags, default is false.
+    pub fn allow_duplicates(mut self, allow_duplicates: bool) -> Self {
+        self.allow_duplicates = allow_duplicates;
+        self
+    }
+
+    /// Set the max number of the tags, default is unlimited.
+    pub fn max_tags(mut self, max_tags: usize) -> Self {
+        self.max_tags = Some(max_tags);
+        self
+    }
+
+    /// Set the source of the suggestions, shown in a popup when typing.
+    pub fn suggestions(mut self, suggestions: impl TagSuggestions) -> Self {
+        self.suggestions = Some(Rc::new(suggestions));
+        self
+    }
+
+    /// Set the placeholder of the input.
+    pub fn set_placeholder(
+        &mut self,
+        placeholder: impl Into<SharedString>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let placeholder = placeholder.into();
+        self.input.update(cx, |input, cx| {
+            input.set_placeholder(placeholder, window, cx);
+        });
+    }
+
+    /// Return the tags.
+    pub fn tags(&self) -> &[SharedString] {
+        &self.tags
+    }
+
+    /// Set the tags, the duplicate tags and the max count are not checked.
+    pub fn set_tags(
+        &mut self,
+        tags: impl IntoIterator<Item = impl Into<SharedString>>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        cx.emit(TagInputEvent::Change(self.tags.clone()));
+        cx.notify();
+    }
+
+    /// Add the tag, return false if it is a duplicate or the max count is reached.
+    pub fn add_tag(
+        &mut self,
+        tag: impl Into<SharedString>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let tag: SharedString = tag.into();
+        self.push_tags([tag.to_string()], cx)
+    }
+
+    /// Remove the tag at the index.
+    pub fn remove_tag(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Self>) {
+        if ix < self.tags.len() {
+            self.tags.remove(ix);
+            cx.emit(TagInputEvent::Change(self.tags.clone()));
+            cx.notify();
+        }
+    }
+
+    fn push_tags(
+        &mut self,
+        new_tags: impl IntoIterator<Item = String>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if !push_tags(
+            &mut self.tags,
+            new_tags,
+            self.allow_duplicates,
+            self.max_tags,
+        ) {
+            return false;
+        }
+
+        cx.emit(TagInputEvent::Change(self.tags.clone()));
+        cx.notify();
+        true
+    }
+
+    /// Push the tags of the text before the last separator, return the rest text to keep in the input.
+    fn push_text_tags<'a>(&mut self, text: &'a str, cx: &mut Context<Self>) -> &'a str {
+        let (added, rest) =
+            push_text_tags(&mut self.tags, text, self.allow_duplicates, self.max_tags);
+        if added {
+            cx.emit(TagInputEvent::Change(self.tags.clone()));
+            cx.notify();
+        }
+        rest
+    }
+
+    /// Add the text of the input as a tag, and clear the input if the tag is accepted.
+    fn confirm_input(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let text = self.input.read(cx).value();
+        let tag = match self
+            .selected_suggestion
+            .and_then(|ix| self.matched_suggestions.get(ix))
+        {
+            Some(suggestion) => suggestion.to_string(),
+            None => text.trim().to_string(),
+        };
+        if tag.is_empty() {
+            return false;
+        }
+
+        // Keep the rejected text in the input, e.g. a duplicate tag.
+        if !self.push_tags([tag], cx) {
+            return true;
+        }
+        self.input
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        self.update_suggestions(cx);
+        true
+    }
+
+    fn on_input_event(
+        &mut self,
+        _: &Entity<InputState>,
+        event: &InputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            InputEvent::Change(text) => {
+                let rest = self.push_text_tags(text, cx);
+                if rest.len() < text.len() {
+                    let rest = rest.to_string();
+                    self.input
+                        .update(cx, |input, cx| input.set_value(rest, window, cx));
+                }
+                self.update_suggestions(cx);
+            }
+            InputEvent::Blur => {
+                self.matched_suggestions.clear();
+                self.selected_suggestion = None;
+                cx.notify();
+            }
+            _ => {}
+        }
+    }
+
+    fn update_suggestions(&mut self, cx: &mut Context<Self>) {
+        self.selected_suggestion = None;
+        self.matched_suggestions.clear();
+
+        let query = self.input.read(cx).value();
+        let query = query.trim();
+        if let Some(suggestions) = self.suggestions.as_ref().filter(|_| !query.is_empty()) {
+            self.matched_suggestions = suggestions
+                .search(query, cx)
+                .into_iter()
+                .filter(|suggestion| self.allow_duplicates || !self.tags.contains(suggestion))
+                .take(MAX_SUGGESTIONS)
+                .collect();
+        }
+        cx.notify();
+    }
+
+    fn on_backspace(&mut self, _: &Backspace, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.input.read(cx).value().is_empty() || self.tags.is_empty() {
+            return;
+        }
+
+        cx.stop_propagation();
+        self.remove_tag(self.tags.len() - 1, window, cx);
+    }
+
+    fn on_enter(&mut self, _: &Enter, window: &mut Window, cx: &mut Context<Self>) {
+        if self.confirm_input(window, cx) {
+            cx.stop_propagation();
+        }
+    }
+
+    fn on_paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        // Paste as usual without the separators.
+        if !text.contains(SEPARATORS) {
+            return;
+        }
+
+        // The single line input drops the new lines, so split the pasted text here,
+        // the text after the last separator is a tag too.
+        cx.stop_propagation();
+        let text = format!("{}\n", text.trim_end());
+        let rest = self
+            .push_text_tags(&text, cx)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.input
+            .update(cx, |input, cx| input.set_value(rest, window, cx));
+        self.update_suggestions(cx);
+    }
+
+    fn on_up(&mut self, _: &Up, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_suggestion(-1, cx);
+    }
+
+    fn on_down(&mut self, _: &Down, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_suggestion(1, cx);
+    }
+
+    fn move_suggestion(&mut self, delta: isize, cx: &mut Context<Self>) {
+        let len = self.matched_suggestions.len();
+        if len == 0 {
+            return;
+        }
+
+        cx.stop_propagation();
+        self.selected_suggestion = Some(match self.selected_suggestion {
+            Some(ix) => (ix as isize + delta).rem_euclid(len as isize) as usize,
+            None if delta > 0 => 0,
+            None => len - 1,
+        });
+        cx.notify();
+    }
+
+    fn on_escape(&mut self, _: &Escape, _: &mut Window, cx: &mut Context<Self>) {
+        if self.matched_suggestions.is_empty() {
+            return;
+        }
+
+        cx.stop_propagation();
+        self.matched_suggestions.clear();
+        self.selected_suggestion = None;
+        cx.notify();
+    }
+
+    fn on_suggestion_click(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.selected_suggestion = Some(ix);
+        self.confirm_input(window, cx);
+        self.input.read(cx).focus_handle.focus(window);
+    }
+}
+
+impl EventEmitter<TagInputEvent> for TagInputState {}
+
+impl Focusable for TagInputState {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.input.focus_handle(cx)
+    }
+}
+
+impl FormControl for TagInputState {
+    fn field_value(&self, _: &App) -> serde_json::Value {
+        serde_json::Value::Array(
+            self.tags
+                .iter()
+                .map(|tag| serde_json::Value::String(tag.to_string()))
+                .collect(),
+        )
+    }
+
+    fn set_field_value(
+        &mut self,
+        value: &serde_json::Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let tags: Vec<SharedString> = value
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|tag| tag.as_str())
+            .map(|tag| SharedString::from(tag.to_string()))
+            .collect();
+        if tags != self.tags {
+            self.set_tags(tags, window, cx);
+        }
+    }
+
+    fn set_invalid(&mut self, invalid: bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.invalid = invalid;
+        cx.notify();
+    }
+
+    fn subscribe_change(
+        control: &Entity<Self>,
+        window: &mut Window,
+        cx: &mut Context<FormState>,
+        on_change: impl Fn(&mut FormState, &mut Window, &mut Context<FormState>) + 'static,
+    ) -> Subscription {
+        cx.subscribe_in(
+            control,
+            window,
+            move |form, _, _: &TagInputEvent, window, cx| {
+                on_change(form, window, cx);
+            },
+        )
+    }
+}
+
+/// A input to enter a list of values, the values are shown as the [`Tag`]s in the input.
+#[derive(IntoElement)]
+pub struct TagInput {
+    state: Entity<TagInputState>,
+    size: Size,
+    disabled: bool,
+}
+
+impl TagInput {
+    pub fn new(state: &Entity<TagInputState>) -> Self {
+        Self {
+            state: state.clone(),
+            size: Size::default(),
+            disabled: false,
+        }
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+}
+
+impl Sizable for TagInput {
+    fn with_size(mut self, size: impl Into<Size>) -> Self {
+        self.size = size.into();
+        self
+    }
+}
+
+impl RenderOnce for TagInput {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let state = self.state.read(cx);
+        let input = state.input.clone();
+        let focused = input.focus_handle(cx).is_focused(window);
+        let show_suggestions = focused && !state.matched_suggestions.is_empty();
+        let tag_size = match self.size {
+            Size::Large => Size::Medium,
+            _ => Size::Small,
+        };
+
+        v_flex()
+            .w_full()
+            .child(
+                h_flex()
+                    .id(("tag-input", self.state.entity_id()))
+                    .when(!self.disabled, |this| {
+                        this.capture_action(
+                            window.listener_for(&self.state, TagInputState::on_backspace),
+                        )
+                        .capture_action(window.listener_for(&self.state, TagInputState::on_enter))
+                        .capture_action(window.listener_for(&self.state, TagInputState::on_paste))
+                        .capture_action(window.listener_for(&self.state, TagInputState::on_up))
+                        .capture_action(window.listener_for(&self.state, TagInputState::on_down))
+                        .capture_action(window.listener_for(&self.state, TagInputState::on_escape))
+                    })
+                    .w_full()
+                    .flex_wrap()
+                    .gap_1()
+                    .input_px(self.size)
+                    .py_1()
+                    .bg(cx.theme().background)
+                    .border_1()
+                    .border_color(cx.theme().input)
+                    .rounded(cx.theme().radius)
+                    .when(cx.theme().shadow, |this| this.shadow_sm())
+                    .when(focused, |this| this.focused_border(cx))
+                    .when(state.invalid, |this| this.border_color(cx.theme().danger))
+                    .on_mouse_down(MouseButton::Left, {
+                        let input = input.clone();
+                        move |_, window, cx| {
+                            input.read(cx).focus_handle.focus(window);
+                        }
+                    })
+                    .children(state.tags.iter().enumerate().map(|(ix, tag)| {
+                        Tag::secondary().with_size(tag_size).child(
+                            h_flex()
+                                .gap_1()
+                                .child(tag.clone())
+                                .when(!self.disabled, |this| {
+                                    this.child(
+                                        div()
+                                            .id(("remove-tag", ix))
+                                            .cursor_pointer()
+                                            .child(Icon::new(IconName::Close).xsmall())
+                                            .on_click(window.listener_for(
+                                                &self.state,
+                                                move |this, _: &ClickEvent, window, cx| {
+                                                    this.remove_tag(ix, window, cx);
+                                                },
+                                            )),
+                                    )
+                                }),
+                        )
+                    }))
+                    .child(
+                        div().flex_1().min_w(px(80.)).child(
+                            TextInput::new(&input)
+                                .appearance(false)
+                                .with_size(self.size)
+                                .disabled(self.disabled),
+                        ),
+                    ),
+            )
+            .when(show_suggestions, |this| {
+                this.child(deferred(
+                    anchored().snap_to_window_with_margin(px(8.)).child(
+                        v_flex()
+                            .occlude()
+                            .mt_1p5()
+                            .p_1()
+                            .min_w(px(160.))
+                            .bg(cx.theme().background)
+                            .border_1()
+                            .border_color(cx.theme().border)
+                            .rounded(cx.theme().radius)
+                            .shadow_md()
+                            .children(state.matched_suggestions.iter().enumerate().map(
+                                |(ix, suggestion)| {
+                                    let selected = state.selected_suggestion == Some(ix);
+                                    div()
+                                        .id(("suggestion", ix))
+                                        .px_2()
+                                        .py_1()
+                                        .rounded(cx.theme().radius)
+                                        .text_sm()
+                                        .when(selected, |this| this.bg(cx.theme().accent))
+                                        .hover(|this| this.bg(cx.theme().accent))
+                                        .child(suggestion.clone())
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            window.listener_for(
+                                                &self.state,
+                                                move |this, _, window, cx| {
+                                                    cx.stop_propagation();
+                                                    window.prevent_default();
+                                                    this.on_suggestion_click(ix, window, cx);
+                                                },
+                                            ),
+                                        )
+                                },
+                            )),
+                    ),
+                ))
+            })
+    }
+}
+
+/// Push the tags split by the commas and new lines before the last separator, stop at the first
+/// tag that is a duplicate or over the max count.
+///
+/// Return true if any tag is added, and the rest text from the rejected tag
+/// (or after the last separator) to keep in the input.
+pub(super) fn push_text_tags<'a>(
+    tags: &mut Vec<SharedString>,
+    text: &'a str,
+    allow_duplicates: bool,
+    max_tags: Option<usize>,
+) -> (bool, &'a str) {
+    let mut start = 0;
+    let mut added = false;
+    for (ix, _) in text.match_indices(SEPARATORS) {
+        let tag = text[start..ix].trim();
+        if !tag.is_empty() {
+            if !push_tags(tags, [tag.to_string()], allow_duplicates, max_tags) {
+                return (added, text[start..].trim_start());
+            }
+            added = true;
+        }
+        start = ix + 1;
+    }
+    (added, &text[start..])
+}
+
+/// Push the new tags, the duplicates and the tags over the max count are skipped,
+/// return true if any tag is added.
+pub(super) fn push_tags(
+    tags: &mut Vec<SharedString>,
+    new_tags: impl IntoIterator<Item = String>,
+    allow_duplicates: bool,
+    max_tags: Option<usize>,
+) -> bool {
+    let len = tags.len();
+    for tag in new_tags {
+        if max_tags.is_some_and(|max| tags.len() >= max) {
+            break;
+        }
+        if !allow_duplicates && tags.iter().any(|t| t.as_ref() == tag) {
+            continue;
+        }
+        tags.push(tag.into());
+    }
+    tags.len() > len
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::SharedString;
+
+    use super::{push_tags, push_text_tags};
+
+    #[test]
+    fn test_push_text_tags() {
+        let mut tags: Vec<SharedString> = vec![];
+        assert_eq!(
+            push_text_tags(&mut tags, "foo", false, None),
+            (false, "foo")
+        );
+        assert_eq!(push_text_tags(&mut tags, "foo,", false, None), (true, ""));
+        assert_eq!(
+            push_text_tags(&mut tags, "bar\nbaz\r\n,,qu", false, None),
+            (true, "qu")
+        );
+        assert_eq!(tags, vec!["foo", "bar", "baz"]);
+        assert_eq!(push_text_tags(&mut tags, " , ", false, None), (false, " "));
+
+        // The duplicate tag and the text after it are kept.
+        assert_eq!(
+            push_text_tags(&mut tags, "a, foo, b, c", false, None),
+            (true, "foo, b, c")
+        );
+        assert_eq!(tags, vec!["foo", "bar", "baz", "a"]);
+        assert_eq!(
+            push_text_tags(&mut tags, "foo,", false, None),
+            (false, "foo,")
+        );
+        assert_eq!(push_text_tags(&mut tags, "foo,", true, None), (true, ""));
+
+        // The tags over the max count are kept.
+        let mut tags: Vec<SharedString> = vec![];
+        assert_eq!(
+            push_text_tags(&mut tags, "a, b, c", false, Some(1)),
+            (true, "b, c")
+        );
+        assert_eq!(tags, vec!["a"]);
+    }
+
+    #[test]
+    fn test_push_tags() {
+        let mut tags: Vec<SharedString> = vec!["foo".into()];
+        assert!(push_tags(
+            &mut tags,
+            ["bar".to_string(), "foo".to_string()],
+            false,
+            None
+        ));
+        assert_eq!(tags, vec!["foo", "bar"]);
+
+        assert!(!push_tags(&mut tags, ["foo".to_string()], false, None));
+        assert!(push_tags(&mut tags, ["foo".to_string()], true, None));
+        assert_eq!(tags, vec!["foo", "bar", "foo"]);
+
+        let mut tags: Vec<SharedString> = vec![];
+        assert!(push_tags(
+            &mut tags,
+            ["a".to_string(), "b".to_string(), "c".to_string()],
+            false,
+            Some(2)
+        ));
+        assert_eq!(tags, vec!["a", "b"]);
+        assert!(!push_tags(&mut tags, ["d".to_string()], false, Some(2)));
+    }
+}
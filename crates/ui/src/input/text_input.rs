@@ -1,7 +1,8 @@
 use gpui::prelude::FluentBuilder as _;
 use gpui::{
-    div, px, relative, AnyElement, App, DefiniteLength, Entity, InteractiveElement as _,
-    IntoElement, MouseButton, ParentElement as _, Rems, RenderOnce, Styled as _, Window,
+    anchored, deferred, div, point, px, relative, AnyElement, App, DefiniteLength, Entity,
+    InteractiveElement as _, IntoElement, MouseButton, ParentElement as _, Rems, RenderOnce,
+    Styled as _, Window,
 };
 
 use crate::button::{Button, ButtonVariants as _};
@@ -9,11 +10,11 @@ use crate::indicator::Indicator;
 use crate::input::clear_button;
 use crate::scroll::{Scrollbar, ScrollbarAxis};
 use crate::ActiveTheme;
-use crate::{h_flex, StyledExt};
+use crate::{h_flex, v_flex, StyledExt};
 use crate::{IconName, Size};
 use crate::{Sizable, StyleSized};
 
-use super::InputState;
+use super::{completion::COMPLETION_MENU_WIDTH, InputState};
 
 #[derive(IntoElement)]
 pub struct TextInput {
@@ -139,9 +140,16 @@ impl RenderOnce for TextInput {
 
         self.state.update(cx, |state, cx| {
             state.mode.set_height(self.height);
-            state.text_wrapper.set_font(font, font_size, cx);
+            state
+                .text_wrapper
+                .set_font(font, font_size, &state.text, cx);
             state.disabled = self.disabled;
         });
+        let completion_menu = self.state.update(cx, |state, cx| {
+            let origin = state.completion_menu_origin(window, cx)?;
+            let list = state.completion_menu.as_ref()?.list.clone();
+            Some((origin, list))
+        });
 
         let state = self.state.read(cx);
         let focused = state.focus_handle.is_focused(window);
@@ -154,6 +162,19 @@ impl RenderOnce for TextInput {
             gap_x = px(0.);
         }
 
+        let search_panel = state
+            .search_panel
+            .clone()
+            .filter(|panel| state.is_multi_line() && panel.read(cx).open);
+        let go_to_line_prompt = state
+            .go_to_line_prompt
+            .as_ref()
+            .filter(|_| state.is_multi_line())
+            .map(|prompt| prompt.render(cx));
+        let diagnostic_tooltip = state
+            .diagnostic_tooltip
+            .as_ref()
+            .map(|tooltip| (tooltip.position, tooltip.view.clone()));
         let prefix = self.prefix;
         let suffix = self.suffix;
         let show_clear_button =
@@ -185,13 +206,44 @@ impl RenderOnce for TextInput {
             .on_action(window.listener_for(&self.state, InputState::right))
             .on_action(window.listener_for(&self.state, InputState::select_left))
             .on_action(window.listener_for(&self.state, InputState::select_right))
-            .when(state.is_multi_line(), |this| {
+            .when(state.is_multi_line() || completion_menu.is_some(), |this| {
                 this.on_action(window.listener_for(&self.state, InputState::up))
                     .on_action(window.listener_for(&self.state, InputState::down))
-                    .on_action(window.listener_for(&self.state, InputState::select_up))
-                    .on_action(window.listener_for(&self.state, InputState::select_down))
                     .on_action(window.listener_for(&self.state, InputState::indent))
+            })
+            .when(state.is_multi_line(), |this| {
+                this.on_action(window.listener_for(&self.state, InputState::select_up))
+                    .on_action(window.listener_for(&self.state, InputState::select_down))
                     .on_action(window.listener_for(&self.state, InputState::outdent))
+                    .on_action(window.listener_for(&self.state, InputState::select_next_occurrence))
+                    .on_action(window.listener_for(&self.state, InputState::find))
+                    .on_action(window.listener_for(&self.state, InputState::find_next))
+                    .on_action(window.listener_for(&self.state, InputState::find_previous))
+                    .on_action(window.listener_for(&self.state, InputState::select_line))
+                    .on_action(window.listener_for(&self.state, InputState::go_to_line))
+                    .when(state.mode.is_code_editor(), |this| {
+                        this.on_action(window.listener_for(&self.state, InputState::fold))
+                            .on_action(window.listener_for(&self.state, InputState::unfold))
+                            .on_action(window.listener_for(&self.state, InputState::fold_all))
+                            .on_action(window.listener_for(&self.state, InputState::unfold_all))
+                    })
+                    .when(!state.disabled, |this| {
+                        this.on_action(
+                            window.listener_for(&self.state, InputState::replace_search_match),
+                        )
+                        .on_action(window.listener_for(&self.state, InputState::move_line_up))
+                        .on_action(window.listener_for(&self.state, InputState::move_line_down))
+                        .on_action(window.listener_for(&self.state, InputState::duplicate_line))
+                        .on_action(window.listener_for(&self.state, InputState::delete_line))
+                        .on_action(window.listener_for(&self.state, InputState::join_lines))
+                        .on_action(
+                            window.listener_for(&self.state, InputState::toggle_line_comment),
+                        )
+                        .on_action(
+                            window
+                                .listener_for(&self.state, InputState::replace_all_search_matches),
+                        )
+                    })
             })
             .on_action(window.listener_for(&self.state, InputState::select_all))
             .on_action(window.listener_for(&self.state, InputState::select_to_start_of_line))
@@ -239,6 +291,7 @@ impl RenderOnce for TextInput {
                     .rounded(cx.theme().radius)
                     .when(cx.theme().shadow, |this| this.shadow_sm())
                     .when(focused, |this| this.focused_border(cx))
+                    .when(state.invalid, |this| this.border_color(cx.theme().danger))
             })
             .when(prefix.is_none(), |this| this.input_pl(self.size))
             .input_pr(self.size)
@@ -273,6 +326,46 @@ impl RenderOnce for TextInput {
                     })
                     .children(suffix),
             )
+            .when_some(search_panel, |this, panel| {
+                this.relative()
+                    .child(div().absolute().top_1().right_4().child(panel))
+            })
+            .when_some(go_to_line_prompt, |this, prompt| {
+                this.relative()
+                    .child(div().absolute().top_1().right_4().child(prompt))
+            })
+            .when_some(diagnostic_tooltip, |this, (position, view)| {
+                this.child(
+                    deferred(
+                        anchored()
+                            .position(position + point(px(0.), px(8.)))
+                            .snap_to_window_with_margin(px(8.))
+                            .child(view),
+                    )
+                    .with_priority(1),
+                )
+            })
+            .when_some(completion_menu, |this, (origin, list)| {
+                this.child(
+                    deferred(
+                        anchored()
+                            .position(origin + point(px(0.), px(4.)))
+                            .snap_to_window_with_margin(px(8.))
+                            .child(
+                                v_flex()
+                                    .occlude()
+                                    .w(COMPLETION_MENU_WIDTH)
+                                    .bg(cx.theme().background)
+                                    .border_1()
+                                    .border_color(cx.theme().border)
+                                    .rounded(cx.theme().radius.min(px(8.)))
+                                    .shadow_md()
+                                    .child(list),
+                            ),
+                    )
+                    .with_priority(1),
+                )
+            })
             .when(state.is_multi_line(), |this| {
                 let entity_id = self.state.entity_id();
                 if state.last_layout.is_some() {
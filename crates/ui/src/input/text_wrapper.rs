@@ -1,105 +1,263 @@
 use std::ops::Range;
 
-use gpui::{App, Font, LineFragment, Pixels, SharedString};
+use gpui::{App, Font, LineFragment, Pixels};
+
+use super::{
+    folding::FoldMap,
+    rope::{LinesEdit, Rope},
+};
 
-#[allow(unused)]
 pub(super) struct LineWrap {
     /// The number of soft wrapped lines of this line (Not include first line.)
     pub(super) wrap_lines: usize,
-    /// The range of the line text in the entire text.
-    pub(super) range: Range<usize>,
 }
 
 /// Used to prepare the text with soft_wrap to be get lines to displayed in the TextArea
 ///
 /// After use lines to calculate the scroll size of the TextArea
 pub(super) struct TextWrapper {
-    pub(super) text: SharedString,
-    /// The wrapped lines, value is start and end index of the line (by split \n).
-    pub(super) wrapped_lines: Vec<Range<usize>>,
     /// The lines by split \n
-    pub(super) lines: Vec<LineWrap>,
+    lines: Vec<LineWrap>,
+    /// The number of the wrapped lines before each line, the last one is the total number of the wrapped lines.
+    ///
+    /// Used to find the visible lines by the scroll offset without shaping the lines.
+    line_tops: Vec<usize>,
     pub(super) font: Font,
     pub(super) font_size: Pixels,
     /// If is none, it means the text is not wrapped
     pub(super) wrap_width: Option<Pixels>,
 }
 
-#[allow(unused)]
 impl TextWrapper {
     pub(super) fn new(font: Font, font_size: Pixels, wrap_width: Option<Pixels>) -> Self {
         Self {
-            text: SharedString::default(),
             font,
             font_size,
             wrap_width,
-            wrapped_lines: Vec::new(),
             lines: Vec::new(),
+            line_tops: vec![0],
         }
     }
 
-    pub(super) fn set_wrap_width(&mut self, wrap_width: Option<Pixels>, cx: &mut App) {
+    /// Return the total number of the wrapped lines.
+    #[inline]
+    pub(super) fn len(&self) -> usize {
+        self.line_tops.last().copied().unwrap_or(0)
+    }
+
+    /// Return the total number of the wrapped lines, the lines hidden by the `folds` are excluded.
+    pub(super) fn visible_len(&self, folds: &FoldMap) -> usize {
+        wrapped_line_for_row(&self.line_tops, folds.hidden_rows(), self.lines.len())
+    }
+
+    /// Return the index of the first wrapped line of the `row`, the lines hidden by the `folds` are excluded.
+    pub(super) fn wrapped_line_for_row(&self, row: usize, folds: &FoldMap) -> usize {
+        wrapped_line_for_row(&self.line_tops, folds.hidden_rows(), row)
+    }
+
+    /// Return the row of the wrapped line at `line`, the lines hidden by the `folds` are excluded.
+    pub(super) fn row_for_wrapped_line(&self, line: usize, folds: &FoldMap) -> usize {
+        row_for_wrapped_line(&self.line_tops, folds.hidden_rows(), line)
+    }
+
+    /// Clear the wrapped lines, to let them be wrapped again on next [`Self::prepare`].
+    pub(super) fn clear(&mut self) {
+        self.lines.clear();
+        self.line_tops = vec![0];
+    }
+
+    pub(super) fn set_wrap_width(&mut self, wrap_width: Option<Pixels>, text: &Rope, cx: &mut App) {
+        if self.wrap_width == wrap_width {
+            return;
+        }
+
         self.wrap_width = wrap_width;
-        self.update(self.text.clone(), true, cx);
+        self.wrap_all(text, cx);
     }
 
-    pub(super) fn set_font(&mut self, font: Font, font_size: Pixels, cx: &mut App) {
+    pub(super) fn set_font(&mut self, font: Font, font_size: Pixels, text: &Rope, cx: &mut App) {
+        if self.font == font && self.font_size == font_size && !self.lines.is_empty() {
+            return;
+        }
+
         self.font = font;
         self.font_size = font_size;
-        self.update(self.text.clone(), true, cx);
+        self.wrap_all(text, cx);
     }
 
-    /// Update the text wrapper and recalculate the wrapped lines.
-    ///
-    /// If the `text` is the same as the current text, do nothing.
-    pub(super) fn update(&mut self, text: SharedString, force: bool, cx: &mut App) {
-        if self.text == text && !force {
-            return;
+    /// Wrap the whole text if it has not been wrapped yet.
+    pub(super) fn prepare(&mut self, text: &Rope, cx: &mut App) {
+        if self.lines.is_empty() {
+            self.wrap_all(text, cx);
+        }
+    }
+
+    /// Recalculate the wrapped lines of the whole text.
+    pub(super) fn wrap_all(&mut self, text: &Rope, cx: &mut App) {
+        self.clear();
+        self.update(
+            text,
+            &LinesEdit {
+                start_row: 0,
+                old_rows: 0,
+                new_rows: text.lines_len(),
+            },
+            cx,
+        );
+    }
+
+    /// Update the text wrapper and recalculate the wrapped lines of the changed lines only.
+    pub(super) fn update(&mut self, text: &Rope, edit: &LinesEdit, cx: &mut App) {
+        if self.lines.is_empty() && edit.old_rows > 0 {
+            return self.wrap_all(text, cx);
         }
 
-        let mut wrapped_lines = vec![];
-        let mut lines = vec![];
         let wrap_width = self.wrap_width.unwrap_or(Pixels::MAX);
         let mut line_wrapper = cx
             .text_system()
             .line_wrapper(self.font.clone(), self.font_size);
 
-        let mut prev_line_ix = 0;
-        for line in text.lines() {
-            let mut line_wraps = vec![];
-            let mut prev_boundary_ix = 0;
-
-            // Here only have wrapped line, if there is no wrap meet, the `line_wraps` result will empty.
-            for boundary in line_wrapper.wrap_line(&[LineFragment::text(line)], wrap_width) {
-                line_wraps.push(prev_boundary_ix..boundary.ix);
-                prev_boundary_ix = boundary.ix;
-            }
-
-            lines.push(LineWrap {
-                wrap_lines: line_wraps.len(),
-                range: prev_line_ix..line.len(),
-            });
-
-            wrapped_lines.extend(line_wraps);
-            // Reset of the line
-            if !line[prev_boundary_ix..].is_empty() || prev_boundary_ix == 0 {
-                wrapped_lines.push(prev_line_ix + prev_boundary_ix..prev_line_ix + line.len());
-            }
-
-            prev_line_ix += line.len();
+        let new_lines = text
+            .lines_at(edit.start_row)
+            .take(edit.new_rows)
+            .map(|line| {
+                // Here only have wrapped line, if there is no wrap meet, the wrap_lines will be 0.
+                let wrap_lines = line_wrapper
+                    .wrap_line(&[LineFragment::text(&line)], wrap_width)
+                    .count();
+                LineWrap { wrap_lines }
+            })
+            .collect::<Vec<_>>();
+
+        let old_end = (edit.start_row + edit.old_rows).min(self.lines.len());
+        let start = edit.start_row.min(old_end);
+        let heights = new_lines
+            .iter()
+            .map(|line| line.wrap_lines + 1)
+            .collect::<Vec<_>>();
+        self.lines.splice(start..old_end, new_lines);
+        splice_line_tops(&mut self.line_tops, start..old_end, &heights);
+    }
+}
+
+/// Replace the tops of the `rows` by the `heights` (the number of the wrapped lines of each new row),
+/// the tops of the rows after are shifted by the changed height instead of being recalculated.
+fn splice_line_tops(line_tops: &mut Vec<usize>, rows: Range<usize>, heights: &[usize]) {
+    let start_top = line_tops[rows.start];
+    let old_end_top = line_tops[rows.end];
+    let new_tops = heights
+        .iter()
+        .scan(start_top, |top, height| {
+            *top += height;
+            Some(*top)
+        })
+        .collect::<Vec<_>>();
+    let new_end_top = new_tops.last().copied().unwrap_or(start_top);
+
+    line_tops.splice(rows.start + 1..rows.end + 1, new_tops);
+    if new_end_top != old_end_top {
+        for top in &mut line_tops[rows.start + heights.len() + 1..] {
+            *top = *top + new_end_top - old_end_top;
         }
+    }
+}
 
-        // Add last empty line.
-        if text.chars().last().unwrap_or('\n') == '\n' {
-            wrapped_lines.push(text.len()..text.len());
-            lines.push(LineWrap {
-                wrap_lines: 0,
-                range: text.len()..text.len(),
-            });
+/// Return the index of the first wrapped line of the `row` by the `line_tops` (see [`TextWrapper`]),
+/// the wrapped lines of the `hidden` rows are skipped.
+fn wrapped_line_for_row(line_tops: &[usize], hidden: &[Range<usize>], row: usize) -> usize {
+    let row = row.min(line_tops.len() - 1);
+    let skipped = hidden
+        .iter()
+        .take_while(|range| range.start < row)
+        .map(|range| line_tops[range.end.min(row)] - line_tops[range.start])
+        .sum::<usize>();
+
+    line_tops[row] - skipped
+}
+
+/// Return the row of the wrapped line at `line` by the `line_tops` (see [`TextWrapper`]),
+/// the wrapped lines of the `hidden` rows are skipped.
+fn row_for_wrapped_line(line_tops: &[usize], hidden: &[Range<usize>], line: usize) -> usize {
+    let rows = line_tops.len() - 1;
+    let mut line = line;
+    for range in hidden {
+        let start = range.start.min(rows);
+        if line_tops[start] > line {
+            break;
         }
+        line += line_tops[range.end.min(rows)] - line_tops[start];
+    }
+
+    line_tops
+        .partition_point(|top| *top <= line)
+        .saturating_sub(1)
+        .min(rows.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapped_line_for_row() {
+        // The wrapped lines of each row: 1, 3, 1, 2, 1
+        let line_tops = [0, 1, 4, 5, 7, 8];
+        assert_eq!(wrapped_line_for_row(&line_tops, &[], 0), 0);
+        assert_eq!(wrapped_line_for_row(&line_tops, &[], 2), 4);
+        assert_eq!(wrapped_line_for_row(&line_tops, &[], 5), 8);
+        assert_eq!(wrapped_line_for_row(&line_tops, &[], 10), 8);
+
+        // The rows 1..3 are hidden.
+        let hidden = [1..3];
+        assert_eq!(wrapped_line_for_row(&line_tops, &hidden, 1), 1);
+        assert_eq!(wrapped_line_for_row(&line_tops, &hidden, 2), 1);
+        assert_eq!(wrapped_line_for_row(&line_tops, &hidden, 3), 1);
+        assert_eq!(wrapped_line_for_row(&line_tops, &hidden, 4), 3);
+        assert_eq!(wrapped_line_for_row(&line_tops, &hidden, 5), 4);
+    }
+
+    #[test]
+    fn test_row_for_wrapped_line() {
+        let line_tops = [0, 1, 4, 5, 7, 8];
+        assert_eq!(row_for_wrapped_line(&line_tops, &[], 0), 0);
+        assert_eq!(row_for_wrapped_line(&line_tops, &[], 1), 1);
+        assert_eq!(row_for_wrapped_line(&line_tops, &[], 3), 1);
+        assert_eq!(row_for_wrapped_line(&line_tops, &[], 4), 2);
+        assert_eq!(row_for_wrapped_line(&line_tops, &[], 7), 4);
+        assert_eq!(row_for_wrapped_line(&line_tops, &[], 100), 4);
+
+        let hidden = [1..3];
+        assert_eq!(row_for_wrapped_line(&line_tops, &hidden, 0), 0);
+        assert_eq!(row_for_wrapped_line(&line_tops, &hidden, 1), 3);
+        assert_eq!(row_for_wrapped_line(&line_tops, &hidden, 2), 3);
+        assert_eq!(row_for_wrapped_line(&line_tops, &hidden, 3), 4);
+
+        // Out of the end, returns the last row even if it is hidden.
+        assert_eq!(row_for_wrapped_line(&line_tops, &[3..5], 4), 2);
+        assert_eq!(row_for_wrapped_line(&line_tops, &[3..5], 5), 4);
+        assert_eq!(row_for_wrapped_line(&[0], &[], 3), 0);
+    }
+
+    #[test]
+    fn test_splice_line_tops() {
+        let mut line_tops = vec![0];
+        splice_line_tops(&mut line_tops, 0..0, &[1, 3, 1, 2, 1]);
+        assert_eq!(line_tops, vec![0, 1, 4, 5, 7, 8]);
+
+        // The row 1 is wrapped to 2 lines.
+        splice_line_tops(&mut line_tops, 1..2, &[2]);
+        assert_eq!(line_tops, vec![0, 1, 3, 4, 6, 7]);
+
+        // The rows 2..4 are replaced by 3 rows.
+        splice_line_tops(&mut line_tops, 2..4, &[1, 1, 4]);
+        assert_eq!(line_tops, vec![0, 1, 3, 4, 5, 9, 10]);
+
+        // The rows 0..3 are removed.
+        splice_line_tops(&mut line_tops, 0..3, &[]);
+        assert_eq!(line_tops, vec![0, 1, 5, 6]);
 
-        self.text = text;
-        self.wrapped_lines = wrapped_lines;
-        self.lines = lines;
+        // Insert a row at the end.
+        splice_line_tops(&mut line_tops, 3..3, &[2]);
+        assert_eq!(line_tops, vec![0, 1, 5, 6, 8]);
     }
 }
@@ -1,5 +1,8 @@
+use std::ops::Range;
+
 use gpui::{
-    div, rems, App, Div, IntoElement, ParentElement, RenderOnce, SharedString, Styled, Window,
+    div, prelude::FluentBuilder as _, rems, App, Div, FontWeight, HighlightStyle, IntoElement,
+    ParentElement, RenderOnce, SharedString, Styled, StyledText, Window,
 };
 
 use crate::ActiveTheme;
@@ -12,6 +15,7 @@ pub struct Label {
     label: SharedString,
     chars_count: usize,
     masked: bool,
+    highlights: Vec<Range<usize>>,
 }
 
 impl Label {
@@ -23,6 +27,7 @@ impl Label {
             label,
             chars_count,
             masked: false,
+            highlights: vec![],
         }
     }
 
@@ -30,6 +35,14 @@ impl Label {
         self.masked = masked;
         self
     }
+
+    /// Set the byte ranges of the text to highlight in bold, e.g. the matched characters of a search.
+    ///
+    /// See also [`FuzzyMatch::ranges`](crate::fuzzy::FuzzyMatch::ranges).
+    pub fn highlights(mut self, highlights: impl IntoIterator<Item = Range<usize>>) -> Self {
+        self.highlights = highlights.into_iter().collect();
+        self
+    }
 }
 
 impl Styled for Label {
@@ -45,9 +58,31 @@ impl RenderOnce for Label {
         } else {
             self.label
         };
+        let highlights = if self.masked { vec![] } else { self.highlights };
 
+        // Inherit the text color with the highlights, e.g. the selected item of a list.
         div()
-            .text_color(cx.theme().foreground)
-            .child(self.base.child(text))
+            .when(highlights.is_empty(), |this| {
+                this.text_color(cx.theme().foreground)
+            })
+            .child(self.base.map(|this| {
+                if highlights.is_empty() {
+                    this.child(text)
+                } else {
+                    this.child(
+                        StyledText::new(text).with_highlights(highlights.into_iter().map(
+                            |range| {
+                                (
+                                    range,
+                                    HighlightStyle {
+                                        font_weight: Some(FontWeight::BOLD),
+                                        ..Default::default()
+                                    },
+                                )
+                            },
+                        )),
+                    )
+                }
+            }))
     }
 }
@@ -29,6 +29,7 @@ pub mod dock;
 pub mod drawer;
 pub mod dropdown;
 pub mod form;
+pub mod fuzzy;
 pub mod highlighter;
 pub mod history;
 pub mod indicator;
@@ -4,21 +4,24 @@ use std::{cell::Cell, rc::Rc};
 use crate::actions::{Cancel, Confirm, SelectNext, SelectPrev};
 use crate::input::InputState;
 use crate::{
+    h_flex,
     input::{InputEvent, TextInput},
     scroll::{Scrollbar, ScrollbarState},
-    v_flex, ActiveTheme, IconName, Size,
+    v_flex, ActiveTheme, IconName, Size, StyleSized as _,
 };
 use crate::{Icon, Sizable as _};
 use gpui::{
     div, prelude::FluentBuilder, uniform_list, AnyElement, AppContext, Entity, FocusHandle,
     Focusable, InteractiveElement, IntoElement, KeyBinding, Length, ListSizingBehavior,
-    MouseButton, ParentElement, Render, Styled, Task, UniformListScrollHandle, Window,
+    MouseButton, ParentElement, Render, SharedString, Styled, Task, UniformListScrollHandle,
+    Window,
 };
 use gpui::{px, App, Context, EventEmitter, MouseDownEvent, ScrollStrategy, Subscription};
 use rust_i18n::t;
 use smol::Timer;
 
 use super::loading::Loading;
+use super::section::{ListRow, SectionRows};
 
 pub fn init(cx: &mut App) {
     let context: Option<&str> = Some("List");
@@ -60,6 +63,26 @@ pub trait ListDelegate: Sized + 'static {
     /// Return the number of items in the list.
     fn items_count(&self, cx: &App) -> usize;
 
+    /// Return the number of sections, default is 1.
+    fn sections_count(&self, cx: &App) -> usize {
+        1
+    }
+
+    /// Return the number of items in the section.
+    ///
+    /// The items are indexed in order of the sections,
+    /// so the sum of the items in all sections must be equal to `items_count`.
+    fn section_items_count(&self, section: usize, cx: &App) -> usize {
+        self.items_count(cx)
+    }
+
+    /// Return the title to show as the header of the section, default is None to hide the header.
+    ///
+    /// The header is not selectable, and sticky on the top of the list when scrolling.
+    fn section_title(&self, section: usize, cx: &App) -> Option<SharedString> {
+        None
+    }
+
     /// Render the item at the given index.
     ///
     /// Return None will skip the item.
@@ -285,8 +308,10 @@ where
 
     /// Scroll to the item at the given index.
     pub fn scroll_to_item(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Self>) {
-        self.vertical_scroll_handle
-            .scroll_to_item(ix, ScrollStrategy::Top);
+        if let Some(row) = self.section_rows(cx).item_row(ix) {
+            self.vertical_scroll_handle
+                .scroll_to_item(row, ScrollStrategy::Top);
+        }
         cx.notify();
     }
 
@@ -295,11 +320,46 @@ where
         &self.vertical_scroll_handle
     }
 
-    fn scroll_to_selected_item(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
-        if let Some(ix) = self.selected_index {
+    fn scroll_to_selected_item(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(row) = self
+            .selected_index
+            .and_then(|ix| self.section_rows(cx).item_row(ix))
+        {
             self.vertical_scroll_handle
-                .scroll_to_item(ix, ScrollStrategy::Top);
+                .scroll_to_item(row, ScrollStrategy::Top);
+        }
+    }
+
+    /// Return the layout of the rows with the section headers.
+    fn section_rows(&self, cx: &App) -> SectionRows {
+        let sections_count = self.delegate.sections_count(cx);
+        if sections_count <= 1 && self.delegate.section_title(0, cx).is_none() {
+            return SectionRows::new([(self.delegate.items_count(cx), false)]);
         }
+
+        SectionRows::new((0..sections_count).map(|section| {
+            (
+                self.delegate.section_items_count(section, cx),
+                self.delegate.section_title(section, cx).is_some(),
+            )
+        }))
+    }
+
+    /// Return the section of the first visible row to show the sticky header.
+    fn sticky_section(&self, rows: &SectionRows) -> Option<usize> {
+        let state = self.vertical_scroll_handle.0.borrow();
+        let offset = -state.base_handle.offset().y;
+        let rows_count = rows.rows_count();
+        if offset <= px(0.) || rows_count == 0 {
+            return None;
+        }
+
+        let row_height = state.last_item_size?.contents.height / rows_count as f32;
+        if row_height <= px(0.) {
+            return None;
+        }
+
+        rows.header_section((offset / row_height).floor() as usize)
     }
 
     fn on_query_input_event(
@@ -478,6 +538,16 @@ where
         self.select_item(selected_index, window, cx);
     }
 
+    fn render_section_header(&self, section: usize, cx: &App) -> impl IntoElement {
+        h_flex()
+            .w_full()
+            .list_size(self.size)
+            .bg(cx.theme().background)
+            .text_color(cx.theme().muted_foreground)
+            .font_semibold()
+            .children(self.delegate.section_title(section, cx))
+    }
+
     fn render_list_item(
         &mut self,
         ix: usize,
@@ -552,6 +622,9 @@ where
         let view = cx.entity().clone();
         let vertical_scroll_handle = self.vertical_scroll_handle.clone();
         let items_count = self.delegate.items_count(cx);
+        let rows = self.section_rows(cx);
+        let rows_count = rows.rows_count();
+        let sticky_section = self.sticky_section(&rows);
         let loading = self.delegate.loading(cx);
         let sizing_behavior = if self.max_height.is_some() {
             ListSizingBehavior::Infer
@@ -620,18 +693,29 @@ where
                                     })
                                     .when(items_count > 0, |this| {
                                         this.child(
-                                            uniform_list(view, "uniform-list", items_count, {
+                                            uniform_list(view, "uniform-list", rows_count, {
                                                 move |list, visible_range, window, cx| {
                                                     list.load_more_if_need(
-                                                        items_count,
+                                                        rows_count,
                                                         visible_range.end,
                                                         window,
                                                         cx,
                                                     );
 
                                                     visible_range
-                                                        .map(|ix| {
-                                                            list.render_list_item(ix, window, cx)
+                                                        .filter_map(|row| match rows.row(row)? {
+                                                            ListRow::Header(section) => Some(
+                                                                list.render_section_header(
+                                                                    section, cx,
+                                                                )
+                                                                .into_any_element(),
+                                                            ),
+                                                            ListRow::Item(ix) => Some(
+                                                                list.render_list_item(
+                                                                    ix, window, cx,
+                                                                )
+                                                                .into_any_element(),
+                                                            ),
                                                         })
                                                         .collect::<Vec<_>>()
                                                 }
@@ -642,6 +726,16 @@ where
                                             .into_any_element(),
                                         )
                                     })
+                                    .when_some(sticky_section, |this, section| {
+                                        this.child(
+                                            div()
+                                                .absolute()
+                                                .top_0()
+                                                .left_0()
+                                                .right_0()
+                                                .child(self.render_section_header(section, cx)),
+                                        )
+                                    })
                                     .children(self.render_scrollbar(window, cx)),
                             )
                         }
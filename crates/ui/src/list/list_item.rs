@@ -1,8 +1,11 @@
-use crate::{h_flex, ActiveTheme, Disableable, Icon, Selectable, Sizable as _};
+use crate::{
+    fuzzy::match_ranges, h_flex, label::Label, ActiveTheme, Disableable, Icon, Selectable,
+    Sizable as _,
+};
 use gpui::{
     div, prelude::FluentBuilder as _, AnyElement, App, ClickEvent, Div, ElementId,
     InteractiveElement, IntoElement, MouseButton, MouseMoveEvent, ParentElement, RenderOnce,
-    Stateful, StatefulInteractiveElement as _, Styled, Window,
+    SharedString, Stateful, StatefulInteractiveElement as _, Styled, Window,
 };
 use smallvec::SmallVec;
 
@@ -60,6 +63,22 @@ impl ListItem {
         self
     }
 
+    /// Add the title as a child, the matched characters are highlighted in bold.
+    ///
+    /// The `positions` are the byte offsets of the matched characters in the title,
+    /// see [`FuzzyMatch`](crate::fuzzy::FuzzyMatch).
+    pub fn matched_title(mut self, title: impl Into<SharedString>, positions: &[usize]) -> Self {
+        let title: SharedString = title.into();
+        let highlights = match_ranges(&title, positions);
+        self.children.push(
+            Label::new(title)
+                .whitespace_nowrap()
+                .highlights(highlights)
+                .into_any_element(),
+        );
+        self
+    }
+
     /// Set the suffix element of the input field, for example a clear button.
     pub fn suffix<F, E>(mut self, builder: F) -> Self
     where
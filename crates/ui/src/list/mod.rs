@@ -1,6 +1,7 @@
 mod list;
 mod list_item;
 mod loading;
+mod section;
 
 pub use list::*;
 pub use list_item::*;
@@ -0,0 +1,145 @@
+/// A row of the [`List`](super::List), the header of a section or an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ListRow {
+    /// The header of the section at the index.
+    Header(usize),
+    /// The item at the index, the items are indexed in order of the sections.
+    Item(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SectionRange {
+    /// The row index of the first row (header or item) of the section.
+    row: usize,
+    /// The item index of the first item of the section.
+    item: usize,
+    /// The number of the items in the section.
+    count: usize,
+    header: bool,
+}
+
+impl SectionRange {
+    fn rows_count(&self) -> usize {
+        self.count + self.header as usize
+    }
+}
+
+/// The layout of the rows of a list with section headers.
+///
+/// The empty sections are skipped, so the headers of the sections without search results are hidden.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(super) struct SectionRows {
+    sections: Vec<SectionRange>,
+}
+
+impl SectionRows {
+    /// Create with the items count and whether to show the header of each section.
+    pub(super) fn new(sections: impl IntoIterator<Item = (usize, bool)>) -> Self {
+        let mut row = 0;
+        let mut item = 0;
+        let mut ranges = vec![];
+        for (count, header) in sections {
+            let range = SectionRange {
+                row,
+                item,
+                count,
+                header: header && count > 0,
+            };
+            row += range.rows_count();
+            item += count;
+            ranges.push(range);
+        }
+
+        Self { sections: ranges }
+    }
+
+    pub(super) fn rows_count(&self) -> usize {
+        self.sections
+            .last()
+            .map_or(0, |section| section.row + section.rows_count())
+    }
+
+    /// Return the row at the row index.
+    pub(super) fn row(&self, row: usize) -> Option<ListRow> {
+        let (ix, section) = self.section_at_row(row)?;
+        let offset = row - section.row;
+        if section.header {
+            if offset == 0 {
+                return Some(ListRow::Header(ix));
+            }
+            Some(ListRow::Item(section.item + offset - 1))
+        } else {
+            Some(ListRow::Item(section.item + offset))
+        }
+    }
+
+    /// Return the row index of the item.
+    pub(super) fn item_row(&self, item: usize) -> Option<usize> {
+        self.sections
+            .iter()
+            .find(|section| item >= section.item && item < section.item + section.count)
+            .map(|section| section.row + section.header as usize + item - section.item)
+    }
+
+    /// Return the section index with a header that contains the row, used for the sticky header.
+    pub(super) fn header_section(&self, row: usize) -> Option<usize> {
+        self.section_at_row(row)
+            .filter(|(_, section)| section.header)
+            .map(|(ix, _)| ix)
+    }
+
+    fn section_at_row(&self, row: usize) -> Option<(usize, &SectionRange)> {
+        self.sections
+            .iter()
+            .enumerate()
+            .find(|(_, section)| row >= section.row && row < section.row + section.rows_count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ListRow, SectionRows};
+
+    #[test]
+    fn test_section_rows() {
+        let rows = SectionRows::new([(2, true), (0, true), (3, false), (1, true)]);
+        assert_eq!(rows.rows_count(), 8);
+        assert_eq!(
+            (0..rows.rows_count())
+                .filter_map(|row| rows.row(row))
+                .collect::<Vec<_>>(),
+            vec![
+                ListRow::Header(0),
+                ListRow::Item(0),
+                ListRow::Item(1),
+                ListRow::Item(2),
+                ListRow::Item(3),
+                ListRow::Item(4),
+                ListRow::Header(3),
+                ListRow::Item(5),
+            ]
+        );
+        assert_eq!(rows.row(8), None);
+
+        assert_eq!(rows.item_row(0), Some(1));
+        assert_eq!(rows.item_row(2), Some(3));
+        assert_eq!(rows.item_row(5), Some(7));
+        assert_eq!(rows.item_row(6), None);
+
+        assert_eq!(rows.header_section(0), Some(0));
+        assert_eq!(rows.header_section(2), Some(0));
+        assert_eq!(rows.header_section(4), None);
+        assert_eq!(rows.header_section(7), Some(3));
+    }
+
+    #[test]
+    fn test_section_rows_without_headers() {
+        let rows = SectionRows::new([(3, false)]);
+        assert_eq!(rows.rows_count(), 3);
+        assert_eq!(rows.row(2), Some(ListRow::Item(2)));
+        assert_eq!(rows.item_row(1), Some(1));
+        assert_eq!(rows.header_section(0), None);
+
+        assert_eq!(SectionRows::new([]).rows_count(), 0);
+    }
+}
@@ -1,9 +1,10 @@
 use crate::{
-    h_flex, text::Text, tooltip::Tooltip, ActiveTheme, Colorize, Disableable, Side, Sizable, Size,
+    form::FormState, h_flex, text::Text, tooltip::Tooltip, ActiveTheme, Colorize, Disableable,
+    Side, Sizable, Size,
 };
 use gpui::{
     div, prelude::FluentBuilder as _, px, Animation, AnimationExt as _, AnyElement, App, Div,
-    Element, ElementId, GlobalElementId, InteractiveElement, IntoElement, LayoutId,
+    Element, ElementId, Entity, GlobalElementId, InteractiveElement, IntoElement, LayoutId,
     ParentElement as _, SharedString, StatefulInteractiveElement, Styled, Window,
 };
 use std::{cell::RefCell, rc::Rc, time::Duration};
@@ -19,6 +20,7 @@ pub struct Switch {
     on_click: Option<Rc<dyn Fn(&bool, &mut Window, &mut App)>>,
     size: Size,
     tooltip: Option<SharedString>,
+    bind: Option<(Entity<FormState>, SharedString)>,
 }
 
 impl Switch {
@@ -34,6 +36,7 @@ impl Switch {
             label_side: Side::Right,
             size: Size::Medium,
             tooltip: None,
+            bind: None,
         }
     }
 
@@ -55,6 +58,12 @@ impl Switch {
         self
     }
 
+    /// Bind the checked state to the value field of the [`FormState`] by name.
+    pub fn bind(mut self, form: &Entity<FormState>, name: impl Into<SharedString>) -> Self {
+        self.bind = Some((form.clone(), name.into()));
+        self
+    }
+
     pub fn label_side(mut self, label_side: Side) -> Self {
         self.label_side = label_side;
         self
@@ -114,6 +123,13 @@ impl Element for Switch {
         window: &mut Window,
         cx: &mut App,
     ) -> (LayoutId, Self::RequestLayoutState) {
+        if let Some((form, name)) = self.bind.take() {
+            let (checked, on_click) =
+                FormState::bind_checked(&form, name, self.on_click.take(), cx);
+            self.checked = checked;
+            self.on_click = Some(on_click);
+        }
+
         window.with_element_state::<SwitchState, _>(global_id.unwrap(), move |state, window| {
             let state = state.unwrap_or_default();
             let checked = self.checked;
@@ -1,25 +1,59 @@
 use std::{cell::Cell, ops::Range, rc::Rc, time::Duration};
 
 use crate::{
-    actions::{Cancel, SelectNext, SelectPrev},
+    actions::{Cancel, Confirm, SelectNext, SelectPrev},
     context_menu::ContextMenuExt,
-    h_flex,
+    h_flex, input,
     popup_menu::PopupMenu,
     scroll::{self, ScrollableMask, Scrollbar, ScrollbarState},
+    theme::box_shadow,
+    tooltip::Tooltip,
     v_flex, ActiveTheme, Icon, IconName, Sizable, Size, StyleSized as _, StyledExt,
 };
 use gpui::{
-    actions, canvas, div, prelude::FluentBuilder, px, uniform_list, App, AppContext, Axis, Bounds,
-    Context, Div, DragMoveEvent, Edges, Empty, EntityId, EventEmitter, FocusHandle, Focusable,
-    InteractiveElement, IntoElement, KeyBinding, ListSizingBehavior, MouseButton, MouseDownEvent,
-    ParentElement, Pixels, Point, Render, ScrollHandle, ScrollStrategy, ScrollWheelEvent,
-    SharedString, Stateful, StatefulInteractiveElement as _, Styled, Task, UniformListScrollHandle,
-    Window,
+    actions, canvas, div, prelude::FluentBuilder, px, uniform_list, AnyElement, App, AppContext,
+    Axis, Bounds, ClipboardItem, Context, Div, DragMoveEvent, Edges, Empty, EntityId, EventEmitter,
+    FocusHandle, Focusable, InteractiveElement, IntoElement, KeyBinding, ListSizingBehavior,
+    MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point,
+    Render, ScrollHandle, ScrollStrategy, ScrollWheelEvent, SharedString, Stateful,
+    StatefulInteractiveElement as _, Styled, Task, UniformListScrollHandle, Window,
 };
+use serde_json::Value;
 
+mod editor;
+mod export;
 mod loading;
+mod selection;
+mod tree;
+
+pub use editor::CellEditor;
+use editor::{next_editable_cell, EditingCell};
+pub use export::ExportFormat;
+use export::{to_delimited, to_html};
+use selection::RowSelection;
+pub use selection::{CellRange, TableCell, TableSelection};
+use tree::{
+    is_removed_row, list_ix, parent_row, row_ix_at, shift_row, tree_item, TreeItem, TreeState,
+};
 
-actions!(table, [SelectPrevColumn, SelectNextColumn]);
+/// The indentation of each depth of the tree rows.
+const TREE_INDENT: Pixels = px(16.);
+
+actions!(
+    table,
+    [
+        SelectPrevColumn,
+        SelectNextColumn,
+        SelectUp,
+        SelectDown,
+        SelectLeft,
+        SelectRight,
+        SelectAll,
+        SelectNextCell,
+        SelectPrevCell,
+        Copy
+    ]
+);
 
 pub fn init(cx: &mut App) {
     let context = Some("Table");
@@ -29,12 +63,22 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("down", SelectNext, context),
         KeyBinding::new("left", SelectPrevColumn, context),
         KeyBinding::new("right", SelectNextColumn, context),
+        KeyBinding::new("shift-up", SelectUp, context),
+        KeyBinding::new("shift-down", SelectDown, context),
+        KeyBinding::new("shift-left", SelectLeft, context),
+        KeyBinding::new("shift-right", SelectRight, context),
+        KeyBinding::new("secondary-a", SelectAll, context),
+        KeyBinding::new("enter", Confirm { secondary: false }, context),
+        KeyBinding::new("tab", SelectNextCell, context),
+        KeyBinding::new("shift-tab", SelectPrevCell, context),
+        KeyBinding::new("secondary-c", Copy, context),
     ]);
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColFixed {
     Left,
+    Right,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -94,6 +138,7 @@ impl Render for ResizeCol {
 enum SelectionState {
     Column,
     Row,
+    Cell,
 }
 
 #[derive(Clone)]
@@ -105,11 +150,29 @@ pub enum TableEvent {
     SelectCol(usize),
     ColWidthsChanged(Vec<Pixels>),
     MoveCol(usize, usize),
+    /// The selected rows or cells have been changed.
+    SelectionChanged(TableSelection),
 }
 
 #[derive(Clone, Copy, Default)]
 struct FixedCols {
     left: usize,
+    right: usize,
+}
+
+impl FixedCols {
+    /// Returns the range of the right fixed columns, they are the last columns.
+    fn right_range(&self, cols_count: usize) -> Range<usize> {
+        cols_count.saturating_sub(self.right)..cols_count
+    }
+}
+
+/// The edge of the scrollable area, where the content scrolls under the fixed columns or frozen rows.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ScrollEdge {
+    Left,
+    Right,
+    Top,
 }
 
 /// The visible range of the rows and columns.
@@ -140,6 +203,8 @@ pub struct Table<D: TableDelegate> {
     bounds: Bounds<Pixels>,
     /// The bounds of the fixed head cols.
     fixed_head_cols_bounds: Bounds<Pixels>,
+    /// The bounds of the right fixed head cols.
+    fixed_right_head_cols_bounds: Bounds<Pixels>,
     /// The bounds of the table head content.
     head_content_bounds: Bounds<Pixels>,
 
@@ -156,6 +221,13 @@ pub struct Table<D: TableDelegate> {
     selection_state: SelectionState,
     right_clicked_row: Option<usize>,
     selected_col: Option<usize>,
+    /// The selected rows, the `selected_row` is the last moved row.
+    selected_rows: RowSelection,
+    selected_cells: Option<CellRange>,
+    /// The cell where the mouse down, to start the cells selection by dragging.
+    cell_drag_anchor: Option<TableCell>,
+    /// The cell in editing.
+    editing: Option<EditingCell>,
 
     /// The column index that is being resized.
     resizing_col: Option<usize>,
@@ -171,6 +243,8 @@ pub struct Table<D: TableDelegate> {
 
     _measure: Vec<Duration>,
     _load_more_task: Task<()>,
+    /// The rows that are loading the children.
+    tree: TreeState,
 }
 
 #[allow(unused)]
@@ -193,6 +267,63 @@ pub trait TableDelegate: Sized + 'static {
         false
     }
 
+    /// Return true to select multiple rows by shift-click, ctrl/cmd-click, shift-arrow and select all.
+    ///
+    /// Default: false
+    fn can_select_rows(&self, cx: &App) -> bool {
+        false
+    }
+
+    /// Return true to select a rectangular range of the cells by dragging.
+    ///
+    /// Default: false
+    fn can_select_cells(&self, cx: &App) -> bool {
+        false
+    }
+
+    /// Return true if the cell at the given row and column can be edited,
+    /// the editing is started by double-click or `enter` on the cell.
+    ///
+    /// Default: false
+    fn can_edit_cell(&self, row_ix: usize, col_ix: usize, cx: &App) -> bool {
+        false
+    }
+
+    /// Create the editor of the cell when start editing, e.g. [`CellEditor::input`]
+    /// with an `InputState` of the cell value.
+    ///
+    /// Return None to not edit the cell.
+    fn cell_editor(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Option<CellEditor> {
+        None
+    }
+
+    /// Commit the value of the editor to the cell at the given row and column, by `enter` or `tab`.
+    ///
+    /// Return an error message to keep the editor open with the invalid style.
+    fn commit_cell(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        value: Value,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Result<(), SharedString> {
+        Ok(())
+    }
+
+    /// Return the plain text of the cell at the given row and column, to copy and export the table.
+    ///
+    /// Default: empty
+    fn cell_text(&self, row_ix: usize, col_ix: usize, cx: &App) -> SharedString {
+        SharedString::default()
+    }
+
     /// Returns the width of the column at the given index.
     /// Return None, use auto width.
     ///
@@ -211,10 +342,19 @@ pub trait TableDelegate: Sized + 'static {
     }
 
     /// Return the fixed side of the column at the given index.
+    ///
+    /// The left fixed columns must be the first columns, and the right fixed columns must be the last columns.
     fn col_fixed(&self, col_ix: usize, cx: &App) -> Option<ColFixed> {
         None
     }
 
+    /// Return the number of the first rows to freeze below the header, e.g. the totals row.
+    ///
+    /// Default: 0
+    fn frozen_rows(&self, cx: &App) -> usize {
+        0
+    }
+
     /// Return the padding of the column at the given index to override the default padding.
     ///
     /// Return None, use the default padding.
@@ -342,6 +482,53 @@ pub trait TableDelegate: Sized + 'static {
     /// so you must check if there is more data to load or lock the loading state.
     fn load_more(&mut self, window: &mut Window, cx: &mut Context<Table<Self>>) {}
 
+    /// Return true to show the rows as a tree, the first column of the rows is indented by
+    /// the depth, with the disclosure chevron or the space of it.
+    ///
+    /// Default: false
+    fn is_tree(&self, cx: &App) -> bool {
+        false
+    }
+
+    /// Return the depth of the row in the tree, the first column is indented by the depth,
+    /// see [`Self::is_tree`].
+    ///
+    /// Default: 0
+    fn row_depth(&self, row_ix: usize, cx: &App) -> usize {
+        0
+    }
+
+    /// Return true if the row has children, to show the disclosure chevron in the first column.
+    ///
+    /// Default: false
+    fn row_has_children(&self, row_ix: usize, cx: &App) -> bool {
+        false
+    }
+
+    /// Return true if the children of the row are shown.
+    ///
+    /// Default: false
+    fn is_row_expanded(&self, row_ix: usize, cx: &App) -> bool {
+        false
+    }
+
+    /// Expand or collapse the row, by clicking the chevron or `right` / `left` key.
+    ///
+    /// The rows of the tree are flattened, so the delegate should insert the children after the row
+    /// when expanded, and remove the descendants of the row when collapsed.
+    ///
+    /// Return a task to load the children lazily, a loading row is shown below the row until the task is finished.
+    /// The task inserts the children after the row, and returns the number of the inserted rows.
+    fn set_row_expanded(
+        &mut self,
+        row_ix: usize,
+        expanded: bool,
+        window: &mut Window,
+        cx: &mut Context<Table<Self>>,
+    ) -> Option<Task<usize>> {
+        None
+    }
+
     /// Render the last empty column, default to empty.
     fn render_last_empty_col(
         &mut self,
@@ -398,9 +585,14 @@ where
             selected_row: None,
             right_clicked_row: None,
             selected_col: None,
+            selected_rows: RowSelection::default(),
+            selected_cells: None,
+            cell_drag_anchor: None,
+            editing: None,
             resizing_col: None,
             bounds: Bounds::default(),
             fixed_head_cols_bounds: Bounds::default(),
+            fixed_right_head_cols_bounds: Bounds::default(),
             head_content_bounds: Bounds::default(),
             stripe: false,
             border: true,
@@ -408,6 +600,7 @@ where
             scrollbar_visible: Edges::all(true),
             visible_range: VisibleRangeState::default(),
             _load_more_task: Task::ready(()),
+            tree: TreeState::default(),
             _measure: Vec::new(),
         };
 
@@ -481,16 +674,41 @@ where
             .iter()
             .filter(|col| col.fixed == Some(ColFixed::Left))
             .count();
+        self.fixed_cols.right = self
+            .col_groups
+            .iter()
+            .filter(|col| col.fixed == Some(ColFixed::Right))
+            .count();
+        debug_assert!(
+            is_fixed_at_edges(&self.col_fixed_groups()),
+            "The left fixed columns must be the first columns, and the right fixed columns must be the last columns."
+        );
         cx.notify();
     }
 
+    /// Returns the fixed side of the columns.
+    fn col_fixed_groups(&self) -> Vec<Option<ColFixed>> {
+        self.col_groups.iter().map(|col| col.fixed).collect()
+    }
+
     /// Scroll to the row at the given index.
     pub fn scroll_to_row(&mut self, row_ix: usize, cx: &mut Context<Self>) {
-        self.vertical_scroll_handle
-            .scroll_to_item(row_ix, ScrollStrategy::Top);
+        self.scroll_to_row_item(row_ix, cx);
         cx.notify();
     }
 
+    /// Scroll the body to the row.
+    ///
+    /// Do nothing for the frozen rows, they are always visible above the body.
+    fn scroll_to_row_item(&self, row_ix: usize, cx: &App) {
+        let frozen_rows = self.delegate.frozen_rows(cx);
+        let loading_rows = self.tree.loading_rows(frozen_rows);
+        if let Some(item_ix) = body_item_ix(row_ix, frozen_rows, &loading_rows) {
+            self.vertical_scroll_handle
+                .scroll_to_item(item_ix, ScrollStrategy::Top);
+        }
+    }
+
     // Scroll to the column at the given index.
     // TODO: Fix scroll to selected col, this was not working after fixed col.
     // pub fn scroll_to_col(&mut self, col_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
@@ -508,14 +726,105 @@ where
         self.selection_state = SelectionState::Row;
         self.right_clicked_row = None;
         self.selected_row = Some(row_ix);
-        if let Some(row_ix) = self.selected_row {
-            self.vertical_scroll_handle
-                .scroll_to_item(row_ix, ScrollStrategy::Top);
-        }
+        self.selected_rows.select(row_ix);
+        self.scroll_to_row_item(row_ix, cx);
         cx.emit(TableEvent::SelectRow(row_ix));
+        self.emit_selection_changed(cx);
+        cx.notify();
+    }
+
+    /// Returns the selected rows in ascending order, e.g. to perform the bulk actions.
+    pub fn selected_rows(&self) -> Vec<usize> {
+        match self.selection_state {
+            SelectionState::Row => self.selected_rows.rows(),
+            _ => vec![],
+        }
+    }
+
+    /// Sets the selected rows, the last row is the current row.
+    pub fn set_selected_rows(
+        &mut self,
+        rows: impl IntoIterator<Item = usize>,
+        cx: &mut Context<Self>,
+    ) {
+        self.selection_state = SelectionState::Row;
+        self.right_clicked_row = None;
+        self.selected_rows.clear();
+        for row_ix in rows {
+            if !self.selected_rows.contains(row_ix) {
+                self.selected_rows.toggle(row_ix);
+            }
+            self.selected_row = Some(row_ix);
+        }
+        self.emit_selection_changed(cx);
         cx.notify();
     }
 
+    /// Returns the selected range of the cells.
+    pub fn selected_cells(&self) -> Option<CellRange> {
+        match self.selection_state {
+            SelectionState::Cell => self.selected_cells,
+            _ => None,
+        }
+    }
+
+    /// Sets the selected range of the cells.
+    pub fn set_selected_cells(&mut self, range: CellRange, cx: &mut Context<Self>) {
+        self.selection_state = SelectionState::Cell;
+        self.right_clicked_row = None;
+        self.selected_cells = Some(range);
+        self.emit_selection_changed(cx);
+        cx.notify();
+    }
+
+    /// Returns the selected rows and cells.
+    pub fn selection(&self) -> TableSelection {
+        TableSelection {
+            rows: match self.selection_state {
+                SelectionState::Row => self.selected_rows.ranges(),
+                _ => vec![],
+            },
+            cells: self.selected_cells(),
+        }
+    }
+
+    /// Select all the rows, or all the cells if the cells are selected.
+    pub fn select_all(&mut self, cx: &mut Context<Self>) {
+        let rows_count = self.delegate.rows_count(cx);
+        let cols_count = self.delegate.cols_count(cx);
+        if rows_count == 0 {
+            return;
+        }
+
+        if self.selection_state == SelectionState::Cell
+            || (!self.delegate.can_select_rows(cx) && self.delegate.can_select_cells(cx))
+        {
+            if cols_count == 0 {
+                return;
+            }
+            self.set_selected_cells(
+                CellRange::new(
+                    TableCell::new(0, 0),
+                    TableCell::new(rows_count - 1, cols_count - 1),
+                ),
+                cx,
+            );
+        } else if self.delegate.can_select_rows(cx) {
+            self.selection_state = SelectionState::Row;
+            self.selected_rows.select_all(rows_count);
+            self.emit_selection_changed(cx);
+            cx.notify();
+        }
+    }
+
+    fn emit_selection_changed(&self, cx: &mut Context<Self>) {
+        cx.emit(TableEvent::SelectionChanged(self.selection()));
+    }
+
+    fn is_row_selected(&self, row_ix: usize) -> bool {
+        self.selection_state == SelectionState::Row && self.selected_rows.contains(row_ix)
+    }
+
     /// Returns the selected column index.
     pub fn selected_col(&self) -> Option<usize> {
         self.selected_col
@@ -540,9 +849,279 @@ where
         self.selection_state = SelectionState::Row;
         self.selected_row = None;
         self.selected_col = None;
+        self.selected_rows.clear();
+        self.selected_cells = None;
+        self.emit_selection_changed(cx);
         cx.notify();
     }
 
+    /// Returns the cell in editing.
+    pub fn editing_cell(&self) -> Option<TableCell> {
+        self.editing.as_ref().map(|editing| editing.cell)
+    }
+
+    /// Start editing the cell, the cell in editing is committed first.
+    ///
+    /// Returns false if the cell can't be edited or the commit is failed.
+    pub fn edit_cell(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let cell = TableCell::new(row_ix, col_ix);
+        if self.editing_cell() == Some(cell) {
+            return true;
+        }
+        if !self.delegate.can_edit_cell(row_ix, col_ix, cx) || !self.commit_edit(window, cx) {
+            return false;
+        }
+        let Some(editor) = self.delegate.cell_editor(row_ix, col_ix, window, cx) else {
+            return false;
+        };
+
+        editor.focus(window);
+        self.editing = Some(EditingCell {
+            cell,
+            editor,
+            error: None,
+        });
+        cx.notify();
+        true
+    }
+
+    /// Commit the value of the editing cell by [`TableDelegate::commit_cell`], and stop editing.
+    ///
+    /// Returns false if the commit is failed, the editor keeps open to show the error.
+    pub fn commit_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let Some(editing) = self.editing.as_ref() else {
+            return true;
+        };
+        let cell = editing.cell;
+        let value = editing.editor.value(cx);
+
+        match self
+            .delegate
+            .commit_cell(cell.row, cell.col, value, window, cx)
+        {
+            Ok(()) => {
+                self.editing = None;
+                self.focus_handle.focus(window);
+                cx.notify();
+                true
+            }
+            Err(error) => {
+                if let Some(editing) = self.editing.as_mut() {
+                    editing.editor.set_invalid(true, window, cx);
+                    editing.error = Some(error);
+                    editing.editor.focus(window);
+                }
+                cx.notify();
+                false
+            }
+        }
+    }
+
+    /// Cancel the editing, the value of the editor is discarded.
+    pub fn cancel_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.editing.take().is_some() {
+            self.focus_handle.focus(window);
+            cx.notify();
+        }
+    }
+
+    /// Expand or collapse the row by [`TableDelegate::set_row_expanded`].
+    pub fn set_row_expanded(
+        &mut self,
+        row_ix: usize,
+        expanded: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.delegate.row_has_children(row_ix, cx)
+            || self.delegate.is_row_expanded(row_ix, cx) == expanded
+        {
+            return;
+        }
+
+        // The rows may be inserted or removed, the cell in editing is moved.
+        self.cancel_edit(window, cx);
+        self.tree.cancel_loading(row_ix);
+        let rows_count = self.delegate.rows_count(cx);
+
+        let task = self.delegate.set_row_expanded(row_ix, expanded, window, cx);
+        let delta = self.delegate.rows_count(cx) as isize - rows_count as isize;
+        self.rows_changed(row_ix, delta, cx);
+
+        if let Some(task) = task {
+            self.tree.start_loading(row_ix, |id| {
+                cx.spawn_in(window, async move |view, window| {
+                    let inserted = task.await;
+                    _ = view.update_in(window, |view, _, cx| {
+                        if let Some(row_ix) = view.tree.finish_loading(id) {
+                            view.rows_changed(row_ix, inserted as isize, cx);
+                        }
+                        cx.notify();
+                    });
+                })
+            });
+        }
+        cx.notify();
+    }
+
+    /// Toggle the row between expanded and collapsed.
+    pub fn toggle_row(&mut self, row_ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let expanded = self.delegate.is_row_expanded(row_ix, cx);
+        self.set_row_expanded(row_ix, !expanded, window, cx);
+    }
+
+    /// Returns true if the children of the row are loading.
+    pub fn is_row_loading(&self, row_ix: usize) -> bool {
+        self.tree.is_loading(row_ix)
+    }
+
+    /// Shift the loading rows and the selection after the `delta` children of the row were
+    /// inserted (or removed if negative), to keep the selection on the same rows.
+    fn rows_changed(&mut self, row_ix: usize, delta: isize, cx: &mut Context<Self>) {
+        if delta == 0 {
+            return;
+        }
+
+        self.tree.rows_changed(row_ix, delta);
+        let selection = self.selection();
+        self.selected_rows.shift(row_ix, delta);
+        if let Some(range) = &mut self.selected_cells {
+            range.shift_rows(row_ix, delta);
+        }
+        self.right_clicked_row = self
+            .right_clicked_row
+            .map(|ix| shift_row(ix, row_ix, delta));
+        if let Some(selected_row) = self.selected_row {
+            // Select the collapsed row instead of its removed descendant.
+            if is_removed_row(selected_row, row_ix, delta) && !self.selected_rows.contains(row_ix) {
+                self.selected_rows.toggle(row_ix);
+            }
+            self.selected_row = Some(shift_row(selected_row, row_ix, delta));
+        }
+        if self.selection() != selection {
+            self.emit_selection_changed(cx);
+        }
+    }
+
+    /// Returns the current cell to start editing by `enter`.
+    fn current_cell(&self, cx: &App) -> Option<TableCell> {
+        if self.selection_state == SelectionState::Cell {
+            return self.selected_cells.map(|range| range.focus);
+        }
+
+        let row_ix = self.selected_row?;
+        let col_ix = self
+            .selected_col
+            .filter(|col_ix| self.delegate.can_edit_cell(row_ix, *col_ix, cx))
+            .or_else(|| {
+                (0..self.delegate.cols_count(cx))
+                    .find(|col_ix| self.delegate.can_edit_cell(row_ix, *col_ix, cx))
+            })?;
+        Some(TableCell::new(row_ix, col_ix))
+    }
+
+    /// Commit the editing cell and edit the next (or previous if `backward`) editable cell.
+    fn edit_next_cell(&mut self, backward: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(cell) = self.editing_cell() else {
+            return;
+        };
+        let next_cell = next_editable_cell(
+            cell,
+            self.delegate.rows_count(cx),
+            self.delegate.cols_count(cx),
+            backward,
+            |cell| self.delegate.can_edit_cell(cell.row, cell.col, cx),
+        );
+
+        if !self.commit_edit(window, cx) {
+            return;
+        }
+        if let Some(next_cell) = next_cell {
+            if next_cell.row != cell.row && self.selection_state == SelectionState::Row {
+                self.set_selected_row(next_cell.row, cx);
+            }
+            self.edit_cell(next_cell.row, next_cell.col, window, cx);
+        }
+    }
+
+    /// Export all the rows of the table to the format, with the column names as the header.
+    pub fn export(&self, format: ExportFormat, cx: &App) -> String {
+        let rows: Vec<usize> = (0..self.delegate.rows_count(cx)).collect();
+        let cols: Vec<usize> = (0..self.delegate.cols_count(cx)).collect();
+        self.export_cells(format, &rows, &cols, cx)
+    }
+
+    /// Export the selected rows, cells or column to the format, with the column names as header.
+    pub fn export_selection(&self, format: ExportFormat, cx: &App) -> String {
+        let (rows, cols) = self.selection_area(cx);
+        self.export_cells(format, &rows, &cols, cx)
+    }
+
+    /// Copy the selected rows, cells or column to the clipboard as TSV, to paste into spreadsheets.
+    ///
+    /// The HTML table of the cells is written as the metadata of the clipboard item,
+    /// because the clipboard of GPUI only supports the plain text.
+    pub fn copy_selection(&self, cx: &mut App) {
+        let (rows, cols) = self.selection_area(cx);
+        if rows.is_empty() || cols.is_empty() {
+            return;
+        }
+
+        let cells = self.cells_text(&rows, &cols, cx);
+        cx.write_to_clipboard(ClipboardItem::new_string_with_metadata(
+            to_delimited(None, &cells, '\t'),
+            to_html(None, &cells),
+        ));
+    }
+
+    /// Returns the rows and columns of the selection.
+    fn selection_area(&self, cx: &App) -> (Vec<usize>, Vec<usize>) {
+        match self.selection_state {
+            SelectionState::Row => (
+                self.selected_rows.rows(),
+                (0..self.delegate.cols_count(cx)).collect(),
+            ),
+            SelectionState::Cell => self
+                .selected_cells
+                .map(|range| (range.rows().collect(), range.cols().collect()))
+                .unwrap_or_default(),
+            SelectionState::Column => self
+                .selected_col
+                .map(|col_ix| ((0..self.delegate.rows_count(cx)).collect(), vec![col_ix]))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn cells_text(&self, rows: &[usize], cols: &[usize], cx: &App) -> Vec<Vec<SharedString>> {
+        rows.iter()
+            .map(|row_ix| {
+                cols.iter()
+                    .map(|col_ix| self.delegate.cell_text(*row_ix, *col_ix, cx))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn export_cells(
+        &self,
+        format: ExportFormat,
+        rows: &[usize],
+        cols: &[usize],
+        cx: &App,
+    ) -> String {
+        let header: Vec<SharedString> = cols
+            .iter()
+            .map(|col_ix| self.delegate.col_name(*col_ix, cx))
+            .collect();
+        format.format(&header, &self.cells_text(rows, cols, cx))
+    }
+
     /// Returns the visible range of the rows and columns.
     pub fn visible_range(&self) -> &VisibleRangeState {
         &self.visible_range
@@ -552,30 +1131,245 @@ where
         &mut self,
         ev: &MouseDownEvent,
         row_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // Commit the editing cell when click outside the editor.
+        if !self.commit_edit(window, cx) {
+            return;
+        }
+
+        if ev.button == MouseButton::Right {
+            self.right_clicked_row = Some(row_ix);
+        } else if self.delegate.can_select_rows(cx)
+            && (ev.modifiers.shift || ev.modifiers.secondary())
+        {
+            if ev.modifiers.shift {
+                self.selected_rows.extend(row_ix);
+            } else {
+                self.selected_rows.toggle(row_ix);
+            }
+            self.selection_state = SelectionState::Row;
+            self.right_clicked_row = None;
+            self.selected_row = Some(row_ix);
+            self.emit_selection_changed(cx);
+            cx.notify();
+        } else {
+            self.set_selected_row(row_ix, cx);
+
+            if ev.click_count == 2 {
+                cx.emit(TableEvent::DoubleClickedRow(row_ix));
+            }
+        }
+    }
+
+    fn on_cell_mouse_down(
+        &mut self,
+        ev: &MouseDownEvent,
+        cell: TableCell,
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if ev.button == MouseButton::Right {
-            self.right_clicked_row = Some(row_ix);
-        } else {
-            self.set_selected_row(row_ix, cx);
+        if ev.modifiers.shift && self.selection_state == SelectionState::Cell {
+            if let Some(range) = self.selected_cells {
+                cx.stop_propagation();
+                self.set_selected_cells(CellRange::new(range.anchor, cell), cx);
+                return;
+            }
+        }
+
+        self.cell_drag_anchor = Some(cell);
+    }
+
+    fn on_cell_mouse_move(
+        &mut self,
+        ev: &MouseMoveEvent,
+        cell: TableCell,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if ev.pressed_button != Some(MouseButton::Left) {
+            return;
+        }
+        let Some(anchor) = self.cell_drag_anchor else {
+            return;
+        };
+
+        let range = CellRange::new(anchor, cell);
+        if self.selection_state == SelectionState::Cell {
+            if self.selected_cells == Some(range) {
+                return;
+            }
+        } else if anchor == cell {
+            return;
+        }
+
+        self.set_selected_cells(range, cx);
+    }
+
+    /// Stop the drag selection of the cells, the mouse may be released outside the table.
+    fn on_cell_mouse_up(&mut self, _: &MouseUpEvent, _: &mut Window, _: &mut Context<Self>) {
+        self.cell_drag_anchor = None;
+    }
+
+    fn on_col_head_click(&mut self, col_ix: usize, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.delegate.can_select_col(col_ix, cx) {
+            return;
+        }
+
+        self.set_selected_col(col_ix, cx)
+    }
+
+    fn action_cancel(&mut self, _: &Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        if self.editing.is_some() {
+            self.cancel_edit(window, cx);
+            return;
+        }
+
+        self.clear_selection(cx);
+    }
+
+    fn action_copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        if self.editing.is_some() {
+            cx.propagate();
+            return;
+        }
+
+        self.copy_selection(cx);
+    }
+
+    fn action_confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.editing.is_some() {
+            self.commit_edit(window, cx);
+            return;
+        }
+
+        match self.current_cell(cx) {
+            Some(cell) => {
+                self.edit_cell(cell.row, cell.col, window, cx);
+            }
+            None => cx.propagate(),
+        }
+    }
+
+    fn action_select_next_cell(
+        &mut self,
+        _: &SelectNextCell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.editing.is_none() {
+            cx.propagate();
+            return;
+        }
+
+        self.edit_next_cell(false, window, cx);
+    }
+
+    fn action_select_prev_cell(
+        &mut self,
+        _: &SelectPrevCell,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.editing.is_none() {
+            cx.propagate();
+            return;
+        }
+
+        self.edit_next_cell(true, window, cx);
+    }
+
+    fn on_editor_enter(
+        &mut self,
+        action: &input::Enter,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // Keep `secondary-enter` to insert a new line in the multi-line input.
+        if action.secondary {
+            return;
+        }
+
+        cx.stop_propagation();
+        self.commit_edit(window, cx);
+    }
+
+    fn on_editor_escape(&mut self, _: &input::Escape, window: &mut Window, cx: &mut Context<Self>) {
+        self.cancel_edit(window, cx);
+    }
+
+    fn on_editor_indent(&mut self, _: &input::Indent, window: &mut Window, cx: &mut Context<Self>) {
+        cx.stop_propagation();
+        self.edit_next_cell(false, window, cx);
+    }
+
+    fn on_editor_outdent(
+        &mut self,
+        _: &input::Outdent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.stop_propagation();
+        self.edit_next_cell(true, window, cx);
+    }
 
-            if ev.click_count == 2 {
-                cx.emit(TableEvent::DoubleClickedRow(row_ix));
-            }
-        }
+    fn action_select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_all(cx);
     }
 
-    fn on_col_head_click(&mut self, col_ix: usize, _: &mut Window, cx: &mut Context<Self>) {
-        if !self.delegate.can_select_col(col_ix, cx) {
+    /// Extend the selected rows or cells by the offset of the rows and columns.
+    fn extend_selection(&mut self, rows: isize, cols: isize, cx: &mut Context<Self>) {
+        let rows_count = self.delegate.rows_count(cx);
+        let cols_count = self.delegate.cols_count(cx);
+        if rows_count == 0 || cols_count == 0 {
             return;
         }
+        let move_by = |ix: usize, offset: isize, count: usize| {
+            ix.saturating_add_signed(offset).min(count - 1)
+        };
 
-        self.set_selected_col(col_ix, cx)
+        match self.selection_state {
+            SelectionState::Cell => {
+                let Some(range) = self.selected_cells else {
+                    return;
+                };
+                let focus = TableCell::new(
+                    move_by(range.focus.row, rows, rows_count),
+                    move_by(range.focus.col, cols, cols_count),
+                );
+                self.scroll_to_row_item(focus.row, cx);
+                self.set_selected_cells(CellRange::new(range.anchor, focus), cx);
+            }
+            SelectionState::Row if rows != 0 && self.delegate.can_select_rows(cx) => {
+                let Some(selected_row) = self.selected_row else {
+                    return;
+                };
+                let row_ix = move_by(selected_row, rows, rows_count);
+                self.selected_rows.extend(row_ix);
+                self.selected_row = Some(row_ix);
+                self.scroll_to_row_item(row_ix, cx);
+                self.emit_selection_changed(cx);
+                cx.notify();
+            }
+            _ => {}
+        }
     }
 
-    fn action_cancel(&mut self, _: &Cancel, _: &mut Window, cx: &mut Context<Self>) {
-        self.clear_selection(cx);
+    fn action_select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.extend_selection(-1, 0, cx);
+    }
+
+    fn action_select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.extend_selection(1, 0, cx);
+    }
+
+    fn action_select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.extend_selection(0, -1, cx);
+    }
+
+    fn action_select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.extend_selection(0, 1, cx);
     }
 
     fn action_select_prev(&mut self, _: &SelectPrev, _: &mut Window, cx: &mut Context<Self>) {
@@ -620,9 +1414,13 @@ where
     fn action_select_prev_col(
         &mut self,
         _: &SelectPrevColumn,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.expand_selected_row(false, window, cx) {
+            return;
+        }
+
         let mut selected_col = self.selected_col.unwrap_or(0);
         let cols_count = self.delegate.cols_count(cx);
         if selected_col > 0 {
@@ -638,9 +1436,13 @@ where
     fn action_select_next_col(
         &mut self,
         _: &SelectNextColumn,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if self.expand_selected_row(true, window, cx) {
+            return;
+        }
+
         let mut selected_col = self.selected_col.unwrap_or(0);
         if selected_col < self.delegate.cols_count(cx).saturating_sub(1) {
             selected_col += 1;
@@ -653,6 +1455,42 @@ where
         self.set_selected_col(selected_col, cx);
     }
 
+    /// Expand the selected row or select its first child by `right`,
+    /// collapse the selected row or select its parent by `left`.
+    ///
+    /// Returns false if the selected row is not a row of the tree.
+    fn expand_selected_row(
+        &mut self,
+        expand: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(row_ix) = self.selected_row else {
+            return false;
+        };
+        if self.selection_state != SelectionState::Row || !self.delegate.is_tree(cx) {
+            return false;
+        }
+
+        let has_children = self.delegate.row_has_children(row_ix, cx);
+        let expanded = self.delegate.is_row_expanded(row_ix, cx);
+        if has_children && expanded != expand {
+            self.set_row_expanded(row_ix, expand, window, cx);
+        } else if expand {
+            let depth = self.delegate.row_depth(row_ix, cx);
+            let child_ix = row_ix + 1;
+            if has_children
+                && child_ix < self.delegate.rows_count(cx)
+                && self.delegate.row_depth(child_ix, cx) > depth
+            {
+                self.set_selected_row(child_ix, cx);
+            }
+        } else if let Some(parent_ix) = parent_row(row_ix, |ix| self.delegate.row_depth(ix, cx)) {
+            self.set_selected_row(parent_ix, cx);
+        }
+        true
+    }
+
     /// Scroll table when mouse position is near the edge of the table bounds.
     fn scroll_table_by_col_resizing(&mut self, mouse_position: Point<Pixels>, col_group: ColGroup) {
         // Do nothing if pos out of the table bounds right for avoid scroll to the right.
@@ -742,7 +1580,7 @@ where
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if col_ix == to_ix {
+        if !can_move_col(&self.col_fixed_groups(), col_ix, to_ix) {
             return;
         }
 
@@ -817,12 +1655,97 @@ where
                 self.head_content_bounds.origin
             },
             size: gpui::size(
-                self.fixed_head_cols_bounds.size.width + self.head_content_bounds.size.width,
+                self.fixed_head_cols_bounds.size.width
+                    + self.head_content_bounds.size.width
+                    + self.fixed_right_head_cols_bounds.size.width,
                 self.head_content_bounds.size.height,
             ),
         }
     }
 
+    /// Returns the sizes of the scrollable (not fixed) columns.
+    fn scrollable_col_sizes(&self) -> Rc<Vec<gpui::Size<Pixels>>> {
+        Rc::new(
+            self.col_groups
+                .iter()
+                .filter(|col| col.fixed.is_none())
+                .map(|col| col.bounds.size)
+                .collect(),
+        )
+    }
+
+    /// Returns true if the content is scrolled under the fixed columns or frozen rows at the edge.
+    fn is_scrolled_under(&self, edge: ScrollEdge, cx: &App) -> bool {
+        let offset = self.horizontal_scroll_handle.offset();
+        match edge {
+            ScrollEdge::Left => self.fixed_cols.left > 0 && offset.x < px(0.),
+            ScrollEdge::Right => {
+                self.fixed_cols.right > 0
+                    && self.head_content_bounds.size.width + offset.x
+                        > self.horizontal_scroll_handle.bounds().size.width + px(1.)
+            }
+            ScrollEdge::Top => {
+                self.delegate.frozen_rows(cx) > 0
+                    && self
+                        .vertical_scroll_handle
+                        .0
+                        .borrow()
+                        .base_handle
+                        .offset()
+                        .y
+                        < px(0.)
+            }
+        }
+    }
+
+    /// Render the shadow at the edge of the scrollable area, it must be the last child of the area.
+    fn render_scroll_shadow(&self, edge: ScrollEdge, cx: &App) -> Div {
+        let color = cx.theme().foreground.opacity(0.15);
+        let el = div().absolute();
+
+        match edge {
+            ScrollEdge::Left => {
+                el.top_0()
+                    .bottom_0()
+                    .left(px(-1.))
+                    .w(px(1.))
+                    .shadow(smallvec::smallvec![box_shadow(
+                        px(2.),
+                        px(0.),
+                        px(6.),
+                        px(0.),
+                        color
+                    )])
+            }
+            ScrollEdge::Right => {
+                el.top_0()
+                    .bottom_0()
+                    .right(px(-1.))
+                    .w(px(1.))
+                    .shadow(smallvec::smallvec![box_shadow(
+                        px(-2.),
+                        px(0.),
+                        px(6.),
+                        px(0.),
+                        color
+                    )])
+            }
+            ScrollEdge::Top => {
+                el.top(px(-1.))
+                    .left_0()
+                    .right_0()
+                    .h(px(1.))
+                    .shadow(smallvec::smallvec![box_shadow(
+                        px(0.),
+                        px(2.),
+                        px(6.),
+                        px(0.),
+                        color
+                    )])
+            }
+        }
+    }
+
     #[inline]
     fn render_cell(&self, col_ix: usize, _window: &mut Window, _cx: &mut Context<Self>) -> Div {
         let Some(col_group) = self.col_groups.get(col_ix) else {
@@ -849,20 +1772,159 @@ where
             })
     }
 
-    /// Show Column selection style, when the column is selected and the selection state is Column.
-    fn render_col_wrap(&self, col_ix: usize, _: &mut Window, cx: &mut Context<Self>) -> Div {
+    /// Show Column selection style, when the column is selected and the selection state is Column,
+    /// or the cell selection style, when the cell is in the selected cells.
+    fn render_col_wrap(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Div {
         let el = h_flex().h_full();
-
-        if self.delegate().can_select_col(col_ix, cx)
+        let is_col_selected = self.delegate().can_select_col(col_ix, cx)
             && self.selected_col == Some(col_ix)
-            && self.selection_state == SelectionState::Column
-        {
-            el.bg(cx.theme().table_active)
-        } else {
-            el
+            && self.selection_state == SelectionState::Column;
+        let is_cell_selected = self
+            .selected_cells()
+            .map_or(false, |range| range.contains(row_ix, col_ix));
+        let cell = TableCell::new(row_ix, col_ix);
+
+        el.when(is_col_selected || is_cell_selected, |this| {
+            this.bg(cx.theme().table_active)
+        })
+        .when(self.delegate().can_edit_cell(row_ix, col_ix, cx), |this| {
+            this.on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, ev: &MouseDownEvent, window, cx| {
+                    if ev.click_count == 2 && this.edit_cell(cell.row, cell.col, window, cx) {
+                        cx.stop_propagation();
+                    }
+                }),
+            )
+        })
+        .when(self.delegate().can_select_cells(cx), |this| {
+            this.on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, ev, window, cx| {
+                    this.on_cell_mouse_down(ev, cell, window, cx);
+                }),
+            )
+            .on_mouse_move(cx.listener(move |this, ev, window, cx| {
+                this.on_cell_mouse_move(ev, cell, window, cx);
+            }))
+        })
+    }
+
+    /// Render the cell of the body, or the editor if the cell is in editing.
+    fn render_body_cell(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        let el = self.render_col_wrap(row_ix, col_ix, window, cx);
+        let cell = self.render_cell(col_ix, window, cx);
+
+        match self.render_cell_editor(row_ix, col_ix, window, cx) {
+            Some(editor) => el.child(cell.py_0().px_0p5().child(editor)),
+            None if col_ix == 0 && self.delegate.is_tree(cx) => {
+                let td = self.measure_render_td(row_ix, col_ix, window, cx);
+                el.child(cell.child(self.render_tree_td(row_ix, td, cx)))
+            }
+            None => el.child(cell.child(self.measure_render_td(row_ix, col_ix, window, cx))),
         }
     }
 
+    /// Render the first cell of the tree row, with the indentation and the disclosure chevron.
+    fn render_tree_td(
+        &self,
+        row_ix: usize,
+        td: impl IntoElement,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let depth = self.delegate.row_depth(row_ix, cx);
+        let has_children = self.delegate.row_has_children(row_ix, cx);
+        let expanded = self.delegate.is_row_expanded(row_ix, cx);
+
+        h_flex()
+            .size_full()
+            .gap_1()
+            .pl(TREE_INDENT * depth as f32)
+            .child(
+                h_flex()
+                    .id(("table-tree-toggle", row_ix))
+                    .flex_shrink_0()
+                    .size_4()
+                    .justify_center()
+                    .when(has_children, |this| {
+                        this.text_color(cx.theme().muted_foreground)
+                            .rounded(cx.theme().radius)
+                            .hover(|this| this.bg(cx.theme().secondary_hover))
+                            .child(
+                                Icon::new(if expanded {
+                                    IconName::ChevronDown
+                                } else {
+                                    IconName::ChevronRight
+                                })
+                                .size_3p5(),
+                            )
+                            // Toggle the row without changing the selection.
+                            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.toggle_row(row_ix, window, cx);
+                            }))
+                    }),
+            )
+            .child(div().flex_1().overflow_hidden().child(td))
+    }
+
+    /// Render the loading row below the row that is loading the children.
+    fn render_tree_loading_row(&self, row_ix: usize, cx: &mut Context<Self>) -> impl IntoElement {
+        let depth = self.delegate.row_depth(row_ix, cx) + 1;
+
+        div()
+            .w_full()
+            .pl(TREE_INDENT * depth as f32)
+            .child(loading::LoadingRow::row().size(self.size))
+    }
+
+    fn render_cell_editor(
+        &self,
+        row_ix: usize,
+        col_ix: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<AnyElement> {
+        let editing = self
+            .editing
+            .as_ref()
+            .filter(|editing| editing.cell == TableCell::new(row_ix, col_ix))?;
+        let size = match self.size {
+            Size::XSmall => Size::XSmall,
+            Size::Large => Size::Medium,
+            _ => Size::Small,
+        };
+
+        Some(
+            h_flex()
+                .id("table-cell-editor")
+                .size_full()
+                .capture_action(cx.listener(Self::on_editor_enter))
+                .capture_action(cx.listener(Self::on_editor_indent))
+                .capture_action(cx.listener(Self::on_editor_outdent))
+                .on_action(cx.listener(Self::on_editor_escape))
+                // Avoid the row click to commit the editing.
+                .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                .when_some(editing.error.clone(), |this, error| {
+                    this.tooltip(move |window, cx| Tooltip::new(error.clone()).build(window, cx))
+                })
+                .child(editing.editor.render(size, window, cx))
+                .into_any_element(),
+        )
+    }
+
     fn render_vertical_scrollbar(
         &self,
         _: &mut Window,
@@ -874,7 +1936,7 @@ where
             div()
                 .occlude()
                 .absolute()
-                .top(self.size.table_row_height())
+                .top(self.size.table_row_height() * (1 + self.delegate.frozen_rows(cx)) as f32)
                 .right_0()
                 .bottom_0()
                 .w(scroll::WIDTH)
@@ -1136,6 +2198,7 @@ where
     ) -> impl IntoElement {
         let view = cx.entity().clone();
         let horizontal_scroll_handle = self.horizontal_scroll_handle.clone();
+        let right_cols_range = self.fixed_cols.right_range(self.col_groups.len());
 
         h_flex()
             .w_full()
@@ -1184,39 +2247,87 @@ where
                 )
             })
             .child(
-                // Columns
                 h_flex()
-                    .id("table-head")
                     .size_full()
-                    .overflow_scroll()
                     .relative()
-                    .track_scroll(&horizontal_scroll_handle)
-                    .bg(cx.theme().table_head)
+                    .overflow_hidden()
                     .child(
+                        // Columns
                         h_flex()
+                            .id("table-head")
+                            .size_full()
+                            .overflow_scroll()
                             .relative()
-                            .children(
-                                self.col_groups
-                                    .iter()
-                                    .filter(|col| col.fixed == None)
-                                    .enumerate()
-                                    .map(|(col_ix, _)| {
-                                        self.render_th(left_cols_count + col_ix, window, cx)
-                                    }),
-                            )
-                            .child(self.delegate.render_last_empty_col(window, cx))
+                            .track_scroll(&horizontal_scroll_handle)
+                            .bg(cx.theme().table_head)
                             .child(
-                                canvas(
-                                    move |bounds, _, cx| {
-                                        view.update(cx, |r, _| r.head_content_bounds = bounds)
-                                    },
-                                    |_, _, _, _| {},
-                                )
-                                .absolute()
-                                .size_full(),
+                                h_flex()
+                                    .relative()
+                                    .children(
+                                        self.col_groups
+                                            .iter()
+                                            .filter(|col| col.fixed == None)
+                                            .enumerate()
+                                            .map(|(col_ix, _)| {
+                                                self.render_th(left_cols_count + col_ix, window, cx)
+                                            }),
+                                    )
+                                    .child(self.delegate.render_last_empty_col(window, cx))
+                                    .child({
+                                        let view = view.clone();
+                                        canvas(
+                                            move |bounds, _, cx| {
+                                                view.update(cx, |r, _| {
+                                                    r.head_content_bounds = bounds
+                                                })
+                                            },
+                                            |_, _, _, _| {},
+                                        )
+                                        .absolute()
+                                        .size_full()
+                                    }),
                             ),
-                    ),
+                    )
+                    .when(self.is_scrolled_under(ScrollEdge::Left, cx), |this| {
+                        this.child(self.render_scroll_shadow(ScrollEdge::Left, cx))
+                    })
+                    .when(self.is_scrolled_under(ScrollEdge::Right, cx), |this| {
+                        this.child(self.render_scroll_shadow(ScrollEdge::Right, cx))
+                    }),
             )
+            .when(!right_cols_range.is_empty(), |this| {
+                // Render right fixed columns
+                this.child(
+                    h_flex()
+                        .relative()
+                        .h_full()
+                        .flex_shrink_0()
+                        .bg(cx.theme().table_head)
+                        .children(right_cols_range.map(|col_ix| self.render_th(col_ix, window, cx)))
+                        .child(
+                            // Fixed columns border
+                            div()
+                                .absolute()
+                                .top_0()
+                                .left_0()
+                                .bottom_0()
+                                .w_0()
+                                .flex_shrink_0()
+                                .border_l_1()
+                                .border_color(cx.theme().border),
+                        )
+                        .child(
+                            canvas(
+                                move |bounds, _, cx| {
+                                    view.update(cx, |r, _| r.fixed_right_head_cols_bounds = bounds)
+                                },
+                                |_, _, _, _| {},
+                            )
+                            .absolute()
+                            .size_full(),
+                        ),
+                )
+            })
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1231,8 +2342,9 @@ where
         cx: &mut Context<Self>,
     ) -> impl IntoElement {
         let horizontal_scroll_handle = self.horizontal_scroll_handle.clone();
+        let right_cols_range = self.fixed_cols.right_range(cols_count);
         let is_stripe_row = self.stripe && row_ix % 2 != 0;
-        let is_selected = self.selected_row == Some(row_ix);
+        let is_selected = self.is_row_selected(row_ix);
         let view = cx.entity().clone();
 
         if row_ix < rows_count {
@@ -1264,11 +2376,7 @@ where
                                 let mut items = Vec::with_capacity(left_cols_count);
 
                                 (0..left_cols_count).for_each(|col_ix| {
-                                    items.push(self.render_col_wrap(col_ix, window, cx).child(
-                                        self.render_cell(col_ix, window, cx).child(
-                                            self.measure_render_td(row_ix, col_ix, window, cx),
-                                        ),
-                                    ));
+                                    items.push(self.render_body_cell(row_ix, col_ix, window, cx));
                                 });
 
                                 items
@@ -1314,16 +2422,9 @@ where
 
                                         visible_range.for_each(|col_ix| {
                                             let col_ix = col_ix + left_cols_count;
-                                            let el =
-                                                table.render_col_wrap(col_ix, window, cx).child(
-                                                    table.render_cell(col_ix, window, cx).child(
-                                                        table.measure_render_td(
-                                                            row_ix, col_ix, window, cx,
-                                                        ),
-                                                    ),
-                                                );
-
-                                            items.push(el);
+                                            items.push(
+                                                table.render_body_cell(row_ix, col_ix, window, cx),
+                                            );
                                         });
 
                                         items
@@ -1332,25 +2433,52 @@ where
                             )
                             .with_scroll_handle(&self.horizontal_scroll_handle),
                         )
-                        .child(self.delegate.render_last_empty_col(window, cx)),
+                        .child(self.delegate.render_last_empty_col(window, cx))
+                        .when(self.is_scrolled_under(ScrollEdge::Left, cx), |this| {
+                            this.child(self.render_scroll_shadow(ScrollEdge::Left, cx))
+                        })
+                        .when(self.is_scrolled_under(ScrollEdge::Right, cx), |this| {
+                            this.child(self.render_scroll_shadow(ScrollEdge::Right, cx))
+                        }),
                 )
-                // Row selected style
-                .when_some(self.selected_row, |this, _| {
-                    this.when(
-                        is_selected && self.selection_state == SelectionState::Row,
-                        |this| {
-                            this.border_color(gpui::transparent_white()).child(
+                .when(!right_cols_range.is_empty(), |this| {
+                    // Right fixed columns
+                    this.child(
+                        h_flex()
+                            .relative()
+                            .h_full()
+                            .flex_shrink_0()
+                            .children(
+                                right_cols_range
+                                    .map(|col_ix| self.render_body_cell(row_ix, col_ix, window, cx))
+                                    .collect::<Vec<_>>(),
+                            )
+                            .child(
+                                // Fixed columns border
                                 div()
-                                    .top(if row_ix == 0 { px(0.) } else { px(-1.) })
-                                    .left(px(0.))
-                                    .right(px(0.))
-                                    .bottom_0()
                                     .absolute()
-                                    .bg(cx.theme().table_active)
-                                    .border_1()
-                                    .border_color(cx.theme().table_active_border),
-                            )
-                        },
+                                    .top_0()
+                                    .left_0()
+                                    .bottom_0()
+                                    .w_0()
+                                    .flex_shrink_0()
+                                    .border_l_1()
+                                    .border_color(cx.theme().border),
+                            ),
+                    )
+                })
+                // Row selected style
+                .when(is_selected, |this| {
+                    this.border_color(gpui::transparent_white()).child(
+                        div()
+                            .top(if row_ix == 0 { px(0.) } else { px(-1.) })
+                            .left(px(0.))
+                            .right(px(0.))
+                            .bottom_0()
+                            .absolute()
+                            .bg(cx.theme().table_active)
+                            .border_1()
+                            .border_color(cx.theme().table_active_border),
                     )
                 })
                 // Row right click row style
@@ -1501,21 +2629,58 @@ where
         let cols_count: usize = self.delegate.cols_count(cx);
         let left_cols_count = self.fixed_cols.left;
         let rows_count = self.delegate.rows_count(cx);
+        let frozen_rows = self.delegate.frozen_rows(cx).min(rows_count);
+        // The rows in the scrollable body, below the frozen rows.
+        let body_rows_count = rows_count - frozen_rows;
+        // The loading rows of the tree are shown in the body.
+        let loading_rows = Rc::new(self.tree.loading_rows(frozen_rows));
+        let body_items_count = body_rows_count + loading_rows.len();
         let loading = self.delegate.loading(cx);
-        let extra_rows_needed = self.calculate_extra_rows_needed(rows_count);
+        let extra_rows_needed = self.calculate_extra_rows_needed(body_items_count);
 
         let inner_table = v_flex()
             .key_context("Table")
             .id("table")
             .track_focus(&self.focus_handle)
             .on_action(cx.listener(Self::action_cancel))
+            .on_action(cx.listener(Self::action_confirm))
+            .on_action(cx.listener(Self::action_copy))
+            .on_action(cx.listener(Self::action_select_next_cell))
+            .on_action(cx.listener(Self::action_select_prev_cell))
             .on_action(cx.listener(Self::action_select_next))
             .on_action(cx.listener(Self::action_select_prev))
             .on_action(cx.listener(Self::action_select_next_col))
             .on_action(cx.listener(Self::action_select_prev_col))
+            .on_action(cx.listener(Self::action_select_up))
+            .on_action(cx.listener(Self::action_select_down))
+            .on_action(cx.listener(Self::action_select_left))
+            .on_action(cx.listener(Self::action_select_right))
+            .on_action(cx.listener(Self::action_select_all))
+            .on_mouse_up(MouseButton::Left, cx.listener(Self::on_cell_mouse_up))
+            .on_mouse_up_out(MouseButton::Left, cx.listener(Self::on_cell_mouse_up))
             .size_full()
             .overflow_hidden()
             .child(self.render_table_head(left_cols_count, window, cx))
+            .when(frozen_rows > 0, |this| {
+                let col_sizes = self.scrollable_col_sizes();
+                this.child(
+                    v_flex().w_full().flex_shrink_0().children(
+                        (0..frozen_rows)
+                            .map(|row_ix| {
+                                self.render_table_row(
+                                    row_ix,
+                                    rows_count,
+                                    left_cols_count,
+                                    col_sizes.clone(),
+                                    cols_count,
+                                    window,
+                                    cx,
+                                )
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                )
+            })
             .context_menu({
                 let view = view.clone();
                 move |this, window: &mut Window, cx: &mut Context<PopupMenu>| {
@@ -1537,75 +2702,96 @@ where
                     )
                 } else {
                     this.child(
-                        h_flex().id("table-body").flex_grow().size_full().child(
-                            uniform_list(
-                                view,
-                                "table-uniform-list",
-                                rows_count + extra_rows_needed,
-                                {
-                                    move |table, visible_range, window, cx| {
-                                        // We must calculate the col sizes here, because the col sizes
-                                        // need render_th first, then that method will set the bounds of each col.
-                                        let col_sizes: Rc<Vec<gpui::Size<Pixels>>> = Rc::new(
-                                            table
-                                                .col_groups
-                                                .iter()
-                                                .skip(left_cols_count)
-                                                .map(|col| col.bounds.size)
-                                                .collect(),
-                                        );
-
-                                        table.load_more_if_need(
-                                            rows_count,
-                                            visible_range.end,
-                                            window,
-                                            cx,
-                                        );
-                                        table.update_visible_range_if_need(
-                                            visible_range.clone(),
-                                            Axis::Vertical,
-                                            window,
-                                            cx,
-                                        );
-
-                                        if visible_range.end > rows_count {
-                                            table.scroll_to_row(
-                                                std::cmp::min(
-                                                    visible_range.start,
-                                                    rows_count.saturating_sub(1),
-                                                ),
+                        h_flex()
+                            .id("table-body")
+                            .relative()
+                            .flex_grow()
+                            .size_full()
+                            .child(
+                                uniform_list(
+                                    view,
+                                    "table-uniform-list",
+                                    body_items_count + extra_rows_needed,
+                                    {
+                                        move |table, visible_range, window, cx| {
+                                            // We must calculate the col sizes here, because the col sizes
+                                            // need render_th first, then that method will set the bounds of each col.
+                                            let col_sizes = table.scrollable_col_sizes();
+                                            // The list items are the rows below the frozen rows,
+                                            // with the loading rows of the tree.
+                                            let visible_range = visible_range.start + frozen_rows
+                                                ..visible_range.end + frozen_rows;
+                                            let visible_rows =
+                                                row_ix_at(visible_range.start, &loading_rows)
+                                                    ..row_ix_at(visible_range.end, &loading_rows);
+
+                                            table.load_more_if_need(
+                                                rows_count,
+                                                visible_rows.end,
+                                                window,
                                                 cx,
                                             );
-                                        }
-
-                                        let mut items = Vec::with_capacity(
-                                            visible_range.end.saturating_sub(visible_range.start),
-                                        );
-
-                                        // Render fake rows to fill the table
-                                        visible_range.for_each(|row_ix| {
-                                            // Render real rows for available data
-                                            items.push(table.render_table_row(
-                                                row_ix,
-                                                rows_count,
-                                                left_cols_count,
-                                                col_sizes.clone(),
-                                                cols_count,
+                                            table.update_visible_range_if_need(
+                                                visible_rows.clone(),
+                                                Axis::Vertical,
                                                 window,
                                                 cx,
-                                            ));
-                                        });
+                                            );
 
-                                        items
-                                    }
-                                },
+                                            if visible_rows.end > rows_count {
+                                                table.scroll_to_row(
+                                                    std::cmp::min(
+                                                        visible_rows.start,
+                                                        rows_count.saturating_sub(1),
+                                                    ),
+                                                    cx,
+                                                );
+                                            }
+
+                                            let mut items = Vec::with_capacity(
+                                                visible_range
+                                                    .end
+                                                    .saturating_sub(visible_range.start),
+                                            );
+
+                                            // Render fake rows to fill the table
+                                            visible_range.for_each(|ix| {
+                                                match tree_item(ix, &loading_rows) {
+                                                    TreeItem::Loading(row_ix) => items.push(
+                                                        table
+                                                            .render_tree_loading_row(row_ix, cx)
+                                                            .into_any_element(),
+                                                    ),
+                                                    // Render real rows for available data
+                                                    TreeItem::Row(row_ix) => items.push(
+                                                        table
+                                                            .render_table_row(
+                                                                row_ix,
+                                                                rows_count,
+                                                                left_cols_count,
+                                                                col_sizes.clone(),
+                                                                cols_count,
+                                                                window,
+                                                                cx,
+                                                            )
+                                                            .into_any_element(),
+                                                    ),
+                                                }
+                                            });
+
+                                            items
+                                        }
+                                    },
+                                )
+                                .flex_grow()
+                                .size_full()
+                                .with_sizing_behavior(ListSizingBehavior::Auto)
+                                .track_scroll(vertical_scroll_handle)
+                                .into_any_element(),
                             )
-                            .flex_grow()
-                            .size_full()
-                            .with_sizing_behavior(ListSizingBehavior::Auto)
-                            .track_scroll(vertical_scroll_handle)
-                            .into_any_element(),
-                        ),
+                            .when(self.is_scrolled_under(ScrollEdge::Top, cx), |this| {
+                                this.child(self.render_scroll_shadow(ScrollEdge::Top, cx))
+                            }),
                     )
                 }
             });
@@ -1654,3 +2840,99 @@ where
             )
     }
 }
+
+/// Returns true if the left fixed columns are the first columns, and the right fixed columns are
+/// the last columns.
+fn is_fixed_at_edges(fixed: &[Option<ColFixed>]) -> bool {
+    let left = fixed
+        .iter()
+        .take_while(|fixed| **fixed == Some(ColFixed::Left))
+        .count();
+    let right = fixed[left..]
+        .iter()
+        .rev()
+        .take_while(|fixed| **fixed == Some(ColFixed::Right))
+        .count();
+    fixed[left..fixed.len() - right].iter().all(Option::is_none)
+}
+
+/// Returns true if the column can be moved to the index, the columns are only moved in the same
+/// group, so the fixed columns keep on the sides.
+fn can_move_col(fixed: &[Option<ColFixed>], col_ix: usize, to_ix: usize) -> bool {
+    col_ix != to_ix && col_ix < fixed.len() && to_ix < fixed.len() && fixed[col_ix] == fixed[to_ix]
+}
+
+/// Returns the index of the row in the body list, or None for the frozen rows above the body.
+///
+/// The `loading_rows` are the loading rows of the tree in the body, in ascending order.
+fn body_item_ix(row_ix: usize, frozen_rows: usize, loading_rows: &[usize]) -> Option<usize> {
+    (row_ix >= frozen_rows).then(|| list_ix(row_ix, loading_rows) - frozen_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        body_item_ix, can_move_col, is_fixed_at_edges, tree_item, ColFixed, FixedCols, TreeItem,
+    };
+
+    const L: Option<ColFixed> = Some(ColFixed::Left);
+    const R: Option<ColFixed> = Some(ColFixed::Right);
+
+    #[test]
+    fn test_is_fixed_at_edges() {
+        assert!(is_fixed_at_edges(&[]));
+        assert!(is_fixed_at_edges(&[None, None]));
+        assert!(is_fixed_at_edges(&[L, L, None, R]));
+        assert!(is_fixed_at_edges(&[L, R]));
+        assert!(is_fixed_at_edges(&[R, R]));
+        assert!(!is_fixed_at_edges(&[None, L, None]));
+        assert!(!is_fixed_at_edges(&[L, R, None]));
+        assert!(!is_fixed_at_edges(&[R, None]));
+    }
+
+    #[test]
+    fn test_can_move_col() {
+        let fixed = [L, L, None, None, None, R];
+        assert!(can_move_col(&fixed, 0, 1));
+        assert!(can_move_col(&fixed, 4, 2));
+        assert!(!can_move_col(&fixed, 2, 2));
+        // Refuse to move the columns across the groups.
+        assert!(!can_move_col(&fixed, 1, 2));
+        assert!(!can_move_col(&fixed, 2, 0));
+        assert!(!can_move_col(&fixed, 4, 5));
+        assert!(!can_move_col(&fixed, 5, 3));
+        assert!(!can_move_col(&fixed, 2, 6));
+    }
+
+    #[test]
+    fn test_right_fixed_cols() {
+        let fixed_cols = FixedCols { left: 1, right: 2 };
+        assert_eq!(fixed_cols.right_range(6), 4..6);
+        assert_eq!(FixedCols { left: 1, right: 0 }.right_range(6), 6..6);
+        // Clamp to the columns if the cols count is less than the fixed columns.
+        assert_eq!(fixed_cols.right_range(1), 0..1);
+    }
+
+    #[test]
+    fn test_body_item_ix() {
+        // The frozen rows are not in the body.
+        assert_eq!(body_item_ix(0, 2, &[]), None);
+        assert_eq!(body_item_ix(1, 2, &[]), None);
+        assert_eq!(body_item_ix(2, 2, &[]), Some(0));
+        assert_eq!(body_item_ix(5, 0, &[]), Some(5));
+
+        // The row 3 is loading the children, its loading row is shown below it.
+        let (frozen_rows, loading_rows) = (2, [3]);
+        assert_eq!(body_item_ix(3, frozen_rows, &loading_rows), Some(1));
+        assert_eq!(body_item_ix(4, frozen_rows, &loading_rows), Some(3));
+
+        // The list items are the rows after the frozen rows.
+        for row_ix in frozen_rows..8 {
+            let item_ix = body_item_ix(row_ix, frozen_rows, &loading_rows).unwrap();
+            assert_eq!(
+                tree_item(item_ix + frozen_rows, &loading_rows),
+                TreeItem::Row(row_ix)
+            );
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use gpui::{
+    div, prelude::FluentBuilder as _, AnyElement, App, AppContext as _, Context, Entity,
+    EventEmitter, FocusHandle, Focusable, InteractiveElement as _, IntoElement, ParentElement as _,
+    SharedString, Styled as _, Subscription, Window,
+};
+use serde_json::Value;
+
+use super::TableCell;
+use crate::{
+    checkbox::Checkbox,
+    date_picker::{DatePicker, DatePickerState},
+    dropdown::{Dropdown, DropdownDelegate, DropdownItem, DropdownState},
+    form::{FormControl, FormState},
+    input::{InputState, TextInput},
+    ActiveTheme as _, Sizable, Size,
+};
+
+/// The editor of the editing cell, created by [`TableDelegate::cell_editor`](super::TableDelegate::cell_editor).
+///
+/// The editor is backed by a [`FormControl`] state, the value of the state is committed by
+/// [`TableDelegate::commit_cell`](super::TableDelegate::commit_cell).
+pub struct CellEditor {
+    focus_handle: FocusHandle,
+    value: Box<dyn Fn(&App) -> Value>,
+    set_invalid: Box<dyn Fn(bool, &mut Window, &mut App)>,
+    render: Box<dyn Fn(Size, &mut Window, &mut App) -> AnyElement>,
+}
+
+impl CellEditor {
+    /// Create an editor with the state and a function to render the editor element with the size.
+    pub fn new<T, E>(
+        state: &Entity<T>,
+        render: impl Fn(&Entity<T>, Size, &mut Window, &mut App) -> E + 'static,
+        cx: &App,
+    ) -> Self
+    where
+        T: FormControl,
+        E: IntoElement,
+    {
+        Self {
+            focus_handle: state.read(cx).focus_handle(cx),
+            value: Box::new({
+                let state = state.clone();
+                move |cx| state.read(cx).field_value(cx)
+            }),
+            set_invalid: Box::new({
+                let state = state.clone();
+                move |invalid, window, cx| {
+                    state.update(cx, |state, cx| state.set_invalid(invalid, window, cx))
+                }
+            }),
+            render: Box::new({
+                let state = state.clone();
+                move |size, window, cx| render(&state, size, window, cx).into_any_element()
+            }),
+        }
+    }
+
+    /// Create an editor with [`TextInput`], the value is a string.
+    pub fn input(state: &Entity<InputState>, cx: &App) -> Self {
+        Self::new(
+            state,
+            |state, size, _, _| TextInput::new(state).with_size(size),
+            cx,
+        )
+    }
+
+    /// Create an editor with [`Dropdown`], the value is the selected value.
+    pub fn dropdown<D>(state: &Entity<DropdownState<D>>, cx: &App) -> Self
+    where
+        D: DropdownDelegate + 'static,
+        <D::Item as DropdownItem>::Value:
+            PartialEq + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        Self::new(
+            state,
+            |state, size, _, _| Dropdown::new(state).with_size(size),
+            cx,
+        )
+    }
+
+    /// Create an editor with [`DatePicker`], the value is the date in `%Y-%m-%d` format.
+    pub fn date_picker(state: &Entity<DatePickerState>, cx: &App) -> Self {
+        Self::new(
+            state,
+            |state, size, _, _| DatePicker::new(state).with_size(size),
+            cx,
+        )
+    }
+
+    /// Create an editor with [`Checkbox`], the value is a boolean.
+    pub fn checkbox(checked: bool, cx: &mut App) -> Self {
+        let state = cx.new(|cx| CheckboxCell {
+            focus_handle: cx.focus_handle(),
+            checked,
+            invalid: false,
+        });
+
+        Self::new(
+            &state,
+            |state, size, _, cx| {
+                let this = state.read(cx);
+                let state = state.clone();
+                div()
+                    .track_focus(&this.focus_handle)
+                    .when(this.invalid, |this| {
+                        this.border_1().border_color(cx.theme().danger)
+                    })
+                    .child(
+                        Checkbox::new("table-cell-checkbox")
+                            .with_size(size)
+                            .checked(this.checked)
+                            .on_click(move |checked, _, cx| {
+                                state.update(cx, |state, cx| {
+                                    state.checked = *checked;
+                                    cx.emit(CheckboxCellEvent::Change);
+                                    cx.notify();
+                                })
+                            }),
+                    )
+            },
+            cx,
+        )
+    }
+
+    pub(super) fn focus(&self, window: &mut Window) {
+        self.focus_handle.focus(window);
+    }
+
+    pub(super) fn value(&self, cx: &App) -> Value {
+        (self.value)(cx)
+    }
+
+    pub(super) fn set_invalid(&self, invalid: bool, window: &mut Window, cx: &mut App) {
+        (self.set_invalid)(invalid, window, cx)
+    }
+
+    pub(super) fn render(&self, size: Size, window: &mut Window, cx: &mut App) -> AnyElement {
+        (self.render)(size, window, cx)
+    }
+}
+
+/// The state of the [`CellEditor::checkbox`], the [`Checkbox`] is stateless.
+struct CheckboxCell {
+    focus_handle: FocusHandle,
+    checked: bool,
+    invalid: bool,
+}
+
+enum CheckboxCellEvent {
+    Change,
+}
+
+impl EventEmitter<CheckboxCellEvent> for CheckboxCell {}
+
+impl Focusable for CheckboxCell {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl FormControl for CheckboxCell {
+    fn field_value(&self, _: &App) -> Value {
+        Value::Bool(self.checked)
+    }
+
+    fn set_field_value(&mut self, value: &Value, _: &mut Window, cx: &mut Context<Self>) {
+        self.checked = value.as_bool().unwrap_or_default();
+        cx.notify();
+    }
+
+    fn set_invalid(&mut self, invalid: bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.invalid = invalid;
+        cx.notify();
+    }
+
+    fn subscribe_change(
+        control: &Entity<Self>,
+        window: &mut Window,
+        cx: &mut Context<FormState>,
+        on_change: impl Fn(&mut FormState, &mut Window, &mut Context<FormState>) + 'static,
+    ) -> Subscription {
+        cx.subscribe_in(
+            control,
+            window,
+            move |form, _, _: &CheckboxCellEvent, window, cx| {
+                on_change(form, window, cx);
+            },
+        )
+    }
+}
+
+/// The editing cell of the table.
+pub(super) struct EditingCell {
+    pub(super) cell: TableCell,
+    pub(super) editor: CellEditor,
+    /// The error message of the last commit.
+    pub(super) error: Option<SharedString>,
+}
+
+/// Returns the next (or previous if `backward`) cell that can be edited in the row-major order,
+/// moving to the next row after the last column.
+pub(super) fn next_editable_cell(
+    cell: TableCell,
+    rows_count: usize,
+    cols_count: usize,
+    backward: bool,
+    can_edit: impl Fn(TableCell) -> bool,
+) -> Option<TableCell> {
+    let total = rows_count * cols_count;
+    if total == 0 {
+        return None;
+    }
+
+    let ix = cell.row * cols_count + cell.col;
+    let cells = (1..total).map(|offset| {
+        if backward {
+            ix.checked_sub(offset)
+        } else {
+            Some(ix + offset).filter(|ix| *ix < total)
+        }
+    });
+
+    cells
+        .map_while(|ix| ix)
+        .map(|ix| TableCell::new(ix / cols_count, ix % cols_count))
+        .find(|cell| can_edit(*cell))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_editable_cell, TableCell};
+
+    #[test]
+    fn test_next_editable_cell() {
+        // The column 0 and 2 can be edited.
+        let can_edit = |cell: TableCell| cell.col != 1;
+        let next = |row, col, backward| {
+            next_editable_cell(TableCell::new(row, col), 3, 3, backward, can_edit)
+        };
+
+        assert_eq!(next(0, 0, false), Some(TableCell::new(0, 2)));
+        assert_eq!(next(0, 2, false), Some(TableCell::new(1, 0)));
+        assert_eq!(next(2, 2, false), None);
+        assert_eq!(next(1, 0, true), Some(TableCell::new(0, 2)));
+        assert_eq!(next(0, 2, true), Some(TableCell::new(0, 0)));
+        assert_eq!(next(0, 0, true), None);
+
+        assert_eq!(
+            next_editable_cell(TableCell::new(0, 0), 0, 3, false, can_edit),
+            None
+        );
+        assert_eq!(
+            next_editable_cell(TableCell::new(1, 1), 3, 3, false, |_| false),
+            None
+        );
+    }
+}
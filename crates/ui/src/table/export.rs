@@ -0,0 +1,189 @@
+use serde::{ser::SerializeMap as _, Deserialize, Serialize, Serializer};
+
+/// The format to export the table, see [`Table::export`](super::Table::export).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ExportFormat {
+    /// Comma-separated values.
+    Csv,
+    /// Tab-separated values, can be pasted into the spreadsheets.
+    Tsv,
+    /// An array of the objects with the column names as the keys.
+    Json,
+    /// Markdown table.
+    Markdown,
+    /// HTML table.
+    Html,
+}
+
+impl ExportFormat {
+    /// Format the rows with the column names as the header.
+    pub(super) fn format<S: AsRef<str>>(&self, header: &[S], rows: &[Vec<S>]) -> String {
+        match self {
+            Self::Csv => to_delimited(Some(header), rows, ','),
+            Self::Tsv => to_delimited(Some(header), rows, '\t'),
+            Self::Json => to_json(header, rows),
+            Self::Markdown => to_markdown(header, rows),
+            Self::Html => to_html(Some(header), rows),
+        }
+    }
+}
+
+/// Join the cells with the delimiter, quote the cells contain the delimiter, quotes or line breaks.
+pub(super) fn to_delimited<S: AsRef<str>>(
+    header: Option<&[S]>,
+    rows: &[Vec<S>],
+    delimiter: char,
+) -> String {
+    let quote = |text: &str| {
+        if text.contains([delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    };
+
+    header
+        .into_iter()
+        .chain(rows.iter().map(|row| row.as_slice()))
+        .map(|row| {
+            row.iter()
+                .map(|cell| quote(cell.as_ref()))
+                .collect::<Vec<_>>()
+                .join(&delimiter.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A row serialized as an object, the keys are the column names in the order of the columns.
+struct JsonRow<'a, S> {
+    header: &'a [S],
+    row: &'a [S],
+}
+
+impl<S: AsRef<str>> Serialize for JsonRow<'_, S> {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        let mut map = serializer.serialize_map(Some(self.header.len().min(self.row.len())))?;
+        for (key, value) in self.header.iter().zip(self.row) {
+            map.serialize_entry(key.as_ref(), value.as_ref())?;
+        }
+        map.end()
+    }
+}
+
+/// Format the rows as an array of the objects, the keys are in the order of the columns.
+fn to_json<S: AsRef<str>>(header: &[S], rows: &[Vec<S>]) -> String {
+    let rows = rows
+        .iter()
+        .map(|row| JsonRow { header, row })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&rows).unwrap_or_default()
+}
+
+fn to_markdown<S: AsRef<str>>(header: &[S], rows: &[Vec<S>]) -> String {
+    let escape = |text: &str| {
+        text.replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    };
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+
+    let mut lines = vec![
+        line(header.iter().map(|cell| escape(cell.as_ref())).collect()),
+        line(header.iter().map(|_| "---".to_string()).collect()),
+    ];
+    lines.extend(
+        rows.iter()
+            .map(|row| line(row.iter().map(|cell| escape(cell.as_ref())).collect())),
+    );
+    lines.join("\n")
+}
+
+pub(super) fn to_html<S: AsRef<str>>(header: Option<&[S]>, rows: &[Vec<S>]) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    };
+    let line = |cells: &[S], tag: &str| {
+        let cells = cells
+            .iter()
+            .map(|cell| format!("<{tag}>{}</{tag}>", escape(cell.as_ref())))
+            .collect::<String>();
+        format!("<tr>{}</tr>", cells)
+    };
+
+    let mut html = String::from("<table>");
+    if let Some(header) = header {
+        html.push_str(&format!("<thead>{}</thead>", line(header, "th")));
+    }
+    html.push_str("<tbody>");
+    for row in rows {
+        html.push_str(&line(row, "td"));
+    }
+    html.push_str("</tbody></table>");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_delimited, to_html, ExportFormat};
+
+    fn table() -> (Vec<&'static str>, Vec<Vec<&'static str>>) {
+        (
+            vec!["Name", "Note"],
+            vec![vec!["Apple", "Red, \"sweet\""], vec!["Banana", "a|b\nc"]],
+        )
+    }
+
+    #[test]
+    fn test_delimited() {
+        let (header, rows) = table();
+        assert_eq!(
+            ExportFormat::Csv.format(&header, &rows),
+            "Name,Note\nApple,\"Red, \"\"sweet\"\"\"\nBanana,\"a|b\nc\""
+        );
+        assert_eq!(
+            ExportFormat::Tsv.format(&header, &rows),
+            "Name\tNote\nApple\t\"Red, \"\"sweet\"\"\"\nBanana\t\"a|b\nc\""
+        );
+        assert_eq!(
+            to_delimited(None, &[vec!["a", "b c"], vec!["", "d"]], '\t'),
+            "a\tb c\n\td"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let (header, rows) = table();
+        assert_eq!(
+            ExportFormat::Json.format(&header, &rows),
+            "[\n  {\n    \"Name\": \"Apple\",\n    \"Note\": \"Red, \\\"sweet\\\"\"\n  },\n  {\n    \"Name\": \"Banana\",\n    \"Note\": \"a|b\\nc\"\n  }\n]"
+        );
+        // The keys are in the order of the columns, not sorted.
+        assert_eq!(
+            ExportFormat::Json.format(&["B", "A"], &[vec!["1", "2"]]),
+            "[\n  {\n    \"B\": \"1\",\n    \"A\": \"2\"\n  }\n]"
+        );
+        assert_eq!(ExportFormat::Json.format(&header, &[]), "[]");
+    }
+
+    #[test]
+    fn test_markdown() {
+        let (header, rows) = table();
+        assert_eq!(
+            ExportFormat::Markdown.format(&header, &rows),
+            "| Name | Note |\n| --- | --- |\n| Apple | Red, \"sweet\" |\n| Banana | a\\|b<br>c |"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        assert_eq!(
+            to_html(Some(&["A", "B"][..]), &[vec!["<1>", "a & b"]]),
+            "<table><thead><tr><th>A</th><th>B</th></tr></thead><tbody><tr><td>&lt;1&gt;</td><td>a &amp; b</td></tr></tbody></table>"
+        );
+        assert_eq!(to_html::<&str>(None, &[]), "<table><tbody></tbody></table>");
+    }
+}
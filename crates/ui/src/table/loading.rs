@@ -18,7 +18,7 @@ impl Loading {
 }
 
 #[derive(IntoElement)]
-struct LoadingRow {
+pub(super) struct LoadingRow {
     header: bool,
     size: Size,
 }
@@ -0,0 +1,293 @@
+use std::{
+    collections::BTreeSet,
+    ops::{Range, RangeInclusive},
+};
+
+use super::tree::{is_removed_row, shift_row};
+
+/// The position of a cell in the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TableCell {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl TableCell {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+}
+
+/// A rectangular range of the cells, from the `anchor` cell to the `focus` cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRange {
+    /// The cell where the selection started.
+    pub anchor: TableCell,
+    /// The cell where the selection ended, it moves when extending the selection.
+    pub focus: TableCell,
+}
+
+impl CellRange {
+    pub fn new(anchor: TableCell, focus: TableCell) -> Self {
+        Self { anchor, focus }
+    }
+
+    /// Returns the range of the rows.
+    pub fn rows(&self) -> RangeInclusive<usize> {
+        self.anchor.row.min(self.focus.row)..=self.anchor.row.max(self.focus.row)
+    }
+
+    /// Returns the range of the columns.
+    pub fn cols(&self) -> RangeInclusive<usize> {
+        self.anchor.col.min(self.focus.col)..=self.anchor.col.max(self.focus.col)
+    }
+
+    /// Returns true if the cell is in the range.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.rows().contains(&row) && self.cols().contains(&col)
+    }
+
+    /// Shift the rows after the `delta` children of the row were inserted (or removed if negative),
+    /// the removed rows are moved to the row.
+    pub(super) fn shift_rows(&mut self, row_ix: usize, delta: isize) {
+        self.anchor.row = shift_row(self.anchor.row, row_ix, delta);
+        self.focus.row = shift_row(self.focus.row, row_ix, delta);
+    }
+}
+
+/// The selection of the table, see [`TableEvent::SelectionChanged`](super::TableEvent::SelectionChanged).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableSelection {
+    /// The selected rows, as the ranges of the consecutive rows in ascending order.
+    pub rows: Vec<Range<usize>>,
+    /// The selected range of the cells.
+    pub cells: Option<CellRange>,
+}
+
+impl TableSelection {
+    /// Returns true if the row is selected.
+    pub fn contains_row(&self, row: usize) -> bool {
+        self.rows.iter().any(|range| range.contains(&row))
+    }
+
+    /// Returns the number of the selected rows.
+    pub fn rows_count(&self) -> usize {
+        self.rows.iter().map(|range| range.len()).sum()
+    }
+}
+
+/// The selected rows, with the anchor row to extend the selection by shift-click or shift-arrow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct RowSelection {
+    rows: BTreeSet<usize>,
+    /// The rows count if all the rows are selected, the `rows` is empty then.
+    all: Option<usize>,
+    anchor: Option<usize>,
+}
+
+impl RowSelection {
+    /// Select only the row.
+    pub(super) fn select(&mut self, row: usize) {
+        self.all = None;
+        self.rows.clear();
+        self.rows.insert(row);
+        self.anchor = Some(row);
+    }
+
+    /// Toggle the row, e.g. ctrl/cmd-click.
+    pub(super) fn toggle(&mut self, row: usize) {
+        if let Some(rows_count) = self.all.take() {
+            self.rows = (0..rows_count).collect();
+        }
+        if !self.rows.remove(&row) {
+            self.rows.insert(row);
+        }
+        self.anchor = Some(row);
+    }
+
+    /// Select the rows from the anchor row to the row, e.g. shift-click.
+    pub(super) fn extend(&mut self, row: usize) {
+        let anchor = *self.anchor.get_or_insert(row);
+        self.all = None;
+        self.rows = (anchor.min(row)..=anchor.max(row)).collect();
+    }
+
+    /// Select all the rows, the anchor is kept to extend the selection from it.
+    pub(super) fn select_all(&mut self, rows_count: usize) {
+        self.rows.clear();
+        self.all = Some(rows_count);
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.rows.clear();
+        self.all = None;
+        self.anchor = None;
+    }
+
+    /// Shift the rows after the `delta` children of the row were inserted (or removed if negative),
+    /// the removed rows are unselected.
+    pub(super) fn shift(&mut self, row_ix: usize, delta: isize) {
+        if let Some(rows_count) = &mut self.all {
+            *rows_count = rows_count.saturating_add_signed(delta);
+        }
+        self.rows = self
+            .rows
+            .iter()
+            .filter(|ix| !is_removed_row(**ix, row_ix, delta))
+            .map(|ix| shift_row(*ix, row_ix, delta))
+            .collect();
+        self.anchor = self.anchor.map(|ix| shift_row(ix, row_ix, delta));
+    }
+
+    pub(super) fn contains(&self, row: usize) -> bool {
+        match self.all {
+            Some(rows_count) => row < rows_count,
+            None => self.rows.contains(&row),
+        }
+    }
+
+    pub(super) fn rows(&self) -> Vec<usize> {
+        match self.all {
+            Some(rows_count) => (0..rows_count).collect(),
+            None => self.rows.iter().copied().collect(),
+        }
+    }
+
+    /// Returns the ranges of the consecutive selected rows in ascending order.
+    pub(super) fn ranges(&self) -> Vec<Range<usize>> {
+        if let Some(rows_count) = self.all {
+            return (rows_count > 0)
+                .then_some(0..rows_count)
+                .into_iter()
+                .collect();
+        }
+
+        let mut ranges: Vec<Range<usize>> = vec![];
+        for &row in &self.rows {
+            match ranges.last_mut() {
+                Some(range) if range.end == row => range.end += 1,
+                _ => ranges.push(row..row + 1),
+            }
+        }
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CellRange, RowSelection, TableCell, TableSelection};
+
+    #[test]
+    fn test_row_selection() {
+        let mut selection = RowSelection::default();
+        selection.select(3);
+        assert_eq!(selection.rows(), vec![3]);
+
+        selection.extend(6);
+        assert_eq!(selection.rows(), vec![3, 4, 5, 6]);
+        selection.extend(1);
+        assert_eq!(selection.rows(), vec![1, 2, 3]);
+
+        selection.toggle(2);
+        assert_eq!(selection.rows(), vec![1, 3]);
+        selection.toggle(8);
+        assert_eq!(selection.rows(), vec![1, 3, 8]);
+        assert!(selection.contains(8));
+        // The toggled row is the new anchor.
+        selection.extend(10);
+        assert_eq!(selection.rows(), vec![8, 9, 10]);
+
+        selection.select_all(12);
+        assert_eq!(selection.ranges(), vec![0..12]);
+        assert!(selection.contains(11));
+        assert!(!selection.contains(12));
+        // Extend from the anchor row 8 after selecting all.
+        selection.extend(2);
+        assert_eq!(selection.rows(), (2..=8).collect::<Vec<_>>());
+
+        selection.select_all(4);
+        selection.toggle(1);
+        assert_eq!(selection.rows(), vec![0, 2, 3]);
+
+        selection.clear();
+        assert!(selection.rows().is_empty());
+        // Extend from the row itself without the anchor.
+        selection.select_all(4);
+        selection.extend(2);
+        assert_eq!(selection.rows(), vec![2]);
+    }
+
+    #[test]
+    fn test_row_selection_ranges() {
+        let mut selection = RowSelection::default();
+        assert!(selection.ranges().is_empty());
+        selection.select_all(0);
+        assert!(selection.ranges().is_empty());
+
+        selection.select(2);
+        selection.extend(4);
+        selection.toggle(7);
+        selection.toggle(9);
+        selection.toggle(8);
+        assert_eq!(selection.ranges(), vec![2..5, 7..10]);
+
+        let table_selection = TableSelection {
+            rows: selection.ranges(),
+            cells: None,
+        };
+        assert_eq!(table_selection.rows_count(), 6);
+        assert!(table_selection.contains_row(4));
+        assert!(!table_selection.contains_row(5));
+    }
+
+    #[test]
+    fn test_shift_row_selection() {
+        let mut selection = RowSelection::default();
+        selection.select(1);
+        selection.toggle(4);
+        selection.toggle(6);
+        selection.toggle(8);
+        // Expand the row 4 with 2 children.
+        selection.shift(4, 2);
+        assert_eq!(selection.rows(), vec![1, 4, 8, 10]);
+        // The anchor row 8 is shifted to 10.
+        selection.extend(9);
+        assert_eq!(selection.rows(), vec![9, 10]);
+
+        // Collapse the row 4 with the children 5..=8, the selected rows in them are unselected.
+        selection.select(2);
+        selection.toggle(4);
+        selection.toggle(6);
+        selection.toggle(9);
+        selection.shift(4, -4);
+        assert_eq!(selection.rows(), vec![2, 4, 5]);
+        // The anchor row 9 is shifted to 5.
+        selection.extend(3);
+        assert_eq!(selection.rows(), vec![3, 4, 5]);
+
+        selection.select_all(10);
+        selection.shift(2, 3);
+        assert_eq!(selection.ranges(), vec![0..13]);
+        selection.shift(2, -5);
+        assert_eq!(selection.ranges(), vec![0..8]);
+    }
+
+    #[test]
+    fn test_cell_range() {
+        let range = CellRange::new(TableCell::new(5, 1), TableCell::new(2, 3));
+        assert_eq!(range.rows(), 2..=5);
+        assert_eq!(range.cols(), 1..=3);
+        assert!(range.contains(2, 1));
+        assert!(range.contains(5, 3));
+        assert!(!range.contains(1, 2));
+        assert!(!range.contains(3, 4));
+
+        let mut range = CellRange::new(TableCell::new(5, 1), TableCell::new(2, 3));
+        range.shift_rows(3, 2);
+        assert_eq!(range.rows(), 2..=7);
+        // The removed focus row is moved to the collapsed row.
+        range.shift_rows(1, -3);
+        assert_eq!(range.anchor, TableCell::new(4, 1));
+        assert_eq!(range.focus, TableCell::new(1, 3));
+    }
+}
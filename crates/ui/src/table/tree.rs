@@ -0,0 +1,237 @@
+use gpui::Task;
+
+/// The item of the table body list, the loading row is shown below the row that loading the children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TreeItem {
+    Row(usize),
+    /// The loading row of the children of the row.
+    Loading(usize),
+}
+
+struct LoadingChildren {
+    id: usize,
+    row_ix: usize,
+    _task: Task<()>,
+}
+
+/// The rows that are loading the children, see [`TableDelegate::set_row_expanded`](super::TableDelegate::set_row_expanded).
+#[derive(Default)]
+pub(super) struct TreeState {
+    /// The loading rows in ascending order.
+    loading: Vec<LoadingChildren>,
+    next_id: usize,
+}
+
+impl TreeState {
+    /// Start loading the children of the row, returns the id to finish the loading.
+    pub(super) fn start_loading(
+        &mut self,
+        row_ix: usize,
+        task: impl FnOnce(usize) -> Task<()>,
+    ) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.loading.retain(|loading| loading.row_ix != row_ix);
+
+        let ix = self
+            .loading
+            .partition_point(|loading| loading.row_ix < row_ix);
+        self.loading.insert(
+            ix,
+            LoadingChildren {
+                id,
+                row_ix,
+                _task: task(id),
+            },
+        );
+        id
+    }
+
+    /// Finish the loading, returns the row of the loading, or None if the loading was canceled.
+    ///
+    /// Call [`Self::rows_changed`] with the number of the inserted children after it.
+    pub(super) fn finish_loading(&mut self, id: usize) -> Option<usize> {
+        let ix = self.loading.iter().position(|loading| loading.id == id)?;
+        Some(self.loading.remove(ix).row_ix)
+    }
+
+    /// Cancel the loading of the row, e.g. the row is collapsed before the children are loaded.
+    pub(super) fn cancel_loading(&mut self, row_ix: usize) {
+        self.loading.retain(|loading| loading.row_ix != row_ix);
+    }
+
+    /// Shift the loading rows after the `delta` children of the row were inserted (or removed if
+    /// negative), the loading rows in the removed descendant rows are canceled.
+    pub(super) fn rows_changed(&mut self, row_ix: usize, delta: isize) {
+        self.loading.retain_mut(|loading| {
+            if is_removed_row(loading.row_ix, row_ix, delta) {
+                return false;
+            }
+            loading.row_ix = shift_row(loading.row_ix, row_ix, delta);
+            true
+        });
+    }
+
+    pub(super) fn is_loading(&self, row_ix: usize) -> bool {
+        self.loading.iter().any(|loading| loading.row_ix == row_ix)
+    }
+
+    /// Returns the loading rows from the `start` row.
+    pub(super) fn loading_rows(&self, start: usize) -> Vec<usize> {
+        self.loading
+            .iter()
+            .map(|loading| loading.row_ix)
+            .filter(|row_ix| *row_ix >= start)
+            .collect()
+    }
+}
+
+/// Returns the index of the row `ix` after the children of the row `row_ix` were inserted,
+/// or removed if `delta` is negative, the removed rows are moved to `row_ix`.
+pub(super) fn shift_row(ix: usize, row_ix: usize, delta: isize) -> usize {
+    if ix <= row_ix {
+        ix
+    } else if delta < 0 && ix <= row_ix + delta.unsigned_abs() {
+        row_ix
+    } else {
+        ix.saturating_add_signed(delta)
+    }
+}
+
+/// Returns true if the row `ix` is a descendant of the row `row_ix` that was removed,
+/// when the children were removed if `delta` is negative.
+pub(super) fn is_removed_row(ix: usize, row_ix: usize, delta: isize) -> bool {
+    delta < 0 && ix > row_ix && ix <= row_ix + delta.unsigned_abs()
+}
+
+/// Returns the row index of the list item at `ix`, or the number of the rows before the loading row.
+///
+/// The `loading` rows must be in ascending order.
+pub(super) fn row_ix_at(ix: usize, loading: &[usize]) -> usize {
+    let loading_before = loading
+        .iter()
+        .enumerate()
+        .take_while(|(k, row_ix)| *row_ix + k + 1 < ix)
+        .count();
+    ix - loading_before
+}
+
+/// Returns the item of the list at `ix`, the loading row is placed right after its row.
+pub(super) fn tree_item(ix: usize, loading: &[usize]) -> TreeItem {
+    match loading
+        .iter()
+        .enumerate()
+        .find(|(k, row_ix)| *row_ix + k + 1 == ix)
+    {
+        Some((_, row_ix)) => TreeItem::Loading(*row_ix),
+        None => TreeItem::Row(row_ix_at(ix, loading)),
+    }
+}
+
+/// Returns the index of the row in the list, with the loading rows before it.
+pub(super) fn list_ix(row_ix: usize, loading: &[usize]) -> usize {
+    row_ix + loading.iter().filter(|ix| **ix < row_ix).count()
+}
+
+/// Returns the parent row of the row, the nearest previous row with a smaller depth.
+pub(super) fn parent_row(row_ix: usize, depth: impl Fn(usize) -> usize) -> Option<usize> {
+    let row_depth = depth(row_ix);
+    if row_depth == 0 {
+        return None;
+    }
+
+    (0..row_ix).rev().find(|ix| depth(*ix) < row_depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::Task;
+
+    use super::{
+        is_removed_row, list_ix, parent_row, row_ix_at, shift_row, tree_item, TreeItem, TreeState,
+    };
+
+    #[test]
+    fn test_tree_item() {
+        // The loading rows are shown after the row 1 and row 3.
+        let loading = [1, 3];
+        let items = (0..7).map(|ix| tree_item(ix, &loading)).collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            vec![
+                TreeItem::Row(0),
+                TreeItem::Row(1),
+                TreeItem::Loading(1),
+                TreeItem::Row(2),
+                TreeItem::Row(3),
+                TreeItem::Loading(3),
+                TreeItem::Row(4),
+            ]
+        );
+
+        assert_eq!(list_ix(1, &loading), 1);
+        assert_eq!(list_ix(2, &loading), 3);
+        assert_eq!(list_ix(4, &loading), 6);
+        // The rows count before the position.
+        assert_eq!(row_ix_at(3, &loading), 2);
+        assert_eq!(row_ix_at(6, &loading), 4);
+        assert_eq!(row_ix_at(7, &loading), 5);
+        assert_eq!(tree_item(3, &[]), TreeItem::Row(3));
+    }
+
+    #[test]
+    fn test_tree_state() {
+        let mut state = TreeState::default();
+        let id = state.start_loading(5, |_| Task::ready(()));
+        state.start_loading(2, |_| Task::ready(()));
+        assert_eq!(state.loading_rows(0), vec![2, 5]);
+        assert_eq!(state.loading_rows(3), vec![5]);
+
+        // Expand the row 0 with 3 children.
+        state.rows_changed(0, 3);
+        assert_eq!(state.loading_rows(0), vec![5, 8]);
+        assert!(state.is_loading(8));
+
+        // The row 8 loaded 2 children, the rows after it are shifted.
+        state.start_loading(12, |_| Task::ready(()));
+        assert_eq!(state.finish_loading(id), Some(8));
+        state.rows_changed(8, 2);
+        assert_eq!(state.loading_rows(0), vec![5, 14]);
+        assert_eq!(state.finish_loading(id), None);
+
+        // Collapse the row 4 with 2 descendants, the loading row 5 is removed.
+        state.cancel_loading(14);
+        state.start_loading(10, |_| Task::ready(()));
+        state.rows_changed(4, -2);
+        assert_eq!(state.loading_rows(0), vec![8]);
+
+        state.cancel_loading(8);
+        assert!(state.loading_rows(0).is_empty());
+    }
+
+    #[test]
+    fn test_shift_row() {
+        assert_eq!(shift_row(3, 3, 2), 3);
+        assert_eq!(shift_row(4, 3, 2), 6);
+        assert_eq!(shift_row(4, 3, -2), 3);
+        assert_eq!(shift_row(5, 3, -2), 3);
+        assert_eq!(shift_row(6, 3, -2), 4);
+        assert_eq!(shift_row(1, 3, -2), 1);
+
+        assert!(!is_removed_row(3, 3, -2));
+        assert!(is_removed_row(4, 3, -2));
+        assert!(is_removed_row(5, 3, -2));
+        assert!(!is_removed_row(6, 3, -2));
+        assert!(!is_removed_row(4, 3, 2));
+    }
+
+    #[test]
+    fn test_parent_row() {
+        let depths = [0, 1, 2, 2, 1, 0];
+        let depth = |ix: usize| depths[ix];
+        assert_eq!(parent_row(0, depth), None);
+        assert_eq!(parent_row(3, depth), Some(1));
+        assert_eq!(parent_row(4, depth), Some(0));
+        assert_eq!(parent_row(5, depth), None);
+    }
+}
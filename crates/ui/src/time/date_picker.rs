@@ -10,6 +10,7 @@ use rust_i18n::t;
 use crate::{
     actions::Cancel,
     button::{Button, ButtonVariants as _},
+    form::{FormControl, FormState},
     h_flex,
     input::clear_button,
     v_flex, ActiveTheme, Icon, IconName, Sizable, Size, StyleSized as _, StyledExt as _,
@@ -61,6 +62,7 @@ pub struct DatePickerState {
     focus_handle: FocusHandle,
     date: Date,
     open: bool,
+    invalid: bool,
     calendar: Entity<CalendarState>,
     date_format: SharedString,
     number_of_months: usize,
@@ -72,6 +74,66 @@ impl Focusable for DatePickerState {
         self.focus_handle.clone()
     }
 }
+
+impl FormControl for DatePickerState {
+    fn field_value(&self, _: &App) -> serde_json::Value {
+        let format = |date: Option<NaiveDate>| {
+            date.map(|date| serde_json::Value::String(date.format("%Y-%m-%d").to_string()))
+                .unwrap_or_default()
+        };
+
+        match self.date {
+            Date::Single(date) => format(date),
+            Date::Range(None, None) => serde_json::Value::Null,
+            Date::Range(start, end) => serde_json::Value::Array(vec![format(start), format(end)]),
+        }
+    }
+
+    fn set_field_value(
+        &mut self,
+        value: &serde_json::Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let parse = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .and_then(|text| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok())
+        };
+
+        let date = match (value, self.date) {
+            (serde_json::Value::Array(items), _) => {
+                Date::Range(items.first().and_then(parse), items.get(1).and_then(parse))
+            }
+            (_, Date::Range(_, _)) => Date::Range(None, None),
+            (value, Date::Single(_)) => Date::Single(parse(value)),
+        };
+        if date != self.date {
+            self.set_date(date, window, cx);
+        }
+    }
+
+    fn set_invalid(&mut self, invalid: bool, _: &mut Window, cx: &mut Context<Self>) {
+        self.invalid = invalid;
+        cx.notify();
+    }
+
+    fn subscribe_change(
+        control: &Entity<Self>,
+        window: &mut Window,
+        cx: &mut Context<FormState>,
+        on_change: impl Fn(&mut FormState, &mut Window, &mut Context<FormState>) + 'static,
+    ) -> Subscription {
+        cx.subscribe_in(
+            control,
+            window,
+            move |form, _, _: &DatePickerEvent, window, cx| {
+                on_change(form, window, cx);
+            },
+        )
+    }
+}
+
 impl EventEmitter<DatePickerEvent> for DatePickerState {}
 
 impl DatePickerState {
@@ -114,6 +176,7 @@ impl DatePickerState {
             date,
             calendar,
             open: false,
+            invalid: false,
             date_format: "%Y/%m/%d".into(),
             number_of_months: 1,
             _subscriptions,
@@ -347,6 +410,7 @@ impl RenderOnce for DatePicker {
                     .overflow_hidden()
                     .input_text_size(self.size)
                     .when(is_focused, |this| this.focused_border(cx))
+                    .when(state.invalid, |this| this.border_color(cx.theme().danger))
                     .input_size(self.size)
                     .when(!state.open, |this| {
                         this.on_click(